@@ -0,0 +1,21 @@
+// @title Feedback Trail
+// @tags feedback, multi-pass
+//
+// Image pass: displays the trail buffer with a slight blur
+//
+// channel0: trail buffer (this frame)
+
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / uniforms.resolution;
+    let uv = (coords + 0.5) * texel;
+
+    var color = vec3<f32>(0.0);
+    for (var x = -1; x <= 1; x++) {
+        for (var y = -1; y <= 1; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            color += textureSampleLevel(channel0, channel_sampler, uv + offset, 0.0).rgb;
+        }
+    }
+
+    return color / 9.0;
+}
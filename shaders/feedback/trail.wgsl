@@ -0,0 +1,15 @@
+// Buffer pass: fades its own previous frame and draws an orbiting dot on top
+//
+// channel0: this buffer's previous frame
+
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let previous = textureLoad(channel0, vec2<i32>(coords), 0).rgb;
+
+    let center = uniforms.resolution * 0.5;
+    let radius = min(uniforms.resolution.x, uniforms.resolution.y) * 0.35;
+    let dot_position = center + radius * vec2<f32>(cos(uniforms.time * 2.0), sin(uniforms.time * 3.0));
+    let dot_color = 0.5 + 0.5 * cos(uniforms.time + vec3<f32>(0.0, 2.0, 4.0));
+    let dot_mask = 1.0 - smoothstep(1.5, 3.0, distance(coords, dot_position));
+
+    return max(previous * 0.97, dot_color * dot_mask);
+}
@@ -0,0 +1,36 @@
+// @title Two Trails
+// @tags feedback, multi-pass
+//
+// Single-file multi-pass: code above the first `// @pass` is shared by every pass, and every
+// pass sees all buffers as channel0 (bufferA) and channel1 (bufferB)
+
+fn orbit(phase: f32) -> vec2<f32> {
+    let center = uniforms.resolution * 0.5;
+    let radius = min(uniforms.resolution.x, uniforms.resolution.y) * 0.35;
+    let t = uniforms.time + phase;
+    return center + radius * vec2<f32>(cos(t * 2.0), sin(t * 3.0));
+}
+
+fn trail(previous: vec3<f32>, coords: vec2<f32>, phase: f32, color: vec3<f32>) -> vec3<f32> {
+    let dot_mask = 1.0 - smoothstep(1.5, 3.0, distance(coords, orbit(phase)));
+    return max(previous * 0.97, color * dot_mask);
+}
+
+// @pass bufferA
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let previous = textureLoad(channel0, vec2<i32>(coords), 0).rgb;
+    return trail(previous, coords, 0.0, vec3<f32>(1.0, 0.4, 0.1));
+}
+
+// @pass bufferB
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let previous = textureLoad(channel1, vec2<i32>(coords), 0).rgb;
+    return trail(previous, coords, 1.5, vec3<f32>(0.1, 0.5, 1.0));
+}
+
+// @pass image
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let a = textureLoad(channel0, vec2<i32>(coords), 0).rgb;
+    let b = textureLoad(channel1, vec2<i32>(coords), 0).rgb;
+    return a + b;
+}
@@ -0,0 +1,30 @@
+// @title Oscillator
+// @tags audio, dsp
+// @domain 1d
+
+// A detuned saw chord that fades to a sine and back, evaluated one sample per index.
+// Try `--sample-view spectrogram` to watch the harmonics move.
+
+const SAMPLE_RATE: f32 = 44100.0;
+const VOICES: i32 = 3;
+
+fn saw(phase: f32) -> f32 {
+    return fract(phase) * 2.0 - 1.0;
+}
+
+fn compute_sample(index: u32) -> f32 {
+    let t = uniforms.time + f32(index) / SAMPLE_RATE;
+    let base = 110.0 * (1.0 + 0.5 * floor(fract(uniforms.time * 0.25) * 4.0));
+
+    var value = 0.0;
+    for (var i = 0; i < VOICES; i++) {
+        let detune = 1.0 + 0.004 * f32(i - 1);
+        value += saw(t * base * detune);
+    }
+    value /= f32(VOICES);
+
+    // Blend toward a sine as the "filter" closes, so the upper harmonics fade in and out
+    let brightness = 0.5 + 0.5 * sin(uniforms.time * 0.7);
+    let sine = sin(t * base * 6.2831853);
+    return mix(sine, value, brightness) * 0.8;
+}
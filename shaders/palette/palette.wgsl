@@ -0,0 +1,13 @@
+// @title Sunset Bands
+// @tags palette
+// @palette "sunset.json"
+
+// Edit sunset.json while this runs to recolor the bands
+
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let uv = coords / uniforms.resolution;
+    let wave = uv.y + 0.1 * sin(uv.x * 6.0 + uniforms.time);
+    let t = clamp(wave, 0.0, 0.999) * f32(PALETTE_SUNSET_SIZE - 1u);
+    let index = u32(t);
+    return mix(PALETTE_SUNSET[index], PALETTE_SUNSET[index + 1u], fract(t));
+}
@@ -0,0 +1,28 @@
+// Unit tests for the std/sdf helpers, run with `shadertui test shaders/sdf_tests.wgsl`
+// @import "std/sdf"
+
+const EPSILON: f32 = 1e-5;
+
+fn approx(a: f32, b: f32) -> bool {
+    return abs(a - b) < EPSILON;
+}
+
+// @test
+fn circle_is_negative_inside() -> bool {
+    return sd_circle(vec2<f32>(0.0), 1.0) < 0.0;
+}
+
+// @test
+fn circle_distance_outside() -> bool {
+    return approx(sd_circle(vec2<f32>(3.0, 4.0), 1.0), 4.0);
+}
+
+// @test
+fn box_edge_is_zero() -> bool {
+    return approx(sd_box2(vec2<f32>(1.0, 0.0), vec2<f32>(1.0, 0.5)), 0.0);
+}
+
+// @test
+fn union_takes_the_nearer_shape() -> bool {
+    return approx(op_union(0.25, 2.0), 0.25);
+}
@@ -0,0 +1,17 @@
+// @title Shadertoy Tunnel
+// @tags shadertoy, glsl, tunnel
+
+// A Shadertoy-style GLSL shader: shadertui translates mainImage() and maps iTime, iResolution,
+// iMouse, iFrame and iTimeDelta onto its own uniforms.
+
+void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+    vec2 p = (2.0 * fragCoord - iResolution.xy) / iResolution.y;
+    float radius = length(p);
+    float angle = atan(p.y, p.x);
+
+    float depth = 0.3 / radius + iTime * 0.5;
+    float stripes = 0.5 + 0.5 * sin(depth * 12.0) * sin(angle * 5.0 + iTime);
+    vec3 color = 0.5 + 0.5 * cos(vec3(0.0, 2.0, 4.0) + depth * 2.0);
+
+    fragColor = vec4(color * stripes * smoothstep(0.0, 0.4, radius), 1.0);
+}
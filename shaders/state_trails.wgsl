@@ -0,0 +1,22 @@
+// @title State Trails
+// @tags simulation, state
+
+// Run with `--state-size 1048576`: each pixel keeps its own glow in `state`, so a dot orbiting the
+// screen leaves a fading trail. Every invocation writes only its own element of `state`.
+
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let index = u32(coords.y) * u32(uniforms.resolution.x) + u32(coords.x);
+    if (index >= arrayLength(&state)) {
+        return vec3<f32>(0.0);
+    }
+
+    let t = uniforms.time;
+    let center = uniforms.resolution * (0.5 + 0.35 * vec2<f32>(cos(t * 1.3), sin(t * 2.1)));
+    let radius = min(uniforms.resolution.x, uniforms.resolution.y) * 0.06;
+    let hit = 1.0 - smoothstep(radius * 0.5, radius, distance(coords, center));
+
+    let glow = max(state[index] * exp(-2.0 * uniforms.delta_time), hit);
+    state[index] = glow;
+
+    return glow * mix(vec3<f32>(0.1, 0.3, 1.0), vec3<f32>(1.0, 0.9, 0.6), glow * glow);
+}
@@ -0,0 +1,120 @@
+use crate::audio::AudioCapture;
+use crate::commands::{
+    run_bench, run_cache, run_check, run_ctl, run_doctor, run_list, run_list_gpus, run_new,
+    run_print, run_render, run_replay, run_sheet, run_stdlib, run_test, run_thumb,
+};
+use crate::gpu::AdapterSupport;
+use crate::threaded_event_loop::run_threaded_event_loop;
+use crate::utils::cli::{load_validated_project, Command};
+use crate::utils::control::{self, ControlServer};
+use crate::utils::messages;
+use crate::utils::mpris::MprisPlayer;
+use crate::utils::screensaver::wait_for_idle;
+use crate::utils::sequencer::{self, DemoScript};
+use crate::utils::Cli;
+use crate::windowed_event_loop::run_windowed_event_loop;
+use crate::ShadertuiError;
+
+// AIDEV-NOTE: The whole command line program; main.rs only calls this. Embedders want the engine
+// module instead, everything else stays private
+pub fn run() -> Result<(), ShadertuiError> {
+    let mut cli = Cli::parse_args()?;
+    messages::init();
+
+    if cli.list_gpus {
+        run_list_gpus();
+        return Ok(());
+    }
+
+    // A window opened with W runs the same command line, unless a subcommand started this run
+    if cli.command.is_none() {
+        cli.mirror_args = std::env::args_os().skip(1).collect();
+    }
+    let mut daemon = false;
+    let mut demo = None;
+    if let Some(command) = cli.command.take() {
+        match command {
+            Command::New(args) => {
+                let path = run_new(&args)?;
+                if !args.run {
+                    return Ok(());
+                }
+                // Captured here since --audio is handled while parsing, before subcommands
+                if args.template.needs_audio() && cli.shell_options.audio.is_none() {
+                    cli.shell_options.audio = Some(AudioCapture::start(None)?);
+                }
+                cli.shader_file = Some(path);
+            }
+            Command::Daemon(args) => {
+                cli.shader_file = Some(args.shader_file);
+                cli.window = args.window;
+                daemon = true;
+            }
+            Command::Demo(args) => {
+                let script = DemoScript::load(&args.script)?;
+                cli.shader_file = Some(script.shader.clone());
+                demo = Some(script);
+            }
+            Command::Thumb(args) => return run_thumb(args, cli.shell_options, cli.watermark),
+            Command::Sheet(args) => return run_sheet(args, cli.shell_options, cli.watermark),
+            Command::Render(args) => return run_render(args, cli.shell_options, cli.watermark),
+            Command::Bench(args) => return run_bench(args, cli.shell_options),
+            Command::List(args) => return run_list(args, cli.shell_options),
+            Command::Cache { action } => return run_cache(action),
+            Command::Doctor(args) => return run_doctor(args, cli.shell_options),
+            Command::Stdlib { action } => return run_stdlib(action),
+            Command::Ctl { action } => return run_ctl(action, cli.shell_options),
+            Command::Replay(args) => return run_replay(args, cli.shell_options),
+            Command::Test(args) => return run_test(args, cli.shell_options),
+            Command::Check(args) => return run_check(args, cli.shell_options),
+            Command::Print(args) => return run_print(args, cli.shell_options),
+        }
+    }
+    if cli.mirror_args.is_empty() {
+        cli.mirror_args.push(cli.shader_file().into());
+    }
+    if cli.screensaver && !wait_for_idle(cli.idle_delay)? {
+        return Ok(());
+    }
+
+    // Shaders a demo switches to are checked now, not minutes into the show
+    if let Some(script) = &demo {
+        for shader in script.shaders().skip(1) {
+            load_validated_project(shader, &cli.shell_options)?;
+        }
+    }
+    let project = load_validated_project(cli.shader_file(), &cli.shell_options)?;
+    cli.apply_settings(&project.settings);
+    cli.apply_adapter(AdapterSupport::probe().as_ref());
+
+    // AIDEV-NOTE: A daemon is a normal run that also takes commands from `shadertui ctl`. The server
+    // starts once the first shader is valid (validation errors exit without running destructors) and
+    // is kept alive until the renderer exits, then removes its socket. The MPRIS player likewise keeps
+    // its bus name until then
+    let control_server = daemon
+        .then(|| ControlServer::start(cli.shell_options.textures.len()))
+        .transpose()?;
+    let mpris = cli
+        .mpris
+        .then(|| MprisPlayer::start(cli.shader_file()))
+        .transpose()?;
+    let control = control::merge(
+        [
+            control_server
+                .as_ref()
+                .map(|server| server.commands.clone()),
+            demo.map(sequencer::start),
+            mpris.as_ref().map(|player| player.commands.clone()),
+            cli.follow
+                .then(|| control::read_commands(std::io::BufReader::new(std::io::stdin()))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+    );
+    if cli.is_windowed_mode() {
+        run_windowed_event_loop(cli, project, control)
+    } else {
+        run_threaded_event_loop(cli, project, control)
+    }
+}
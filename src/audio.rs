@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::utils::sample_view::spectrum;
+
+// AIDEV-NOTE: Shadertoy-style audio channel: a 512x2 texture whose bottom row (v = 0.25) is the
+// spectrum of the latest samples in [0, 1] and whose top row (v = 0.75) is the waveform, with
+// silence at 0.5
+pub const AUDIO_TEXTURE_WIDTH: u32 = 512;
+pub const AUDIO_TEXTURE_HEIGHT: u32 = 2;
+// Enough samples for AUDIO_TEXTURE_WIDTH spectrum bins
+const CAPTURE_WINDOW: usize = AUDIO_TEXTURE_WIDTH as usize * 2;
+
+type SampleRing = Arc<Mutex<VecDeque<f32>>>;
+
+// AIDEV-NOTE: Mono mix of the most recent input samples. The capture thread owns the audio stream
+// (cpal streams can't move between threads) and keeps it alive for the rest of the process
+#[derive(Debug, Clone)]
+pub struct AudioCapture {
+    samples: SampleRing,
+}
+
+impl AudioCapture {
+    // AIDEV-NOTE: `device` is an input device name; None picks the system default
+    pub fn start(device: Option<&str>) -> Result<Self, String> {
+        let samples: SampleRing = Arc::new(Mutex::new(VecDeque::from(vec![0.0; CAPTURE_WINDOW])));
+        capture::spawn(device, Arc::clone(&samples))?;
+        Ok(Self { samples })
+    }
+
+    // Silence, for validating shaders that read the audio channel without capturing
+    #[cfg(test)]
+    pub fn silent() -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(VecDeque::from(vec![0.0; CAPTURE_WINDOW]))),
+        }
+    }
+
+    // AIDEV-NOTE: R8 texels for the audio texture, bottom row first
+    pub fn texture_data(&self) -> Vec<u8> {
+        let samples: Vec<f32> = self.samples.lock().unwrap().iter().copied().collect();
+        let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        let mut data: Vec<u8> = spectrum(&samples).into_iter().map(to_byte).collect();
+        data.extend(
+            samples[samples.len() - AUDIO_TEXTURE_WIDTH as usize..]
+                .iter()
+                .map(|&sample| to_byte(sample * 0.5 + 0.5)),
+        );
+        data
+    }
+}
+
+#[cfg(feature = "audio")]
+mod capture {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::{FromSample, Sample, SampleFormat, SizedSample, StreamConfig};
+
+    use super::SampleRing;
+
+    pub fn spawn(device_name: Option<&str>, samples: SampleRing) -> Result<(), String> {
+        let device_name = device_name.map(str::to_string);
+        let (ready_sender, ready_receiver) = flume::bounded(1);
+
+        std::thread::spawn(move || {
+            let stream = match open_stream(device_name.as_deref(), samples) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = ready_sender.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready_sender.send(Ok(()));
+            let _stream = stream;
+            loop {
+                std::thread::park();
+            }
+        });
+
+        ready_receiver
+            .recv()
+            .map_err(|_| "audio capture thread exited".to_string())?
+    }
+
+    fn open_stream(device_name: Option<&str>, samples: SampleRing) -> Result<cpal::Stream, String> {
+        let host = cpal::default_host();
+        let device = match device_name {
+            None => host.default_input_device(),
+            Some(name) => host
+                .input_devices()
+                .map_err(|e| e.to_string())?
+                .find(|device| device.name().is_ok_and(|device_name| device_name == name)),
+        }
+        .ok_or_else(|| match device_name {
+            Some(name) => format!("audio input device '{name}' not found"),
+            None => "no default audio input device".to_string(),
+        })?;
+
+        let supported = device.default_input_config().map_err(|e| e.to_string())?;
+        let config = supported.config();
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, samples),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, samples),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, samples),
+            SampleFormat::I32 => build_stream::<i32>(&device, &config, samples),
+            other => return Err(format!("unsupported audio sample format '{other}'")),
+        }
+        .map_err(|e| e.to_string())?;
+        stream.play().map_err(|e| e.to_string())?;
+
+        Ok(stream)
+    }
+
+    fn push_samples(samples: &SampleRing, new_samples: impl Iterator<Item = f32>) {
+        let mut samples = samples.lock().unwrap();
+        for sample in new_samples {
+            samples.pop_front();
+            samples.push_back(sample);
+        }
+    }
+
+    fn build_stream<T>(
+        device: &cpal::Device,
+        config: &StreamConfig,
+        samples: SampleRing,
+    ) -> Result<cpal::Stream, cpal::BuildStreamError>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        let channels = config.channels.max(1) as usize;
+        device.build_input_stream(
+            config,
+            move |data: &[T], _| {
+                let mono = data.chunks(channels).map(|frame| {
+                    frame.iter().map(|&s| f32::from_sample(s)).sum::<f32>() / frame.len() as f32
+                });
+                push_samples(&samples, mono);
+            },
+            // A dropout just leaves the last samples in place; printing would garble the terminal
+            |_| {},
+            None,
+        )
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod capture {
+    use super::SampleRing;
+
+    pub fn spawn(_device_name: Option<&str>, _samples: SampleRing) -> Result<(), String> {
+        Err("shadertui was built without audio support (rebuild with --features audio)".to_string())
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::Instant;
+
+use crate::error::ShadertuiError;
+use crate::gpu::Uniforms;
+use crate::renderers::headless_renderer::EXPORT_FPS;
+use crate::renderers::HeadlessRenderer;
+use crate::utils::benchmark::{compare, BenchResults};
+use crate::utils::cli::{load_validated_project, BenchArgs};
+use crate::utils::shader_shell::ShellOptions;
+
+// AIDEV-NOTE: Times whole frames (every pass, waiting for the GPU to finish) without reading the
+// output back, so results measure the shader rather than the transfer. Frame N is rendered at
+// N / EXPORT_FPS seconds, making runs repeatable
+pub fn run_bench(args: BenchArgs, shell_options: ShellOptions) -> Result<(), ShadertuiError> {
+    let project = load_validated_project(&args.shader_file, &shell_options)?;
+    // Load the baseline first, so a bad path fails before the benchmark runs
+    let baseline = args
+        .baseline
+        .as_deref()
+        .map(BenchResults::load)
+        .transpose()?;
+    let (width, height) = args.size;
+    let mut renderer = HeadlessRenderer::new(width, height, &project, shell_options)?;
+
+    let cursor = [width as i32 / 2, height as i32 / 2];
+    let mut frame_times_ms = Vec::with_capacity(args.frames as usize);
+    for frame in 0..args.warmup + args.frames {
+        let uniforms = Uniforms::new(
+            width,
+            height,
+            frame as f32 / EXPORT_FPS,
+            cursor,
+            frame,
+            1.0 / EXPORT_FPS,
+        );
+        let started = Instant::now();
+        renderer.compute(&uniforms)?;
+        if frame >= args.warmup {
+            frame_times_ms.push(started.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+
+    let results = BenchResults {
+        shader: args.shader_file.display().to_string(),
+        width,
+        height,
+        frame_times_ms,
+    };
+    let summary = results.summary();
+    println!(
+        "{} frames at {width}x{height} ({:.1} FPS by mean frame time)",
+        args.frames,
+        1000.0 / summary.mean
+    );
+    for (name, value) in summary.metrics() {
+        println!("  {name:<6} {value:>9.3} ms");
+    }
+
+    if let Some(path) = &args.save {
+        results.save(path)?;
+        println!("Saved results to {}", path.display());
+    }
+
+    let Some(baseline) = baseline else {
+        return Ok(());
+    };
+    if (baseline.width, baseline.height) != (width, height) {
+        eprintln!(
+            "Warning: the baseline was measured at {}x{}, not {width}x{height}",
+            baseline.width, baseline.height
+        );
+    }
+    println!(
+        "\nCompared to {}:",
+        args.baseline.unwrap_or_default().display()
+    );
+    let changes = compare(&baseline.summary(), &summary, args.threshold);
+    for change in &changes {
+        let verdict = if change.regressed {
+            "REGRESSION"
+        } else if change.percent < -args.threshold {
+            "improvement"
+        } else {
+            ""
+        };
+        let line = format!(
+            "  {:<6} {:>9.3} ms -> {:>9.3} ms  {:>+7.1}%  {verdict}",
+            change.name, change.baseline, change.current, change.percent
+        );
+        println!("{}", line.trim_end());
+    }
+
+    let regressions = changes.iter().filter(|change| change.regressed).count();
+    if regressions > 0 {
+        return Err(format!(
+            "{regressions} frame time metric(s) got more than {}% slower than the baseline",
+            args.threshold
+        )
+        .into());
+    }
+    println!("Within {}% of the baseline", args.threshold);
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::ShadertuiError;
+use crate::utils::cli::CacheAction;
+use crate::utils::paths::cache_dir;
+
+pub fn run_cache(action: CacheAction) -> Result<(), ShadertuiError> {
+    let dir = cache_dir().ok_or("Could not determine the cache directory (is $HOME set?)")?;
+
+    match action {
+        CacheAction::Ls => {
+            println!("{}", dir.display());
+            for (name, size) in list_entries(&dir)? {
+                println!("  {:>10}  {name}", format_size(size));
+            }
+        }
+        CacheAction::Size => {
+            let total: u64 = list_entries(&dir)?.iter().map(|(_, size)| size).sum();
+            println!("{}  {}", format_size(total), dir.display());
+        }
+        CacheAction::Clear => {
+            if dir.exists() {
+                fs::remove_dir_all(&dir)
+                    .map_err(|e| format!("Could not clear '{}': {e}", dir.display()))?;
+            }
+            println!("Cleared {}", dir.display());
+        }
+    }
+
+    Ok(())
+}
+
+// AIDEV-NOTE: Top-level cache entries with their total size; a missing cache dir is just empty
+fn list_entries(dir: &Path) -> Result<Vec<(String, u64)>, ShadertuiError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        entries.push((
+            entry.file_name().to_string_lossy().into_owned(),
+            disk_usage(&entry.path()),
+        ));
+    }
+    entries.sort();
+
+    Ok(entries)
+}
+
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| disk_usage(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
@@ -0,0 +1,71 @@
+use crate::error::ShadertuiError;
+use crate::utils::cli::{CheckArgs, CheckFormat};
+use crate::utils::project::ShaderProject;
+use crate::utils::shader_shell::{ShellOptions, ShellType};
+use crate::utils::validation::{check_project, CompileFailure, Diagnostic};
+
+// AIDEV-NOTE: Everything a run does before touching the GPU: imports (or the Shadertoy and manifest
+// loaders), injection into the terminal and the window shell, and naga validation. An error that
+// both shells report is listed once
+pub fn run_check(args: CheckArgs, shell_options: ShellOptions) -> Result<(), ShadertuiError> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut failed = 0;
+    for path in &args.shader_files {
+        let failures = match ShaderProject::load(path) {
+            Ok(project) => shell_failures(&project, &shell_options),
+            Err(e) => {
+                failed += 1;
+                let error = ShadertuiError::from(e);
+                match args.format {
+                    CheckFormat::Text => println!("{error}"),
+                    CheckFormat::Json => {
+                        diagnostics.push(Diagnostic::unlocated(path, error.to_string()))
+                    }
+                }
+                continue;
+            }
+        };
+
+        if !failures.is_empty() {
+            failed += 1;
+        }
+        match args.format {
+            CheckFormat::Text if failures.is_empty() => println!("{}: ok", path.display()),
+            CheckFormat::Text => {
+                for failure in failures {
+                    println!("{}", ShadertuiError::from(failure));
+                }
+            }
+            CheckFormat::Json => {
+                diagnostics.extend(failures.iter().map(|failure| failure.diagnostic(path)));
+            }
+        }
+    }
+
+    if args.format == CheckFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string(&diagnostics).map_err(|e| e.to_string())?
+        );
+    }
+    if failed > 0 {
+        return Err(format!(
+            "{failed} of {} shaders failed to compile",
+            args.shader_files.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn shell_failures(project: &ShaderProject, shell_options: &ShellOptions) -> Vec<CompileFailure> {
+    let mut failures = Vec::new();
+    for shell_type in [ShellType::Terminal, ShellType::Window] {
+        if let Err(failure) = check_project(project, shell_type, shell_options) {
+            if !failures.contains(&failure) {
+                failures.push(failure);
+            }
+        }
+    }
+    failures
+}
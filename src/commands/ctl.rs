@@ -0,0 +1,36 @@
+use crate::error::ShadertuiError;
+use crate::utils::cli::{load_validated_project, CtlAction};
+use crate::utils::control::{send_command, ControlCommand};
+use crate::utils::image_import::load_image;
+use crate::utils::shader_shell::ShellOptions;
+
+// AIDEV-NOTE: Shaders and images are checked here first, so mistakes are reported to the caller
+// instead of only showing up on the daemon's screen. The daemon resolves nothing itself, so paths are absolute
+pub fn run_ctl(action: CtlAction, shell_options: ShellOptions) -> Result<(), ShadertuiError> {
+    let command = match action {
+        CtlAction::Load { shader_file } => {
+            load_validated_project(&shader_file, &shell_options)?;
+            let path = shader_file
+                .canonicalize()
+                .map_err(|e| format!("Could not resolve '{}': {e}", shader_file.display()))?;
+            ControlCommand::Load(path)
+        }
+        CtlAction::Texture { index, image } => {
+            load_image(&image)?;
+            let path = image
+                .canonicalize()
+                .map_err(|e| format!("Could not resolve '{}': {e}", image.display()))?;
+            ControlCommand::Texture(index, path)
+        }
+        CtlAction::Pause => ControlCommand::Pause(Some(true)),
+        CtlAction::Resume => ControlCommand::Pause(Some(false)),
+        CtlAction::Seek { offset } => ControlCommand::Seek(offset),
+        CtlAction::Next => ControlCommand::Skip(1),
+        CtlAction::Previous => ControlCommand::Skip(-1),
+        CtlAction::Window => ControlCommand::Window,
+        CtlAction::Quit => ControlCommand::Quit,
+    };
+
+    send_command(&command)?;
+    Ok(())
+}
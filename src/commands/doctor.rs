@@ -0,0 +1,208 @@
+use std::io::IsTerminal;
+
+use crate::error::ShadertuiError;
+use crate::gpu::{
+    compatibility_problems, enumerate_adapters, render_nodes, AdapterSupport, GpuDevice, RenderSize,
+};
+use crate::utils::antialias::scaled_size;
+use crate::utils::cli::{load_validated_project, DoctorArgs};
+use crate::utils::shader_shell::ShellOptions;
+use crate::utils::terminal_caps::{Multiplexer, TerminalCapabilities};
+
+// AIDEV-NOTE: --list-gpus. The numbers are what --adapter takes, so they follow --backend the way
+// adapter selection does
+pub fn run_list_gpus() {
+    let adapters = enumerate_adapters();
+    if adapters.is_empty() {
+        println!("No GPU adapters found");
+    }
+    for (index, adapter) in adapters.iter().enumerate() {
+        let info = adapter.get_info();
+        let driver = [info.driver, info.driver_info]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        print!(
+            "{index}: {} ({:?}, {:?}",
+            info.name, info.backend, info.device_type
+        );
+        if !driver.is_empty() {
+            print!(", {driver}");
+        }
+        println!(")");
+    }
+}
+
+pub fn run_doctor(args: DoctorArgs, shell_options: ShellOptions) -> Result<(), ShadertuiError> {
+    let caps = TerminalCapabilities::probe();
+    let adapters = GpuDevice::list_adapters();
+    let mut suggestions = Vec::new();
+
+    println!("Terminal");
+    println!(
+        "  TERM:            {}",
+        caps.term.as_deref().unwrap_or("(unset)")
+    );
+    println!(
+        "  TERM_PROGRAM:    {}",
+        caps.term_program.as_deref().unwrap_or("(unset)")
+    );
+    println!("  Truecolor:       {}", yes_no(caps.truecolor));
+    println!("  Kitty graphics:  {}", yes_no(caps.kitty_graphics));
+    println!("  Sixel:           {}", yes_no(caps.sixel));
+    println!(
+        "  Multiplexer:     {}",
+        match caps.multiplexer {
+            Some(Multiplexer::Tmux) => "tmux",
+            Some(Multiplexer::Screen) => "screen",
+            None => "none",
+        }
+    );
+    match caps.cell_pixel_size {
+        Some((width, height)) => println!("  Cell size:       {width}x{height} px"),
+        None => println!("  Cell size:       unknown"),
+    }
+    match caps.half_block_width {
+        Some(width) => println!("  Width of ▀:      {width} column(s)"),
+        None if std::io::stdout().is_terminal() => {
+            println!("  Width of ▀:      unknown (terminal did not answer)")
+        }
+        None => println!("  Width of ▀:      unknown (not a TTY)"),
+    }
+
+    if !caps.truecolor {
+        suggestions.push(
+            "The terminal does not advertise truecolor, so colors may be banded or wrong. \
+             If it does support 24-bit color, set COLORTERM=truecolor."
+                .to_string(),
+        );
+    }
+    match caps.multiplexer {
+        Some(Multiplexer::Tmux) => suggestions.push(
+            "Inside tmux, enable truecolor with `set -as terminal-features ',*:RGB'` and graphics \
+             passthrough with `set -g allow-passthrough on` in ~/.tmux.conf, then export \
+             COLORTERM=truecolor."
+                .to_string(),
+        ),
+        Some(Multiplexer::Screen) => suggestions.push(
+            "GNU screen 5 supports truecolor with `truecolor on` in ~/.screenrc; older versions \
+             are limited to 256 colors."
+                .to_string(),
+        ),
+        None => {}
+    }
+    if caps.half_block_width.is_some_and(|width| width != 1) {
+        suggestions.push(
+            "The ▀ glyph is not one column wide here; try `--cell-mode foreground`.".to_string(),
+        );
+    }
+
+    println!();
+    println!("GPU adapters");
+    if adapters.is_empty() {
+        println!("  none found");
+        suggestions.push(
+            "No GPU adapter was found. Install Vulkan/Metal/DX12 drivers (or Mesa's lavapipe for \
+             a software fallback)."
+                .to_string(),
+        );
+    }
+    for adapter in &adapters {
+        println!(
+            "  {} ({:?}, {:?})",
+            adapter.name, adapter.backend, adapter.device_type
+        );
+    }
+    if !adapters.is_empty()
+        && adapters
+            .iter()
+            .all(|adapter| adapter.device_type == wgpu::DeviceType::Cpu)
+    {
+        suggestions.push(
+            "Only a software adapter is available; use a smaller terminal or `--max-fps 15`."
+                .to_string(),
+        );
+    }
+
+    // Drivers open these when there is no display, so on servers they decide whether a GPU is usable
+    if cfg!(target_os = "linux") {
+        println!();
+        println!("Render nodes");
+        let nodes = render_nodes();
+        if nodes.is_empty() {
+            println!("  none in /dev/dri");
+        }
+        for node in &nodes {
+            match &node.access {
+                Ok(()) => println!("  {} (accessible)", node.path.display()),
+                Err(e) => {
+                    println!("  {} (not accessible: {e})", node.path.display());
+                    suggestions.push(format!(
+                        "{} can't be opened; add your user to the `render` group and log in again.",
+                        node.path.display()
+                    ));
+                }
+            }
+        }
+    }
+
+    // AIDEV-NOTE: The limits GpuDevice will get, and with a shader, whether it fits them at this
+    // terminal's size in the default cell mode (the same check a normal run does at startup)
+    if let Some(support) = AdapterSupport::probe() {
+        println!();
+        println!("Default adapter limits ({})", support.name);
+        println!(
+            "  Storage buffer:  {} MB",
+            support.max_storage_buffer() / (1024 * 1024)
+        );
+        println!(
+            "  Texture size:    {} px",
+            support.limits.max_texture_dimension_2d
+        );
+        println!("  f16 shaders:     {}", yes_no(support.supports_f16()));
+
+        if let Some(shader_file) = &args.shader_file {
+            let project = load_validated_project(shader_file, &shell_options)?;
+            let render_size = crossterm::terminal::size().ok().map(|(columns, rows)| {
+                let (pixels_x, pixels_y) = caps.default_cell_mode().pixels_per_cell();
+                let scale = project.settings.scale.unwrap_or(1.0);
+                let (width, height) =
+                    scaled_size(columns as u32 * pixels_x, rows as u32 * pixels_y, scale);
+                RenderSize {
+                    width,
+                    height,
+                    scale,
+                }
+            });
+            let problems = compatibility_problems(&support, &project, &shell_options, render_size);
+            match problems.len() {
+                0 => println!("  Shader:          fits"),
+                count => println!("  Shader:          {count} problem(s), see below"),
+            }
+            for problem in problems {
+                suggestions.push(format!("{}: {problem}.", shader_file.display()));
+            }
+        }
+    }
+
+    println!();
+    if suggestions.is_empty() {
+        println!("Everything looks good.");
+    } else {
+        println!("Suggestions");
+        for suggestion in &suggestions {
+            println!("  - {suggestion}");
+        }
+    }
+
+    Ok(())
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
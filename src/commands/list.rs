@@ -0,0 +1,102 @@
+use std::fs;
+use std::io::{stdout, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use crate::error::ShadertuiError;
+use crate::renderers::HeadlessRenderer;
+use crate::utils::cli::{ColorMode, ListArgs};
+use crate::utils::color::push_ansi_color;
+use crate::utils::image_export::RgbaImage;
+use crate::utils::project::{is_shader_path, ShaderProject};
+use crate::utils::shader_shell::{ShellOptions, ShellType};
+use crate::utils::terminal_caps::TerminalCapabilities;
+use crate::utils::validation::validate_project;
+
+pub fn run_list(args: ListArgs, shell_options: ShellOptions) -> Result<(), ShadertuiError> {
+    let entries = list_entries(&args.dir)
+        .map_err(|e| format!("Error reading '{}': {e}", args.dir.display()))?;
+    if entries.is_empty() {
+        println!("No shaders found in '{}'", args.dir.display());
+        return Ok(());
+    }
+
+    // Previews are escape codes, so leave them out when the list is piped somewhere
+    let preview = !args.no_preview && stdout().is_terminal();
+    let color_mode = args
+        .color_mode
+        .unwrap_or_else(|| TerminalCapabilities::detect().default_color_mode());
+
+    for (index, path) in entries.iter().enumerate() {
+        if preview && index > 0 {
+            println!();
+        }
+        let name = path.strip_prefix(&args.dir).unwrap_or(path).display();
+
+        let loaded = ShaderProject::load(path)
+            .map_err(|e| e.to_string())
+            .and_then(|project| {
+                validate_project(&project, ShellType::Terminal, &shell_options)
+                    .map(|()| project)
+                    .map_err(|e| e.to_string())
+            });
+        let project = match loaded {
+            Ok(project) => project,
+            Err(e) => {
+                println!("{name}  (error: {})", e.lines().next().unwrap_or_default());
+                continue;
+            }
+        };
+        match project.metadata.display_title() {
+            Some(title) => println!("{name}  {title}"),
+            None => println!("{name}"),
+        }
+        if !preview {
+            continue;
+        }
+
+        let (columns, rows) = args.cells;
+        let mut renderer =
+            HeadlessRenderer::new(columns, rows * 2, &project, shell_options.clone())?;
+        let gpu_data = renderer.render_at(args.at)?;
+        let image = RgbaImage::from_gpu_data(&gpu_data, columns, rows * 2);
+        print!("{}", half_block_preview(&image, color_mode));
+    }
+
+    Ok(())
+}
+
+// AIDEV-NOTE: Shader files and project.toml files one directory down, sorted by path
+fn list_entries(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && is_shader_path(&path) {
+            entries.push(path);
+        } else if path.join("project.toml").is_file() {
+            entries.push(path.join("project.toml"));
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+// AIDEV-NOTE: Two image rows per line of ▀ cells, indented under the shader's name
+fn half_block_preview(image: &RgbaImage, color_mode: ColorMode) -> String {
+    let pixel = |x: u32, y: u32| {
+        let index = ((y * image.width + x) * 4) as usize;
+        let rgb = &image.pixels[index..index + 3];
+        (rgb[0], rgb[1], rgb[2])
+    };
+
+    let mut preview = String::new();
+    for row in 0..image.height / 2 {
+        preview.push_str("  ");
+        for x in 0..image.width {
+            push_ansi_color(&mut preview, "38", pixel(x, row * 2), color_mode);
+            push_ansi_color(&mut preview, "48", pixel(x, row * 2 + 1), color_mode);
+            preview.push('▀');
+        }
+        preview.push_str("\x1b[0m\n");
+    }
+    preview
+}
@@ -0,0 +1,29 @@
+pub mod bench;
+pub mod cache;
+pub mod check;
+pub mod ctl;
+pub mod doctor;
+pub mod list;
+pub mod new;
+pub mod print;
+pub mod render;
+pub mod replay;
+pub mod sheet;
+pub mod stdlib;
+pub mod test;
+pub mod thumb;
+
+pub use bench::run_bench;
+pub use cache::run_cache;
+pub use check::run_check;
+pub use ctl::run_ctl;
+pub use doctor::{run_doctor, run_list_gpus};
+pub use list::run_list;
+pub use new::run_new;
+pub use print::run_print;
+pub use render::run_render;
+pub use replay::run_replay;
+pub use sheet::run_sheet;
+pub use stdlib::run_stdlib;
+pub use test::run_test;
+pub use thumb::run_thumb;
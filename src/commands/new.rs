@@ -0,0 +1,41 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::ShadertuiError;
+use crate::utils::cli::NewArgs;
+use crate::utils::templates::template_source;
+
+// AIDEV-NOTE: Writes the template and returns where it went; main opens it afterwards for --run
+pub fn run_new(args: &NewArgs) -> Result<PathBuf, ShadertuiError> {
+    let mut path = args.path.clone();
+    if path.extension().is_none() {
+        path.set_extension("wgsl");
+    }
+    if path.exists() && !args.force {
+        return Err(format!(
+            "'{}' already exists (use --force to replace it)",
+            path.display()
+        )
+        .into());
+    }
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Could not create '{}': {e}", parent.display()))?;
+    }
+    fs::write(&path, template_source(args.template))
+        .map_err(|e| format!("Could not write '{}': {e}", path.display()))?;
+
+    println!("Wrote {}", path.display());
+    if !args.run {
+        let audio = if args.template.needs_audio() {
+            "--audio "
+        } else {
+            ""
+        };
+        println!("Run it with: shadertui {audio}{}", path.display());
+    }
+    Ok(path)
+}
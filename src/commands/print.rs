@@ -0,0 +1,35 @@
+use crate::engine::TerminalFrame;
+use crate::error::ShadertuiError;
+use crate::gpu::FramePixels;
+use crate::renderers::HeadlessRenderer;
+use crate::utils::cli::{load_validated_project, CellMode, PrintArgs};
+use crate::utils::shader_shell::ShellOptions;
+
+// AIDEV-NOTE: One frame of half blocks on stdout, for chat bots, MOTD generators and other places
+// that take text rather than a terminal. With --max-bytes the colors get coarser until the frame
+// fits (see TerminalFrame::to_ansi_within)
+pub fn run_print(args: PrintArgs, shell_options: ShellOptions) -> Result<(), ShadertuiError> {
+    let project = load_validated_project(&args.shader_file, &shell_options)?;
+    let (columns, rows) = args.cells;
+    let (columns, rows) = (
+        u16::try_from(columns).map_err(|_| format!("Too many columns: {columns}"))?,
+        u16::try_from(rows).map_err(|_| format!("Too many rows: {rows}"))?,
+    );
+    let (width, height) = (columns as u32, rows as u32 * 2);
+    let mut renderer = HeadlessRenderer::new(width, height, &project, shell_options)?;
+
+    let pixels = FramePixels::Linear(renderer.render_at(args.at)?);
+    let frame =
+        TerminalFrame::from_pixels(&pixels, width as usize, columns, rows, CellMode::HalfBlock);
+    let max_bytes = args.max_bytes.unwrap_or(usize::MAX);
+    let text = frame
+        .to_ansi_within(args.color_mode, max_bytes)
+        .ok_or_else(|| {
+            format!(
+                "A {columns}x{rows} frame needs more than {max_bytes} bytes even without colors"
+            )
+        })?;
+    println!("{text}");
+
+    Ok(())
+}
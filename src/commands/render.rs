@@ -0,0 +1,137 @@
+use std::fs;
+
+use crate::error::ShadertuiError;
+use crate::renderers::HeadlessRenderer;
+use crate::utils::cli::{load_validated_project, RenderArgs};
+use crate::utils::image_export::RgbaImage;
+use crate::utils::shader_shell::ShellOptions;
+use crate::utils::watermark::Watermark;
+
+// Frame numbers are zero-padded to at least this many digits so files sort in order
+const MIN_FRAME_DIGITS: usize = 4;
+// Exit statuses above this are truncated by the OS
+const MAX_EXIT_CODE: u32 = 255;
+
+// AIDEV-NOTE: A shader calling request_exit(code) ends the render after that frame is written, and
+// shadertui exits with `code`, so batch jobs can run "until converged" with --frames as the limit
+pub fn run_render(
+    args: RenderArgs,
+    shell_options: ShellOptions,
+    watermark: Option<Watermark>,
+) -> Result<(), ShadertuiError> {
+    let shell_options = ShellOptions {
+        exit_requests: true,
+        ..shell_options
+    };
+    let project = load_validated_project(&args.shader_file, &shell_options)?;
+    let (width, height) = args.size;
+    let mut renderer = HeadlessRenderer::new(width, height, &project, shell_options)?;
+
+    fs::create_dir_all(&args.out)
+        .map_err(|e| format!("Could not create '{}': {e}", args.out.display()))?;
+    let frame_range = args.frame_range()?;
+    let digits = (frame_range.end - 1)
+        .to_string()
+        .len()
+        .max(MIN_FRAME_DIGITS);
+    let fps = args.fps as f32;
+
+    let mut frames = frame_range.len() as u32;
+    let mut exit_code = None;
+    for (index, frame) in frame_range.clone().enumerate() {
+        let gpu_data = renderer.render_frame(frame, fps)?;
+        let mut image = RgbaImage::from_gpu_data(&gpu_data, width, height);
+        // The matte shows the shader's alpha, not the watermark box
+        if args.matte {
+            let path = args.out.join(format!("matte_{frame:0digits$}.png"));
+            image.alpha_matte().write_png(&path, &project.metadata)?;
+        }
+        if let Some(watermark) = &watermark {
+            watermark.draw(&mut image, frame as f32 / fps, frame);
+        }
+        let path = args.out.join(format!("frame_{frame:0digits$}.png"));
+        image.write_png(&path, &project.metadata)?;
+
+        if let Some(code) = renderer.exit_requested() {
+            frames = index as u32 + 1;
+            exit_code = Some(code.min(MAX_EXIT_CODE));
+            break;
+        }
+    }
+
+    println!(
+        "Wrote {} {}x{} frames ({:.2}s from {:.2}s at {} FPS) to {}",
+        frames,
+        width,
+        height,
+        frames as f32 / fps,
+        frame_range.start as f32 / fps,
+        args.fps,
+        args.out.display()
+    );
+
+    match exit_code {
+        Some(code) => {
+            println!("The shader requested exit code {code}");
+            Err(ShadertuiError::ShaderExit(code))
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_segment() {
+        let dir = std::env::temp_dir().join("shadertui_test_render_segment");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let shader_file = dir.join("shader.wgsl");
+        fs::write(
+            &shader_file,
+            "fn compute_color(coords: vec2<f32>) -> vec3<f32> {\n    \
+             return vec3<f32>(fract(uniforms.time));\n}\n",
+        )
+        .unwrap();
+        let out = dir.join("frames");
+        let args = RenderArgs {
+            shader_file,
+            out: out.clone(),
+            frames: 60,
+            fps: 4,
+            from: 1.0,
+            to: Some(1.6),
+            matte: true,
+            size: (4, 2),
+        };
+        match run_render(args, ShellOptions::default(), None) {
+            Err(ShadertuiError::NoAdapter(_)) => return,
+            result => result.unwrap(),
+        }
+
+        // 1s and 1.6s snap to frames 4 and 6 at 4 FPS; the end is exclusive
+        let mut written: Vec<PathBuf> = fs::read_dir(&out)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        written.sort();
+        let names: Vec<_> = written
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "frame_0004.png",
+                "frame_0005.png",
+                "matte_0004.png",
+                "matte_0005.png"
+            ]
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+use std::fs;
+
+use crate::error::ShadertuiError;
+use crate::renderers::HeadlessRenderer;
+use crate::utils::cli::{load_validated_project, ReplayArgs};
+use crate::utils::image_export::RgbaImage;
+use crate::utils::shader_shell::ShellOptions;
+use crate::utils::trace::{self, TraceEvent};
+
+// AIDEV-NOTE: Replays the uniform timeline of a --trace on a HeadlessRenderer: every frame the GPU
+// thread computed is computed again in order (so buffer passes and state evolve the same way) with
+// the recorded uniforms and `// @uniform` values, resizing and reloading where the GPU thread did.
+// Reloads recompile the one shader being replayed (resetting state as they did), since the trace
+// doesn't keep the sources; neither does it keep `ctl texture` images
+pub fn run_replay(args: ReplayArgs, shell_options: ShellOptions) -> Result<(), ShadertuiError> {
+    let records = trace::read(&args.trace)?;
+    let recorded_shader = records.iter().find_map(|record| match &record.event {
+        TraceEvent::Start { shader } => Some(shader.clone()),
+        _ => None,
+    });
+    let shader = args
+        .shader
+        .or(recorded_shader)
+        .ok_or("The trace doesn't name its shader; pass one with --shader")?;
+    let project = load_validated_project(&shader, &shell_options)?;
+
+    // Frames that reached the frame buffer are the ones worth looking at
+    let written: HashSet<u32> = records
+        .iter()
+        .filter_map(|record| match record.event {
+            TraceEvent::FrameWritten { frame, .. } => Some(frame),
+            _ => None,
+        })
+        .collect();
+    if let Some(out) = &args.out {
+        fs::create_dir_all(out)
+            .map_err(|e| format!("Could not create '{}': {e}", out.display()))?;
+    }
+
+    let mut renderer: Option<HeadlessRenderer> = None;
+    let (mut computed, mut saved, mut dropped, mut read, mut mutations, mut errors) =
+        (0, 0, 0, 0, 0, 0);
+    for record in &records {
+        if args.timeline {
+            println!("{:>10.4}s {:<9} {}", record.t, record.thread, record.event);
+        }
+        match &record.event {
+            TraceEvent::Frame(frame) => {
+                let (width, height) = frame.size();
+                let renderer = match &mut renderer {
+                    Some(renderer) => renderer,
+                    None => renderer.insert(HeadlessRenderer::new(
+                        width,
+                        height,
+                        &project,
+                        shell_options.clone(),
+                    )?),
+                };
+                if renderer.size() != (width, height) {
+                    renderer.resize(width, height);
+                }
+                renderer.set_param_values(frame.param_values());
+                computed += 1;
+
+                let out = args.out.as_ref().filter(|_| written.contains(&frame.frame));
+                let result = match out {
+                    Some(out) => renderer.render(&frame.uniforms()).and_then(|pixels| {
+                        let image = RgbaImage::from_gpu_data(&pixels, width, height);
+                        let path = out.join(format!("frame_{:06}.png", frame.frame));
+                        saved += 1;
+                        image.write_png(&path, &project.metadata)
+                    }),
+                    None => renderer.compute(&frame.uniforms()),
+                };
+                if let Err(e) = result {
+                    println!("{:>10.4}s frame {}: {e}", record.t, frame.frame);
+                }
+            }
+            TraceEvent::Reload => {
+                if let Some(renderer) = &mut renderer {
+                    if let Err(e) = renderer.reload_shader(&project) {
+                        println!("{:>10.4}s reload: {e}", record.t);
+                    }
+                }
+            }
+            TraceEvent::FrameWritten {
+                dropped: Some(_), ..
+            }
+            | TraceEvent::FrameSkipped => dropped += 1,
+            TraceEvent::FrameRead { .. } => read += 1,
+            TraceEvent::Mutation { .. } => mutations += 1,
+            TraceEvent::Error { .. } => errors += 1,
+            _ => {}
+        }
+    }
+
+    let duration = records.last().map_or(0.0, |record| record.t);
+    println!(
+        "Replayed {computed} frames of {} ({duration:.2}s): {} written to the frame buffer, \
+         {dropped} dropped or not read back, {read} read by the terminal, {mutations} uniform \
+         changes, {errors} thread errors",
+        shader.display(),
+        written.len(),
+    );
+    if let Some(out) = &args.out {
+        println!("Wrote {saved} frames to {}", out.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use crate::error::ShadertuiError;
+use crate::renderers::{headless_renderer::EXPORT_FPS, HeadlessRenderer};
+use crate::utils::cli::{load_validated_project, SheetArgs};
+use crate::utils::image_export::RgbaImage;
+use crate::utils::shader_shell::ShellOptions;
+use crate::utils::watermark::Watermark;
+
+pub fn run_sheet(
+    args: SheetArgs,
+    shell_options: ShellOptions,
+    watermark: Option<Watermark>,
+) -> Result<(), ShadertuiError> {
+    let project = load_validated_project(&args.shader_file, &shell_options)?;
+    let (width, height) = args.size;
+    let times = args.cell_times();
+    let cols = args.cols.min(times.len() as u32);
+    let rows = (times.len() as u32).div_ceil(cols);
+    let mut renderer = HeadlessRenderer::new(width, height, &project, shell_options)?;
+
+    // Render in time order so buffer passes only step forward, then place each frame in its cell
+    let mut order: Vec<usize> = (0..times.len()).collect();
+    order.sort_by(|&a, &b| times[a].total_cmp(&times[b]));
+
+    let mut sheet = RgbaImage::new(width * cols, height * rows);
+    let mut matte = args.matte.as_ref().map(|_| sheet.alpha_matte());
+    for index in order {
+        let gpu_data = renderer.render_at(times[index])?;
+        let mut frame = RgbaImage::from_gpu_data(&gpu_data, width, height);
+        let (col, row) = (index as u32 % cols, index as u32 / cols);
+        // Each cell is labeled with its own time; the matte shows the shader's alpha only
+        if let Some(matte) = &mut matte {
+            matte.blit(&frame.alpha_matte(), col * width, row * height);
+        }
+        if let Some(watermark) = &watermark {
+            let time = times[index];
+            watermark.draw(&mut frame, time, (time * EXPORT_FPS).round() as u32);
+        }
+        sheet.blit(&frame, col * width, row * height);
+    }
+
+    sheet.write_png(&args.output, &project.metadata)?;
+    println!(
+        "Wrote {}x{} contact sheet ({} frames) to {}",
+        sheet.width,
+        sheet.height,
+        times.len(),
+        args.output.display()
+    );
+    if let (Some(matte_path), Some(matte)) = (&args.matte, matte) {
+        matte.write_png(matte_path, &project.metadata)?;
+        println!("Wrote alpha matte to {}", matte_path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use crate::error::ShadertuiError;
+use crate::utils::cli::StdlibAction;
+use crate::utils::stdlib::{function_names, MODULES, STD_PREFIX};
+
+pub fn run_stdlib(action: StdlibAction) -> Result<(), ShadertuiError> {
+    match action {
+        StdlibAction::List => {
+            for module in MODULES {
+                println!("{STD_PREFIX}{:<10} {}", module.name, module.description);
+                println!("    {}", function_names(module).join(", "));
+            }
+        }
+    }
+
+    Ok(())
+}
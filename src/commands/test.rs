@@ -0,0 +1,66 @@
+use std::fs;
+
+use crate::error::ShadertuiError;
+use crate::gpu::Uniforms;
+use crate::renderers::HeadlessRenderer;
+use crate::utils::cli::TestArgs;
+use crate::utils::project::ShaderProject;
+use crate::utils::shader_shell::{ShellOptions, ShellType};
+use crate::utils::shader_tests::{discover_tests, test_shell, with_compute_color, TEST_PRAGMA};
+use crate::utils::validation::validate_project;
+
+// AIDEV-NOTE: Compiles the shader (imports included) into the test shell and renders one frame a
+// pixel per test wide, at time 0 with the default `// @uniform` values. Tests in imported files
+// run too, so a file importing a library can serve as its test suite
+pub fn run_test(args: TestArgs, shell_options: ShellOptions) -> Result<(), ShadertuiError> {
+    let path = &args.shader_file;
+    let source = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read '{}': {e}", path.display()))?;
+    let project = ShaderProject::from_source(path, &with_compute_color(&source)?)?;
+    let tests =
+        discover_tests(&project.output.source).map_err(|e| format!("{}: {e}", path.display()))?;
+    if tests.is_empty() {
+        println!("No `{TEST_PRAGMA}` functions in {}", path.display());
+        return Ok(());
+    }
+
+    let shell_options = ShellOptions {
+        custom_shell: Some(test_shell(&tests)),
+        ..shell_options
+    };
+    // Reported like load_validated_project does
+    validate_project(&project, ShellType::Terminal, &shell_options)?;
+    let width = tests.len() as u32;
+    let mut renderer = HeadlessRenderer::new(width, 1, &project, shell_options)?;
+    let results = renderer.render(&Uniforms::new(width, 1, 0.0, [0, 0], 0, 0.0))?;
+
+    println!("running {} tests from {}", tests.len(), path.display());
+    let mut failed = 0;
+    for (test, result) in tests.iter().zip(results.chunks_exact(4)) {
+        let verdict = match (result[1] > 0.5, result[0] > 0.5) {
+            (true, true) => "ok",
+            (true, false) => "FAILED",
+            (false, _) => "DID NOT RUN",
+        };
+        if verdict == "ok" {
+            println!("test {} ... ok", test.name);
+            continue;
+        }
+        failed += 1;
+        match project.output.source_map.origin(test.line_index) {
+            Some(origin) => println!(
+                "test {} ... {verdict} ({}:{})",
+                test.name,
+                origin.file.display(),
+                origin.line
+            ),
+            None => println!("test {} ... {verdict}", test.name),
+        }
+    }
+
+    println!("\n{} passed, {failed} failed", tests.len() - failed);
+    if failed > 0 {
+        return Err(format!("{failed} of {} shader tests failed", tests.len()).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::error::ShadertuiError;
+use crate::renderers::{headless_renderer::EXPORT_FPS, HeadlessRenderer};
+use crate::utils::cli::{load_validated_project, ThumbArgs};
+use crate::utils::image_export::RgbaImage;
+use crate::utils::shader_shell::ShellOptions;
+use crate::utils::watermark::Watermark;
+
+pub fn run_thumb(
+    args: ThumbArgs,
+    shell_options: ShellOptions,
+    watermark: Option<Watermark>,
+) -> Result<(), ShadertuiError> {
+    let project = load_validated_project(&args.shader_file, &shell_options)?;
+    let (width, height) = args.size;
+    let mut renderer = HeadlessRenderer::new(width, height, &project, shell_options)?;
+
+    let gpu_data = renderer.render_at(args.at)?;
+    let mut image = RgbaImage::from_gpu_data(&gpu_data, width, height);
+    // The matte shows the shader's alpha, not the watermark box
+    let matte = args.matte.as_ref().map(|_| image.alpha_matte());
+    if let Some(watermark) = &watermark {
+        let frame = (args.at * EXPORT_FPS).round() as u32;
+        watermark.draw(&mut image, args.at, frame);
+    }
+    image.write_png(&args.output, &project.metadata)?;
+    println!(
+        "Wrote {}x{} thumbnail to {}",
+        width,
+        height,
+        args.output.display()
+    );
+    if let (Some(matte_path), Some(matte)) = (&args.matte, matte) {
+        matte.write_png(matte_path, &project.metadata)?;
+        println!("Wrote alpha matte to {}", matte_path.display());
+    }
+
+    Ok(())
+}
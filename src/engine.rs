@@ -0,0 +1,445 @@
+//! Render shaders into terminal cells from another program, e.g. a ratatui widget:
+//!
+//! ```no_run
+//! use shadertui::{ColorMode, ShaderEngine};
+//!
+//! let mut engine = ShaderEngine::load("plasma.wgsl", 80, 24)?;
+//! let frame = engine.render(1.5)?;
+//! for row in 0..frame.rows() {
+//!     for cell in frame.row(row) {
+//!         // Draw cell.glyph with cell.fg and cell.bg into your own buffer
+//!     }
+//! }
+//! print!("{}", frame.to_ansi(ColorMode::Truecolor));
+//! # Ok::<(), shadertui::ShadertuiError>(())
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::error::ShadertuiError;
+use crate::gpu::FramePixels;
+use crate::renderers::cells::pixel_cell;
+use crate::renderers::HeadlessRenderer;
+use crate::utils::color::push_ansi_color;
+use crate::utils::project::ShaderProject;
+use crate::utils::shader_shell::{ShellOptions, ShellType};
+use crate::utils::uniform_params::ParamValues;
+use crate::utils::validation::validate_project;
+
+pub use crate::gpu::Uniforms;
+pub use crate::renderers::cell_writer::Cell;
+pub use crate::utils::options::{CellMode, ColorMode};
+
+// AIDEV-NOTE: The library's GPU facade: one HeadlessRenderer sized for a block of terminal cells.
+// Threading, file watching and input stay in the binary; embedders call render() from their own
+// loop and reload() when they see the shader change
+/// A compiled shader (a `.wgsl`/`.frag` file or `project.toml`) rendering into terminal cells.
+pub struct ShaderEngine {
+    renderer: HeadlessRenderer,
+    path: PathBuf,
+    shell_options: ShellOptions,
+    param_values: ParamValues,
+    cell_mode: CellMode,
+    columns: u16,
+    rows: u16,
+    frame: u32,
+    last_time: f32,
+    // The last frame when it was tiled, whose other bands are gone from the GPU (see sample_pixel)
+    tiled_pixels: Option<Vec<f32>>,
+}
+
+impl ShaderEngine {
+    /// Loads, validates and compiles the shader for `columns` x `rows` half-block cells.
+    pub fn load(path: impl AsRef<Path>, columns: u16, rows: u16) -> Result<Self, ShadertuiError> {
+        let path = path.as_ref().to_path_buf();
+        let shell_options = ShellOptions::default();
+        let project = load_project(&path, &shell_options)?;
+        let cell_mode = CellMode::HalfBlock;
+        let (width, height) = gpu_size(columns, rows, cell_mode);
+        let renderer = HeadlessRenderer::new(width, height, &project, shell_options.clone())?;
+
+        Ok(Self {
+            renderer,
+            path,
+            shell_options,
+            param_values: ParamValues::new(&project.params),
+            cell_mode,
+            columns,
+            rows,
+            frame: 0,
+            last_time: 0.0,
+            tiled_pixels: None,
+        })
+    }
+
+    /// Reads the shader and its imports again. On error the previous shader keeps rendering.
+    pub fn reload(&mut self) -> Result<(), ShadertuiError> {
+        let project = load_project(&self.path, &self.shell_options)?;
+        self.renderer.reload_shader(&project)?;
+        self.param_values.set_params(&project.params);
+        self.frame = 0;
+        Ok(())
+    }
+
+    pub fn resize(&mut self, columns: u16, rows: u16) {
+        self.columns = columns;
+        self.rows = rows;
+        let (width, height) = gpu_size(columns, rows, self.cell_mode);
+        self.renderer.resize(width, height);
+        self.tiled_pixels = None;
+    }
+
+    /// How pixels map to cells; half blocks (two pixels per cell) by default.
+    pub fn set_cell_mode(&mut self, cell_mode: CellMode) {
+        self.cell_mode = cell_mode;
+        self.resize(self.columns, self.rows);
+    }
+
+    /// Calls `hook` with every frame's uniforms before they are uploaded to the GPU, to inject
+    /// values of your own (cursor, mouse, time, ...). The resolution and `// @uniform` parameters
+    /// are set afterwards; use [`set_param`](Self::set_param) for those.
+    pub fn on_before_upload(&mut self, hook: impl FnMut(&mut Uniforms) + Send + 'static) {
+        self.renderer.set_before_upload(Some(Box::new(hook)));
+    }
+
+    /// Calls `hook` with every frame's pixels as soon as they are read back: RGBA floats as the
+    /// shader returned them, bottom row first, before any conversion to cells.
+    pub fn on_after_readback(&mut self, hook: impl FnMut(&[f32]) + Send + 'static) {
+        self.renderer.set_after_readback(Some(Box::new(hook)));
+    }
+
+    /// Removes the hooks set with [`on_before_upload`](Self::on_before_upload) and
+    /// [`on_after_readback`](Self::on_after_readback).
+    pub fn clear_hooks(&mut self) {
+        self.renderer.set_before_upload(None);
+        self.renderer.set_after_readback(None);
+    }
+
+    /// Sets a `// @uniform` parameter; unknown names are ignored.
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        self.param_values.set(name, value);
+    }
+
+    /// Renders the frame at `time` seconds. Feedback passes advance one step per call.
+    pub fn render(&mut self, time: f32) -> Result<TerminalFrame, ShadertuiError> {
+        let (width, height) = gpu_size(self.columns, self.rows, self.cell_mode);
+        let uniforms = Uniforms::new(
+            width,
+            height,
+            time,
+            [width as i32 / 2, height as i32 / 2],
+            self.frame,
+            (time - self.last_time).max(0.0),
+        );
+        self.renderer.set_param_values(self.param_values.clone());
+        let pixels = FramePixels::Linear(self.renderer.render(&uniforms)?);
+        self.frame += 1;
+        self.last_time = time;
+
+        let frame = TerminalFrame::from_pixels(
+            &pixels,
+            width as usize,
+            self.columns,
+            self.rows,
+            self.cell_mode,
+        );
+        self.tiled_pixels = self.renderer.is_tiled().then(|| pixels.into_linear());
+        Ok(frame)
+    }
+
+    /// The RGBA color the shader returned for pixel (`x`, `y`) of the last rendered frame,
+    /// counted from the top left, before any conversion to cells. Each cell covers
+    /// [`CellMode::pixels_per_cell`] pixels. Only this pixel is copied off the GPU, so it is cheap
+    /// enough for color pickers and hit-testing on every mouse move. Frames too large for one GPU
+    /// buffer are rendered in bands; those are kept on the CPU and sampled from there.
+    pub fn sample_pixel(&self, x: u32, y: u32) -> Result<[f32; 4], ShadertuiError> {
+        let (width, height) = gpu_size(self.columns, self.rows, self.cell_mode);
+        if x >= width || y >= height {
+            return Err(ShadertuiError::Other(format!(
+                "Pixel ({x}, {y}) is outside the {width}x{height} frame"
+            )));
+        }
+        let y = height - 1 - y;
+        match &self.tiled_pixels {
+            Some(pixels) => {
+                let index = (y * width + x) as usize * 4;
+                Ok(pixels[index..index + 4].try_into().unwrap())
+            }
+            None => self.renderer.sample_pixel(x, y),
+        }
+    }
+}
+
+/// One rendered frame as terminal cells, top row first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalFrame {
+    pub(crate) columns: u16,
+    pub(crate) rows: u16,
+    pub(crate) cells: Vec<Cell>,
+}
+
+// AIDEV-NOTE: Steps of to_ansi_within(), from the most faithful colors to the fewest bytes. Colors
+// are snapped to multiples of the step first, so more neighboring cells share one escape. Past the
+// last step only a brightness ramp without escapes is left
+const FIDELITY_STEPS: [(ColorMode, u8); 6] = [
+    (ColorMode::Truecolor, 1),
+    (ColorMode::Truecolor, 8),
+    (ColorMode::Truecolor, 32),
+    (ColorMode::Ansi256, 1),
+    (ColorMode::Ansi256, 64),
+    (ColorMode::Ansi16, 1),
+];
+const BRIGHTNESS_RAMP: &[u8] = b" .:-=+*#%@";
+
+impl TerminalFrame {
+    // `pixels` is a GPU frame `width` pixels wide, with rows counting from the bottom
+    pub(crate) fn from_pixels(
+        pixels: &FramePixels,
+        width: usize,
+        columns: u16,
+        rows: u16,
+        cell_mode: CellMode,
+    ) -> Self {
+        let mut cells = Vec::with_capacity(columns as usize * rows as usize);
+        for row in 0..rows as usize {
+            let flipped_row = rows as usize - 1 - row;
+            cells.extend(
+                (0..columns as usize)
+                    .map(|column| pixel_cell(pixels, width, column, flipped_row, cell_mode)),
+            );
+        }
+        Self {
+            columns,
+            rows,
+            cells,
+        }
+    }
+
+    pub fn columns(&self) -> u16 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u16 {
+        self.rows
+    }
+
+    /// The cells of `row`, counted from the top; empty past the last row.
+    pub fn row(&self, row: u16) -> &[Cell] {
+        let start = row as usize * self.columns as usize;
+        self.cells
+            .get(start..start + self.columns as usize)
+            .unwrap_or_default()
+    }
+
+    pub fn cell(&self, column: u16, row: u16) -> Option<&Cell> {
+        self.row(row).get(column as usize)
+    }
+
+    /// The frame as text with color escapes, one line per row, for writing at the cursor.
+    pub fn to_ansi(&self, mode: ColorMode) -> String {
+        self.encode(mode, 1, "\r\n")
+    }
+
+    /// The frame as text of at most `max_bytes` bytes, for chat bots, MOTDs and other outputs with
+    /// size limits. Starting at `mode`, colors get coarser until the text fits, down to a
+    /// brightness ramp of ASCII characters without escapes. Lines end in `\n`. `None` if even
+    /// the ramp is larger.
+    pub fn to_ansi_within(&self, mode: ColorMode, max_bytes: usize) -> Option<String> {
+        let first_step = FIDELITY_STEPS
+            .iter()
+            .position(|&(step_mode, _)| step_mode == mode)?;
+        FIDELITY_STEPS[first_step..]
+            .iter()
+            .map(|&(mode, step)| self.encode(mode, step, "\n"))
+            .chain(std::iter::once(self.brightness_ramp()))
+            .find(|text| text.len() <= max_bytes)
+    }
+
+    // Colors are snapped to multiples of `step`
+    fn encode(&self, mode: ColorMode, step: u8, newline: &str) -> String {
+        let quantize = |color: Option<(u8, u8, u8)>| color.map(|rgb| quantize(rgb, step));
+        let mut out = String::new();
+        for row in 0..self.rows {
+            if row > 0 {
+                out.push_str(newline);
+            }
+            let (mut fg, mut bg) = (None, None);
+            for cell in self.row(row) {
+                let (cell_fg, cell_bg) = (quantize(cell.fg), quantize(cell.bg));
+                if cell_fg != fg {
+                    match cell_fg {
+                        Some(rgb) => push_ansi_color(&mut out, "38", rgb, mode),
+                        None => out.push_str("\x1b[39m"),
+                    }
+                    fg = cell_fg;
+                }
+                if cell_bg != bg {
+                    match cell_bg {
+                        Some(rgb) => push_ansi_color(&mut out, "48", rgb, mode),
+                        None => out.push_str("\x1b[49m"),
+                    }
+                    bg = cell_bg;
+                }
+                out.push(cell.glyph);
+            }
+            out.push_str("\x1b[0m");
+        }
+        out
+    }
+
+    // One ASCII character per cell for the average brightness of its two colors
+    fn brightness_ramp(&self) -> String {
+        let luma = |color: Option<(u8, u8, u8)>| {
+            color.map_or(0.0, |(r, g, b)| {
+                (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0
+            })
+        };
+        let lines: Vec<String> = (0..self.rows)
+            .map(|row| {
+                self.row(row)
+                    .iter()
+                    .map(|cell| {
+                        let brightness = (luma(cell.fg) + luma(cell.bg)) / 2.0;
+                        let index = (brightness * (BRIGHTNESS_RAMP.len() - 1) as f32).round();
+                        BRIGHTNESS_RAMP[index as usize] as char
+                    })
+                    .collect()
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+fn quantize((r, g, b): (u8, u8, u8), step: u8) -> (u8, u8, u8) {
+    let snap =
+        |value: u8| ((value as u32 + step as u32 / 2) / step as u32 * step as u32).min(255) as u8;
+    (snap(r), snap(g), snap(b))
+}
+
+fn load_project(
+    path: &Path,
+    shell_options: &ShellOptions,
+) -> Result<ShaderProject, ShadertuiError> {
+    let project = ShaderProject::load(path)?;
+    validate_project(&project, ShellType::Terminal, shell_options)?;
+    Ok(project)
+}
+
+fn gpu_size(columns: u16, rows: u16, cell_mode: CellMode) -> (u32, u32) {
+    let (x, y) = cell_mode.pixels_per_cell();
+    ((columns as u32 * x).max(1), (rows as u32 * y).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn test_frame_to_ansi() {
+        let red = Cell {
+            glyph: '▀',
+            fg: Some((255, 0, 0)),
+            bg: Some((0, 0, 0)),
+        };
+        let frame = TerminalFrame {
+            columns: 2,
+            rows: 2,
+            cells: vec![red, red, Cell::BLANK, red],
+        };
+        assert_eq!(frame.cell(1, 1), Some(&red));
+        assert_eq!(frame.cell(2, 0), None);
+        assert!(frame.row(2).is_empty());
+        assert_eq!(
+            frame.to_ansi(ColorMode::Truecolor),
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;0m▀▀\x1b[0m\r\n \
+             \x1b[38;2;255;0;0m\x1b[48;2;0;0;0m▀\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_frame_to_ansi_within() {
+        let gray = |level| Cell {
+            glyph: '▀',
+            fg: Some((level, level, level)),
+            bg: Some((level, level, level)),
+        };
+        let frame = TerminalFrame {
+            columns: 3,
+            rows: 1,
+            cells: vec![gray(255), gray(250), gray(0)],
+        };
+        let full = frame
+            .to_ansi_within(ColorMode::Truecolor, usize::MAX)
+            .unwrap();
+        assert_eq!(full, frame.to_ansi(ColorMode::Truecolor));
+        // Snapping 250 to 256 (clamped to 255) merges the first two cells
+        let coarser = frame
+            .to_ansi_within(ColorMode::Truecolor, full.len() - 1)
+            .unwrap();
+        assert!(coarser.len() < full.len());
+        assert!(coarser.starts_with("\x1b[38;2;255;255;255m\x1b[48;2;255;255;255m▀▀"));
+
+        assert_eq!(frame.to_ansi_within(ColorMode::Ansi256, 3).unwrap(), "@@ ");
+        assert_eq!(frame.to_ansi_within(ColorMode::Truecolor, 2), None);
+    }
+
+    #[test]
+    fn test_sample_tiled_frame() {
+        let path = std::env::temp_dir().join("shadertui_test_sample_tiled_frame.wgsl");
+        std::fs::write(
+            &path,
+            "fn compute_color(coords: vec2<f32>) -> vec3<f32> {\n    \
+             return vec3<f32>(coords / uniforms.resolution, 0.0);\n}\n",
+        )
+        .unwrap();
+        let mut engine = match ShaderEngine::load(&path, 4, 4) {
+            Err(ShadertuiError::NoAdapter(_)) => return,
+            engine => engine.unwrap(),
+        };
+        engine.render(0.0).unwrap();
+        let corners = [(0, 0), (3, 0), (0, 7), (3, 7)];
+        let whole: Vec<_> = corners
+            .iter()
+            .map(|&(x, y)| engine.sample_pixel(x, y).unwrap())
+            .collect();
+
+        // Three bands of three rows, so only the top row is left on the GPU
+        engine.renderer.set_band_rows(3);
+        assert!(engine.renderer.is_tiled());
+        engine.render(0.0).unwrap();
+        for (&(x, y), expected) in corners.iter().zip(&whole) {
+            assert_eq!(engine.sample_pixel(x, y).unwrap(), *expected, "({x}, {y})");
+        }
+        assert!(whole[2][1] < whole[0][1]);
+    }
+
+    #[test]
+    fn test_frame_hooks() {
+        let path = std::env::temp_dir().join("shadertui_test_frame_hooks.wgsl");
+        std::fs::write(
+            &path,
+            "fn compute_color(coords: vec2<f32>) -> vec3<f32> {\n    \
+             return vec3<f32>(step(uniforms.cursor.x, coords.x), 0.0, 0.0);\n}\n",
+        )
+        .unwrap();
+        let mut engine = match ShaderEngine::load(&path, 4, 1) {
+            Err(ShadertuiError::NoAdapter(_)) => return,
+            engine => engine.unwrap(),
+        };
+        // The cursor starts in the middle, so only the right half is red until it is moved
+        engine.on_before_upload(|uniforms| uniforms.cursor = [0.0, 0.0]);
+        let pixels = Arc::new(Mutex::new(Vec::new()));
+        engine.on_after_readback({
+            let pixels = Arc::clone(&pixels);
+            move |frame| *pixels.lock().unwrap() = frame.to_vec()
+        });
+        engine.render(0.0).unwrap();
+
+        let pixels = pixels.lock().unwrap();
+        assert_eq!(pixels.len(), 4 * 2 * 4);
+        assert!(pixels.chunks(4).all(|pixel| pixel[0] == 1.0));
+        assert_eq!(engine.sample_pixel(0, 0).unwrap()[0], 1.0);
+    }
+}
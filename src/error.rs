@@ -0,0 +1,168 @@
+//! The error type of [`ShaderEngine`](crate::ShaderEngine) and `run`.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::utils::project::ProjectError;
+use crate::utils::shader_shell::ShaderShellError;
+
+/// Where in a file a shader compile error points. Lines and columns count from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
+// AIDEV-NOTE: Errors by kind, so embedders and main() can tell a broken shader from a missing GPU.
+// Errors from dependencies convert with `?` through the From impls below; plain messages become
+// Other
+/// Everything that can go wrong loading, compiling and rendering a shader.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ShadertuiError {
+    /// The shader doesn't compile. `file` and `span` are set when the error is in the user's
+    /// code; `message` is the compiler's report, with the offending line underlined if so.
+    ShaderCompile {
+        file: Option<PathBuf>,
+        span: Option<SourceSpan>,
+        message: String,
+    },
+    /// The shader, one of its imports or its `project.toml` couldn't be read or processed.
+    Import(String),
+    /// No adapter could create a device; the message lists every one that was tried.
+    NoAdapter(String),
+    /// The GPU did not finish a frame in time, usually a runaway loop in the shader.
+    GpuTimeout(Duration),
+    /// The GPU or driver refused a request (limits, formats, a lost device, ...).
+    Gpu(String),
+    /// The window can't be drawn to.
+    Surface(String),
+    /// The shader asked to stop with this exit code.
+    ShaderExit(u32),
+    Io(io::Error),
+    Other(String),
+}
+
+impl ShadertuiError {
+    /// A compile error without a location in the user's code.
+    pub fn compile(message: impl Into<String>) -> Self {
+        ShadertuiError::ShaderCompile {
+            file: None,
+            span: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ShadertuiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShadertuiError::ShaderCompile { message, .. } => write!(f, "{message}"),
+            ShadertuiError::GpuTimeout(timeout) => write!(
+                f,
+                "Shader timed out after {:.1}s (runaway loop?)",
+                timeout.as_secs_f32()
+            ),
+            ShadertuiError::ShaderExit(code) => write!(f, "The shader exited with code {code}"),
+            ShadertuiError::Io(e) => write!(f, "{e}"),
+            ShadertuiError::Import(message)
+            | ShadertuiError::NoAdapter(message)
+            | ShadertuiError::Gpu(message)
+            | ShadertuiError::Surface(message)
+            | ShadertuiError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl Error for ShadertuiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ShadertuiError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ShadertuiError {
+    fn from(e: io::Error) -> Self {
+        ShadertuiError::Io(e)
+    }
+}
+
+impl From<ProjectError> for ShadertuiError {
+    fn from(e: ProjectError) -> Self {
+        ShadertuiError::Import(e.to_string())
+    }
+}
+
+// Missing compute_color(), an unknown API version and the like are the shader's fault
+impl From<ShaderShellError> for ShadertuiError {
+    fn from(e: ShaderShellError) -> Self {
+        ShadertuiError::compile(e.to_string())
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for ShadertuiError {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        ShadertuiError::Gpu(e.to_string())
+    }
+}
+
+impl From<wgpu::BufferAsyncError> for ShadertuiError {
+    fn from(e: wgpu::BufferAsyncError) -> Self {
+        ShadertuiError::Gpu(e.to_string())
+    }
+}
+
+impl From<wgpu::PollError> for ShadertuiError {
+    fn from(e: wgpu::PollError) -> Self {
+        ShadertuiError::Gpu(e.to_string())
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for ShadertuiError {
+    fn from(e: wgpu::CreateSurfaceError) -> Self {
+        ShadertuiError::Surface(e.to_string())
+    }
+}
+
+impl From<png::EncodingError> for ShadertuiError {
+    fn from(e: png::EncodingError) -> Self {
+        match e {
+            png::EncodingError::IoError(e) => ShadertuiError::Io(e),
+            e => ShadertuiError::Other(e.to_string()),
+        }
+    }
+}
+
+// Hot reload could not watch a shader file
+#[cfg(feature = "cli")]
+impl From<notify::Error> for ShadertuiError {
+    fn from(e: notify::Error) -> Self {
+        ShadertuiError::Other(e.to_string())
+    }
+}
+
+// No display to open a window on, or the event loop failed
+#[cfg(feature = "cli")]
+impl From<winit::error::EventLoopError> for ShadertuiError {
+    fn from(e: winit::error::EventLoopError) -> Self {
+        ShadertuiError::Surface(e.to_string())
+    }
+}
+
+impl From<String> for ShadertuiError {
+    fn from(message: String) -> Self {
+        ShadertuiError::Other(message)
+    }
+}
+
+impl From<&str> for ShadertuiError {
+    fn from(message: &str) -> Self {
+        ShadertuiError::Other(message.to_string())
+    }
+}
@@ -1,13 +1,39 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
 use wgpu;
 
+use crate::error::ShadertuiError;
+use crate::gpu::packing::{FramePixels, Packing};
+
+// Readback buffers the output rotates through, i.e. frames that can be mapping at once
+pub const READBACK_SLOTS: usize = 3;
+// One RGBA float pixel of the output buffer
+const PIXEL_BYTES: wgpu::BufferAddress = 16;
+
 pub struct GpuBuffers {
     pub output_buffer: wgpu::Buffer,
-    pub readback_buffer: wgpu::Buffer,
+    pub readback: ReadbackRing,
     pub size: wgpu::BufferAddress,
+    // Pack pass the readback copies from instead of the output (terminal mode)
+    pub packing: Option<Packing>,
+    // Running average of the output with --accumulate
+    pub accumulation: Option<Accumulation>,
+    // The shader's request_exit() status (`shadertui render`)
+    pub exit_request: Option<ExitRequest>,
+    // One pixel of the output, for read_pixel_blocking()
+    pixel_readback: wgpu::Buffer,
 }
 
 impl GpuBuffers {
-    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+    pub fn new(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        accumulate: bool,
+        exit_requests: bool,
+        packed: bool,
+    ) -> Self {
         let buffer_size =
             (width * height * 4 * std::mem::size_of::<f32>() as u32) as wgpu::BufferAddress;
 
@@ -18,57 +44,470 @@ impl GpuBuffers {
             mapped_at_creation: false,
         });
 
-        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Readback Buffer"),
-            size: buffer_size,
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-            mapped_at_creation: false,
-        });
-
-        Self {
+        let mut buffers = Self {
             output_buffer,
-            readback_buffer,
+            readback: ReadbackRing::new(device, buffer_size, false),
             size: buffer_size,
+            packing: None,
+            accumulation: accumulate.then(|| Accumulation::new(device, width, height)),
+            exit_request: exit_requests.then(|| ExitRequest::new(device)),
+            pixel_readback: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Pixel Readback Buffer"),
+                size: PIXEL_BYTES,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+        };
+        if packed {
+            buffers.set_packed(device, true);
         }
+        buffers
     }
 
-    pub fn copy_to_readback(&self, encoder: &mut wgpu::CommandEncoder) {
-        encoder.copy_buffer_to_buffer(&self.output_buffer, 0, &self.readback_buffer, 0, self.size);
+    // Switch between float and packed readback. Frames still being read back are dropped
+    pub fn set_packed(&mut self, device: &wgpu::Device, packed: bool) {
+        let pixel_count = (self.size / 16) as u32;
+        self.packing = packed.then(|| Packing::new(device, &self.output_buffer, pixel_count));
+        let readback_size = self
+            .packing
+            .as_ref()
+            .map_or(self.size, |packing| packing.buffer.size());
+        self.readback = ReadbackRing::new(device, readback_size, packed);
+    }
+
+    // Copy the output (packed first, with packing) into the next free readback slot, returning the
+    // slot for begin_map()
+    pub fn copy_to_readback(&self, encoder: &mut wgpu::CommandEncoder) -> usize {
+        let slot = self.readback.next_slot();
+        let source = match &self.packing {
+            Some(packing) => {
+                packing.encode(encoder);
+                &packing.buffer
+            }
+            None => &self.output_buffer,
+        };
+        encoder.copy_buffer_to_buffer(source, 0, &self.readback.buffers[slot], 0, source.size());
+        slot
     }
 
-    pub async fn read_data(
+    // AIDEV-NOTE: Reads the RGBA floats of output pixel `index` (a row-major index, bottom row
+    // first) by copying just those 16 bytes, after whatever was submitted before
+    pub fn read_pixel_blocking(
         &self,
         device: &wgpu::Device,
-    ) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
-        let buffer_slice = self.readback_buffer.slice(..);
+        queue: &wgpu::Queue,
+        index: u32,
+        timeout: Duration,
+    ) -> Result<[f32; 4], ShadertuiError> {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Pixel Readback Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(
+            &self.output_buffer,
+            index as wgpu::BufferAddress * PIXEL_BYTES,
+            &self.pixel_readback,
+            0,
+            PIXEL_BYTES,
+        );
+        queue.submit(Some(encoder.finish()));
+        let data = read_buffer_blocking(device, &self.pixel_readback, timeout)?;
+        Ok(bytemuck::pod_read_unaligned(&data))
+    }
+}
+
+// A frame's output once it reached the CPU
+pub struct Readback {
+    pub id: u64,
+    pub data: FramePixels,
+    // From submitting the frame to having its data
+    pub latency: Duration,
+}
+
+struct PendingReadback {
+    id: u64,
+    slot: usize,
+    submitted_at: Instant,
+    receiver: flume::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+// AIDEV-NOTE: Rotating readback buffers, so the GPU thread can submit frame N+1 while frame N is
+// still being mapped instead of stalling on every map. Maps complete in submission order, so
+// readbacks are taken oldest first; once every slot is in flight the oldest has to be waited for
+// before another frame can be copied out
+pub struct ReadbackRing {
+    buffers: Vec<wgpu::Buffer>,
+    pending: VecDeque<PendingReadback>,
+    next_id: u64,
+    // Whether the buffers hold packed RGBA8 pixels
+    packed: bool,
+}
 
-        // Map the buffer for reading
+impl ReadbackRing {
+    fn new(device: &wgpu::Device, size: wgpu::BufferAddress, packed: bool) -> Self {
+        let buffers = (0..READBACK_SLOTS)
+            .map(|_| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Readback Buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+        Self {
+            buffers,
+            pending: VecDeque::new(),
+            next_id: 0,
+            packed,
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.pending.len() == self.buffers.len()
+    }
+
+    fn next_slot(&self) -> usize {
+        self.pending
+            .back()
+            .map_or(0, |pending| (pending.slot + 1) % self.buffers.len())
+    }
+
+    // Start mapping a slot once the commands copying into it were submitted; returns the frame's id
+    pub fn begin_map(&mut self, slot: usize, submitted_at: Instant) -> u64 {
         let (sender, receiver) = flume::unbounded();
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            sender.send(result).unwrap();
+        self.buffers[slot]
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.push_back(PendingReadback {
+            id,
+            slot,
+            submitted_at,
+            receiver,
         });
+        id
+    }
+
+    // The oldest readback if its map has completed, without waiting. A frame still mapping after
+    // `timeout` counts as hung
+    pub fn try_take(
+        &mut self,
+        device: &wgpu::Device,
+        timeout: Duration,
+    ) -> Result<Option<Readback>, ShadertuiError> {
+        let Some(oldest) = self.pending.front() else {
+            return Ok(None);
+        };
+        let _ = device.poll(wgpu::PollType::Poll);
+        match oldest.receiver.try_recv() {
+            Ok(result) => {
+                result?;
+                Ok(Some(self.take_mapped()))
+            }
+            Err(_) if oldest.submitted_at.elapsed() >= timeout => {
+                Err(ShadertuiError::GpuTimeout(timeout))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    // Wait for the oldest readback
+    pub fn take_blocking(
+        &mut self,
+        device: &wgpu::Device,
+        timeout: Duration,
+    ) -> Result<Option<Readback>, ShadertuiError> {
+        let Some(oldest) = self.pending.front() else {
+            return Ok(None);
+        };
+        poll_until(device, &oldest.receiver, timeout)??;
+        Ok(Some(self.take_mapped()))
+    }
+
+    fn take_mapped(&mut self) -> Readback {
+        let pending = self.pending.pop_front().unwrap();
+        let buffer = &self.buffers[pending.slot];
+        let mapped = buffer.slice(..).get_mapped_range();
+        let data = if self.packed {
+            FramePixels::Packed(bytemuck::cast_slice(&mapped).to_vec())
+        } else {
+            FramePixels::Linear(bytemuck::cast_slice(&mapped).to_vec())
+        };
+        drop(mapped);
+        buffer.unmap();
+        Readback {
+            id: pending.id,
+            data,
+            latency: pending.submitted_at.elapsed(),
+        }
+    }
+}
+
+// AIDEV-NOTE: Polls instead of blocking on the device so a runaway shader can't freeze the caller;
+// gives up with ShadertuiError::GpuTimeout once `timeout` has passed
+fn read_buffer_blocking(
+    device: &wgpu::Device,
+    buffer: &wgpu::Buffer,
+    timeout: Duration,
+) -> Result<Vec<u8>, ShadertuiError> {
+    let buffer_slice = buffer.slice(..);
+
+    // Map the buffer for reading
+    let (sender, receiver) = flume::unbounded();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        sender.send(result).unwrap();
+    });
+    poll_until(device, &receiver, timeout)??;
+
+    // Copy the mapped data out, then unmap the buffer
+    let data = buffer_slice.get_mapped_range().to_vec();
+    buffer.unmap();
+
+    Ok(data)
+}
 
-        // Poll the device until the buffer is ready
-        let _ = device.poll(wgpu::MaintainBase::Wait);
+// Wait for everything submitted to `queue` so far, without reading anything back
+pub fn wait_for_queue_blocking(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    timeout: Duration,
+) -> Result<(), ShadertuiError> {
+    let (sender, receiver) = flume::unbounded();
+    queue.on_submitted_work_done(move || {
+        let _ = sender.send(());
+    });
+    poll_until(device, &receiver, timeout)
+}
 
-        // Wait for the mapping to complete
-        receiver.recv_async().await??;
+// Poll the device until a callback delivers its value or the deadline passes
+fn poll_until<T>(
+    device: &wgpu::Device,
+    receiver: &flume::Receiver<T>,
+    timeout: Duration,
+) -> Result<T, ShadertuiError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let _ = device.poll(wgpu::PollType::Poll);
+        if let Ok(value) = receiver.try_recv() {
+            return Ok(value);
+        }
+        if Instant::now() >= deadline {
+            return Err(ShadertuiError::GpuTimeout(timeout));
+        }
+        std::thread::sleep(Duration::from_micros(200));
+    }
+}
+
+// AIDEV-NOTE: Persistent storage for simulation shaders (--state-size). Shaders see it as
+// `state: array<f32>` at this binding of group 0 (see shader_shell::STATE_DECLARATION), shared by the
+// output pass and every buffer pass. It starts zeroed and the renderer never clears it, so values
+// carry over from frame to frame
+pub const STATE_BINDING: u32 = 2;
 
-        // Get the mapped data
-        let data = buffer_slice.get_mapped_range();
-        let result: Vec<f32> = bytemuck::cast_slice(&data).to_vec();
+pub fn create_state_buffer(
+    device: &wgpu::Device,
+    size: Option<u32>,
+) -> Result<Option<wgpu::Buffer>, ShadertuiError> {
+    let Some(size) = size else {
+        return Ok(None);
+    };
+    let max_size = device.limits().max_storage_buffer_binding_size;
+    if size > max_size {
+        return Err(ShadertuiError::Gpu(format!(
+            "--state-size {size} is larger than this GPU supports ({max_size} bytes)"
+        )));
+    }
 
-        // Unmap the buffer
-        drop(data);
-        self.readback_buffer.unmap();
+    Ok(Some(device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("State Buffer"),
+        size: size as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::STORAGE,
+        mapped_at_creation: false,
+    })))
+}
+
+pub fn state_layout_entry() -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding: STATE_BINDING,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: false },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+pub fn state_bind_group_entry(state: &wgpu::Buffer) -> wgpu::BindGroupEntry<'_> {
+    wgpu::BindGroupEntry {
+        binding: STATE_BINDING,
+        resource: state.as_entire_binding(),
+    }
+}
+
+// AIDEV-NOTE: Running average of the output pass for --accumulate, one vec4<f32> per output pixel at
+// ACCUMULATION_BINDING of group 0 (see shader_shell::ACCUMULATE_DECLARATION). It is never cleared: the
+// shader weights the old average by sample_index / (sample_index + 1), so restarting only means
+// sending sample_index 0 again. Shaders ask for a restart by setting the atomic u32 at RESET_BINDING,
+// which the renderer reads back and clears after every frame
+pub const ACCUMULATION_BINDING: u32 = 3;
+pub const RESET_BINDING: u32 = 4;
+
+pub struct Accumulation {
+    average: wgpu::Buffer,
+    reset: wgpu::Buffer,
+    reset_readback: wgpu::Buffer,
+}
+
+impl Accumulation {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let reset_buffer = |label, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+
+        Self {
+            average: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Accumulation Buffer"),
+                size: (width * height * 4 * std::mem::size_of::<f32>() as u32)
+                    as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            }),
+            reset: reset_buffer(
+                "Accumulation Reset Buffer",
+                wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+            ),
+            reset_readback: reset_buffer(
+                "Accumulation Reset Readback Buffer",
+                wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            ),
+        }
+    }
+
+    pub fn layout_entries() -> [wgpu::BindGroupLayoutEntry; 2] {
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding: ACCUMULATION_BINDING,
+                ..state_layout_entry()
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: RESET_BINDING,
+                ..state_layout_entry()
+            },
+        ]
+    }
 
-        Ok(result)
+    pub fn bind_group_entries(&self) -> [wgpu::BindGroupEntry<'_>; 2] {
+        [
+            wgpu::BindGroupEntry {
+                binding: ACCUMULATION_BINDING,
+                resource: self.average.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: RESET_BINDING,
+                resource: self.reset.as_entire_binding(),
+            },
+        ]
+    }
+
+    // Encode after the output pass: fetch this frame's reset request and clear it for the next one
+    pub fn copy_reset_to_readback(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.copy_buffer_to_buffer(&self.reset, 0, &self.reset_readback, 0, self.reset.size());
+        encoder.clear_buffer(&self.reset, 0, None);
+    }
+
+    // Whether the frame copied by copy_reset_to_readback() asked for a restart
+    pub fn read_reset_blocking(
+        &self,
+        device: &wgpu::Device,
+        timeout: Duration,
+    ) -> Result<bool, ShadertuiError> {
+        let data = read_buffer_blocking(device, &self.reset_readback, timeout)?;
+        Ok(data.iter().any(|&byte| byte != 0))
+    }
+}
+
+// AIDEV-NOTE: Status of request_exit() (see shader_shell::EXIT_DECLARATION): two atomic u32 at
+// EXIT_BINDING of group 0, a flag and the exit code. Like the accumulation reset, it is read back
+// and cleared after every frame
+pub const EXIT_BINDING: u32 = 5;
+const EXIT_STATUS_BYTES: wgpu::BufferAddress =
+    2 * std::mem::size_of::<u32>() as wgpu::BufferAddress;
+
+pub struct ExitRequest {
+    status: wgpu::Buffer,
+    readback: wgpu::Buffer,
+}
+
+impl ExitRequest {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let status_buffer = |label, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: EXIT_STATUS_BYTES,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+
+        Self {
+            status: status_buffer(
+                "Exit Status Buffer",
+                wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+            ),
+            readback: status_buffer(
+                "Exit Status Readback Buffer",
+                wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            ),
+        }
+    }
+
+    pub fn layout_entry() -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: EXIT_BINDING,
+            ..state_layout_entry()
+        }
+    }
+
+    pub fn bind_group_entry(&self) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding: EXIT_BINDING,
+            resource: self.status.as_entire_binding(),
+        }
+    }
+
+    // Encode after the output pass (of the last band, for tiled frames)
+    pub fn copy_to_readback(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.copy_buffer_to_buffer(&self.status, 0, &self.readback, 0, EXIT_STATUS_BYTES);
+        encoder.clear_buffer(&self.status, 0, None);
     }
 
-    pub fn read_data_blocking(
+    // The code the frame copied by copy_to_readback() passed to request_exit(), if it did
+    pub fn read_blocking(
         &self,
         device: &wgpu::Device,
-    ) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
-        pollster::block_on(self.read_data(device))
+        timeout: Duration,
+    ) -> Result<Option<u32>, ShadertuiError> {
+        let data = read_buffer_blocking(device, &self.readback, timeout)?;
+        let word =
+            |index: usize| u32::from_ne_bytes(data[index * 4..index * 4 + 4].try_into().unwrap());
+        Ok((word(0) != 0).then(|| word(1)))
     }
 }
@@ -0,0 +1,293 @@
+use wgpu;
+
+use crate::error::ShadertuiError;
+use crate::gpu::{probe_adapter, texture_format};
+use crate::utils::project::{BufferFormat, Domain, ShaderProject};
+use crate::utils::shader_shell::{uses_f16, ShellOptions};
+
+// Bytes per pixel of the output (and accumulation) storage buffers: one vec4<f32>
+const OUTPUT_PIXEL_BYTES: u64 = 16;
+// Matches @workgroup_size(8, 8) of the shells
+const WORKGROUP_SIZE: u32 = 8;
+
+const BUFFER_FORMATS: [BufferFormat; 5] = [
+    BufferFormat::Rgba16Float,
+    BufferFormat::Rgba32Float,
+    BufferFormat::Rg32Float,
+    BufferFormat::R32Float,
+    BufferFormat::Rgba8Unorm,
+];
+
+// What an adapter offers, as far as shadertui's resources go. GpuDevice requests all of it
+#[derive(Debug, Clone)]
+pub struct AdapterSupport {
+    pub name: String,
+    pub limits: wgpu::Limits,
+    pub features: wgpu::Features,
+    // Renders on the CPU (llvmpipe, lavapipe, WARP)
+    pub software: bool,
+    // Buffer pass formats the adapter can write as storage textures
+    pub storage_formats: Vec<BufferFormat>,
+}
+
+impl AdapterSupport {
+    // The adapter GpuDevice would pick, or None without one (device creation reports that)
+    pub fn probe() -> Option<Self> {
+        let adapter = probe_adapter()?;
+
+        let storage_formats = BUFFER_FORMATS
+            .into_iter()
+            .filter(|&format| {
+                adapter
+                    .get_texture_format_features(texture_format(format))
+                    .allowed_usages
+                    .contains(wgpu::TextureUsages::STORAGE_BINDING)
+            })
+            .collect();
+        let info = adapter.get_info();
+        Some(Self {
+            name: info.name,
+            software: info.device_type == wgpu::DeviceType::Cpu,
+            limits: adapter.limits(),
+            features: adapter.features(),
+            storage_formats,
+        })
+    }
+
+    pub fn supports_f16(&self) -> bool {
+        self.features.contains(wgpu::Features::SHADER_F16)
+    }
+
+    // Largest storage buffer a shader can bind, which caps the output buffer
+    pub fn max_storage_buffer(&self) -> u64 {
+        (self.limits.max_storage_buffer_binding_size as u64).min(self.limits.max_buffer_size)
+    }
+}
+
+// AIDEV-NOTE: Output tiling. A render whose output buffer outgrows the largest storage buffer the
+// GPU binds is computed in full-width bands of rows, one submit and readback each, and reassembled
+// on the CPU (HeadlessRenderer::render_bands). Shells other than the built-in one, --accumulate and
+// --state-size assume one dispatch covers the frame, so those renders still fail the size check
+pub fn tiles_output(domain: Domain, options: &ShellOptions) -> bool {
+    domain == Domain::Pixels
+        && options.custom_shell.is_none()
+        && !options.accumulate
+        && options.state_size.is_none()
+}
+
+// Rows of a width x height output computed per band: all of them unless the buffer would outgrow
+// `max_bytes`
+pub fn output_band_rows(width: u32, height: u32, max_bytes: u64) -> u32 {
+    let row_bytes = width.max(1) as u64 * OUTPUT_PIXEL_BYTES;
+    (max_bytes / row_bytes).clamp(1, height.max(1) as u64) as u32
+}
+
+// GPU resolution of the output pass, and the --scale that produced it
+#[derive(Debug, Clone, Copy)]
+pub struct RenderSize {
+    pub width: u32,
+    pub height: u32,
+    pub scale: f32,
+}
+
+// AIDEV-NOTE: Checks what a project needs against the adapter before any GPU resource exists, so
+// older adapters get a message saying what to change instead of a wgpu validation panic. Sizes
+// are only checked when the output resolution is known up front (`render_size`, terminal mode)
+pub fn compatibility_problems(
+    support: &AdapterSupport,
+    project: &ShaderProject,
+    options: &ShellOptions,
+    render_size: Option<RenderSize>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    let limits = &support.limits;
+    let extra_channels = options.textures.len() + usize::from(options.audio.is_some());
+
+    for pass in project.passes() {
+        if uses_f16(&pass.source) && !support.supports_f16() {
+            problems.push(format!(
+                "'{}' uses `enable f16;`, which this GPU doesn't support; use f32 instead",
+                pass.name
+            ));
+        }
+        let channels = pass.inputs.len() + extra_channels;
+        if channels > limits.max_sampled_textures_per_shader_stage as usize {
+            problems.push(format!(
+                "'{}' reads {channels} textures, but this GPU allows {} per pass; use fewer \
+                 inputs or --texture images",
+                pass.name, limits.max_sampled_textures_per_shader_stage
+            ));
+        }
+    }
+    for pass in &project.buffers {
+        if !support.storage_formats.contains(&pass.format) {
+            let suggestion = match support.storage_formats.first() {
+                Some(format) => format!("use format = \"{}\"", format.wgsl_name()),
+                None => "this GPU can't run buffer passes".to_string(),
+            };
+            problems.push(format!(
+                "buffer '{}' is {}, which this GPU can't write; {suggestion}",
+                pass.name,
+                pass.format.wgsl_name()
+            ));
+        }
+    }
+    for (index, image) in options.textures.iter().enumerate() {
+        let max = limits.max_texture_dimension_2d;
+        if image.width > max || image.height > max {
+            problems.push(format!(
+                "--texture {index} is {}x{}, but this GPU's textures are at most {max}x{max}; \
+                 resize the image",
+                image.width, image.height
+            ));
+        }
+    }
+    if let Some(state_size) = options.state_size {
+        let max = limits.max_storage_buffer_binding_size;
+        if state_size > max {
+            problems.push(format!(
+                "--state-size {state_size} is larger than this GPU supports; use at most {max}"
+            ));
+        }
+    }
+
+    let Some(size) = render_size.filter(|_| project.domain == Domain::Pixels) else {
+        return problems;
+    };
+    // The largest fraction of the current size every resource fits at
+    let mut fit: f32 = 1.0;
+    let mut reasons = Vec::new();
+
+    let output_bytes = size.width as u64 * size.height as u64 * OUTPUT_PIXEL_BYTES;
+    let max_buffer = support.max_storage_buffer();
+    if output_bytes > max_buffer && !tiles_output(project.domain, options) {
+        fit = fit.min((max_buffer as f32 / output_bytes as f32).sqrt());
+        reasons.push(format!(
+            "needs a {} output buffer, more than this GPU's {}",
+            megabytes(output_bytes),
+            megabytes(max_buffer)
+        ));
+    }
+    let max_groups = limits.max_compute_workgroups_per_dimension;
+    let groups = size.width.max(size.height).div_ceil(WORKGROUP_SIZE);
+    if groups > max_groups {
+        fit = fit.min(max_groups as f32 / groups as f32);
+        reasons.push(format!(
+            "needs {groups} workgroups across, more than this GPU's {max_groups}"
+        ));
+    }
+    let max_texture = limits.max_texture_dimension_2d;
+    for pass in &project.buffers {
+        let side = (size.width.max(size.height) as f32 * pass.scale).round() as u32;
+        if side > max_texture {
+            fit = fit.min(max_texture as f32 / side as f32);
+            reasons.push(format!(
+                "makes buffer '{}' {side} pixels across, more than this GPU's {max_texture}",
+                pass.name
+            ));
+        }
+    }
+    if !reasons.is_empty() {
+        // Round down to a twentieth so the suggestion fits after rounding
+        let scale = ((size.scale * fit * 20.0).floor() / 20.0).max(0.05);
+        problems.push(format!(
+            "the {}x{} render {}; use --scale {scale} (or a smaller terminal)",
+            size.width,
+            size.height,
+            reasons.join(" and ")
+        ));
+    }
+    problems
+}
+
+// AIDEV-NOTE: Startup check; fails with every problem listed, like load_validated_project does for
+// shaders that don't compile
+pub fn check_compatibility(
+    project: &ShaderProject,
+    options: &ShellOptions,
+    render_size: Option<RenderSize>,
+) -> Result<(), ShadertuiError> {
+    let Some(support) = AdapterSupport::probe() else {
+        return Ok(());
+    };
+    let problems = compatibility_problems(&support, project, options, render_size);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let mut message = format!(
+        "This GPU ({}) can't run the shader as set up:",
+        support.name
+    );
+    for problem in &problems {
+        message.push_str(&format!("\n  - {problem}"));
+    }
+    Err(ShadertuiError::Gpu(message))
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.0} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_compatibility_problems() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders/multipass.wgsl");
+        let source = format!("enable f16;\n{}", std::fs::read_to_string(&path).unwrap());
+        let project = ShaderProject::from_source(&path, &source).unwrap();
+        let support = AdapterSupport {
+            name: "test".to_string(),
+            limits: wgpu::Limits::downlevel_defaults(),
+            features: wgpu::Features::empty(),
+            software: false,
+            storage_formats: vec![BufferFormat::Rgba8Unorm],
+        };
+        let accumulate = ShellOptions {
+            accumulate: true,
+            ..ShellOptions::default()
+        };
+        let problems_with = |width, height, options: &ShellOptions| {
+            let size = RenderSize {
+                width,
+                height,
+                scale: 1.0,
+            };
+            compatibility_problems(&support, &project, options, Some(size))
+        };
+        let problems = |width, height| problems_with(width, height, &accumulate);
+
+        let small = problems(160, 80);
+        assert!(small.iter().any(|p| p.contains("enable f16")));
+        assert!(small
+            .iter()
+            .any(|p| p.contains("use format = \"rgba8unorm\"")));
+        assert!(!small.iter().any(|p| p.contains("--scale")));
+
+        // 256 MB against the 128 MB binding limit, and buffers past 2048 pixels
+        let large = problems(4096, 4096);
+        let size_problem = large.last().unwrap();
+        assert!(
+            size_problem.contains("256 MB output buffer"),
+            "{size_problem}"
+        );
+        assert!(size_problem.ends_with("use --scale 0.5 (or a smaller terminal)"));
+
+        // Without --accumulate the output is tiled, leaving only the buffer passes too large
+        let tiled = problems_with(4096, 4096, &ShellOptions::default());
+        let size_problem = tiled.last().unwrap();
+        assert!(!size_problem.contains("output buffer"), "{size_problem}");
+        assert!(size_problem.ends_with("use --scale 0.5 (or a smaller terminal)"));
+    }
+
+    #[test]
+    fn test_output_band_rows() {
+        let max = 128 * 1024 * 1024;
+        assert_eq!(output_band_rows(1920, 1080, max), 1080);
+        // 4096 x 16 bytes per row
+        assert_eq!(output_band_rows(4096, 4096, max), 2048);
+        assert_eq!(output_band_rows(4096, 4096, 100), 1);
+    }
+}
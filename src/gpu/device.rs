@@ -1,36 +1,435 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
 use wgpu;
 
+use crate::error::ShadertuiError;
+use crate::gpu::PipelineCache;
+
+// AIDEV-NOTE: Which adapters shadertui may use (--backend, --force-fallback, --adapter). Set once at
+// startup like the message catalog, since every renderer and subcommand creates its own device.
+// Without any of them, device creation walks ADAPTER_CHAIN until one adapter gives a device
+#[derive(Debug, Clone, Default)]
+pub struct AdapterSelection {
+    // None means every backend wgpu was built with
+    pub backends: Option<wgpu::Backends>,
+    pub force_fallback: bool,
+    pub adapter: Option<AdapterChoice>,
+}
+
+// One adapter of enumerate_adapters() (--adapter): its index, or part of its name in any case
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdapterChoice {
+    Index(usize),
+    Name(String),
+}
+
+impl FromStr for AdapterChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("expected an adapter index or part of its name".to_string());
+        }
+        Ok(match s.parse() {
+            Ok(index) => AdapterChoice::Index(index),
+            Err(_) => AdapterChoice::Name(s.to_lowercase()),
+        })
+    }
+}
+
+impl fmt::Display for AdapterChoice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdapterChoice::Index(index) => write!(f, "{index}"),
+            AdapterChoice::Name(name) => write!(f, "'{name}'"),
+        }
+    }
+}
+
+impl AdapterChoice {
+    fn matches(&self, index: usize, info: &wgpu::AdapterInfo) -> bool {
+        match self {
+            AdapterChoice::Index(choice) => *choice == index,
+            AdapterChoice::Name(name) => info.name.to_lowercase().contains(name),
+        }
+    }
+}
+
+static SELECTION: OnceLock<AdapterSelection> = OnceLock::new();
+
+pub fn select_adapters(selection: AdapterSelection) {
+    let _ = SELECTION.set(selection);
+}
+
+fn selection() -> AdapterSelection {
+    SELECTION.get().cloned().unwrap_or_default()
+}
+
+// (description, backends, force_fallback_adapter). On a server without a display, GL goes through
+// EGL's surfaceless platform, which only needs a readable /dev/dri render node (or llvmpipe)
+const ADAPTER_CHAIN: [(&str, wgpu::Backends, bool); 3] = [
+    ("default", wgpu::Backends::all(), false),
+    ("OpenGL (EGL)", wgpu::Backends::GL, false),
+    ("software fallback", wgpu::Backends::all(), true),
+];
+
 pub struct GpuDevice {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
+    // Set by wgpu once the device is lost (driver reset, GPU switch, ...)
+    lost: Arc<AtomicBool>,
+    // Only on devices from request_device() that support it (see gpu::pipeline_cache)
+    pipeline_cache: Option<PipelineCache>,
 }
 
 impl GpuDevice {
-    pub async fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let instance = wgpu::Instance::default();
-
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: None,
-                force_fallback_adapter: false,
-            })
-            .await?;
+    // AIDEV-NOTE: Every device goes through here so loss is noticed. Errors from a lost device
+    // would otherwise panic in wgpu's default handler; once lost they are dropped and the
+    // renderers rebuild on a new device (see GpuRenderer::recover_device, WindowRenderer::recover)
+    pub fn from_device(device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        let lost = Arc::new(AtomicBool::new(false));
+        {
+            let lost = Arc::clone(&lost);
+            device.set_device_lost_callback(move |_, _| lost.store(true, Ordering::Relaxed));
+        }
+        {
+            let lost = Arc::clone(&lost);
+            device.on_uncaptured_error(Box::new(move |error| {
+                if !lost.load(Ordering::Relaxed) {
+                    panic!("wgpu error: {error}");
+                }
+            }));
+        }
+        Self {
+            device,
+            queue,
+            lost,
+            pipeline_cache: None,
+        }
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    // For the `cache` field of pipeline descriptors
+    pub fn pipeline_cache(&self) -> Option<&wgpu::PipelineCache> {
+        self.pipeline_cache.as_ref().map(PipelineCache::cache)
+    }
 
+    // AIDEV-NOTE: Called once pipelines are compiled (renderer creation, reloads). Failing to write
+    // only costs the next start some compile time, so errors are ignored
+    pub fn save_pipeline_cache(&self) {
+        if let Some(cache) = &self.pipeline_cache {
+            let _ = cache.save();
+        }
+    }
+
+    pub async fn new() -> Result<Self, ShadertuiError> {
+        if let Some(choice) = selection().adapter {
+            let adapter = chosen_adapter(&gpu_instance(), &choice)?;
+            return Self::request_device(&adapter).await.map_err(|e| {
+                let name = adapter.get_info().name;
+                ShadertuiError::Gpu(format!("{name} could not create a device: {e}"))
+            });
+        }
+        let mut failures = Vec::new();
+        for (description, backends, force_fallback) in adapter_attempts() {
+            let adapter = match request_adapter(backends, force_fallback).await {
+                Ok(adapter) => adapter,
+                Err(e) => {
+                    failures.push(format!("{description}: {e}"));
+                    continue;
+                }
+            };
+            match Self::request_device(&adapter).await {
+                Ok(device) => return Ok(device),
+                Err(e) => failures.push(format!(
+                    "{description}: {} could not create a device: {e}",
+                    adapter.get_info().name
+                )),
+            }
+        }
+        Err(ShadertuiError::NoAdapter(
+            NoAdapterError { failures }.to_string(),
+        ))
+    }
+
+    async fn request_device(adapter: &wgpu::Adapter) -> Result<Self, wgpu::RequestDeviceError> {
+        // AIDEV-NOTE: Ask for everything the adapter offers rather than WebGPU's defaults, so
+        // larger renders fit and older adapters below the defaults still get a device. f16 is the
+        // only optional feature shaders can use (see gpu::compatibility); timestamp queries time
+        // frames for --perf (see gpu::pipeline::GpuTimer); pipeline caches skip recompiling shaders
+        // on the next start (see gpu::pipeline_cache)
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: None,
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::default(),
+                required_features: adapter.features()
+                    & (wgpu::Features::SHADER_F16
+                        | wgpu::Features::TIMESTAMP_QUERY
+                        | wgpu::Features::PIPELINE_CACHE),
+                required_limits: adapter.limits(),
                 memory_hints: wgpu::MemoryHints::default(),
                 trace: Default::default(),
             })
             .await?;
 
-        Ok(GpuDevice { device, queue })
+        let mut gpu_device = GpuDevice::from_device(device, queue);
+        gpu_device.pipeline_cache = PipelineCache::load(&gpu_device.device, &adapter.get_info());
+        Ok(gpu_device)
     }
 
-    pub fn new_blocking() -> Result<Self, Box<dyn std::error::Error>> {
+    // AIDEV-NOTE: Adapters available on every backend, for diagnostics
+    pub fn list_adapters() -> Vec<wgpu::AdapterInfo> {
+        adapters(&wgpu::Instance::default(), wgpu::Backends::all())
+            .iter()
+            .map(|adapter| adapter.get_info())
+            .collect()
+    }
+
+    pub fn new_blocking() -> Result<Self, ShadertuiError> {
         pollster::block_on(Self::new())
     }
+
+    // A device on a given adapter, e.g. one that can present to a window (see surface_adapter)
+    pub fn from_adapter_blocking(adapter: &wgpu::Adapter) -> Result<Self, ShadertuiError> {
+        pollster::block_on(Self::request_device(adapter)).map_err(|e| {
+            let name = adapter.get_info().name;
+            ShadertuiError::Gpu(format!("{name} could not create a device: {e}"))
+        })
+    }
+}
+
+// An instance limited to the selected backends, for the window's surface
+pub fn gpu_instance() -> wgpu::Instance {
+    instance(selection().backends.unwrap_or(wgpu::Backends::all()))
+}
+
+// Adapters on the selected backends, in the order --list-gpus numbers them for --adapter
+pub fn enumerate_adapters() -> Vec<wgpu::Adapter> {
+    let backends = selection().backends.unwrap_or(wgpu::Backends::all());
+    adapters(&instance(backends), backends)
+}
+
+// AIDEV-NOTE: WebGPU hands out one adapter through request_adapter() and can't list them, so on
+// wasm32 there is nothing to enumerate or pick with --adapter
+#[cfg(not(target_arch = "wasm32"))]
+fn adapters(instance: &wgpu::Instance, backends: wgpu::Backends) -> Vec<wgpu::Adapter> {
+    instance.enumerate_adapters(backends)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn adapters(_instance: &wgpu::Instance, _backends: wgpu::Backends) -> Vec<wgpu::Adapter> {
+    Vec::new()
+}
+
+fn chosen_adapter(
+    instance: &wgpu::Instance,
+    choice: &AdapterChoice,
+) -> Result<wgpu::Adapter, ShadertuiError> {
+    let backends = selection().backends.unwrap_or(wgpu::Backends::all());
+    adapters(instance, backends)
+        .into_iter()
+        .enumerate()
+        .find(|(index, adapter)| choice.matches(*index, &adapter.get_info()))
+        .map(|(_, adapter)| adapter)
+        .ok_or_else(|| {
+            ShadertuiError::NoAdapter(format!(
+                "No GPU adapter matches --adapter {choice}; `shadertui --list-gpus` lists them"
+            ))
+        })
+}
+
+// AIDEV-NOTE: The window's adapter has to present to its surface, so it comes from the surface's
+// instance: the --adapter choice if it can, otherwise the best compatible one
+pub fn surface_adapter(
+    instance: &wgpu::Instance,
+    surface: &wgpu::Surface,
+) -> Result<wgpu::Adapter, ShadertuiError> {
+    let selection = selection();
+    let Some(choice) = &selection.adapter else {
+        return pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: Some(surface),
+            force_fallback_adapter: selection.force_fallback,
+        }))
+        .map_err(|e| ShadertuiError::NoAdapter(e.to_string()));
+    };
+    let adapter = chosen_adapter(instance, choice)?;
+    if !adapter.is_surface_supported(surface) {
+        let name = adapter.get_info().name;
+        return Err(ShadertuiError::Surface(format!(
+            "{name} can't draw to this window; pick another with --adapter"
+        )));
+    }
+    Ok(adapter)
+}
+
+// The first adapter GpuDevice would try, or None without one (device creation reports that)
+pub fn probe_adapter() -> Option<wgpu::Adapter> {
+    if let Some(choice) = selection().adapter {
+        return chosen_adapter(&gpu_instance(), &choice).ok();
+    }
+    adapter_attempts()
+        .into_iter()
+        .find_map(|(_, backends, force_fallback)| {
+            pollster::block_on(request_adapter(backends, force_fallback)).ok()
+        })
+}
+
+fn adapter_attempts() -> Vec<(&'static str, wgpu::Backends, bool)> {
+    match selection() {
+        AdapterSelection {
+            backends: None,
+            force_fallback: false,
+            ..
+        } => ADAPTER_CHAIN.to_vec(),
+        AdapterSelection {
+            backends,
+            force_fallback,
+            ..
+        } => vec![(
+            "selected",
+            backends.unwrap_or(wgpu::Backends::all()),
+            force_fallback,
+        )],
+    }
+}
+
+fn instance(backends: wgpu::Backends) -> wgpu::Instance {
+    wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    })
+}
+
+async fn request_adapter(
+    backends: wgpu::Backends,
+    force_fallback: bool,
+) -> Result<wgpu::Adapter, wgpu::RequestAdapterError> {
+    instance(backends)
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+            force_fallback_adapter: force_fallback,
+        })
+        .await
+}
+
+// A DRM render node (/dev/dri/renderD*), the device file GPU drivers open when there is no display
+pub struct RenderNode {
+    pub path: PathBuf,
+    pub access: io::Result<()>,
+}
+
+// AIDEV-NOTE: Render nodes are opened read-write to test access, since a node that exists but
+// belongs to the `render` group is the usual reason a server finds no adapter. Linux only
+pub fn render_nodes() -> Vec<RenderNode> {
+    let Ok(entries) = std::fs::read_dir("/dev/dri") else {
+        return Vec::new();
+    };
+    let mut nodes: Vec<RenderNode> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("renderD"))
+        })
+        .map(|path| {
+            let access = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .map(drop);
+            RenderNode { path, access }
+        })
+        .collect();
+    nodes.sort_by(|a, b| a.path.cmp(&b.path));
+    nodes
+}
+
+// Every adapter GpuDevice tried, with why it failed, for ShadertuiError::NoAdapter
+struct NoAdapterError {
+    failures: Vec<String>,
+}
+
+impl fmt::Display for NoAdapterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "No usable GPU adapter was found. Tried:")?;
+        for failure in &self.failures {
+            writeln!(f, "  - {failure}")?;
+        }
+
+        if cfg!(target_os = "linux") {
+            let nodes = render_nodes();
+            if nodes.is_empty() {
+                writeln!(
+                    f,
+                    "No render nodes in /dev/dri (no GPU driver loaded, or the device isn't passed \
+                     into this container)."
+                )?;
+            }
+            for node in &nodes {
+                match &node.access {
+                    Ok(()) => writeln!(f, "{} is accessible.", node.path.display())?,
+                    Err(e) => writeln!(
+                        f,
+                        "{} can't be opened ({e}); add your user to the `render` group and log in \
+                         again.",
+                        node.path.display()
+                    )?,
+                }
+            }
+        }
+
+        if selection().force_fallback {
+            write!(
+                f,
+                "--software found no software adapter. Install Mesa's llvmpipe or lavapipe (Linux) \
+                 or use Windows' WARP (DirectX 12), or leave out --software to use a GPU. "
+            )?;
+        } else {
+            write!(
+                f,
+                "For a software adapter without any GPU, install Mesa (llvmpipe or lavapipe) and \
+                 run with `--backend gl --software`. "
+            )?;
+        }
+        write!(f, "`shadertui doctor` lists the adapters wgpu can see.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapter_choice() {
+        assert_eq!("1".parse(), Ok(AdapterChoice::Index(1)));
+        assert_eq!(
+            " NVIDIA ".parse(),
+            Ok(AdapterChoice::Name("nvidia".to_string()))
+        );
+        assert!("".parse::<AdapterChoice>().is_err());
+
+        let info = wgpu::AdapterInfo {
+            name: "NVIDIA GeForce RTX 4060".to_string(),
+            vendor: 0,
+            device: 0,
+            device_type: wgpu::DeviceType::DiscreteGpu,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend: wgpu::Backend::Vulkan,
+        };
+        assert!(AdapterChoice::Name("rtx".to_string()).matches(3, &info));
+        assert!(AdapterChoice::Index(3).matches(3, &info));
+        assert!(!AdapterChoice::Index(0).matches(3, &info));
+    }
 }
@@ -1,9 +1,21 @@
+// AIDEV-NOTE: gpu/ also builds for wasm32 with WebGPU (without the `cli` feature, see lib.rs).
+// The blocking helpers (pollster::block_on in device.rs and passes.rs, the readback wait in
+// buffer.rs) compile there, but a browser can't block on the GPU or read std::time::Instant, so a
+// web frontend has to create devices and map readbacks asynchronously from its own event loop
 pub mod buffer;
+pub mod compatibility;
 pub mod device;
+pub mod packing;
+pub mod passes;
 pub mod pipeline;
+pub mod pipeline_cache;
 pub mod uniforms;
 
 pub use buffer::*;
+pub use compatibility::*;
 pub use device::*;
+pub use packing::*;
+pub use passes::*;
 pub use pipeline::*;
+pub use pipeline_cache::*;
 pub use uniforms::*;
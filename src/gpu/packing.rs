@@ -0,0 +1,232 @@
+use wgpu;
+
+use crate::utils::antialias::stretch;
+use crate::utils::image_export::{linear_to_srgb_u8, RgbaImage};
+
+const PACK_SHADER: &str = include_str!("../shaders/pack.wgsl");
+// Matches @workgroup_size(64) of the pack shader
+const PACK_WORKGROUP_SIZE: u32 = 64;
+// WebGPU's guaranteed maximum workgroups per dispatch dimension
+const MAX_WORKGROUPS_PER_DIMENSION: u32 = 65535;
+
+// AIDEV-NOTE: Packs the output pass's vec4<f32> pixels into one RGBA8 u32 each before readback, a
+// quarter of the bytes to copy and map. The terminal only shows 8 bits per channel anyway; the
+// color is gamma-encoded on the GPU exactly like float_rgb_to_u8 did it on the CPU
+pub struct Packing {
+    pub buffer: wgpu::Buffer,
+    pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+    pixel_count: u32,
+}
+
+impl Packing {
+    pub fn new(device: &wgpu::Device, output: &wgpu::Buffer, pixel_count: u32) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Packed Output Buffer"),
+            size: (pixel_count * std::mem::size_of::<u32>() as u32) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Pack Shader"),
+            source: wgpu::ShaderSource::Wgsl(PACK_SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Pack Pipeline"),
+            layout: None,
+            module: &shader_module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Pack Bind Group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: output.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            buffer,
+            pipeline,
+            bind_group,
+            pixel_count,
+        }
+    }
+
+    // Encode after the output pass
+    pub fn encode(&self, encoder: &mut wgpu::CommandEncoder) {
+        let groups = self.pixel_count.div_ceil(PACK_WORKGROUP_SIZE);
+        let groups_x = groups.clamp(1, MAX_WORKGROUPS_PER_DIMENSION);
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Pack Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, &self.bind_group, &[]);
+        compute_pass.dispatch_workgroups(groups_x, groups.div_ceil(groups_x), 1);
+    }
+}
+
+// AIDEV-NOTE: A frame's pixels as read back, bottom row first: RGBA floats, or one gamma-encoded
+// RGBA8 u32 per pixel when the output went through the pack pass
+#[derive(Debug, Clone)]
+pub enum FramePixels {
+    Linear(Vec<f32>),
+    Packed(Vec<u32>),
+}
+
+impl Default for FramePixels {
+    fn default() -> Self {
+        Self::Linear(Vec::new())
+    }
+}
+
+impl FramePixels {
+    pub fn pixel_count(&self) -> usize {
+        match self {
+            Self::Linear(data) => data.len() / 4,
+            Self::Packed(data) => data.len(),
+        }
+    }
+
+    // The color of pixel `index` as the terminal shows it
+    pub fn display_rgb(&self, index: usize) -> Option<(u8, u8, u8)> {
+        match self {
+            Self::Linear(data) => {
+                let rgb = data.get(index * 4..index * 4 + 3)?;
+                Some((
+                    linear_to_srgb_u8(rgb[0]),
+                    linear_to_srgb_u8(rgb[1]),
+                    linear_to_srgb_u8(rgb[2]),
+                ))
+            }
+            Self::Packed(data) => {
+                let [r, g, b, _] = unpack_rgba8(*data.get(index)?);
+                Some((r, g, b))
+            }
+        }
+    }
+
+    // The linear color of pixel `index`, for blending several pixels into one cell
+    pub fn linear_rgb(&self, index: usize) -> Option<[f32; 3]> {
+        match self {
+            Self::Linear(data) => {
+                let rgb = data.get(index * 4..index * 4 + 3)?;
+                Some([rgb[0], rgb[1], rgb[2]])
+            }
+            Self::Packed(data) => {
+                let [r, g, b, _] = unpack_rgba8(*data.get(index)?);
+                Some([r, g, b].map(|value| (value as f32 / 255.0).powf(2.2)))
+            }
+        }
+    }
+
+    pub fn to_image(&self, width: u32, height: u32) -> RgbaImage {
+        match self {
+            Self::Linear(data) => RgbaImage::from_gpu_data(data, width, height),
+            Self::Packed(data) => {
+                let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+                for row in data
+                    .chunks_exact(width as usize)
+                    .take(height as usize)
+                    .rev()
+                {
+                    pixels.extend(row.iter().flat_map(|&pixel| unpack_rgba8(pixel)));
+                }
+                RgbaImage {
+                    width,
+                    height,
+                    pixels,
+                }
+            }
+        }
+    }
+
+    // The first `pixel_count` pixels of a band, after the pixels so far (tiled output)
+    pub fn append_band(&mut self, band: Self, pixel_count: usize) {
+        match (self, band) {
+            (Self::Linear(data), Self::Linear(band)) => {
+                data.extend_from_slice(&band[..(pixel_count * 4).min(band.len())])
+            }
+            (Self::Packed(data), Self::Packed(band)) => {
+                data.extend_from_slice(&band[..pixel_count.min(band.len())])
+            }
+            _ => unreachable!("bands of a frame share the readback format"),
+        }
+    }
+
+    // Nearest-neighbor stretch from `src_width` x `src_height` (--scale)
+    pub fn stretch(&self, src_width: u32, src_height: u32, width: u32, height: u32) -> Self {
+        match self {
+            Self::Linear(data) => {
+                Self::Linear(stretch(data, 4, src_width, src_height, width, height))
+            }
+            Self::Packed(data) => {
+                Self::Packed(stretch(data, 1, src_width, src_height, width, height))
+            }
+        }
+    }
+
+    // RGBA floats, decoding packed pixels
+    pub fn into_linear(self) -> Vec<f32> {
+        match self {
+            Self::Linear(data) => data,
+            Self::Packed(data) => data
+                .into_iter()
+                .flat_map(|pixel| {
+                    let [r, g, b, a] = unpack_rgba8(pixel).map(|value| value as f32 / 255.0);
+                    [r.powf(2.2), g.powf(2.2), b.powf(2.2), a]
+                })
+                .collect(),
+        }
+    }
+}
+
+// The CPU side of pack4x8unorm in the pack shader: byte 0 is red
+fn unpack_rgba8(packed: u32) -> [u8; 4] {
+    packed.to_le_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_pixels_match_linear() {
+        // One red pixel above a half-gray one, packed as the shader would
+        let linear = FramePixels::Linear(vec![0.5, 0.5, 0.5, 1.0, 1.0, 0.0, 0.0, 0.5]);
+        let gray = (0.5f32.powf(1.0 / 2.2) * 255.0).round() as u32;
+        let packed = FramePixels::Packed(vec![
+            gray | gray << 8 | gray << 16 | 255 << 24,
+            255 | 128 << 24,
+        ]);
+
+        assert_eq!(packed.pixel_count(), 2);
+        assert_eq!(packed.display_rgb(1), Some((255, 0, 0)));
+        assert_eq!(packed.display_rgb(2), None);
+        assert!((packed.linear_rgb(0).unwrap()[0] - 0.5).abs() < 0.01);
+
+        // Images flip rows the same way
+        let (linear, packed) = (linear.to_image(1, 2), packed.to_image(1, 2));
+        assert_eq!(packed.pixels[..3], [255, 0, 0]);
+        for (a, b) in linear.pixels.iter().zip(&packed.pixels) {
+            assert!(
+                a.abs_diff(*b) <= 1,
+                "{:?} vs {:?}",
+                linear.pixels,
+                packed.pixels
+            );
+        }
+    }
+}
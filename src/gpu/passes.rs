@@ -0,0 +1,846 @@
+use wgpu;
+use wgpu::util::DeviceExt;
+
+use crate::audio::{AudioCapture, AUDIO_TEXTURE_HEIGHT, AUDIO_TEXTURE_WIDTH};
+use crate::error::ShadertuiError;
+use crate::gpu::{
+    state_bind_group_entry, state_layout_entry, UniformBuffer, Uniforms, IDENTITY_ZOOM,
+};
+use crate::utils::image_export::RgbaImage;
+use crate::utils::project::{BufferFormat, PassSource, ShaderProject};
+use crate::utils::shader_shell::{inject_user_shader, ShellOptions, ShellType};
+
+pub fn texture_format(format: BufferFormat) -> wgpu::TextureFormat {
+    match format {
+        BufferFormat::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+        BufferFormat::Rgba32Float => wgpu::TextureFormat::Rgba32Float,
+        BufferFormat::Rg32Float => wgpu::TextureFormat::Rg32Float,
+        BufferFormat::R32Float => wgpu::TextureFormat::R32Float,
+        BufferFormat::Rgba8Unorm => wgpu::TextureFormat::Rgba8Unorm,
+    }
+}
+
+// AIDEV-NOTE: Each buffer pass has its own uniform buffer, since `resolution` (and the cursor) are
+// in the pass's own pixels when it declares a scale
+struct BufferPass {
+    // Complete generated shader, compared on reload to find the passes that changed
+    shader: String,
+    pipeline: wgpu::ComputePipeline,
+    output_layout: wgpu::BindGroupLayout,
+    channel_layout: Option<wgpu::BindGroupLayout>,
+    uniform_buffer: UniformBuffer,
+    inputs: Vec<usize>,
+    scale: f32,
+    format: BufferFormat,
+    iterations: u32,
+    state: Option<wgpu::Buffer>,
+}
+
+// Buffer passes rebuilt for a live-merge reload, by index (see PassChain::build_changed)
+pub struct ChangedPasses(Vec<(usize, BufferPass)>);
+
+// AIDEV-NOTE: Size-dependent resources for one buffer pass. Each buffer ping-pongs between two
+// textures: on frame parity p it writes textures[p], so readers see this frame's output if the
+// buffer already ran, or the previous frame's output (textures[1 - p]) otherwise
+struct BufferTargets {
+    views: [wgpu::TextureView; 2],
+    output_bind_groups: [wgpu::BindGroup; 2],
+    channel_bind_groups: Option<[wgpu::BindGroup; 2]>,
+    iteration_targets: Option<IterationTargets>,
+    width: u32,
+    height: u32,
+}
+
+// AIDEV-NOTE: Extra resources for a pass dispatched several times per frame (`// @iterations N`).
+// Iterations alternate between a scratch texture and this frame's texture, arranged so the last one
+// writes this frame's texture; the first reads the previous frame as usual and each later one reads
+// the texture its predecessor wrote
+struct IterationTargets {
+    scratch: wgpu::TextureView,
+    scratch_output_bind_group: wgpu::BindGroup,
+    // Channel bind groups where the pass reads itself from this frame's texture / the scratch
+    channels_reading_current: Option<[wgpu::BindGroup; 2]>,
+    channels_reading_scratch: Option<[wgpu::BindGroup; 2]>,
+}
+
+// AIDEV-NOTE: Runs the buffer passes of a ShaderProject and provides the channel bindings for the
+// renderer's own output pass; a single-file project yields an empty chain that does nothing.
+// --texture images are bound after each pass's inputs, then the --audio texture. The --state-size
+// buffer, if any, is bound in group 0 of every pass next to its output
+pub struct PassChain {
+    passes: Vec<BufferPass>,
+    images: Vec<wgpu::TextureView>,
+    audio: Option<AudioChannel>,
+    targets: Vec<BufferTargets>,
+    output_inputs: Vec<usize>,
+    output_channel_layout: Option<wgpu::BindGroupLayout>,
+    output_channel_bind_groups: Option<[wgpu::BindGroup; 2]>,
+    sampler: wgpu::Sampler,
+    // Used by readers of 32-bit float buffers, which can't be filtered
+    nearest_sampler: wgpu::Sampler,
+    width: u32,
+    height: u32,
+    parity: usize,
+}
+
+// AIDEV-NOTE: Audio texture re-uploaded from the capture at the start of every frame
+struct AudioChannel {
+    capture: AudioCapture,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+impl PassChain {
+    #[expect(clippy::too_many_arguments)]
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pipeline_cache: Option<&wgpu::PipelineCache>,
+        project: &ShaderProject,
+        width: u32,
+        height: u32,
+        shell_options: &ShellOptions,
+        state: Option<&wgpu::Buffer>,
+    ) -> Result<Self, ShadertuiError> {
+        let shell_options = &project_shell_options(project, shell_options);
+        let input_formats = |inputs: &[usize]| input_formats(project, shell_options, inputs);
+        let passes = project
+            .buffers
+            .iter()
+            .map(|pass| {
+                BufferPass::new(
+                    device,
+                    pipeline_cache,
+                    pass,
+                    BufferPass::generate_shader(pass, shell_options)?,
+                    &input_formats(&pass.inputs),
+                    state,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Channel Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let nearest_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Channel Nearest Sampler"),
+            ..Default::default()
+        });
+
+        for image in &shell_options.textures {
+            check_image_size(device, image)?;
+        }
+        let images = shell_options
+            .textures
+            .iter()
+            .map(|image| {
+                create_image_texture(device, queue, image)
+                    .create_view(&wgpu::TextureViewDescriptor::default())
+            })
+            .collect();
+
+        let audio = shell_options.audio.as_ref().map(|capture| {
+            let texture = create_audio_texture(device);
+            AudioChannel {
+                capture: capture.clone(),
+                view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                texture,
+            }
+        });
+
+        let mut chain = Self {
+            passes,
+            images,
+            audio,
+            targets: Vec::new(),
+            output_inputs: project.output.inputs.clone(),
+            output_channel_layout: create_channel_layout(
+                device,
+                &input_formats(&project.output.inputs),
+            ),
+            output_channel_bind_groups: None,
+            sampler,
+            nearest_sampler,
+            width,
+            height,
+            parity: 0,
+        };
+        chain.create_targets(device);
+
+        Ok(chain)
+    }
+
+    // AIDEV-NOTE: Layout for bind group 1 of the output pass; None when it samples no channels
+    pub fn output_channel_layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        self.output_channel_layout.as_ref()
+    }
+
+    pub fn output_channel_bind_group(&self) -> Option<&wgpu::BindGroup> {
+        self.output_channel_bind_groups
+            .as_ref()
+            .map(|groups| &groups[self.parity])
+    }
+
+    // AIDEV-NOTE: Swap the image bound as --texture number `index` (0-based). Only the channel bind
+    // groups are rebuilt, so buffer passes keep their contents
+    pub fn set_image(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        index: usize,
+        image: &RgbaImage,
+    ) -> Result<(), String> {
+        let count = self.images.len();
+        let slot = self.images.get_mut(index).ok_or_else(|| {
+            format!("there is no texture {index} ({count} --texture images are bound)")
+        })?;
+        check_image_size(device, image).map_err(|e| e.to_string())?;
+        *slot = create_image_texture(device, queue, image)
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.create_channel_targets(device);
+        Ok(())
+    }
+
+    // AIDEV-NOTE: True if `project` has the same passes as this chain, wired the same way (inputs,
+    // formats, scales, iterations, state buffer), so reload() can rebuild it in place. Compare
+    // before building the output pipeline, which keeps using output_channel_layout()
+    pub fn layout_matches(&self, project: &ShaderProject, has_state: bool) -> bool {
+        self.passes.len() == project.buffers.len()
+            && self.output_inputs == project.output.inputs
+            && self
+                .passes
+                .iter()
+                .zip(&project.buffers)
+                .all(|(pass, source)| {
+                    pass.inputs == source.inputs
+                        && pass.format == source.format
+                        && pass.scale == source.scale
+                        && pass.iterations == source.iterations
+                        && pass.state.is_some() == has_state
+                })
+    }
+
+    // True for a single-file project, whose chain does nothing
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    // Generated shader of every pass, for build_changed()
+    pub fn shaders(&self) -> Vec<String> {
+        self.passes.iter().map(|pass| pass.shader.clone()).collect()
+    }
+
+    // AIDEV-NOTE: Live-merge a reload into the chain; requires layout_matches(). Only passes whose
+    // generated shader changed get a new pipeline and start from cleared textures, so editing one
+    // file of a multi-pass project leaves the other passes running on their contents. Building
+    // needs only the running chain's shaders(), so it can happen on another thread while the chain
+    // keeps rendering; nothing changes until merge(), and not at all if a pass fails to build
+    pub fn build_changed(
+        device: &wgpu::Device,
+        pipeline_cache: Option<&wgpu::PipelineCache>,
+        project: &ShaderProject,
+        shell_options: &ShellOptions,
+        state: Option<&wgpu::Buffer>,
+        shaders: &[String],
+    ) -> Result<ChangedPasses, ShadertuiError> {
+        let shell_options = &project_shell_options(project, shell_options);
+        let mut changed = Vec::new();
+        for (index, (current, source)) in shaders.iter().zip(&project.buffers).enumerate() {
+            let shader = BufferPass::generate_shader(source, shell_options)?;
+            if shader != *current {
+                let formats = input_formats(project, shell_options, &source.inputs);
+                changed.push((
+                    index,
+                    BufferPass::new(device, pipeline_cache, source, shader, &formats, state)?,
+                ));
+            }
+        }
+        Ok(ChangedPasses(changed))
+    }
+
+    // build_changed() and merge() in one go
+    pub fn reload(
+        &mut self,
+        device: &wgpu::Device,
+        pipeline_cache: Option<&wgpu::PipelineCache>,
+        project: &ShaderProject,
+        shell_options: &ShellOptions,
+        state: Option<&wgpu::Buffer>,
+    ) -> Result<(), ShadertuiError> {
+        let changed = Self::build_changed(
+            device,
+            pipeline_cache,
+            project,
+            shell_options,
+            state,
+            &self.shaders(),
+        )?;
+        self.merge(device, changed, state);
+        Ok(())
+    }
+
+    pub fn merge(
+        &mut self,
+        device: &wgpu::Device,
+        changed: ChangedPasses,
+        state: Option<&wgpu::Buffer>,
+    ) {
+        // The state buffer may have been replaced, which every pass binds next to its output
+        for pass in &mut self.passes {
+            pass.state = state.cloned();
+        }
+        for (index, pass) in changed.0 {
+            self.passes[index] = pass;
+            self.targets[index] = self.create_pass_targets(device, &self.passes[index]);
+        }
+        for (pass, targets) in self.passes.iter().zip(&mut self.targets) {
+            targets.output_bind_groups =
+                [0, 1].map(|parity| pass.create_output_bind_group(device, &targets.views[parity]));
+            if let Some(iteration_targets) = &mut targets.iteration_targets {
+                iteration_targets.scratch_output_bind_group =
+                    pass.create_output_bind_group(device, &iteration_targets.scratch);
+            }
+        }
+        self.create_channel_targets(device);
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.create_targets(device);
+    }
+
+    // AIDEV-NOTE: Advances the frame parity, so call exactly once per frame before dispatching the
+    // output pass. `uniforms` are the display's; each pass gets them rescaled to its own size
+    pub fn encode(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        uniforms: &Uniforms,
+    ) {
+        self.parity = 1 - self.parity;
+
+        if let Some(audio) = &self.audio {
+            queue.write_texture(
+                audio.texture.as_image_copy(),
+                &audio.capture.texture_data(),
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(AUDIO_TEXTURE_WIDTH),
+                    rows_per_image: None,
+                },
+                audio.texture.size(),
+            );
+        }
+
+        for (pass, targets) in self.passes.iter().zip(&self.targets) {
+            let scale_x = targets.width as f32 / self.width.max(1) as f32;
+            let scale_y = targets.height as f32 / self.height.max(1) as f32;
+            let pass_uniforms = Uniforms {
+                resolution: [targets.width as f32, targets.height as f32],
+                cursor: [uniforms.cursor[0] * scale_x, uniforms.cursor[1] * scale_y],
+                mouse: [
+                    uniforms.mouse[0] * scale_x,
+                    uniforms.mouse[1] * scale_y,
+                    uniforms.mouse[2] * scale_x,
+                    uniforms.mouse[3] * scale_y,
+                ],
+                // The zoom inspector and supersampling only apply to the output pass
+                zoom: IDENTITY_ZOOM,
+                subsamples: 1,
+                ..*uniforms
+            };
+            pass.uniform_buffer.update(queue, &pass_uniforms);
+
+            for iteration in 0..pass.iterations {
+                let writes_current = (pass.iterations - 1 - iteration) % 2 == 0;
+                let output_bind_group = match &targets.iteration_targets {
+                    Some(iteration_targets) if !writes_current => {
+                        &iteration_targets.scratch_output_bind_group
+                    }
+                    _ => &targets.output_bind_groups[self.parity],
+                };
+                // After the first iteration, read the texture the previous iteration wrote
+                let channel_bind_groups = match &targets.iteration_targets {
+                    Some(iteration_targets) if iteration > 0 && writes_current => {
+                        &iteration_targets.channels_reading_scratch
+                    }
+                    Some(iteration_targets) if iteration > 0 => {
+                        &iteration_targets.channels_reading_current
+                    }
+                    _ => &targets.channel_bind_groups,
+                };
+
+                let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Buffer Pass"),
+                    timestamp_writes: None,
+                });
+
+                compute_pass.set_pipeline(&pass.pipeline);
+                compute_pass.set_bind_group(0, output_bind_group, &[]);
+                if let Some(channel_bind_groups) = channel_bind_groups {
+                    compute_pass.set_bind_group(1, &channel_bind_groups[self.parity], &[]);
+                }
+                compute_pass.dispatch_workgroups(
+                    targets.width.div_ceil(8),
+                    targets.height.div_ceil(8),
+                    1,
+                );
+            }
+        }
+    }
+
+    fn create_targets(&mut self, device: &wgpu::Device) {
+        self.targets = self
+            .passes
+            .iter()
+            .map(|pass| self.create_pass_targets(device, pass))
+            .collect();
+
+        self.create_channel_targets(device);
+    }
+
+    // Cleared textures for `pass`; its channel bind groups are left to create_channel_targets()
+    fn create_pass_targets(&self, device: &wgpu::Device, pass: &BufferPass) -> BufferTargets {
+        let scaled = |size: u32| ((size as f32 * pass.scale).round() as u32).max(1);
+        let (width, height) = (scaled(self.width), scaled(self.height));
+        let views = [0, 1].map(|_| {
+            create_buffer_texture(device, width, height, pass.format)
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+        let output_bind_groups =
+            [0, 1].map(|parity| pass.create_output_bind_group(device, &views[parity]));
+        let iteration_targets = (pass.iterations > 1).then(|| {
+            let scratch = create_buffer_texture(device, width, height, pass.format)
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            IterationTargets {
+                scratch_output_bind_group: pass.create_output_bind_group(device, &scratch),
+                scratch,
+                channels_reading_current: None,
+                channels_reading_scratch: None,
+            }
+        });
+
+        BufferTargets {
+            views,
+            output_bind_groups,
+            channel_bind_groups: None,
+            iteration_targets,
+            width,
+            height,
+        }
+    }
+
+    // AIDEV-NOTE: (Re)builds every channel bind group (group 1) from the current buffer textures,
+    // images and audio, leaving the buffers' contents alone
+    fn create_channel_targets(&mut self, device: &wgpu::Device) {
+        let views: Vec<[wgpu::TextureView; 2]> = self
+            .targets
+            .iter()
+            .map(|targets| targets.views.clone())
+            .collect();
+
+        for index in 0..self.passes.len() {
+            let pass = &self.passes[index];
+            let Some(layout) = &pass.channel_layout else {
+                continue;
+            };
+            let channels_reading = |own_views: Option<[&wgpu::TextureView; 2]>| {
+                self.create_channel_bind_groups(
+                    device,
+                    layout,
+                    &views,
+                    &pass.inputs,
+                    index,
+                    own_views,
+                )
+            };
+            let channel_bind_groups = channels_reading(None);
+            let iteration_channels =
+                self.targets[index]
+                    .iteration_targets
+                    .as_ref()
+                    .map(|iteration_targets| {
+                        let scratch = &iteration_targets.scratch;
+                        (
+                            channels_reading(Some([&views[index][0], &views[index][1]])),
+                            channels_reading(Some([scratch, scratch])),
+                        )
+                    });
+
+            let targets = &mut self.targets[index];
+            targets.channel_bind_groups = Some(channel_bind_groups);
+            if let (Some(iteration_targets), Some((current, scratch))) =
+                (&mut targets.iteration_targets, iteration_channels)
+            {
+                iteration_targets.channels_reading_current = Some(current);
+                iteration_targets.channels_reading_scratch = Some(scratch);
+            }
+        }
+
+        let output_index = self.passes.len();
+        self.output_channel_bind_groups = self.output_channel_layout.as_ref().map(|layout| {
+            self.create_channel_bind_groups(
+                device,
+                layout,
+                &views,
+                &self.output_inputs,
+                output_index,
+                None,
+            )
+        });
+    }
+
+    fn create_channel_bind_groups(
+        &self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        views: &[[wgpu::TextureView; 2]],
+        inputs: &[usize],
+        reader_index: usize,
+        // Per-parity view for a pass reading itself, overriding the previous frame's texture
+        own_views: Option<[&wgpu::TextureView; 2]>,
+    ) -> [wgpu::BindGroup; 2] {
+        let sampler = if inputs
+            .iter()
+            .all(|&input| self.passes[input].format.is_filterable())
+        {
+            &self.sampler
+        } else {
+            &self.nearest_sampler
+        };
+
+        [0, 1].map(|parity| {
+            let mut entries = vec![wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            }];
+            for (channel, &input) in inputs.iter().enumerate() {
+                // Buffers that already ran this frame are read from the current parity
+                let input_parity = if input < reader_index {
+                    parity
+                } else {
+                    1 - parity
+                };
+                let view = match own_views {
+                    Some(own_views) if input == reader_index => own_views[parity],
+                    _ => &views[input][input_parity],
+                };
+                entries.push(wgpu::BindGroupEntry {
+                    binding: channel as u32 + 1,
+                    resource: wgpu::BindingResource::TextureView(view),
+                });
+            }
+            let extra_views = self
+                .images
+                .iter()
+                .chain(self.audio.as_ref().map(|audio| &audio.view));
+            for (index, view) in extra_views.enumerate() {
+                entries.push(wgpu::BindGroupEntry {
+                    binding: (inputs.len() + index) as u32 + 1,
+                    resource: wgpu::BindingResource::TextureView(view),
+                });
+            }
+
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Channel Bind Group"),
+                layout,
+                entries: &entries,
+            })
+        })
+    }
+}
+
+impl BufferPass {
+    fn generate_shader(
+        pass: &PassSource,
+        shell_options: &ShellOptions,
+    ) -> Result<String, ShadertuiError> {
+        let pass_options = ShellOptions {
+            channel_count: pass.inputs.len(),
+            buffer_format: pass.format,
+            ..shell_options.clone()
+        };
+        inject_user_shader(&pass.source, ShellType::Buffer, &pass_options)
+            .map_err(|e| ShadertuiError::compile(format!("pass '{}': {e}", pass.name)))
+    }
+
+    fn new(
+        device: &wgpu::Device,
+        pipeline_cache: Option<&wgpu::PipelineCache>,
+        pass: &PassSource,
+        shader: String,
+        input_formats: &[BufferFormat],
+        state: Option<&wgpu::Buffer>,
+    ) -> Result<Self, ShadertuiError> {
+        check_storage_support(device, pass.format)
+            .map_err(|e| ShadertuiError::Gpu(format!("pass '{}': {e}", pass.name)))?;
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Buffer Pass Shader"),
+            source: wgpu::ShaderSource::Wgsl(shader.as_str().into()),
+        });
+
+        let mut output_entries = vec![
+            // Storage texture this pass writes
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format: texture_format(pass.format),
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                },
+                count: None,
+            },
+            // Uniform buffer
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ];
+        output_entries.extend(state.map(|_| state_layout_entry()));
+        let output_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Buffer Output Bind Group Layout"),
+            entries: &output_entries,
+        });
+        let channel_layout = create_channel_layout(device, input_formats);
+
+        let mut bind_group_layouts = vec![&output_layout];
+        bind_group_layouts.extend(channel_layout.as_ref());
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Buffer Pass Pipeline Layout"),
+            bind_group_layouts: &bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Buffer Pass Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader_module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: pipeline_cache,
+        });
+
+        Ok(Self {
+            shader,
+            pipeline,
+            output_layout,
+            channel_layout,
+            uniform_buffer: UniformBuffer::new(device),
+            inputs: pass.inputs.clone(),
+            scale: pass.scale,
+            format: pass.format,
+            iterations: pass.iterations,
+            state: state.cloned(),
+        })
+    }
+
+    fn create_output_bind_group(
+        &self,
+        device: &wgpu::Device,
+        view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: self.uniform_buffer.buffer.as_entire_binding(),
+            },
+        ];
+        entries.extend(self.state.as_ref().map(state_bind_group_entry));
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Buffer Output Bind Group"),
+            layout: &self.output_layout,
+            entries: &entries,
+        })
+    }
+}
+
+// The project's `// @uniform` declarations are part of every pass's shader
+fn project_shell_options(project: &ShaderProject, shell_options: &ShellOptions) -> ShellOptions {
+    ShellOptions {
+        params: project.params.clone(),
+        ..shell_options.clone()
+    }
+}
+
+// Formats of the channels a pass reads: its buffer inputs, then images and audio, which sample
+// like an rgba8unorm buffer as far as the layout is concerned
+fn input_formats(
+    project: &ShaderProject,
+    shell_options: &ShellOptions,
+    inputs: &[usize],
+) -> Vec<BufferFormat> {
+    let extra_channels = shell_options.textures.len() + usize::from(shell_options.audio.is_some());
+    inputs
+        .iter()
+        .map(|&i| project.buffers[i].format)
+        .chain(std::iter::repeat_n(
+            BufferFormat::Rgba8Unorm,
+            extra_channels,
+        ))
+        .collect()
+}
+
+// AIDEV-NOTE: Must match shader_shell::channel_declarations (sampler at 0, channelN at N + 1). If any
+// input is unfilterable, the sampler is non-filtering so the layout stays valid for every channel
+pub fn create_channel_layout(
+    device: &wgpu::Device,
+    input_formats: &[BufferFormat],
+) -> Option<wgpu::BindGroupLayout> {
+    if input_formats.is_empty() {
+        return None;
+    }
+
+    let sampler_type = if input_formats.iter().all(|format| format.is_filterable()) {
+        wgpu::SamplerBindingType::Filtering
+    } else {
+        wgpu::SamplerBindingType::NonFiltering
+    };
+    let mut entries = vec![wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Sampler(sampler_type),
+        count: None,
+    }];
+    for (channel, format) in input_formats.iter().enumerate() {
+        entries.push(wgpu::BindGroupLayoutEntry {
+            binding: channel as u32 + 1,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float {
+                    filterable: format.is_filterable(),
+                },
+            },
+            count: None,
+        });
+    }
+
+    Some(
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Channel Bind Group Layout"),
+            entries: &entries,
+        }),
+    )
+}
+
+// AIDEV-NOTE: Downlevel backends (e.g. GL) can't write some formats from compute shaders. Probe
+// with a tiny texture inside an error scope instead of letting wgpu's error handler panic later
+fn check_storage_support(device: &wgpu::Device, format: BufferFormat) -> Result<(), String> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    create_buffer_texture(device, 1, 1, format);
+    match pollster::block_on(device.pop_error_scope()) {
+        None => Ok(()),
+        Some(_) => Err(format!(
+            "format '{}' can't be used as a buffer on this GPU",
+            format.wgsl_name()
+        )),
+    }
+}
+
+fn check_image_size(device: &wgpu::Device, image: &RgbaImage) -> Result<(), ShadertuiError> {
+    let max_size = device.limits().max_texture_dimension_2d;
+    if image.width.max(image.height) > max_size {
+        return Err(ShadertuiError::Gpu(format!(
+            "a {}x{} texture is larger than this GPU supports ({max_size} pixels per side)",
+            image.width, image.height
+        )));
+    }
+    Ok(())
+}
+
+// AIDEV-NOTE: Rows are uploaded bottom first, so v = 0 is the bottom of the image like every other
+// channel. The sRGB format makes samples linear
+fn create_image_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    image: &RgbaImage,
+) -> wgpu::Texture {
+    let row_len = image.width as usize * 4;
+    let flipped: Vec<u8> = image
+        .pixels
+        .chunks_exact(row_len)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+
+    device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some("Image Channel Texture"),
+            size: wgpu::Extent3d {
+                width: image.width,
+                height: image.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        &flipped,
+    )
+}
+
+fn create_audio_texture(device: &wgpu::Device) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Audio Channel Texture"),
+        size: wgpu::Extent3d {
+            width: AUDIO_TEXTURE_WIDTH,
+            height: AUDIO_TEXTURE_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::R8Unorm,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    })
+}
+
+fn create_buffer_texture(
+    device: &wgpu::Device,
+    width: u32,
+    height: u32,
+    format: BufferFormat,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Buffer Pass Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: texture_format(format),
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    })
+}
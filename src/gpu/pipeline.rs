@@ -1,18 +1,65 @@
-use crate::gpu::{GpuBuffers, UniformBuffer};
+use std::time::Duration;
+
+use crate::error::ShadertuiError;
+use crate::gpu::{
+    state_bind_group_entry, state_layout_entry, Accumulation, ExitRequest, GpuBuffers,
+    UniformBuffer,
+};
 use wgpu;
 
+// Bytes of the two resolved u64 timestamps
+const TIMESTAMP_BYTES: wgpu::BufferAddress = 16;
+
 pub struct ComputePipeline {
     pub pipeline: wgpu::ComputePipeline,
     pub bind_group: wgpu::BindGroup,
+    bind_group_layout: wgpu::BindGroupLayout,
+    state: Option<wgpu::Buffer>,
 }
 
-impl ComputePipeline {
-    pub fn new(
+// A compiled output pipeline without its bind group, so it can be built before (or without
+// access to) the output buffers it will write
+pub struct UnboundPipeline {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    state: Option<wgpu::Buffer>,
+}
+
+impl UnboundPipeline {
+    pub fn bind(
+        self,
         device: &wgpu::Device,
         buffers: &GpuBuffers,
         uniform_buffer: &UniformBuffer,
+    ) -> ComputePipeline {
+        let bind_group = ComputePipeline::create_bind_group(
+            device,
+            &self.bind_group_layout,
+            buffers,
+            uniform_buffer,
+            self.state.as_ref(),
+        );
+        ComputePipeline {
+            pipeline: self.pipeline,
+            bind_group,
+            bind_group_layout: self.bind_group_layout,
+            state: self.state,
+        }
+    }
+}
+
+impl ComputePipeline {
+    // Compiles the pipeline; UnboundPipeline::bind() finishes it. `accumulate` and `exit_requests`
+    // must match the buffers it is bound to
+    pub fn compile(
+        device: &wgpu::Device,
+        pipeline_cache: Option<&wgpu::PipelineCache>,
         shader_source: &str,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+        channel_layout: Option<&wgpu::BindGroupLayout>,
+        state: Option<&wgpu::Buffer>,
+        accumulate: bool,
+        exit_requests: bool,
+    ) -> Result<UnboundPipeline, ShadertuiError> {
         // Create the shader module
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Compute Shader"),
@@ -20,54 +67,48 @@ impl ComputePipeline {
         });
 
         // Create the bind group layout
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Bind Group Layout"),
-            entries: &[
-                // Storage buffer for output
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: false },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
+        let mut entries = vec![
+            // Storage buffer for output
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
                 },
-                // Uniform buffer
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
+                count: None,
+            },
+            // Uniform buffer
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
                 },
-            ],
-        });
-
-        // Create the bind group
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Bind Group"),
-            layout: &bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: buffers.output_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: uniform_buffer.buffer.as_entire_binding(),
-                },
-            ],
+                count: None,
+            },
+        ];
+        entries.extend(state.map(|_| state_layout_entry()));
+        if accumulate {
+            entries.extend(Accumulation::layout_entries());
+        }
+        if exit_requests {
+            entries.push(ExitRequest::layout_entry());
+        }
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bind Group Layout"),
+            entries: &entries,
         });
 
-        // Create the pipeline layout
+        // Create the pipeline layout, with multi-pass channels in group 1 when present
+        let mut bind_group_layouts = vec![&bind_group_layout];
+        bind_group_layouts.extend(channel_layout);
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
+            bind_group_layouts: &bind_group_layouts,
             push_constant_ranges: &[],
         });
 
@@ -78,27 +119,209 @@ impl ComputePipeline {
             module: &shader_module,
             entry_point: Some("main"),
             compilation_options: Default::default(),
-            cache: None,
+            cache: pipeline_cache,
         });
 
-        Ok(Self {
+        Ok(UnboundPipeline {
             pipeline,
-            bind_group,
+            bind_group_layout,
+            state: state.cloned(),
         })
     }
 
-    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder, width: u32, height: u32) {
+    // AIDEV-NOTE: Point the pipeline at new output buffers (e.g. after a resize) without recompiling
+    pub fn rebind(
+        &mut self,
+        device: &wgpu::Device,
+        buffers: &GpuBuffers,
+        uniform_buffer: &UniformBuffer,
+    ) {
+        self.bind_group = Self::create_bind_group(
+            device,
+            &self.bind_group_layout,
+            buffers,
+            uniform_buffer,
+            self.state.as_ref(),
+        );
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        buffers: &GpuBuffers,
+        uniform_buffer: &UniformBuffer,
+        state: Option<&wgpu::Buffer>,
+    ) -> wgpu::BindGroup {
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffers.output_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: uniform_buffer.buffer.as_entire_binding(),
+            },
+        ];
+        entries.extend(state.map(state_bind_group_entry));
+        if let Some(accumulation) = &buffers.accumulation {
+            entries.extend(accumulation.bind_group_entries());
+        }
+        if let Some(exit_request) = &buffers.exit_request {
+            entries.push(exit_request.bind_group_entry());
+        }
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Bind Group"),
+            layout,
+            entries: &entries,
+        })
+    }
+
+    pub fn dispatch(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        width: u32,
+        height: u32,
+        channel_bind_group: Option<&wgpu::BindGroup>,
+        timer: Option<&GpuTimer>,
+    ) {
         // Calculate dispatch size (workgroup size is 8x8)
         let dispatch_width = width.div_ceil(8);
         let dispatch_height = height.div_ceil(8);
 
         let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
             label: Some("Compute Pass"),
-            timestamp_writes: None,
+            timestamp_writes: timer.map(GpuTimer::end_writes),
         });
 
         compute_pass.set_pipeline(&self.pipeline);
         compute_pass.set_bind_group(0, &self.bind_group, &[]);
+        if let Some(channel_bind_group) = channel_bind_group {
+            compute_pass.set_bind_group(1, channel_bind_group, &[]);
+        }
         compute_pass.dispatch_workgroups(dispatch_width, dispatch_height, 1);
     }
 }
+
+// AIDEV-NOTE: GPU time per frame for --perf from two timestamp queries (TIMESTAMP_QUERY, which not
+// every adapter has): begin() writes the first from an empty compute pass before the buffer
+// passes, the output pass writes the second, so the time covers every pass. The timestamps come
+// back through one mappable buffer without stalling; frames finished while it is still mapping
+// aren't timed
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    // Nanoseconds per timestamp tick
+    period: f32,
+    copied: bool,
+    mapping: Option<flume::Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    last: Option<Duration>,
+}
+
+impl GpuTimer {
+    // None when the device can't write timestamps
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Frame Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size: TIMESTAMP_BYTES,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Readback Buffer"),
+            size: TIMESTAMP_BYTES,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: queue.get_timestamp_period(),
+            copied: false,
+            mapping: None,
+            last: None,
+        })
+    }
+
+    // Writes the frame's start; encode before its first compute pass
+    pub fn begin(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Timer Start"),
+            timestamp_writes: Some(wgpu::ComputePassTimestampWrites {
+                query_set: &self.query_set,
+                beginning_of_pass_write_index: Some(0),
+                end_of_pass_write_index: None,
+            }),
+        });
+    }
+
+    // For the frame's last compute pass
+    pub fn end_writes(&self) -> wgpu::ComputePassTimestampWrites<'_> {
+        wgpu::ComputePassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: None,
+            end_of_pass_write_index: Some(1),
+        }
+    }
+
+    // Copies the timestamps out after the last pass, unless the previous ones are still mapping
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.mapping.is_some() {
+            return;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            TIMESTAMP_BYTES,
+        );
+        self.copied = true;
+    }
+
+    // Starts reading the timestamps back once the frame was submitted
+    pub fn submitted(&mut self) {
+        if !std::mem::take(&mut self.copied) {
+            return;
+        }
+        let (sender, receiver) = flume::bounded(1);
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        self.mapping = Some(receiver);
+    }
+
+    // GPU time of the most recent frame read back, None until the first one arrives
+    pub fn gpu_time(&mut self, device: &wgpu::Device) -> Option<Duration> {
+        if let Some(mapping) = &self.mapping {
+            let _ = device.poll(wgpu::PollType::Poll);
+            match mapping.try_recv() {
+                Ok(Ok(())) => {
+                    let ticks: [u64; 2] = {
+                        let data = self.readback_buffer.slice(..).get_mapped_range();
+                        bytemuck::pod_read_unaligned(&data)
+                    };
+                    self.readback_buffer.unmap();
+                    self.mapping = None;
+                    let nanos = ticks[1].saturating_sub(ticks[0]) as f64 * self.period as f64;
+                    self.last = Some(Duration::from_nanos(nanos as u64));
+                }
+                Err(flume::TryRecvError::Empty) => {}
+                Ok(Err(_)) | Err(flume::TryRecvError::Disconnected) => self.mapping = None,
+            }
+        }
+        self.last
+    }
+}
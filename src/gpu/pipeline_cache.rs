@@ -0,0 +1,112 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::utils::paths::cache_dir;
+
+// Subdirectory of cache_dir() holding one file per driver
+const PIPELINE_CACHE_DIR: &str = "pipelines";
+
+// AIDEV-NOTE: The driver's compiled pipelines, persisted so the next start doesn't recompile
+// unchanged shaders. Only Vulkan supports pipeline caches; elsewhere GpuDevice has none and every
+// pipeline is created with `cache: None`. The file name is wgpu's pipeline_cache_key, so a driver
+// update or another GPU starts a new file; `shadertui cache clear` removes stale ones
+pub struct PipelineCache {
+    cache: wgpu::PipelineCache,
+    path: PathBuf,
+}
+
+impl PipelineCache {
+    pub fn load(device: &wgpu::Device, adapter_info: &wgpu::AdapterInfo) -> Option<Self> {
+        Self::load_from(&cache_dir()?, device, adapter_info)
+    }
+
+    fn load_from(
+        dir: &Path,
+        device: &wgpu::Device,
+        adapter_info: &wgpu::AdapterInfo,
+    ) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            return None;
+        }
+        let path = dir
+            .join(PIPELINE_CACHE_DIR)
+            .join(wgpu::util::pipeline_cache_key(adapter_info)?);
+        // A missing or unreadable file just starts an empty cache
+        let data = fs::read(&path).ok();
+        // SAFETY: The data was written by save() for the same pipeline_cache_key. With `fallback`
+        // wgpu starts an empty cache when the driver rejects it (e.g. after an update)
+        let cache = unsafe {
+            device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("Pipeline Cache"),
+                data: data.as_deref(),
+                fallback: true,
+            })
+        };
+        Some(Self { cache, path })
+    }
+
+    pub fn cache(&self) -> &wgpu::PipelineCache {
+        &self.cache
+    }
+
+    // Written to a temporary file and renamed over the old one, so a crash or another shadertui
+    // saving at the same time never leaves a truncated cache
+    pub fn save(&self) -> io::Result<()> {
+        let Some(data) = self.cache.get_data() else {
+            return Ok(());
+        };
+        let Some(dir) = self.path.parent() else {
+            return Ok(());
+        };
+        fs::create_dir_all(dir)?;
+        let temp_path = self
+            .path
+            .with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::{probe_adapter, GpuDevice};
+
+    #[test]
+    fn test_pipeline_cache_round_trip() {
+        let Some(adapter) = probe_adapter() else {
+            return;
+        };
+        let adapter_info = adapter.get_info();
+        let gpu_device = GpuDevice::from_adapter_blocking(&adapter).unwrap();
+        let device = &gpu_device.device;
+        let dir = std::env::temp_dir().join("shadertui_test_pipeline_cache");
+        let _ = fs::remove_dir_all(&dir);
+
+        // Backends without pipeline caches have nothing to persist
+        let Some(cache) = PipelineCache::load_from(&dir, device, &adapter_info) else {
+            return;
+        };
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl("@compute @workgroup_size(1) fn main() {}".into()),
+        });
+        let _pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: Some(cache.cache()),
+        });
+        cache.save().unwrap();
+
+        let saved = fs::read(&cache.path).unwrap();
+        assert!(!saved.is_empty());
+        let reloaded = PipelineCache::load_from(&dir, device, &adapter_info).unwrap();
+        assert!(reloaded.cache().get_data().is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
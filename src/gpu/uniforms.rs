@@ -1,20 +1,29 @@
 use bytemuck::{Pod, Zeroable};
 
+use crate::utils::uniform_params::{ParamValues, UniformParam, MAX_PARAMS};
+
 // AIDEV-NOTE: WGSL uniform buffer alignment requirements are strict!
 // - vec2<f32> fields must be aligned to 8-byte boundaries
 // - The total struct size must be a multiple of 16 bytes for uniforms
 // - Field ordering matters: putting vec2<f32> fields together avoids implicit padding
 // - Original issue: time:f32 followed by cursor:vec2<f32> created implicit padding
 // - Solution: group vec2<f32> fields together, then scalar fields, then explicit padding
+/// The values a frame's shader reads as `uniforms`, see [`ShaderEngine::on_before_upload`].
+///
+/// [`ShaderEngine::on_before_upload`]: crate::ShaderEngine::on_before_upload
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
 pub struct Uniforms {
-    pub resolution: [f32; 2], // Terminal resolution (cols, rows*2)
-    pub cursor: [f32; 2],     // Cursor position (x, y)
-    pub time: f32,            // Seconds since start
-    pub frame: u32,           // Frame number
-    pub delta_time: f32,      // Time since last frame
-    pub _padding: f32,        // Ensure 16-byte alignment
+    pub resolution: [f32; 2],      // Terminal resolution (cols, rows*2)
+    pub cursor: [f32; 2],          // Cursor position (x, y)
+    pub time: f32,                 // Seconds since start
+    pub frame: u32,                // Frame number
+    pub delta_time: f32,           // Time since last frame
+    pub sample_index: u32,         // Frames averaged so far with --accumulate
+    pub mouse: [f32; 4],           // Shadertoy-style iMouse, see MouseState
+    pub zoom: [f32; 4],            // Pixels shown by the zoom inspector, see zoom_transform
+    pub subsamples: u32,           // Samples per axis averaged into each pixel (supersampling)
+    pub params: [u32; MAX_PARAMS], // `// @uniform` values, see pack_params
 }
 
 impl Uniforms {
@@ -32,11 +41,109 @@ impl Uniforms {
             time,
             frame,
             delta_time,
-            _padding: 0.0,
+            sample_index: 0,
+            mouse: [0.0; 4],
+            zoom: IDENTITY_ZOOM,
+            subsamples: 1,
+            params: [0; MAX_PARAMS],
         }
     }
 }
 
+// The prelude evaluates the shader at `coords * zoom.zw + zoom.xy`
+pub const IDENTITY_ZOOM: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+// AIDEV-NOTE: Samples per axis with --supersample. Unlike --aa, which renders a bigger image and
+// filters it on the CPU, the prelude averages them per pixel, so nothing else grows
+pub const SHADER_SUBSAMPLES: u32 = 2;
+
+// AIDEV-NOTE: The zoom inspector's region arrives as fractions of the frame (x, y, width, height,
+// Y=0 at the bottom) so it's independent of --aa and --scale; this maps it onto `width`x`height`
+// pixels. The region keeps the frame's aspect ratio, so both axes share one scale
+pub fn zoom_transform(region: [f32; 4], width: u32, height: u32) -> [f32; 4] {
+    [
+        region[0] * width as f32,
+        region[1] * height as f32,
+        region[2],
+        region[3],
+    ]
+}
+
+// AIDEV-NOTE: Zooms into `selection` (x, y, width, height as fractions of what's on screen). The
+// selection is widened to a square fraction around its center, which keeps the frame's aspect
+// ratio, and nests inside the `current` region so the inspector can zoom repeatedly
+pub fn zoom_into(current: Option<[f32; 4]>, selection: [f32; 4]) -> [f32; 4] {
+    let [x, y, width, height] = selection;
+    let size = width.max(height).min(1.0);
+    let x = (x + (width - size) / 2.0).clamp(0.0, 1.0 - size);
+    let y = (y + (height - size) / 2.0).clamp(0.0, 1.0 - size);
+    let [origin_x, origin_y, scale, _] = current.unwrap_or(IDENTITY_ZOOM);
+    [
+        origin_x + x * scale,
+        origin_y + y * scale,
+        size * scale,
+        size * scale,
+    ]
+}
+
+// AIDEV-NOTE: `// @uniform` parameters are appended to the shader's Uniforms struct in declaration
+// order (shader_shell injects the fields). They are all 4-byte scalars right after the built-ins,
+// so parameter N sits in slot N with no padding in between
+pub fn pack_params(params: &[UniformParam], values: &ParamValues) -> [u32; MAX_PARAMS] {
+    let mut packed = [0; MAX_PARAMS];
+    for (slot, param) in packed.iter_mut().zip(params) {
+        *slot = param.to_bits(values.value(param));
+    }
+    packed
+}
+
+// AIDEV-NOTE: Button state behind `uniforms.mouse`, with Shadertoy's iMouse semantics: xy is the
+// pointer position while the button is held (kept after release), zw the position of the last
+// click. z is negated while the button is up, and w is negated except in the first frame after a
+// click. Positions are in the renderer's pixels, Y=0 at the bottom
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MouseState {
+    position: [f32; 2],
+    click: [f32; 2],
+    down: bool,
+    clicked: bool,
+}
+
+impl MouseState {
+    pub fn press(&mut self, position: [f32; 2]) {
+        self.position = position;
+        self.click = position;
+        self.down = true;
+        self.clicked = true;
+    }
+
+    pub fn drag(&mut self, position: [f32; 2]) {
+        if self.down {
+            self.position = position;
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.down = false;
+    }
+
+    // The value for the next frame; the click only shows as such once
+    pub fn take_uniform(&mut self) -> [f32; 4] {
+        let z = if self.down {
+            self.click[0]
+        } else {
+            -self.click[0]
+        };
+        let w = if self.clicked {
+            self.click[1]
+        } else {
+            -self.click[1]
+        };
+        self.clicked = false;
+        [self.position[0], self.position[1], z, w]
+    }
+}
+
 pub struct UniformBuffer {
     pub buffer: wgpu::Buffer,
 }
@@ -45,7 +152,8 @@ impl UniformBuffer {
     pub fn new(device: &wgpu::Device) -> Self {
         let buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Uniform Buffer"),
-            size: std::mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+            // WGSL rounds the struct's size up to its 16-byte alignment
+            size: std::mem::size_of::<Uniforms>().next_multiple_of(16) as wgpu::BufferAddress,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -57,3 +165,42 @@ impl UniformBuffer {
         queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[*uniforms]));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mouse_signs() {
+        // Matches the WGSL struct: the vec4s land on 16-byte boundaries, parameters follow them
+        assert_eq!(std::mem::offset_of!(Uniforms, params), 68);
+
+        let mut mouse = MouseState::default();
+        assert_eq!(mouse.take_uniform(), [0.0; 4]);
+
+        // Click frame: both click coordinates positive
+        mouse.press([10.0, 20.0]);
+        assert_eq!(mouse.take_uniform(), [10.0, 20.0, 10.0, 20.0]);
+
+        // Dragging moves xy; w turns negative after the click frame
+        mouse.drag([15.0, 5.0]);
+        assert_eq!(mouse.take_uniform(), [15.0, 5.0, 10.0, -20.0]);
+
+        // Released: xy stays put, z turns negative, and moves are ignored
+        mouse.release();
+        mouse.drag([30.0, 30.0]);
+        assert_eq!(mouse.take_uniform(), [15.0, 5.0, -10.0, -20.0]);
+    }
+
+    #[test]
+    fn test_zoom_into() {
+        // A wide selection grows into a square fraction around its center
+        let zoom = zoom_into(None, [0.25, 0.5, 0.5, 0.25]);
+        assert_eq!(zoom, [0.25, 0.375, 0.5, 0.5]);
+
+        // Near an edge the region shifts back inside the frame, and zooms nest
+        let nested = zoom_into(Some(zoom), [0.9, 0.0, 0.2, 0.1]);
+        assert_eq!(nested, [0.65, 0.375, 0.1, 0.1]);
+        assert_eq!(zoom_transform(nested, 200, 100), [130.0, 37.5, 0.1, 0.1]);
+    }
+}
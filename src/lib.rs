@@ -0,0 +1,32 @@
+//! shadertui renders WGSL compute shaders to the terminal. Besides the command line program
+//! (`run`, with the default `cli` feature), [`ShaderEngine`] renders a shader into
+//! [`TerminalFrame`]s for other terminal UIs.
+
+// AIDEV-NOTE: Without the `cli` feature only the rendering core is built: gpu/, the shader shell,
+// uniforms, HeadlessRenderer and the engine, which is what compiles for wasm32 with WebGPU
+// (`cargo build --lib --no-default-features --target wasm32-unknown-unknown`). The frontends,
+// file watching and the render threads all live behind `cli`, and most of the core's helpers are
+// only called from there
+#![cfg_attr(not(feature = "cli"), allow(dead_code))]
+
+#[cfg(feature = "cli")]
+mod app;
+mod audio;
+#[cfg(feature = "cli")]
+mod commands;
+pub mod engine;
+pub mod error;
+mod gpu;
+mod renderers;
+#[cfg(feature = "cli")]
+mod threaded_event_loop;
+mod utils;
+#[cfg(feature = "ratatui")]
+pub mod widget;
+#[cfg(feature = "cli")]
+mod windowed_event_loop;
+
+#[cfg(feature = "cli")]
+pub use app::run;
+pub use engine::{Cell, CellMode, ColorMode, ShaderEngine, TerminalFrame, Uniforms};
+pub use error::{ShadertuiError, SourceSpan};
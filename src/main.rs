@@ -1,19 +1,20 @@
-mod gpu;
-mod renderers;
-mod threaded_event_loop;
-mod utils;
-mod windowed_event_loop;
+use std::process::ExitCode;
 
-use threaded_event_loop::run_threaded_event_loop;
-use utils::Cli;
-use windowed_event_loop::run_windowed_event_loop;
+use shadertui::ShadertuiError;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let (cli, shader_source) = Cli::parse_and_load()?;
-
-    if cli.is_windowed_mode() {
-        run_windowed_event_loop(cli, shader_source)
-    } else {
-        run_threaded_event_loop(cli, shader_source)
+// AIDEV-NOTE: Compiler reports already start with where the error is, so they're printed as they are
+fn main() -> ExitCode {
+    match shadertui::run() {
+        Ok(()) => ExitCode::SUCCESS,
+        // Already reported; the process exits with the shader's own code
+        Err(ShadertuiError::ShaderExit(code)) => std::process::exit(code as i32),
+        Err(e @ ShadertuiError::ShaderCompile { .. }) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
     }
 }
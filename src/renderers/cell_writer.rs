@@ -0,0 +1,187 @@
+use std::fmt::Write;
+
+use crate::utils::color::push_ansi_color;
+use crate::utils::options::ColorMode;
+
+type Rgb = (u8, u8, u8);
+
+// One terminal cell of the render; no color means the terminal's default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph: char,
+    pub fg: Option<Rgb>,
+    pub bg: Option<Rgb>,
+}
+
+impl Cell {
+    pub const BLANK: Self = Self {
+        glyph: ' ',
+        fg: None,
+        bg: None,
+    };
+
+    // Colors scaled towards black by `brightness` (0 to 1)
+    pub fn dimmed(self, brightness: f32) -> Self {
+        let dim = |(r, g, b): Rgb| {
+            let scale = |c: u8| (c as f32 * brightness) as u8;
+            (scale(r), scale(g), scale(b))
+        };
+        Self {
+            fg: self.fg.map(dim),
+            bg: self.bg.map(dim),
+            ..self
+        }
+    }
+}
+
+// AIDEV-NOTE: Writes the render's cells with as few bytes as possible, which is what limits frame
+// rates over SSH. Color escapes are only sent when a color changes along a row (SGR state carries
+// over between cells and rows), and cells showing the same thing as in the previous frame are
+// skipped with a relative cursor move. Anything else drawing over the render (overlays, clears)
+// must invalidate the cells it touched so the next frame redraws them
+pub struct CellWriter {
+    // What each cell shows on screen, None where that is unknown
+    previous: Vec<Option<Cell>>,
+    width: usize,
+    // Colors set on the terminal while writing a frame
+    fg: Option<Rgb>,
+    bg: Option<Rgb>,
+}
+
+impl CellWriter {
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+            width: 0,
+            fg: None,
+            bg: None,
+        }
+    }
+
+    // Redraw every cell with the next frame
+    pub fn invalidate(&mut self) {
+        self.previous.clear();
+    }
+
+    pub fn invalidate_row(&mut self, row: usize) {
+        let width = self.width;
+        if let Some(cells) = self.previous.get_mut(row * width..(row + 1) * width) {
+            cells.fill(None);
+        }
+    }
+
+    // Start a frame of `width` x `height` cells; a new size redraws everything
+    pub fn begin_frame(&mut self, out: &mut String, width: usize, height: usize) {
+        if self.width != width || self.previous.len() != width * height {
+            self.width = width;
+            self.previous = vec![None; width * height];
+        }
+        out.push_str("\x1b[0m");
+        self.fg = None;
+        self.bg = None;
+    }
+
+    // Write the cells of `row` that changed, the first one `column_offset` columns from the left
+    pub fn push_row(
+        &mut self,
+        out: &mut String,
+        row: usize,
+        column_offset: usize,
+        cells: &[Cell],
+        mode: ColorMode,
+    ) {
+        let Some(previous) = self
+            .previous
+            .get_mut(row * self.width..(row + 1) * self.width)
+        else {
+            return;
+        };
+        // Column the terminal cursor is at, once something in this row was written
+        let mut cursor = None;
+        for (column, (cell, shown)) in cells.iter().zip(previous.iter_mut()).enumerate() {
+            if *shown == Some(*cell) {
+                continue;
+            }
+            match cursor {
+                Some(at) if at == column => {}
+                Some(at) => {
+                    let _ = write!(out, "\x1b[{}C", column - at);
+                }
+                None => {
+                    let _ = write!(out, "\x1b[{};{}H", row + 1, column_offset + column + 1);
+                }
+            }
+            if cell.fg != self.fg {
+                match cell.fg {
+                    Some(rgb) => push_ansi_color(out, "38", rgb, mode),
+                    None => out.push_str("\x1b[39m"),
+                }
+                self.fg = cell.fg;
+            }
+            if cell.bg != self.bg {
+                match cell.bg {
+                    Some(rgb) => push_ansi_color(out, "48", rgb, mode),
+                    None => out.push_str("\x1b[49m"),
+                }
+                self.bg = cell.bg;
+            }
+            out.push(cell.glyph);
+            *shown = Some(*cell);
+            cursor = Some(column + 1);
+        }
+    }
+
+    // Leave the terminal with default colors for whatever is drawn after the frame
+    pub fn end_frame(&mut self, out: &mut String) {
+        if self.fg.is_some() || self.bg.is_some() {
+            out.push_str("\x1b[0m");
+            self.fg = None;
+            self.bg = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_writer_batches_and_skips() {
+        let red = Cell {
+            glyph: '▀',
+            fg: Some((255, 0, 0)),
+            bg: Some((0, 0, 0)),
+        };
+        let blue = Cell {
+            fg: Some((0, 0, 255)),
+            ..red
+        };
+        let mut writer = CellWriter::new();
+
+        // Colors are sent once for a run of equal cells
+        let mut out = String::new();
+        writer.begin_frame(&mut out, 4, 1);
+        writer.push_row(&mut out, 0, 0, &[red, red, red, blue], ColorMode::Truecolor);
+        writer.end_frame(&mut out);
+        assert_eq!(
+            out,
+            "\x1b[0m\x1b[1;1H\x1b[38;2;255;0;0m\x1b[48;2;0;0;0m▀▀▀\x1b[38;2;0;0;255m▀\x1b[0m"
+        );
+
+        // An unchanged frame writes no cells; a changed cell is reached with a relative move
+        let mut out = String::new();
+        writer.begin_frame(&mut out, 4, 1);
+        writer.push_row(
+            &mut out,
+            0,
+            2,
+            &[red, blue, red, Cell::BLANK],
+            ColorMode::Truecolor,
+        );
+        writer.end_frame(&mut out);
+        assert_eq!(
+            out,
+            "\x1b[0m\x1b[1;4H\x1b[38;2;0;0;255m\x1b[48;2;0;0;0m▀\x1b[1C\x1b[39m\x1b[49m "
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use crate::gpu::FramePixels;
+use crate::renderers::cell_writer::Cell;
+use crate::utils::options::CellMode;
+
+// AIDEV-NOTE: Sparse to dense, so brighter pixels put more ink on screen
+const DENSITY_GLYPHS: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+// AIDEV-NOTE: Braille dot bits and ordered-dither thresholds, indexed [row from top][column]
+const BRAILLE_BASE: u32 = 0x2800;
+const BRAILLE_DOT_BITS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+const BRAILLE_THRESHOLDS: [[f32; 2]; 4] = [
+    [0.5 / 8.0, 4.5 / 8.0],
+    [6.5 / 8.0, 2.5 / 8.0],
+    [1.5 / 8.0, 5.5 / 8.0],
+    [7.5 / 8.0, 3.5 / 8.0],
+];
+
+// Quadrant glyphs by mask: bit 0 top left, 1 top right, 2 bottom left, 3 bottom right
+const QUADRANT_GLYPHS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+// Sextants are U+1FB00.. in mask order, skipping the four patterns older blocks already cover
+const SEXTANT_BASE: u32 = 0x1FB00;
+// Rounds of 2-means clustering per cell; with at most six pixels it settles within a few
+const CLUSTER_ROUNDS: usize = 3;
+
+// AIDEV-NOTE: The cell at terminal column `column` and row `row`, counted from the bottom like GPU
+// pixel rows, of a render `gpu_width` pixels wide covering `mode.pixels_per_cell()` per cell.
+// Shared by the terminal frontend and the library's TerminalFrame so both draw the same
+pub fn pixel_cell(
+    pixels: &FramePixels,
+    gpu_width: usize,
+    column: usize,
+    row: usize,
+    mode: CellMode,
+) -> Cell {
+    // Pixel indices of the top and bottom halves of a two-pixel cell
+    let top_idx = (row * 2 + 1) * gpu_width + column;
+    let bottom_idx = row * 2 * gpu_width + column;
+
+    match mode {
+        CellMode::Braille => braille_cell(pixels, gpu_width, column, row),
+        CellMode::Quadrant => block_cell(pixels, gpu_width, column, row, 2, quadrant_glyph),
+        CellMode::Sextant => block_cell(pixels, gpu_width, column, row, 3, sextant_glyph),
+        CellMode::Foreground | CellMode::Ascii => ramp_cell(
+            pixels,
+            gpu_width,
+            column,
+            row,
+            &DENSITY_GLYPHS,
+            mode == CellMode::Foreground,
+        ),
+        // ▀ with the top color as foreground and the bottom one as background, gamma-encoded
+        CellMode::HalfBlock => Cell {
+            glyph: '▀',
+            fg: Some(pixels.display_rgb(top_idx).unwrap_or_default()),
+            bg: Some(pixels.display_rgb(bottom_idx).unwrap_or_default()),
+        },
+    }
+}
+
+// AIDEV-NOTE: Cell of a glyph from `ramp` (dark to bright) for the brightness of its two pixels.
+// Never sets a background; `colored` draws the glyph in the pixels' color, otherwise the terminal's
+// text color is kept. The terminal frontend calls this directly for a --cell-mode ramp
+pub fn ramp_cell(
+    pixels: &FramePixels,
+    gpu_width: usize,
+    column: usize,
+    row: usize,
+    ramp: &[char],
+    colored: bool,
+) -> Cell {
+    // Average in linear space
+    let top = pixels
+        .linear_rgb((row * 2 + 1) * gpu_width + column)
+        .unwrap_or_default();
+    let bottom = pixels
+        .linear_rgb(row * 2 * gpu_width + column)
+        .unwrap_or_default();
+    let (r, g, b) = float_rgb_to_u8(
+        (top[0] + bottom[0]) * 0.5,
+        (top[1] + bottom[1]) * 0.5,
+        (top[2] + bottom[2]) * 0.5,
+    );
+    let luma = (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0;
+    let glyph_index = (luma * (ramp.len() - 1) as f32).round() as usize;
+    Cell {
+        glyph: ramp[glyph_index.min(ramp.len() - 1)],
+        fg: colored.then_some((r, g, b)),
+        bg: None,
+    }
+}
+
+// AIDEV-NOTE: Cell over 2 x `rows` pixels in two colors. The pixels are split into two clusters
+// (2-means in gamma-encoded RGB, seeded with the two most different pixels); each cluster's mean
+// becomes the foreground or background, and the pixels in the foreground one pick the glyph. Mask
+// bits go left to right, top to bottom
+fn block_cell(
+    pixels: &FramePixels,
+    gpu_width: usize,
+    column: usize,
+    row: usize,
+    rows: usize,
+    glyph: fn(u32) -> char,
+) -> Cell {
+    let mut colors = [[0.0f32; 3]; 6];
+    for (i, color) in colors.iter_mut().take(rows * 2).enumerate() {
+        // Rows go top to bottom on screen, so start at the highest pixel row of the cell
+        let pixel_y = row * rows + rows - 1 - i / 2;
+        let (r, g, b) = pixels
+            .display_rgb(pixel_y * gpu_width + column * 2 + i % 2)
+            .unwrap_or_default();
+        *color = [r as f32, g as f32, b as f32];
+    }
+    let (mask, fg, bg) = two_colors(&colors[..rows * 2]);
+    let to_rgb = |c: [f32; 3]| (c[0].round() as u8, c[1].round() as u8, c[2].round() as u8);
+    Cell {
+        glyph: glyph(mask),
+        fg: Some(to_rgb(fg)),
+        bg: Some(to_rgb(bg)),
+    }
+}
+
+// The pixels nearer the first color as a mask, and the two cluster means
+fn two_colors(colors: &[[f32; 3]]) -> (u32, [f32; 3], [f32; 3]) {
+    let distance = |a: [f32; 3], b: [f32; 3]| {
+        (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+    };
+    let mut seeds = (0, 0);
+    let mut widest = 0.0;
+    for i in 0..colors.len() {
+        for j in i + 1..colors.len() {
+            let d = distance(colors[i], colors[j]);
+            if d > widest {
+                widest = d;
+                seeds = (i, j);
+            }
+        }
+    }
+    let (mut fg, mut bg) = (colors[seeds.0], colors[seeds.1]);
+    if widest == 0.0 {
+        return (0, fg, bg);
+    }
+
+    let mut mask = 0;
+    for _ in 0..CLUSTER_ROUNDS {
+        let mut sums = [[0.0f32; 3]; 2];
+        let mut counts = [0.0f32; 2];
+        mask = 0;
+        for (i, &color) in colors.iter().enumerate() {
+            let cluster = if distance(color, fg) <= distance(color, bg) {
+                mask |= 1 << i;
+                0
+            } else {
+                1
+            };
+            counts[cluster] += 1.0;
+            for channel in 0..3 {
+                sums[cluster][channel] += color[channel];
+            }
+        }
+        // A cluster left without pixels keeps its last mean
+        if counts[0] > 0.0 {
+            fg = sums[0].map(|sum| sum / counts[0]);
+        }
+        if counts[1] > 0.0 {
+            bg = sums[1].map(|sum| sum / counts[1]);
+        }
+    }
+    (mask, fg, bg)
+}
+
+fn quadrant_glyph(mask: u32) -> char {
+    QUADRANT_GLYPHS[mask as usize & 0xF]
+}
+
+fn sextant_glyph(mask: u32) -> char {
+    match mask {
+        0 => ' ',
+        0b010101 => '▌',
+        0b101010 => '▐',
+        0b111111 => '█',
+        _ => {
+            let skipped = (mask > 0b010101) as u32 + (mask > 0b101010) as u32;
+            char::from_u32(SEXTANT_BASE + mask - 1 - skipped).unwrap_or(' ')
+        }
+    }
+}
+
+// AIDEV-NOTE: Braille cell over 2x4 pixels. Dots are lit by ordered dithering on brightness and
+// drawn in the lit pixels' average color normalized to full brightness, so dot density carries
+// brightness and the color carries hue
+fn braille_cell(pixels: &FramePixels, gpu_width: usize, column: usize, row: usize) -> Cell {
+    let mut bits = 0;
+    let mut sum = [0.0f32; 3];
+    for (dot_row, (row_bits, row_thresholds)) in
+        BRAILLE_DOT_BITS.iter().zip(&BRAILLE_THRESHOLDS).enumerate()
+    {
+        // Rows go top to bottom on screen, so start at the highest pixel row of the cell
+        let pixel_y = row * 4 + 3 - dot_row;
+        for dot_col in 0..2 {
+            let idx = pixel_y * gpu_width + column * 2 + dot_col;
+            let Some(rgb) = pixels.linear_rgb(idx) else {
+                continue;
+            };
+            let luma = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+            if luma.max(0.0).powf(1.0 / 2.2) > row_thresholds[dot_col] {
+                bits |= row_bits[dot_col];
+                sum[0] += rgb[0];
+                sum[1] += rgb[1];
+                sum[2] += rgb[2];
+            }
+        }
+    }
+
+    if bits == 0 {
+        return Cell::BLANK;
+    }
+    let peak = sum[0].max(sum[1]).max(sum[2]);
+    Cell {
+        glyph: char::from_u32(BRAILLE_BASE + bits).unwrap_or(' '),
+        fg: Some(float_rgb_to_u8(sum[0] / peak, sum[1] / peak, sum[2] / peak)),
+        bg: None,
+    }
+}
+
+// AIDEV-NOTE: Gamma correction to match the window renderer (linear -> sRGB)
+fn float_rgb_to_u8(r: f32, g: f32, b: f32) -> (u8, u8, u8) {
+    let r = (r.powf(1.0 / 2.2) * 255.0) as u8;
+    let g = (g.powf(1.0 / 2.2) * 255.0) as u8;
+    let b = (b.powf(1.0 / 2.2) * 255.0) as u8;
+    (r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_cells() {
+        // 2x2 pixels, bottom row first: blue below red
+        let pixels = FramePixels::Linear(vec![
+            0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0, //
+            1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0,
+        ]);
+        let cell = pixel_cell(&pixels, 2, 0, 0, CellMode::Quadrant);
+        assert_eq!(cell.glyph, '▀');
+        assert_eq!(cell.fg, Some((255, 0, 0)));
+        assert_eq!(cell.bg, Some((0, 0, 255)));
+
+        assert_eq!(two_colors(&[[10.0; 3]; 4]).0, 0);
+        assert_eq!(
+            two_colors(&[[0.0; 3], [0.0; 3], [90.0; 3], [100.0; 3]]).0,
+            0b0011
+        );
+
+        assert_eq!(sextant_glyph(0b000001), '\u{1FB00}');
+        assert_eq!(sextant_glyph(0b010110), '\u{1FB14}');
+        assert_eq!(sextant_glyph(0b111110), '\u{1FB3B}');
+        assert_eq!(sextant_glyph(0b010101), '▌');
+    }
+}
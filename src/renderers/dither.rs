@@ -0,0 +1,118 @@
+use crate::renderers::cell_writer::Cell;
+use crate::utils::color::palette_color;
+use crate::utils::options::{ColorMode, Dither};
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// AIDEV-NOTE: Dithers cells as they are built, treating each cell's foreground and background as two
+// pixel rows (the top and bottom half of ▀). Colors come out as palette colors, which
+// push_ansi_color maps back to their own index. Truecolor never gets a Ditherer, so its path is
+// unchanged
+pub struct Ditherer {
+    method: Dither,
+    mode: ColorMode,
+    // Floyd-Steinberg error for the pixel row being dithered and the one below it
+    errors: Vec<[f32; 3]>,
+    next_errors: Vec<[f32; 3]>,
+}
+
+impl Ditherer {
+    // None when there is nothing to dither
+    pub fn new(method: Dither, mode: ColorMode) -> Option<Self> {
+        (method != Dither::None && mode != ColorMode::Truecolor).then(|| Self {
+            method,
+            mode,
+            errors: Vec::new(),
+            next_errors: Vec::new(),
+        })
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.next_errors.clear();
+    }
+
+    // `row` is the cell row on screen, which places the ordered pattern
+    pub fn dither_row(&mut self, row: usize, cells: &mut [Cell]) {
+        for half in 0..2 {
+            let y = row * 2 + half;
+            std::mem::swap(&mut self.errors, &mut self.next_errors);
+            self.errors.resize(cells.len() + 1, [0.0; 3]);
+            self.next_errors.clear();
+            self.next_errors.resize(cells.len() + 1, [0.0; 3]);
+
+            for (x, cell) in cells.iter_mut().enumerate() {
+                let color = if half == 0 {
+                    &mut cell.fg
+                } else {
+                    &mut cell.bg
+                };
+                if let Some(rgb) = color {
+                    *rgb = self.dither_pixel(x, y, *rgb);
+                }
+            }
+        }
+    }
+
+    fn dither_pixel(&mut self, x: usize, y: usize, (r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+        let offset = match self.method {
+            Dither::Ordered => {
+                let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+                [threshold * self.spread(); 3]
+            }
+            _ => self.errors[x],
+        };
+        let value = [
+            (r as f32 + offset[0]).clamp(0.0, 255.0),
+            (g as f32 + offset[1]).clamp(0.0, 255.0),
+            (b as f32 + offset[2]).clamp(0.0, 255.0),
+        ];
+        let snapped = palette_color((value[0] as u8, value[1] as u8, value[2] as u8), self.mode);
+
+        if self.method == Dither::FloydSteinberg {
+            let snapped_channels = [snapped.0, snapped.1, snapped.2];
+            for channel in 0..3 {
+                let error = value[channel] - snapped_channels[channel] as f32;
+                self.errors[x + 1][channel] += error * 7.0 / 16.0;
+                if x > 0 {
+                    self.next_errors[x - 1][channel] += error * 3.0 / 16.0;
+                }
+                self.next_errors[x][channel] += error * 5.0 / 16.0;
+                self.next_errors[x + 1][channel] += error / 16.0;
+            }
+        }
+        snapped
+    }
+
+    // About the distance between neighboring palette levels of one channel
+    fn spread(&self) -> f32 {
+        match self.mode {
+            ColorMode::Ansi16 => 128.0,
+            _ => 51.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dither_mixes_palette_colors() {
+        // Mid gray sits between black and gray in the 16-color palette
+        let gray = Cell {
+            glyph: '▀',
+            fg: Some((64, 64, 64)),
+            bg: Some((64, 64, 64)),
+        };
+        for method in [Dither::Ordered, Dither::FloydSteinberg] {
+            let mut ditherer = Ditherer::new(method, ColorMode::Ansi16).unwrap();
+            ditherer.begin_frame();
+            let mut cells = vec![gray; 8];
+            ditherer.dither_row(0, &mut cells);
+            let colors: Vec<_> = cells.iter().flat_map(|cell| [cell.fg, cell.bg]).collect();
+            assert!(colors.contains(&Some((0, 0, 0))));
+            assert!(colors.contains(&Some((127, 127, 127))));
+        }
+        assert!(Ditherer::new(Dither::Ordered, ColorMode::Truecolor).is_none());
+    }
+}
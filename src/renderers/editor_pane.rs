@@ -0,0 +1,233 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::utils::messages::msg;
+use crate::utils::text_buffer::TextBuffer;
+
+// AIDEV-NOTE: How long typing must pause before the buffer is recompiled
+const RECOMPILE_DEBOUNCE: Duration = Duration::from_millis(300);
+const TAB: &str = "    ";
+
+// AIDEV-NOTE: Minimal text editor drawn in the left columns of the terminal for --edit. Edits are
+// compiled from memory after a pause in typing; the file on disk only changes on Ctrl+S
+pub struct EditorPane {
+    path: PathBuf,
+    buffer: TextBuffer,
+    scroll: usize,
+    last_edit: Option<Instant>,
+    modified: bool,
+    message: Option<String>,
+    error: Option<String>,
+    needs_redraw: bool,
+}
+
+impl EditorPane {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            buffer: TextBuffer::new(&text),
+            scroll: 0,
+            last_edit: None,
+            modified: false,
+            message: Some(msg!("editor.hint")),
+            error: None,
+            needs_redraw: true,
+        })
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, page_height: usize) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let edited = match key.code {
+            KeyCode::Char('s') if ctrl => {
+                self.save();
+                false
+            }
+            _ if ctrl => return,
+            KeyCode::Char(c) => {
+                self.buffer.insert_char(c);
+                true
+            }
+            KeyCode::Tab => {
+                self.buffer.insert_str(TAB);
+                true
+            }
+            KeyCode::Enter => {
+                self.buffer.insert_newline();
+                true
+            }
+            KeyCode::Backspace => {
+                self.buffer.backspace();
+                true
+            }
+            KeyCode::Delete => {
+                self.buffer.delete();
+                true
+            }
+            KeyCode::Left => {
+                self.buffer.move_left();
+                false
+            }
+            KeyCode::Right => {
+                self.buffer.move_right();
+                false
+            }
+            KeyCode::Up => {
+                self.buffer.move_up(1);
+                false
+            }
+            KeyCode::Down => {
+                self.buffer.move_down(1);
+                false
+            }
+            KeyCode::PageUp => {
+                self.buffer.move_up(page_height);
+                false
+            }
+            KeyCode::PageDown => {
+                self.buffer.move_down(page_height);
+                false
+            }
+            KeyCode::Home => {
+                self.buffer.move_home();
+                false
+            }
+            KeyCode::End => {
+                self.buffer.move_end();
+                false
+            }
+            _ => return,
+        };
+
+        if edited {
+            self.last_edit = Some(Instant::now());
+            self.modified = true;
+        }
+        self.needs_redraw = true;
+    }
+
+    // AIDEV-NOTE: Source to recompile once typing has paused, at most once per burst of edits
+    pub fn take_pending_source(&mut self) -> Option<String> {
+        let last_edit = self.last_edit?;
+        if last_edit.elapsed() < RECOMPILE_DEBOUNCE {
+            return None;
+        }
+        self.last_edit = None;
+        Some(self.buffer.text())
+    }
+
+    pub fn set_error(&mut self, error: Option<String>) {
+        if self.error != error {
+            self.error = error;
+            self.needs_redraw = true;
+        }
+    }
+
+    pub fn invalidate(&mut self) {
+        self.needs_redraw = true;
+    }
+
+    pub fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    fn save(&mut self) {
+        self.message = Some(match fs::write(&self.path, self.buffer.text()) {
+            Ok(()) => {
+                self.modified = false;
+                msg!("notice.saved", path = self.path.display())
+            }
+            Err(e) => msg!("editor.save_failed", error = e),
+        });
+    }
+
+    // AIDEV-NOTE: Draws `width` columns of every row plus a separator column, and returns where
+    // the terminal cursor should be shown (column, row)
+    pub fn draw(&mut self, screen_content: &mut String, width: usize, height: usize) -> (u16, u16) {
+        let text_rows = height.saturating_sub(1).max(1);
+        let (cursor_row, cursor_col) = self.buffer.cursor();
+        if cursor_row < self.scroll {
+            self.scroll = cursor_row;
+        } else if cursor_row >= self.scroll + text_rows {
+            self.scroll = cursor_row + 1 - text_rows;
+        }
+
+        let gutter = 4;
+        let text_width = width.saturating_sub(gutter);
+        let h_scroll = cursor_col.saturating_sub(text_width.saturating_sub(1));
+        for row in 0..text_rows {
+            let line_index = self.scroll + row;
+            let line = match self.buffer.lines().get(line_index) {
+                Some(line) => format!(
+                    "{:>3} {}",
+                    line_index + 1,
+                    line.chars()
+                        .skip(h_scroll)
+                        .take(text_width)
+                        .collect::<String>()
+                ),
+                None => "~".to_string(),
+            };
+            push_row(screen_content, row, &line, width);
+        }
+
+        let status = match (&self.error, &self.message) {
+            (Some(error), _) => format!("\x1b[31m{}\x1b[0m", single_line(error)),
+            (None, Some(message)) => message.clone(),
+            (None, None) => String::new(),
+        };
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let modified = if self.modified { " [+]" } else { "" };
+        let status_line = format!(
+            "{file_name}{modified} {}:{} {status}",
+            cursor_row + 1,
+            cursor_col + 1
+        );
+        push_row(screen_content, text_rows, &status_line, width);
+
+        self.needs_redraw = false;
+        (
+            (gutter + cursor_col - h_scroll) as u16,
+            (cursor_row - self.scroll) as u16,
+        )
+    }
+}
+
+// AIDEV-NOTE: Compiler errors span several lines; the status line shows them collapsed
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// AIDEV-NOTE: Positions explicitly since the pane doesn't span the full terminal width. Width is
+// counted in chars, ignoring escape sequences in `text`
+fn push_row(screen_content: &mut String, row: usize, text: &str, width: usize) {
+    screen_content.push_str(&format!("\x1b[{};1H", row + 1));
+
+    let mut visible = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        if c == '\x1b' {
+            in_escape = true;
+        }
+        if in_escape {
+            screen_content.push(c);
+            in_escape = c != 'm';
+            continue;
+        }
+        if visible == width {
+            break;
+        }
+        screen_content.push(c);
+        visible += 1;
+    }
+    screen_content.push_str("\x1b[0m");
+    screen_content.push_str(&" ".repeat(width - visible));
+    screen_content.push('│');
+}
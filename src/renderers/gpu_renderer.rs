@@ -1,150 +1,565 @@
-use std::time::Instant;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::gpu::{ComputePipeline, GpuBuffers, GpuDevice, UniformBuffer, Uniforms};
+use crate::error::ShadertuiError;
+use crate::gpu::{
+    zoom_transform, FramePixels, GpuDevice, Readback, Uniforms, IDENTITY_ZOOM, SHADER_SUBSAMPLES,
+};
+use crate::renderers::headless_renderer::{HeadlessRenderer, PreparedReload};
+use crate::renderers::window::MirrorOutput;
 use crate::utils::{
-    shader_shell::{inject_user_shader, ShellType},
+    antialias::{downsample, scaled_size, SUPERSAMPLE},
+    backoff::{Backoff, MAX_RETRIES},
+    cli::{AntiAliasing, SampleView},
+    frame_pacing::{DeltaSmoother, DeltaSmoothing, FramePacer},
+    image_export::RgbaImage,
+    messages::msg,
+    project::ShaderProject,
+    shader_shell::ShellOptions,
     threading::{
-        DualPerformanceTrackerHandle, ErrorSender, FrameData, SharedFrameBufferHandle,
+        send_error, DualPerformanceTrackerHandle, ErrorSender, FrameData, SharedFrameBufferHandle,
         SharedUniformsHandle, ThreadError,
     },
+    trace::{self, FrameUniforms, TraceEvent},
 };
 
+// How often a lost GPU device is asked for again while none can be created
+const DEVICE_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+// AIDEV-NOTE: Terminal rendering options from the command line (or the shader's header pragmas)
+#[derive(Debug, Clone, Copy)]
+pub struct GpuOptions {
+    pub gpu_timeout: Duration,
+    pub sample_view: SampleView,
+    // Renders at SUPERSAMPLE times the size and filters each frame down to the render size
+    pub aa: Option<AntiAliasing>,
+    // Fraction of width x height actually rendered; frames are stretched back up (--scale)
+    pub scale: f32,
+    // Seconds after which `uniforms.time` wraps around (--loop)
+    pub loop_duration: Option<f32>,
+    // Frames rendered per second at most (--max-fps)
+    pub max_fps: Option<u32>,
+    // Keep rendering while frames are read back, at the cost of a frame or two of latency (off
+    // with --low-latency)
+    pub pipelined_readback: bool,
+    // Measure GPU time per frame with timestamp queries (--perf)
+    pub gpu_timing: bool,
+    // Clamping and averaging of `uniforms.delta_time` (--max-delta, --smooth-delta)
+    pub delta_smoothing: DeltaSmoothing,
+}
+
 // AIDEV-NOTE: GPU renderer runs in dedicated thread for continuous compute
 pub struct GpuRenderer {
-    gpu_device: GpuDevice,
-    gpu_buffers: GpuBuffers,
-    uniform_buffer: UniformBuffer,
-    compute_pipeline: ComputePipeline,
+    renderer: HeadlessRenderer,
+    // Kept to rebuild the renderer on a fresh device after a GPU hang
+    project: ShaderProject,
+    shell_options: ShellOptions,
+    options: GpuOptions,
+    // Set after a timeout; rendering waits for a shader reload instead of hanging again
+    timed_out: bool,
+    // Waits between failed frames; once it gives up, rendering stops until a restart or reload
+    backoff: Backoff,
+    stopped: bool,
     width: u32,
     height: u32,
     frame_count: u32,
+    // Frames averaged into the output since the last restart (--accumulate)
+    sample_index: u32,
+    last_scene_generation: u64,
     start_time: Instant,
     last_frame_time: Instant,
+    delta_smoother: DeltaSmoother,
+    // Frames submitted for readback, keyed by readback id and waiting for their data
+    in_flight: VecDeque<(u64, FrameData)>,
+    // Set by the terminal's adaptive resolution, multiplying --scale
+    adaptive_scale: f32,
+    // A reload compiling on the "compile" thread while the current shader keeps rendering
+    pending_reload: Option<PendingReload>,
+    // While paused, the input generation of the last still frame read back
+    paused_input: Option<u64>,
+    // Reloads start over at time 0 and frame 0 with zeroed state (--reset-on-reload, T)
+    reset_on_reload: bool,
+    // The --mirror window, drawn from the same device until it is closed
+    mirror: Option<MirrorOutput>,
+}
+
+struct PendingReload {
+    project: ShaderProject,
+    result: Receiver<Result<PreparedReload, String>>,
 }
 
 impl GpuRenderer {
     pub fn new(
         width: u32,
         height: u32,
-        user_shader_source: &str,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        // Inject user shader into terminal shell
-        let complete_shader = inject_user_shader(user_shader_source, ShellType::Terminal)?;
-
-        // Initialize GPU - double the height for half-cell rendering
-        let gpu_device = GpuDevice::new_blocking()?;
-        let gpu_buffers = GpuBuffers::new(&gpu_device.device, width, height * 2);
-        let uniform_buffer = UniformBuffer::new(&gpu_device.device);
-        let compute_pipeline = ComputePipeline::new(
-            &gpu_device.device,
-            &gpu_buffers,
-            &uniform_buffer,
-            &complete_shader,
+        project: &ShaderProject,
+        shell_options: ShellOptions,
+        options: GpuOptions,
+        mut mirror: Option<MirrorOutput>,
+    ) -> Result<Self, ShadertuiError> {
+        // Initialize GPU at the pixel resolution of the terminal's cell mode
+        let (gpu_width, gpu_height) = Self::gpu_size(width, height, &options);
+        let gpu_device = Self::create_device(mirror.as_mut())?;
+        let mut renderer = HeadlessRenderer::with_device(
+            gpu_device,
+            gpu_width,
+            gpu_height,
+            project,
+            shell_options.clone(),
         )?;
+        renderer.set_gpu_timeout(options.gpu_timeout);
+        renderer.set_sample_view(options.sample_view);
+        renderer.set_gpu_timing(options.gpu_timing);
+        // --aa filters the frame on the CPU, which needs the float pixels
+        renderer.set_packed_output(options.aa.is_none());
 
         let now = Instant::now();
 
         Ok(Self {
-            gpu_device,
-            gpu_buffers,
-            uniform_buffer,
-            compute_pipeline,
+            renderer,
+            project: project.clone(),
+            shell_options,
+            options,
+            timed_out: false,
+            backoff: Backoff::default(),
+            stopped: false,
             width,
             height,
             frame_count: 0,
+            sample_index: 0,
+            last_scene_generation: 0,
             start_time: now,
             last_frame_time: now,
+            delta_smoother: DeltaSmoother::new(options.delta_smoothing),
+            in_flight: VecDeque::new(),
+            adaptive_scale: 1.0,
+            pending_reload: None,
+            paused_input: None,
+            reset_on_reload: false,
+            mirror,
         })
     }
 
-    // AIDEV-NOTE: Reload shader with new source, called from compute thread
-    pub fn reload_shader(
-        &mut self,
-        user_shader_source: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Inject user shader into terminal shell
-        let complete_shader = inject_user_shader(user_shader_source, ShellType::Terminal)?;
-
-        // Create new compute pipeline
-        let new_pipeline = ComputePipeline::new(
-            &self.gpu_device.device,
-            &self.gpu_buffers,
-            &self.uniform_buffer,
-            &complete_shader,
+    // A device that can also present to the --mirror window, if there is one
+    fn create_device(mirror: Option<&mut MirrorOutput>) -> Result<GpuDevice, ShadertuiError> {
+        let Some(mirror) = mirror else {
+            return GpuDevice::new_blocking();
+        };
+        let gpu_device = GpuDevice::from_adapter_blocking(mirror.adapter())?;
+        mirror.set_device(&gpu_device);
+        Ok(gpu_device)
+    }
+
+    fn supersample(aa: Option<AntiAliasing>) -> u32 {
+        if aa.is_some() {
+            SUPERSAMPLE
+        } else {
+            1
+        }
+    }
+
+    // Size of the rendered image before it is stretched to width x height
+    fn render_size(width: u32, height: u32, options: &GpuOptions) -> (u32, u32) {
+        scaled_size(width, height, options.scale)
+    }
+
+    pub fn gpu_size(width: u32, height: u32, options: &GpuOptions) -> (u32, u32) {
+        let (width, height) = Self::render_size(width, height, options);
+        let supersample = Self::supersample(options.aa);
+        (width * supersample, height * supersample)
+    }
+
+    // The options with the adaptive scale applied
+    fn scaled_options(&self) -> GpuOptions {
+        GpuOptions {
+            scale: self.options.scale * self.adaptive_scale,
+            ..self.options
+        }
+    }
+
+    // AIDEV-NOTE: Reload all passes with new sources, called from compute thread
+    pub fn reload_shader(&mut self, project: &ShaderProject) -> Result<(), ShadertuiError> {
+        self.pending_reload = None;
+        if self.needs_new_device() {
+            self.recreate_device()?;
+        }
+        self.renderer.set_keep_state(self.keeps_state());
+        self.renderer.reload_shader(project)?;
+        self.reloaded(project.clone());
+        Ok(())
+    }
+
+    // After a hang, repeated errors or a device loss, only a renderer on a new device can render
+    fn needs_new_device(&self) -> bool {
+        self.timed_out || self.stopped || self.renderer.device_lost()
+    }
+
+    // AIDEV-NOTE: --reset-on-reload starts the new shader over: `state` is zeroed here (even with
+    // --keep-state), the frame count in reloaded() and the clock by reset_clock() in the run loop
+    fn keeps_state(&self) -> bool {
+        self.shell_options.keep_state && !self.reset_on_reload
+    }
+
+    fn reloaded(&mut self, project: ShaderProject) {
+        if self.reset_on_reload {
+            self.frame_count = 0;
+        }
+        self.in_flight.clear();
+        self.paused_input = None;
+        self.project = project;
+        self.timed_out = false;
+        self.stopped = false;
+        self.backoff.reset();
+        self.sample_index = 0;
+    }
+
+    // AIDEV-NOTE: Hot reloads of a healthy renderer compile on a "compile" thread, so the old shader
+    // keeps rendering instead of the output freezing for as long as the driver takes; poll_reload()
+    // swaps the new pipelines in once they are ready. A newer reload replaces a pending one (its
+    // thread finishes into a closed channel), and one built before a resize or texture change is
+    // started over
+    fn start_reload(&mut self, project: ShaderProject) -> Result<(), ShadertuiError> {
+        self.renderer.set_keep_state(self.keeps_state());
+        let job = self.renderer.start_reload(&project)?;
+        let (sender, result) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("compile".to_string())
+            .spawn(move || {
+                let _ = sender.send(job.build().map_err(|e| e.to_string()));
+            })?;
+        self.pending_reload = Some(PendingReload { project, result });
+        Ok(())
+    }
+
+    // The outcome of the pending reload once its thread is done: Ok once it is rendering
+    fn poll_reload(&mut self) -> Option<Result<(), String>> {
+        let result = match self.pending_reload.as_ref()?.result.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err(msg!("error.compiler_crashed")),
+        };
+        let PendingReload { project, .. } = self.pending_reload.take()?;
+        if self.needs_new_device() {
+            return Some(self.reload_shader(&project).map_err(|e| e.to_string()));
+        }
+        let prepared = match result {
+            Ok(prepared) => prepared,
+            Err(e) => return Some(Err(e)),
+        };
+        if self.renderer.finish_reload(prepared) {
+            self.reloaded(project);
+            return Some(Ok(()));
+        }
+        self.start_reload(project).err().map(|e| Err(e.to_string()))
+    }
+
+    // Time 0 for a shader that was just reloaded with --reset-on-reload
+    fn reset_clock(&self, shared_uniforms: &SharedUniformsHandle) {
+        if self.reset_on_reload {
+            let current_time = self.start_time.elapsed().as_secs_f32();
+            shared_uniforms.lock().unwrap().reset_clock(current_time);
+        }
+    }
+
+    // AIDEV-NOTE: Replace the renderer with one on a brand new device. After a hang the old renderer
+    // is dropped on a thread of its own, since dropping a device with hung work can block until that
+    // work finishes; lost devices and restarts have nothing hung and are dropped right away
+    fn recreate_device(&mut self) -> Result<(), ShadertuiError> {
+        // A reload still compiling on the old device is built into the new one instead
+        if let Some(pending) = self.pending_reload.take() {
+            self.project = pending.project;
+        }
+        let (gpu_width, gpu_height) =
+            Self::gpu_size(self.width, self.height, &self.scaled_options());
+        let gpu_device = Self::create_device(self.mirror.as_mut())?;
+        let mut renderer = HeadlessRenderer::with_device(
+            gpu_device,
+            gpu_width,
+            gpu_height,
+            &self.project,
+            self.shell_options.clone(),
         )?;
+        renderer.set_gpu_timeout(self.options.gpu_timeout);
+        renderer.set_sample_view(self.options.sample_view);
+        renderer.set_packed_output(self.options.aa.is_none());
+        renderer.set_gpu_timing(self.options.gpu_timing);
+        let old_renderer = std::mem::replace(&mut self.renderer, renderer);
+        if self.timed_out {
+            std::thread::spawn(move || drop(old_renderer));
+        } else {
+            drop(old_renderer);
+        }
+        self.in_flight.clear();
+        self.paused_input = None;
+        Ok(())
+    }
 
-        // Replace the old pipeline
-        self.compute_pipeline = new_pipeline;
+    // AIDEV-NOTE: After a device loss, rebuild everything from the retained project, textures and
+    // options. Frames continue where they were; buffer passes, state and the average start over
+    fn recover_device(&mut self) -> Result<(), ShadertuiError> {
+        self.recreate_device()?;
+        self.timed_out = false;
+        self.stopped = false;
+        self.backoff.reset();
+        self.sample_index = 0;
         Ok(())
     }
 
-    // AIDEV-NOTE: Main GPU compute loop - runs continuously without blocking
+    pub fn set_texture(&mut self, index: usize, image: Arc<RgbaImage>) -> Result<(), String> {
+        self.renderer.set_texture(index, image.clone())?;
+        self.shell_options.textures[index] = image;
+        self.sample_index = 0;
+        self.paused_input = None;
+        Ok(())
+    }
+
+    // AIDEV-NOTE: Resize to a new GPU resolution in pixels (terminal size times pixels per cell)
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.sample_index = 0;
+        let (gpu_width, gpu_height) = Self::gpu_size(width, height, &self.scaled_options());
+        self.renderer.resize(gpu_width, gpu_height);
+        self.in_flight.clear();
+        self.paused_input = None;
+    }
+
+    // AIDEV-NOTE: While paused, a still shader (HeadlessRenderer::is_still) shows the same image
+    // until input changes it. Every input (cursor, mouse, parameters, zoom, seeking, pausing)
+    // bumps the input generation, and resizes, reloads and textures forget the paused frame, so
+    // the thread recomputes exactly when something changed instead of redrawing the same frame
+    fn paused_and_current(&self, shared_uniforms: &SharedUniformsHandle) -> bool {
+        let uniforms = shared_uniforms.lock().unwrap();
+        uniforms.time_paused && self.paused_input == Some(uniforms.input_generation)
+    }
+
+    // AIDEV-NOTE: Draw the last computed frame into the --mirror window. Window resizes and closing
+    // come from the main thread through the shared uniforms. A window that can't be drawn to is
+    // dropped with an error, and the terminal keeps rendering
+    fn update_mirror(&mut self, shared_uniforms: &SharedUniformsHandle) -> Result<(), String> {
+        let Some(mirror) = &mut self.mirror else {
+            return Ok(());
+        };
+        let (resize, closed) = {
+            let mut uniforms = shared_uniforms.lock().unwrap();
+            (uniforms.consume_mirror_resize(), uniforms.mirror_closed)
+        };
+        if closed {
+            self.mirror = None;
+            return Ok(());
+        }
+        let gpu_device = self.renderer.gpu_device();
+        if let Some((width, height)) = resize {
+            mirror.resize(gpu_device, width, height);
+        }
+        let Some((output, width, height)) = self.renderer.frame_output() else {
+            return Ok(());
+        };
+        if let Err(e) = mirror.present(gpu_device, output, width, height) {
+            self.mirror = None;
+            return Err(e.to_string());
+        }
+        Ok(())
+    }
+
+    fn present_mirror(
+        &mut self,
+        shared_uniforms: &SharedUniformsHandle,
+        terminal_error_sender: &ErrorSender,
+    ) {
+        if let Err(e) = self.update_mirror(shared_uniforms) {
+            let error_msg = ThreadError::GpuError(msg!("mirror.failed", error = e));
+            send_error(&[terminal_error_sender], error_msg);
+        }
+    }
+
+    pub fn set_adaptive_scale(&mut self, scale: f32) {
+        if scale != self.adaptive_scale {
+            self.adaptive_scale = scale;
+            self.resize(self.width, self.height);
+        }
+    }
+
+    // AIDEV-NOTE: Main GPU compute loop - runs continuously without blocking. Without `readback` the
+    // frame is computed (advancing buffer passes and state) but not returned. Readbacks are
+    // pipelined: a frame comes back from a later call, which returns every frame read back since
+    // the previous one (oldest first)
     pub fn render_frame(
         &mut self,
         shared_uniforms: &SharedUniformsHandle,
-    ) -> Result<FrameData, Box<dyn std::error::Error>> {
+        readback: bool,
+    ) -> Result<Vec<FrameData>, ShadertuiError> {
         // Calculate frame time and delta
         let current_time = Instant::now();
-        let delta_time = current_time
-            .duration_since(self.last_frame_time)
-            .as_secs_f32();
+        let delta_time = self.delta_smoother.smooth(
+            current_time
+                .duration_since(self.last_frame_time)
+                .as_secs_f32(),
+        );
         self.last_frame_time = current_time;
 
         // Get shared uniform data
-        let (cursor, time_paused, paused_time) = {
-            let uniforms = shared_uniforms.lock().unwrap();
-            (uniforms.cursor, uniforms.time_paused, uniforms.paused_time)
+        let (
+            cursor,
+            mouse,
+            zoom,
+            supersample,
+            scene_generation,
+            time_paused,
+            shader_time,
+            time_scale,
+            input_generation,
+            input_at,
+        ) = {
+            let mut uniforms = shared_uniforms.lock().unwrap();
+            let shader_time = uniforms.shader_time(self.start_time.elapsed().as_secs_f32());
+            // --lfo values go into the shared values too, so the parameter panel shows them
+            uniforms.param_values.animate(self.loop_time(shader_time));
+            self.renderer
+                .set_param_values(uniforms.param_values.clone());
+            (
+                uniforms.cursor,
+                uniforms.mouse.take_uniform(),
+                uniforms.zoom,
+                uniforms.supersample,
+                uniforms.scene_generation,
+                uniforms.time_paused,
+                shader_time,
+                uniforms.time_scale,
+                uniforms.input_generation,
+                uniforms.last_input_at,
+            )
         };
 
-        // Calculate effective time (accounting for pause)
-        let effective_time = if time_paused {
-            paused_time
-        } else {
-            self.start_time.elapsed().as_secs_f32()
-        };
+        let effective_time = self.loop_time(shader_time);
 
         // Increment frame count
         self.frame_count += 1;
 
-        // Update uniforms - use doubled height for GPU resolution
-        let uniforms = Uniforms::new(
-            self.width,
-            self.height * 2,
-            effective_time,
-            cursor,
-            self.frame_count,
-            delta_time,
-        );
-        self.uniform_buffer
-            .update(&self.gpu_device.queue, &uniforms);
-
-        // Create command encoder
-        let mut encoder =
-            self.gpu_device
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Render Encoder"),
-                });
-
-        // Dispatch the compute shader - use doubled height
-        self.compute_pipeline
-            .dispatch(&mut encoder, self.width, self.height * 2);
-
-        // Copy output to readback buffer
-        self.gpu_buffers.copy_to_readback(&mut encoder);
-
-        // Submit commands
-        self.gpu_device.queue.submit(Some(encoder.finish()));
-
-        // Read back the GPU data
-        let gpu_data = self
-            .gpu_buffers
-            .read_data_blocking(&self.gpu_device.device)?;
-
-        // Create frame data
-        Ok(FrameData {
+        // Cursor and mouse input change the scene, so the average starts over
+        if scene_generation != self.last_scene_generation {
+            self.last_scene_generation = scene_generation;
+            self.sample_index = 0;
+        }
+        let sample_index = self.sample_index;
+        self.sample_index = self.sample_index.saturating_add(1);
+
+        // Update uniforms with the GPU resolution, which --scale and supersampling change along
+        // with the cursor and mouse positions
+        let (gpu_width, gpu_height) =
+            Self::gpu_size(self.width, self.height, &self.scaled_options());
+        let scale = gpu_width as f32 / self.width as f32;
+        let uniforms = Uniforms {
+            sample_index,
+            mouse: mouse.map(|c| c * scale),
+            zoom: zoom.map_or(IDENTITY_ZOOM, |region| {
+                zoom_transform(region, gpu_width, gpu_height)
+            }),
+            subsamples: if supersample { SHADER_SUBSAMPLES } else { 1 },
+            ..Uniforms::new(
+                gpu_width,
+                gpu_height,
+                effective_time,
+                cursor.map(|c| (c as f32 * scale) as i32),
+                self.frame_count,
+                delta_time * time_scale,
+            )
+        };
+
+        trace::record(|| {
+            TraceEvent::Frame(FrameUniforms::new(
+                &uniforms,
+                self.renderer.param_values(),
+                readback,
+            ))
+        });
+
+        // Compute the frame, starting its readback if requested. Once every readback buffer is in
+        // flight, the oldest frame has to arrive before another one can be submitted
+        let mut readbacks = Vec::new();
+        if readback {
+            while self.renderer.readback_full() {
+                readbacks.extend(self.renderer.take_readback(true)?);
+            }
+            let id = self.renderer.submit(&uniforms)?;
+            self.paused_input =
+                (time_paused && self.renderer.is_still()).then_some(input_generation);
+            let frame_data = FrameData {
+                gpu_data: FramePixels::default(),
+                width: self.width,
+                frame: self.frame_count,
+                time: effective_time,
+                input_generation,
+                input_at,
+                readback_latency: Duration::ZERO,
+                readbacks_in_flight: 0,
+            };
+            self.in_flight.push_back((id, frame_data));
+        } else {
+            self.renderer.compute(&uniforms)?;
+        }
+        if self.renderer.take_reset_request() {
+            self.sample_index = 0;
+        }
+
+        self.collect_frames(readbacks)
+    }
+
+    // Shader time wrapped around by --loop
+    fn loop_time(&self, time: f32) -> f32 {
+        match self.options.loop_duration {
+            Some(loop_duration) => time % loop_duration,
+            None => time,
+        }
+    }
+
+    // Collect the frames whose data has arrived, waiting for them without pipelining
+    fn collect_frames(
+        &mut self,
+        mut readbacks: Vec<Readback>,
+    ) -> Result<Vec<FrameData>, ShadertuiError> {
+        let wait = !self.options.pipelined_readback;
+        while let Some(readback) = self.renderer.take_readback(wait)? {
+            readbacks.push(readback);
+        }
+        Ok(readbacks
+            .into_iter()
+            .filter_map(|readback| self.finish_frame(readback))
+            .collect())
+    }
+
+    // Match a readback with the frame it was submitted as and bring it to width x height. Readbacks
+    // from before a resize or reload have no match and are dropped
+    fn finish_frame(&mut self, readback: Readback) -> Option<FrameData> {
+        while self.in_flight.front()?.0 < readback.id {
+            self.in_flight.pop_front();
+        }
+        if self.in_flight.front()?.0 != readback.id {
+            return None;
+        }
+        let (_, frame_data) = self.in_flight.pop_front()?;
+
+        let (render_width, render_height) =
+            Self::render_size(self.width, self.height, &self.scaled_options());
+        let mut gpu_data = readback.data;
+        if let Some(filter) = self.options.aa {
+            gpu_data = FramePixels::Linear(downsample(
+                &gpu_data.into_linear(),
+                render_width,
+                render_height,
+                filter,
+            ));
+        }
+        if (render_width, render_height) != (self.width, self.height) {
+            gpu_data = gpu_data.stretch(render_width, render_height, self.width, self.height);
+        }
+        Some(FrameData {
             gpu_data,
-            width: self.width,
+            readback_latency: readback.latency,
+            readbacks_in_flight: self.renderer.readbacks_in_flight(),
+            ..frame_data
         })
     }
 
@@ -157,48 +572,179 @@ impl GpuRenderer {
         terminal_error_sender: ErrorSender,
         performance_tracker: Option<DualPerformanceTrackerHandle>,
     ) {
+        let mut pacer = FramePacer::new(self.options.max_fps);
+        let error_senders = [&main_error_sender, &terminal_error_sender];
         loop {
             // Check for shader reload requests
-            if let Some(new_shader_source) = {
+            if let Some((new_project, reset_on_reload)) = {
                 let mut uniforms = shared_uniforms.lock().unwrap();
-                uniforms.consume_shader_reload()
+                let reset_on_reload = uniforms.reset_on_reload;
+                uniforms
+                    .consume_shader_reload()
+                    .map(|project| (project, reset_on_reload))
             } {
-                match self.reload_shader(&new_shader_source) {
+                trace::record(|| TraceEvent::Reload);
+                self.reset_on_reload = reset_on_reload;
+                // A renderer that needs a new device anyway reloads in place
+                let result = if self.needs_new_device() {
+                    self.reload_shader(&new_project).map(|()| true)
+                } else {
+                    self.start_reload(new_project).map(|()| false)
+                };
+                match result {
                     Err(e) => {
-                        let error_msg = ThreadError::ShaderCompilationError(e.to_string());
-                        let _ = main_error_sender.send(error_msg.clone());
-                        let _ = terminal_error_sender.send(error_msg);
+                        send_error(
+                            &error_senders,
+                            ThreadError::ShaderCompilationError(e.to_string()),
+                        );
                         continue;
                     }
-                    Ok(()) => {
+                    Ok(true) => {
                         // Shader reloaded successfully - send signal to clear error state
-                        let _ = terminal_error_sender.send(ThreadError::ShaderReloadSuccess);
+                        self.reset_clock(&shared_uniforms);
+                        send_error(&[&terminal_error_sender], ThreadError::ShaderReloadSuccess);
+                    }
+                    Ok(false) => {
+                        send_error(&[&terminal_error_sender], ThreadError::ShaderCompiling)
+                    }
+                }
+            }
+            match self.poll_reload() {
+                Some(Ok(())) => {
+                    self.reset_clock(&shared_uniforms);
+                    send_error(&[&terminal_error_sender], ThreadError::ShaderReloadSuccess)
+                }
+                Some(Err(e)) => send_error(&error_senders, ThreadError::ShaderCompilationError(e)),
+                None => {}
+            }
+
+            // Swap images sent with `shadertui ctl texture`
+            let textures = shared_uniforms.lock().unwrap().consume_textures();
+            for (index, image) in textures {
+                trace::record(|| TraceEvent::Texture { index });
+                if let Err(e) = self.set_texture(index, image) {
+                    let error_msg =
+                        ThreadError::GpuError(msg!("error.texture_slot", index = index, error = e));
+                    send_error(&[&terminal_error_sender], error_msg);
+                }
+            }
+
+            // Apply terminal resizes before rendering the next frame
+            if let Some((width, height)) = {
+                let mut uniforms = shared_uniforms.lock().unwrap();
+                uniforms.consume_resize()
+            } {
+                trace::record(|| TraceEvent::Resize { width, height });
+                self.resize(width, height);
+            }
+            let adaptive_scale = shared_uniforms.lock().unwrap().adaptive_scale;
+            self.set_adaptive_scale(adaptive_scale);
+
+            // A lost device (driver reset, GPU switch) is replaced, retrying until one is available
+            if self.renderer.device_lost() {
+                match self.recover_device() {
+                    Ok(()) => send_error(&[&terminal_error_sender], ThreadError::DeviceRecovered),
+                    Err(e) => {
+                        let error_msg = ThreadError::GpuError(msg!("error.device_lost", error = e));
+                        send_error(&error_senders, error_msg);
+                        std::thread::sleep(DEVICE_RETRY_INTERVAL);
+                        continue;
                     }
                 }
             }
 
-            // Render frame
-            match self.render_frame(&shared_uniforms) {
-                Ok(frame_data) => {
-                    // Write frame to shared buffer (may drop frames if terminal is slow)
+            // After giving up on repeated errors, wait for G to try again on a new device (a fixed
+            // shader is reloaded onto one too)
+            if self.stopped {
+                if !shared_uniforms.lock().unwrap().consume_restart() {
+                    std::thread::sleep(std::time::Duration::from_millis(16));
+                    continue;
+                }
+                match self.recover_device() {
+                    Ok(()) => send_error(&[&terminal_error_sender], ThreadError::DeviceRecovered),
+                    Err(e) => {
+                        send_error(&error_senders, ThreadError::RenderingStopped(e.to_string()));
+                        continue;
+                    }
+                }
+            }
+
+            // After a hang, wait for the user to fix the shader rather than hanging every frame
+            if self.timed_out {
+                std::thread::sleep(std::time::Duration::from_millis(16));
+                continue;
+            }
+
+            // Nothing new to compute while paused on a still frame; frames on their way back are
+            // still delivered (a readback error surfaces from the next render instead)
+            if self.paused_and_current(&shared_uniforms) {
+                if let Ok(frames) = self.collect_frames(Vec::new()) {
+                    for frame_data in frames {
+                        frame_buffer.lock().unwrap().write_frame(frame_data);
+                    }
+                    self.present_mirror(&shared_uniforms, &terminal_error_sender);
+                    std::thread::sleep(std::time::Duration::from_millis(16));
+                    continue;
+                }
+            }
+
+            // Render frame, reading it back only if the terminal is ready for another one and none
+            // is on its way. Under --max-fps (and --low-power) every frame is meant to be shown, so
+            // each one is read back
+            let readback = self.options.max_fps.is_some()
+                || (self.in_flight.is_empty() && frame_buffer.lock().unwrap().wants_frame());
+            pacer.wait();
+            let render_start = Instant::now();
+            match self.render_frame(&shared_uniforms, readback) {
+                Ok(frames) => {
+                    self.backoff.reset();
+
+                    // Write frames to shared buffer (may drop frames if terminal is slow)
                     {
                         let mut buffer = frame_buffer.lock().unwrap();
-                        buffer.write_frame(frame_data);
+                        buffer.telemetry.record_frame(render_start.elapsed());
+                        if !readback {
+                            buffer.skip_frame();
+                        }
+                        for frame_data in frames {
+                            buffer.write_frame(frame_data);
+                        }
                     }
+                    self.present_mirror(&shared_uniforms, &terminal_error_sender);
 
                     // Record GPU frame for performance tracking
                     if let Some(ref tracker) = performance_tracker {
+                        let gpu_time = self.renderer.gpu_time();
                         let mut perf = tracker.lock().unwrap();
                         perf.record_gpu_frame();
+                        perf.gpu_time = gpu_time;
                     }
                 }
-                Err(e) => {
-                    let error_msg = ThreadError::GpuError(e.to_string());
-                    let _ = main_error_sender.send(error_msg.clone());
-                    let _ = terminal_error_sender.send(error_msg);
-                    // Continue running on error - don't crash the GPU thread
-                    std::thread::sleep(std::time::Duration::from_millis(16)); // ~60 FPS fallback
+                // Recovered at the top of the next iteration
+                Err(_) if self.renderer.device_lost() => {}
+                Err(e @ ShadertuiError::GpuTimeout(_)) => {
+                    self.timed_out = true;
+                    let error_msg = ThreadError::GpuError(msg!("error.gpu_timeout", error = e));
+                    send_error(&error_senders, error_msg);
                 }
+                // Retried after growing waits, until the backoff gives up
+                Err(e) => match self.backoff.failure() {
+                    Some(delay) => {
+                        let error_msg = ThreadError::GpuError(msg!(
+                            "error.gpu_retry",
+                            error = e,
+                            attempt = self.backoff.failures(),
+                            max = MAX_RETRIES,
+                            ms = delay.as_millis(),
+                        ));
+                        send_error(&error_senders, error_msg);
+                        std::thread::sleep(delay);
+                    }
+                    None => {
+                        self.stopped = true;
+                        send_error(&error_senders, ThreadError::RenderingStopped(e.to_string()));
+                    }
+                },
             }
 
             // Small yield to prevent 100% CPU usage
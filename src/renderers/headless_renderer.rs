@@ -0,0 +1,884 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::ShadertuiError;
+use crate::gpu::{
+    create_state_buffer, output_band_rows, pack_params, tiles_output, wait_for_queue_blocking,
+    ChangedPasses, ComputePipeline, FramePixels, GpuBuffers, GpuDevice, GpuTimer, PassChain,
+    Readback, UnboundPipeline, UniformBuffer, Uniforms, READBACK_SLOTS,
+};
+use crate::utils::{
+    image_export::RgbaImage,
+    options::SampleView,
+    project::{Domain, ShaderProject, SAMPLE_COUNT},
+    sample_view::{decode_samples, SampleVisualizer},
+    shader_shell::{inject_user_shader, ShellOptions, ShellType},
+    uniform_params::{ParamValues, UniformParam},
+};
+
+// AIDEV-NOTE: Fixed timestep used by exports, so multi-pass output is deterministic regardless of how
+// fast the GPU renders
+pub const EXPORT_FPS: f32 = 60.0;
+
+// AIDEV-NOTE: Longest a single frame may take on the GPU before it counts as hung
+pub const DEFAULT_GPU_TIMEOUT: Duration = Duration::from_secs(5);
+
+// AIDEV-NOTE: Offscreen compute + readback at an arbitrary pixel resolution. Uses the terminal shell
+// (storage buffer output, Y=0 at the bottom row) and is shared by GpuRenderer and export commands.
+// 1D projects compute SAMPLE_COUNT x 1 on the GPU and are drawn at width x height by the visualizer
+pub struct HeadlessRenderer {
+    gpu_device: GpuDevice,
+    gpu_buffers: GpuBuffers,
+    uniform_buffer: UniformBuffer,
+    compute_pipeline: ComputePipeline,
+    pass_chain: PassChain,
+    width: u32,
+    height: u32,
+    shell_options: ShellOptions,
+    has_buffers: bool,
+    next_frame: u32,
+    gpu_timeout: Duration,
+    domain: Domain,
+    visualizer: SampleVisualizer,
+    state: Option<wgpu::Buffer>,
+    // The last frame called restart_accumulation() (--accumulate)
+    reset_requested: bool,
+    // The code a frame passed to request_exit() (`shadertui render`)
+    exit_code: Option<u32>,
+    // `// @uniform` parameters of the compiled project, valued from param_values
+    params: Vec<UniformParam>,
+    param_values: ParamValues,
+    // Read 2D output back as packed RGBA8 (see gpu::packing); 1D samples always stay floats
+    packed_output: bool,
+    // Times frames on the GPU when enabled and supported (--perf)
+    timer: Option<GpuTimer>,
+    // Output rows per band, less than the GPU height when the output is tiled
+    band_rows: u32,
+    // Tiled frames, reassembled and waiting for take_readback()
+    tiled_readbacks: VecDeque<Readback>,
+    // Bumped by every change a ReloadJob started before it would miss
+    generation: u64,
+    // Embedders' per-frame hooks, called by render() (see ShaderEngine::on_before_upload)
+    before_upload: Option<BeforeUploadHook>,
+    after_readback: Option<AfterReadbackHook>,
+}
+
+pub type BeforeUploadHook = Box<dyn FnMut(&mut Uniforms) + Send>;
+pub type AfterReadbackHook = Box<dyn FnMut(&[f32]) + Send>;
+
+// Everything ReloadJob::build() needs, taken from the renderer by start_reload()
+pub struct ReloadJob {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline_cache: Option<wgpu::PipelineCache>,
+    project: ShaderProject,
+    shell_options: ShellOptions,
+    gpu_width: u32,
+    gpu_height: u32,
+    state: Option<wgpu::Buffer>,
+    // The running chain's output channel layout and shaders, when the passes can be merged
+    merge: Option<(Option<wgpu::BindGroupLayout>, Vec<String>)>,
+    generation: u64,
+}
+
+impl ReloadJob {
+    // The slow part of a reload: generating, validating and compiling every changed pipeline
+    pub fn build(self) -> Result<PreparedReload, ShadertuiError> {
+        let (passes, channel_layout) = match self.merge {
+            Some((channel_layout, shaders)) => {
+                let changed = PassChain::build_changed(
+                    &self.device,
+                    self.pipeline_cache.as_ref(),
+                    &self.project,
+                    &self.shell_options,
+                    self.state.as_ref(),
+                    &shaders,
+                )?;
+                (PreparedPasses::Merge(changed), channel_layout)
+            }
+            None => {
+                let pass_chain = PassChain::new(
+                    &self.device,
+                    &self.queue,
+                    self.pipeline_cache.as_ref(),
+                    &self.project,
+                    self.gpu_width,
+                    self.gpu_height,
+                    &self.shell_options,
+                    self.state.as_ref(),
+                )?;
+                let channel_layout = pass_chain.output_channel_layout().cloned();
+                (
+                    PreparedPasses::Rebuild(Box::new(pass_chain)),
+                    channel_layout,
+                )
+            }
+        };
+        let output = HeadlessRenderer::compile_output_pipeline(
+            &self.device,
+            self.pipeline_cache.as_ref(),
+            &self.project,
+            &self.shell_options,
+            channel_layout.as_ref(),
+            self.state.as_ref(),
+        )?;
+        Ok(PreparedReload {
+            project: self.project,
+            state: self.state,
+            output,
+            passes,
+            generation: self.generation,
+        })
+    }
+}
+
+// A built reload, waiting for HeadlessRenderer::finish_reload()
+pub struct PreparedReload {
+    project: ShaderProject,
+    state: Option<wgpu::Buffer>,
+    output: UnboundPipeline,
+    passes: PreparedPasses,
+    generation: u64,
+}
+
+enum PreparedPasses {
+    Merge(ChangedPasses),
+    Rebuild(Box<PassChain>),
+}
+
+impl HeadlessRenderer {
+    pub fn new(
+        width: u32,
+        height: u32,
+        project: &ShaderProject,
+        shell_options: ShellOptions,
+    ) -> Result<Self, ShadertuiError> {
+        Self::with_device(
+            GpuDevice::new_blocking()?,
+            width,
+            height,
+            project,
+            shell_options,
+        )
+    }
+
+    // On a device picked by the caller, e.g. one shared with a window (--mirror)
+    pub fn with_device(
+        gpu_device: GpuDevice,
+        width: u32,
+        height: u32,
+        project: &ShaderProject,
+        shell_options: ShellOptions,
+    ) -> Result<Self, ShadertuiError> {
+        let (gpu_width, gpu_height) = gpu_size(project.domain, width, height);
+        let band_rows = band_rows(
+            &gpu_device,
+            project.domain,
+            &shell_options,
+            gpu_width,
+            gpu_height,
+        );
+        let gpu_buffers = GpuBuffers::new(
+            &gpu_device.device,
+            gpu_width,
+            band_rows,
+            shell_options.accumulate,
+            shell_options.exit_requests,
+            false,
+        );
+        let uniform_buffer = UniformBuffer::new(&gpu_device.device);
+        let state = create_state_buffer(&gpu_device.device, shell_options.state_size)?;
+        let (pass_chain, compute_pipeline) = Self::create_pipelines(
+            &gpu_device,
+            &gpu_buffers,
+            &uniform_buffer,
+            project,
+            &shell_options,
+            gpu_width,
+            gpu_height,
+            state.as_ref(),
+        )?;
+        gpu_device.save_pipeline_cache();
+
+        Ok(Self {
+            gpu_device,
+            gpu_buffers,
+            uniform_buffer,
+            compute_pipeline,
+            pass_chain,
+            width,
+            height,
+            shell_options,
+            has_buffers: !project.buffers.is_empty(),
+            next_frame: 0,
+            gpu_timeout: DEFAULT_GPU_TIMEOUT,
+            domain: project.domain,
+            visualizer: SampleVisualizer::new(SampleView::Scope),
+            state,
+            reset_requested: false,
+            exit_code: None,
+            params: project.params.clone(),
+            param_values: ParamValues::new(&project.params),
+            packed_output: false,
+            timer: None,
+            band_rows,
+            tiled_readbacks: VecDeque::new(),
+            generation: 0,
+            before_upload: None,
+            after_readback: None,
+        })
+    }
+
+    pub fn set_gpu_timeout(&mut self, timeout: Duration) {
+        self.gpu_timeout = timeout;
+    }
+
+    // AIDEV-NOTE: Packed readback is a quarter of the bytes but only 8 bits per channel, enough for
+    // display. Exports keep the default floats
+    pub fn set_packed_output(&mut self, packed: bool) {
+        self.packed_output = packed;
+        self.gpu_buffers.set_packed(
+            &self.gpu_device.device,
+            packed && self.domain == Domain::Pixels,
+        );
+    }
+
+    pub fn set_sample_view(&mut self, view: SampleView) {
+        self.visualizer = SampleVisualizer::new(view);
+    }
+
+    // Whether reloads keep the `state` contents (--keep-state)
+    pub fn set_keep_state(&mut self, keep: bool) {
+        self.shell_options.keep_state = keep;
+    }
+
+    // Stays off on adapters without timestamp queries
+    pub fn set_gpu_timing(&mut self, enabled: bool) {
+        self.timer = enabled
+            .then(|| GpuTimer::new(&self.gpu_device.device, &self.gpu_device.queue))
+            .flatten();
+    }
+
+    // GPU time of a recent frame with set_gpu_timing()
+    pub fn gpu_time(&mut self) -> Option<Duration> {
+        let device = &self.gpu_device.device;
+        self.timer.as_mut()?.gpu_time(device)
+    }
+
+    // True if a frame depends on nothing but its uniforms: no buffer passes, state, --accumulate
+    // or --audio carry anything over from earlier frames
+    pub fn is_still(&self) -> bool {
+        !self.has_buffers
+            && self.state.is_none()
+            && !self.shell_options.accumulate
+            && self.shell_options.audio.is_none()
+    }
+
+    // True once the driver lost the device; only a renderer on a new device can render again
+    pub fn device_lost(&self) -> bool {
+        self.gpu_device.is_lost()
+    }
+
+    // AIDEV-NOTE: Build the buffer pass chain and the output pipeline that samples its channels
+    #[expect(clippy::too_many_arguments)]
+    fn create_pipelines(
+        gpu_device: &GpuDevice,
+        gpu_buffers: &GpuBuffers,
+        uniform_buffer: &UniformBuffer,
+        project: &ShaderProject,
+        shell_options: &ShellOptions,
+        width: u32,
+        height: u32,
+        state: Option<&wgpu::Buffer>,
+    ) -> Result<(PassChain, ComputePipeline), ShadertuiError> {
+        let pass_chain = PassChain::new(
+            &gpu_device.device,
+            &gpu_device.queue,
+            gpu_device.pipeline_cache(),
+            project,
+            width,
+            height,
+            shell_options,
+            state,
+        )?;
+        let compute_pipeline = Self::compile_output_pipeline(
+            &gpu_device.device,
+            gpu_device.pipeline_cache(),
+            project,
+            shell_options,
+            pass_chain.output_channel_layout(),
+            state,
+        )?
+        .bind(&gpu_device.device, gpu_buffers, uniform_buffer);
+
+        Ok((pass_chain, compute_pipeline))
+    }
+
+    fn compile_output_pipeline(
+        device: &wgpu::Device,
+        pipeline_cache: Option<&wgpu::PipelineCache>,
+        project: &ShaderProject,
+        shell_options: &ShellOptions,
+        channel_layout: Option<&wgpu::BindGroupLayout>,
+        state: Option<&wgpu::Buffer>,
+    ) -> Result<UnboundPipeline, ShadertuiError> {
+        // Inject user shader into terminal shell
+        let output_options = ShellOptions {
+            channel_count: project.output.inputs.len(),
+            params: project.params.clone(),
+            ..shell_options.clone()
+        };
+        let complete_shader =
+            inject_user_shader(&project.output.source, ShellType::Terminal, &output_options)?;
+
+        ComputePipeline::compile(
+            device,
+            pipeline_cache,
+            &complete_shader,
+            channel_layout,
+            state,
+            shell_options.accumulate,
+            shell_options.exit_requests,
+        )
+    }
+
+    // AIDEV-NOTE: Reload with new sources. Buffer passes laid out as before are merged into the
+    // running chain (see PassChain::build_changed), otherwise every pass is rebuilt
+    pub fn reload_shader(&mut self, project: &ShaderProject) -> Result<(), ShadertuiError> {
+        let prepared = self.start_reload(project)?.build()?;
+        self.finish_reload(prepared);
+        Ok(())
+    }
+
+    // AIDEV-NOTE: reload_shader() in three steps, so the pipelines can compile on another thread
+    // while this renderer keeps rendering the old ones (GpuRenderer's hot reloads):
+    // start_reload() takes what the build needs, ReloadJob::build() compiles anywhere, and
+    // finish_reload() swaps the result in. A resize or texture change in between would leave the
+    // build for the old size or image, so finish_reload() refuses it and returns false
+    pub fn start_reload(&self, project: &ShaderProject) -> Result<ReloadJob, ShadertuiError> {
+        let state = if self.shell_options.keep_state {
+            self.state.clone()
+        } else {
+            create_state_buffer(&self.gpu_device.device, self.shell_options.state_size)?
+        };
+        let merge = (project.domain == self.domain
+            && self.pass_chain.layout_matches(project, state.is_some()))
+        .then(|| {
+            (
+                self.pass_chain.output_channel_layout().cloned(),
+                self.pass_chain.shaders(),
+            )
+        });
+        let (gpu_width, gpu_height) = gpu_size(project.domain, self.width, self.height);
+        Ok(ReloadJob {
+            device: self.gpu_device.device.clone(),
+            queue: self.gpu_device.queue.clone(),
+            pipeline_cache: self.gpu_device.pipeline_cache().cloned(),
+            project: project.clone(),
+            shell_options: self.shell_options.clone(),
+            gpu_width,
+            gpu_height,
+            state,
+            merge,
+            generation: self.generation,
+        })
+    }
+
+    pub fn finish_reload(&mut self, prepared: PreparedReload) -> bool {
+        if prepared.generation != self.generation {
+            return false;
+        }
+        let PreparedReload {
+            project,
+            state,
+            output,
+            passes,
+            ..
+        } = prepared;
+        let device = &self.gpu_device.device;
+
+        // Switching between 1D and 2D changes the GPU resolution
+        if project.domain != self.domain {
+            let (gpu_width, gpu_height) = gpu_size(project.domain, self.width, self.height);
+            self.band_rows = band_rows(
+                &self.gpu_device,
+                project.domain,
+                &self.shell_options,
+                gpu_width,
+                gpu_height,
+            );
+            self.gpu_buffers = GpuBuffers::new(
+                device,
+                gpu_width,
+                self.band_rows,
+                self.shell_options.accumulate,
+                self.shell_options.exit_requests,
+                self.packed_output && project.domain == Domain::Pixels,
+            );
+            self.tiled_readbacks.clear();
+        }
+        self.compute_pipeline = output.bind(device, &self.gpu_buffers, &self.uniform_buffer);
+        match passes {
+            PreparedPasses::Merge(changed) => {
+                self.pass_chain.merge(device, changed, state.as_ref())
+            }
+            PreparedPasses::Rebuild(pass_chain) => {
+                self.pass_chain = *pass_chain;
+                self.has_buffers = !project.buffers.is_empty();
+                self.next_frame = 0;
+            }
+        }
+        self.state = state;
+        self.domain = project.domain;
+        self.params = project.params.clone();
+        self.visualizer.reset();
+        self.generation += 1;
+        self.gpu_device.save_pipeline_cache();
+        true
+    }
+
+    // AIDEV-NOTE: Rebind --texture number `index`; later reloads keep using the new image
+    pub fn set_texture(&mut self, index: usize, image: Arc<RgbaImage>) -> Result<(), String> {
+        self.pass_chain.set_image(
+            &self.gpu_device.device,
+            &self.gpu_device.queue,
+            index,
+            &image,
+        )?;
+        self.shell_options.textures[index] = image;
+        self.generation += 1;
+        Ok(())
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn gpu_device(&self) -> &GpuDevice {
+        &self.gpu_device
+    }
+
+    // AIDEV-NOTE: The output buffer holding the whole last frame (RGBA floats, bottom row first)
+    // with its size, for drawing it on the GPU without a readback (--mirror). None for 1D
+    // projects, whose output is samples, and tiled output, which only keeps the last band
+    pub fn frame_output(&self) -> Option<(&wgpu::Buffer, u32, u32)> {
+        (self.domain == Domain::Pixels && self.band_rows == self.height).then_some((
+            &self.gpu_buffers.output_buffer,
+            self.width,
+            self.height,
+        ))
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.generation += 1;
+        if self.domain == Domain::Samples {
+            return;
+        }
+
+        self.band_rows = band_rows(
+            &self.gpu_device,
+            self.domain,
+            &self.shell_options,
+            width,
+            height,
+        );
+        self.rebuild_output();
+        self.pass_chain
+            .resize(&self.gpu_device.device, width, height);
+    }
+
+    // Output buffers for the current width and band_rows
+    fn rebuild_output(&mut self) {
+        self.tiled_readbacks.clear();
+        let device = &self.gpu_device.device;
+        self.gpu_buffers = GpuBuffers::new(
+            device,
+            self.width,
+            self.band_rows,
+            self.shell_options.accumulate,
+            self.shell_options.exit_requests,
+            self.packed_output,
+        );
+        self.compute_pipeline
+            .rebind(device, &self.gpu_buffers, &self.uniform_buffer);
+    }
+
+    // Whether frames are rendered and read back in bands (see render_bands)
+    pub fn is_tiled(&self) -> bool {
+        self.domain == Domain::Pixels && self.band_rows < self.height
+    }
+
+    // Tiles frames of any size, so tests don't need one too big for a single output buffer
+    #[cfg(test)]
+    pub fn set_band_rows(&mut self, band_rows: u32) {
+        self.band_rows = band_rows.min(self.height);
+        self.rebuild_output();
+    }
+
+    // AIDEV-NOTE: Render one frame with the given uniforms and read back RGBA floats (bottom row first,
+    // decoded again with set_packed_output()). The hooks see the frame on the way in and out; the
+    // resolution and `// @uniform` values are filled in after before_upload, in encode_frame()
+    pub fn render(&mut self, uniforms: &Uniforms) -> Result<Vec<f32>, ShadertuiError> {
+        // Frames still in flight from submit() are superseded by this one
+        while self.take_readback(true)?.is_some() {}
+        let mut uniforms = *uniforms;
+        if let Some(hook) = &mut self.before_upload {
+            hook(&mut uniforms);
+        }
+        self.submit(&uniforms)?;
+        let readback = self
+            .take_readback(true)?
+            .ok_or_else(|| ShadertuiError::Gpu("frame readback went missing".to_string()))?;
+        let pixels = readback.data.into_linear();
+        if let Some(hook) = &mut self.after_readback {
+            hook(&pixels);
+        }
+        Ok(pixels)
+    }
+
+    pub fn set_before_upload(&mut self, hook: Option<BeforeUploadHook>) {
+        self.before_upload = hook;
+    }
+
+    pub fn set_after_readback(&mut self, hook: Option<AfterReadbackHook>) {
+        self.after_readback = hook;
+    }
+
+    // AIDEV-NOTE: Pipelined render(): submits the frame and starts reading it back without waiting,
+    // returning the id its Readback will carry. Callers collect frames with take_readback() and must
+    // make room with it first while readback_full()
+    pub fn submit(&mut self, uniforms: &Uniforms) -> Result<u64, ShadertuiError> {
+        Ok(self
+            .encode_frame(uniforms, true)?
+            .expect("readback was requested"))
+    }
+
+    // The oldest submitted frame once it has been read back; `wait` blocks until it is
+    pub fn take_readback(&mut self, wait: bool) -> Result<Option<Readback>, ShadertuiError> {
+        if let Some(readback) = self.tiled_readbacks.pop_front() {
+            return Ok(Some(readback));
+        }
+        let device = &self.gpu_device.device;
+        let readback = if wait {
+            self.gpu_buffers
+                .readback
+                .take_blocking(device, self.gpu_timeout)?
+        } else {
+            self.gpu_buffers
+                .readback
+                .try_take(device, self.gpu_timeout)?
+        };
+        Ok(readback.map(|readback| match self.domain {
+            Domain::Pixels => readback,
+            Domain::Samples => Readback {
+                data: FramePixels::Linear(self.visualizer.draw(
+                    &decode_samples(&readback.data.into_linear()),
+                    self.width,
+                    self.height,
+                )),
+                id: readback.id,
+                latency: readback.latency,
+            },
+        }))
+    }
+
+    pub fn readbacks_in_flight(&self) -> usize {
+        self.gpu_buffers.readback.in_flight() + self.tiled_readbacks.len()
+    }
+
+    pub fn readback_full(&self) -> bool {
+        self.gpu_buffers.readback.is_full() || self.tiled_readbacks.len() >= READBACK_SLOTS
+    }
+
+    // AIDEV-NOTE: One pixel of the last frame ((x, y) from the bottom left, like the readback)
+    // without reading the frame back. Tiled output only keeps the last band on the GPU, so
+    // ShaderEngine samples tiled frames from their readback instead
+    pub fn sample_pixel(&self, x: u32, y: u32) -> Result<[f32; 4], ShadertuiError> {
+        if self.domain == Domain::Samples {
+            return Err("1D shaders (// @domain 1d) have no pixels to sample".into());
+        }
+        if x >= self.width || y >= self.height {
+            return Err(format!(
+                "Pixel ({x}, {y}) is outside the {}x{} frame",
+                self.width, self.height
+            )
+            .into());
+        }
+        let last_band = (self.height - 1) / self.band_rows * self.band_rows;
+        let band_y = y
+            .checked_sub(last_band)
+            .ok_or("Only the last band of a tiled frame can be sampled")?;
+        self.gpu_buffers.read_pixel_blocking(
+            &self.gpu_device.device,
+            &self.gpu_device.queue,
+            band_y * self.width + x,
+            self.gpu_timeout,
+        )
+    }
+
+    // AIDEV-NOTE: Like render(), but skips the output readback, which is most of the per-frame cost
+    // of small shaders. Buffer passes, the state buffer and --accumulate advance exactly as they do
+    // in render(), so frames that are never displayed still count for simulations
+    pub fn compute(&mut self, uniforms: &Uniforms) -> Result<(), ShadertuiError> {
+        self.encode_frame(uniforms, false)?;
+        if self.gpu_buffers.accumulation.is_none() {
+            wait_for_queue_blocking(
+                &self.gpu_device.device,
+                &self.gpu_device.queue,
+                self.gpu_timeout,
+            )?;
+        }
+        Ok(())
+    }
+
+    // Submit one frame, then wait for the reset flag of --accumulate (which also waits for the frame).
+    // With `readback` the output starts mapping and the readback's id is returned
+    fn encode_frame(
+        &mut self,
+        uniforms: &Uniforms,
+        readback: bool,
+    ) -> Result<Option<u64>, ShadertuiError> {
+        let (gpu_width, gpu_height) = gpu_size(self.domain, self.width, self.height);
+        self.param_values.animate(uniforms.time);
+        let uniforms = &Uniforms {
+            resolution: [gpu_width as f32, gpu_height as f32],
+            params: pack_params(&self.params, &self.param_values),
+            ..*uniforms
+        };
+        if readback && self.band_rows < gpu_height {
+            return self.render_bands(uniforms).map(Some);
+        }
+        self.uniform_buffer.update(&self.gpu_device.queue, uniforms);
+
+        // Create command encoder
+        let mut encoder =
+            self.gpu_device
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Render Encoder"),
+                });
+
+        // Dispatch buffer passes, then the output compute shader
+        if let Some(timer) = &self.timer {
+            timer.begin(&mut encoder);
+        }
+        self.pass_chain
+            .encode(&self.gpu_device.queue, &mut encoder, uniforms);
+        // A tiled frame that isn't read back only computes its first band
+        self.compute_pipeline.dispatch(
+            &mut encoder,
+            gpu_width,
+            self.band_rows,
+            self.pass_chain.output_channel_bind_group(),
+            self.timer.as_ref(),
+        );
+        if let Some(timer) = &mut self.timer {
+            timer.resolve(&mut encoder);
+        }
+
+        // Copy output to the next readback buffer
+        let readback_slot = readback.then(|| self.gpu_buffers.copy_to_readback(&mut encoder));
+        if let Some(accumulation) = &self.gpu_buffers.accumulation {
+            accumulation.copy_reset_to_readback(&mut encoder);
+        }
+        if let Some(exit_request) = &self.gpu_buffers.exit_request {
+            exit_request.copy_to_readback(&mut encoder);
+        }
+
+        // Submit commands
+        let submitted_at = Instant::now();
+        self.gpu_device.queue.submit(Some(encoder.finish()));
+        if let Some(timer) = &mut self.timer {
+            timer.submitted();
+        }
+        let readback_id =
+            readback_slot.map(|slot| self.gpu_buffers.readback.begin_map(slot, submitted_at));
+
+        if let Some(accumulation) = &self.gpu_buffers.accumulation {
+            self.reset_requested |=
+                accumulation.read_reset_blocking(&self.gpu_device.device, self.gpu_timeout)?;
+        }
+        self.read_exit_request()?;
+        Ok(readback_id)
+    }
+
+    fn read_exit_request(&mut self) -> Result<(), ShadertuiError> {
+        if let Some(exit_request) = &self.gpu_buffers.exit_request {
+            let code = exit_request.read_blocking(&self.gpu_device.device, self.gpu_timeout)?;
+            self.exit_code = self.exit_code.or(code);
+        }
+        Ok(())
+    }
+
+    // AIDEV-NOTE: Tiled output (see gpu::compatibility::tiles_output). Every band is one submit that
+    // shades rows first_row.. of the full frame, by shifting the zoom origin down first_row pixels
+    // while the resolution stays the frame's, and is read back before the next band reuses the
+    // output buffer. Buffer passes run once, with the first band. The reassembled frame is queued
+    // for take_readback(), so tiled frames are never pipelined
+    fn render_bands(&mut self, uniforms: &Uniforms) -> Result<u64, ShadertuiError> {
+        let (width, height) = (self.width, self.height);
+        let pixel_count = (width * height) as usize;
+        let mut pixels = if self.gpu_buffers.packing.is_some() {
+            FramePixels::Packed(Vec::with_capacity(pixel_count))
+        } else {
+            FramePixels::Linear(Vec::with_capacity(pixel_count * 4))
+        };
+        let submitted_at = Instant::now();
+        let mut id = 0;
+        for first_row in (0..height).step_by(self.band_rows as usize) {
+            let rows = self.band_rows.min(height - first_row);
+            let last_band = first_row + rows == height;
+            let [x, y, scale_x, scale_y] = uniforms.zoom;
+            let band_uniforms = Uniforms {
+                zoom: [x, y + first_row as f32 * scale_y, scale_x, scale_y],
+                ..*uniforms
+            };
+            self.uniform_buffer
+                .update(&self.gpu_device.queue, &band_uniforms);
+
+            let mut encoder =
+                self.gpu_device
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Band Encoder"),
+                    });
+            if first_row == 0 {
+                if let Some(timer) = &self.timer {
+                    timer.begin(&mut encoder);
+                }
+                self.pass_chain
+                    .encode(&self.gpu_device.queue, &mut encoder, uniforms);
+            }
+            self.compute_pipeline.dispatch(
+                &mut encoder,
+                width,
+                rows,
+                self.pass_chain.output_channel_bind_group(),
+                self.timer.as_ref().filter(|_| last_band),
+            );
+            if let Some(timer) = self.timer.as_mut().filter(|_| last_band) {
+                timer.resolve(&mut encoder);
+            }
+            // Every band's request_exit() stays set until the last one copies the status
+            if let Some(exit_request) = self.gpu_buffers.exit_request.as_ref().filter(|_| last_band)
+            {
+                exit_request.copy_to_readback(&mut encoder);
+            }
+            let slot = self.gpu_buffers.copy_to_readback(&mut encoder);
+
+            self.gpu_device.queue.submit(Some(encoder.finish()));
+            if let Some(timer) = self.timer.as_mut().filter(|_| last_band) {
+                timer.submitted();
+            }
+            id = self.gpu_buffers.readback.begin_map(slot, Instant::now());
+            let band = self
+                .gpu_buffers
+                .readback
+                .take_blocking(&self.gpu_device.device, self.gpu_timeout)?
+                .ok_or_else(|| ShadertuiError::Gpu("band readback went missing".to_string()))?;
+            pixels.append_band(band.data, (rows * width) as usize);
+        }
+        self.read_exit_request()?;
+
+        self.tiled_readbacks.push_back(Readback {
+            id,
+            data: pixels,
+            latency: submitted_at.elapsed(),
+        });
+        Ok(id)
+    }
+
+    // Values for `// @uniform` parameters; unset ones use their declared defaults
+    pub fn set_param_values(&mut self, values: ParamValues) {
+        self.param_values = values;
+    }
+
+    pub fn param_values(&self) -> &ParamValues {
+        &self.param_values
+    }
+
+    // Whether a shader asked to restart --accumulate since the last call
+    pub fn take_reset_request(&mut self) -> bool {
+        std::mem::take(&mut self.reset_requested)
+    }
+
+    // The exit code of the first frame that called request_exit(), with exit requests enabled
+    pub fn exit_requested(&self) -> Option<u32> {
+        self.exit_code
+    }
+
+    // AIDEV-NOTE: Render the frame at `time` on the EXPORT_FPS timestep. Single-pass shaders jump
+    // straight to it; buffer passes are stepped through every frame since the previous call, so
+    // callers must request times in increasing order
+    pub fn render_at(&mut self, time: f32) -> Result<Vec<f32>, ShadertuiError> {
+        self.step_to((time * EXPORT_FPS).round() as u32, time, EXPORT_FPS)
+    }
+
+    // AIDEV-NOTE: Frame `frame` of an animation at `fps`, i.e. shader time frame / fps. Same ordering
+    // rules as render_at(), and a renderer should stick to one fps
+    pub fn render_frame(&mut self, frame: u32, fps: f32) -> Result<Vec<f32>, ShadertuiError> {
+        self.step_to(frame, frame as f32 / fps, fps)
+    }
+
+    fn step_to(
+        &mut self,
+        target_frame: u32,
+        time: f32,
+        fps: f32,
+    ) -> Result<Vec<f32>, ShadertuiError> {
+        let first_frame = if self.has_buffers && self.next_frame <= target_frame {
+            self.next_frame
+        } else {
+            target_frame
+        };
+        let cursor = [self.width as i32 / 2, self.height as i32 / 2];
+
+        let mut gpu_data = Vec::new();
+        for frame in first_frame..=target_frame {
+            let frame_time = if frame == target_frame {
+                time
+            } else {
+                frame as f32 / fps
+            };
+            let uniforms = Uniforms::new(
+                self.width,
+                self.height,
+                frame_time,
+                cursor,
+                frame,
+                1.0 / fps,
+            );
+            gpu_data = self.render(&uniforms)?;
+        }
+        self.next_frame = target_frame + 1;
+
+        Ok(gpu_data)
+    }
+}
+
+// All `height` rows unless the output buffer would be larger than the device can bind and the
+// output can be tiled
+fn band_rows(
+    gpu_device: &GpuDevice,
+    domain: Domain,
+    shell_options: &ShellOptions,
+    width: u32,
+    height: u32,
+) -> u32 {
+    if !tiles_output(domain, shell_options) {
+        return height;
+    }
+    let limits = gpu_device.device.limits();
+    let max_bytes = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+    output_band_rows(width, height, max_bytes)
+}
+
+fn gpu_size(domain: Domain, width: u32, height: u32) -> (u32, u32) {
+    match domain {
+        Domain::Pixels => (width, height),
+        Domain::Samples => (SAMPLE_COUNT, 1),
+    }
+}
@@ -1,8 +1,23 @@
+pub mod cell_writer;
+pub mod cells;
+#[cfg(feature = "cli")]
+pub mod dither;
+#[cfg(feature = "cli")]
+pub mod editor_pane;
+#[cfg(feature = "cli")]
 pub mod gpu_renderer;
+pub mod headless_renderer;
+#[cfg(feature = "cli")]
 pub mod terminal_renderer;
+#[cfg(feature = "cli")]
 pub mod window;
+#[cfg(feature = "cli")]
 pub mod window_renderer;
 
-pub use gpu_renderer::GpuRenderer;
-pub use terminal_renderer::TerminalRenderer;
-pub use window_renderer::WindowRenderer;
+#[cfg(feature = "cli")]
+pub use gpu_renderer::{GpuOptions, GpuRenderer};
+pub use headless_renderer::HeadlessRenderer;
+#[cfg(feature = "cli")]
+pub use terminal_renderer::{ImageOutput, ImageProtocol, TerminalOptions, TerminalRenderer};
+#[cfg(feature = "cli")]
+pub use window_renderer::{RetainedState, WindowRenderer};
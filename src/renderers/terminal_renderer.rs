@@ -1,123 +1,661 @@
-use std::fs;
-use std::io::{stdout, Write};
+use std::ffi::OsString;
+use std::io::{self, stdout, Write};
 use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
-    event::{self, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+        MouseButton, MouseEventKind,
+    },
     execute,
     terminal::{
         self as crossterm_terminal, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
+        SetTitle,
     },
 };
 
+use crate::error::ShadertuiError;
+use crate::gpu::zoom_into;
+use crate::renderers::cell_writer::{Cell, CellWriter};
+use crate::renderers::cells::{pixel_cell, ramp_cell};
+use crate::renderers::dither::Ditherer;
+use crate::renderers::editor_pane::EditorPane;
+use crate::utils::adaptive::AdaptiveScale;
+use crate::utils::backoff::MAX_RETRIES;
+use crate::utils::cli::{CellMode, ColorMode, Dither};
+use crate::utils::control::{ControlCommand, ControlReceiver};
+use crate::utils::events::{self, EditorEvent, StatsInterval};
+use crate::utils::external_editor;
+use crate::utils::frame_pacing::FramePacer;
+use crate::utils::image_import::load_image;
+use crate::utils::messages::msg;
+use crate::utils::mirror::{MirrorState, MirrorWindow};
 use crate::utils::multi_file_watcher::MultiFileWatcher;
-use crate::utils::shader_import::{process_imports, DependencyInfo};
+use crate::utils::notification;
+use crate::utils::project::{sibling_shader, ProjectError, ShaderProject};
+use crate::utils::recording::{Recorder, RecordingOptions};
+use crate::utils::sequencer::Fade;
+use crate::utils::shader_shell::{ShellOptions, ShellType};
+use crate::utils::signals::stop_on_signals;
+use crate::utils::terminal_caps::{cell_pixel_size, TerminalCapabilities};
 use crate::utils::threading::{
-    DualPerformanceTrackerHandle, ErrorReceiver, ErrorSender, SharedFrameBufferHandle,
-    SharedUniformsHandle, ThreadError,
+    send_error, DualPerformanceTrackerHandle, ErrorReceiver, ErrorSender, FrameData,
+    SharedFrameBufferHandle, SharedUniformsHandle, ThreadError,
 };
+use crate::utils::timeline::step_time_scale;
+use crate::utils::uniform_params::ParamValues;
+use crate::utils::validation::{check_project, Diagnostic};
+use crate::utils::{kitty, sixel};
+
+// AIDEV-NOTE: Narrowest editor pane and render region kept by the --edit split
+const MIN_EDITOR_COLUMNS: u16 = 24;
+const MIN_RENDER_COLUMNS: u16 = 8;
+
+// AIDEV-NOTE: How long messages like "Saved clip.gif" stay in the top-right corner
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+
+// Frames shown in the --perf frame time sparkline, in the detailed and compact layouts
+const DETAILED_SPARKLINE_WIDTH: usize = 24;
+const COMPACT_SPARKLINE_WIDTH: usize = 8;
+
+// AIDEV-NOTE: Display settings resolved from CLI flags and terminal capabilities
+#[derive(Debug, Clone)]
+pub struct TerminalOptions {
+    pub cell_mode: CellMode,
+    // Glyphs from dark to bright for foreground and ascii cells, instead of the built-in ramp
+    pub glyph_ramp: Option<Vec<char>>,
+    pub color_mode: ColorMode,
+    pub dither: Dither,
+    pub resize_debounce: Duration,
+    // Drain input without waiting and never show frames computed before the latest input
+    pub low_latency: bool,
+    // Show the GPU's estimated load in the bottom-right corner
+    pub low_power: bool,
+    // Halve the resolution while the terminal falls behind (off with --no-adaptive)
+    pub adaptive: bool,
+    // Frames drawn per second at most (--max-fps), shown with --perf
+    pub max_fps: Option<u32>,
+    // Embedded editor pane on the left, recompiling from memory as you type
+    pub edit: bool,
+    // Any key quits
+    pub screensaver: bool,
+    // --output sixel/kitty: draw images instead of cells
+    pub image_output: Option<ImageOutput>,
+    // Rendering on a software adapter, flagged in the --perf overlay
+    pub software: bool,
+    // Ring the bell when a failing or slow shader compiles again (--notify)
+    pub notify: bool,
+    // Command line for a window opened with W, see utils::mirror
+    pub window_args: Vec<OsString>,
+}
+
+impl TerminalOptions {
+    // AIDEV-NOTE: GPU pixels (columns, rows) drawn by one terminal cell
+    pub fn pixels_per_cell(&self) -> (u32, u32) {
+        match &self.image_output {
+            Some(image_output) => image_output.cell_size,
+            None => self.cell_mode.pixels_per_cell(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageProtocol {
+    Sixel,
+    Kitty,
+}
+
+// AIDEV-NOTE: Image frames are rendered at the terminal's own pixel resolution, so the cell size in
+// pixels must be known. Whether the terminal speaks the protocol at all can't be queried without
+// racing the input reader, so that is left to the user asking for it
+#[derive(Debug, Clone)]
+pub struct ImageOutput {
+    pub protocol: ImageProtocol,
+    pub cell_size: (u32, u32),
+    // Wraps each image for tmux/screen
+    pub caps: TerminalCapabilities,
+}
+
+impl ImageOutput {
+    pub fn detect(protocol: ImageProtocol) -> Result<Self, String> {
+        let (cell_width, cell_height) =
+            cell_pixel_size().ok_or_else(|| msg!("error.image_output_size"))?;
+        Ok(Self {
+            protocol,
+            cell_size: (cell_width as u32, cell_height as u32),
+            caps: TerminalCapabilities::detect(),
+        })
+    }
+
+    // Sequences sent once when the renderer stops
+    fn cleanup(&self) -> String {
+        match self.protocol {
+            ImageProtocol::Sixel => "\x1b[?8452l".to_string(),
+            ImageProtocol::Kitty => self.caps.wrap_passthrough(&kitty::delete_image()),
+        }
+    }
+}
 
 // AIDEV-NOTE: Terminal renderer runs in dedicated thread for display and input
 pub struct TerminalRenderer {
     width: u32,
     height: u32,
+    // First terminal column of the render region (non-zero with the --edit pane)
+    x_offset: u32,
+    options: TerminalOptions,
+    pending_resize_since: Option<Instant>,
+    // Input-to-photon latency of the last frame that reflected new input
+    input_latency: Option<Duration>,
+    measured_input_generation: u64,
     error_state: Option<String>,
     displayed_error: Option<String>,
+    // From the shader metadata header, shown in the status bar and terminal title
+    title: Option<String>,
+    editor: Option<EditorPane>,
+    recording: RecordingOptions,
+    recorder: Option<Recorder>,
+    recordings: u32,
+    notice: Option<(String, Instant)>,
+    // Since when the GPU thread is compiling a reload; the old shader is still on screen
+    compiling: Option<Instant>,
+    // The last reload failed, so the next one that succeeds rings for --notify
+    shader_failed: bool,
+    // `// @uniform` panel opened with Tab
+    param_panel: bool,
+    // Corners (column, row) of the rectangle being selected with the right mouse button
+    zoom_selection: Option<[(u16, u16); 2]>,
+    // Cells of the render as last written
+    cell_writer: CellWriter,
+    // AIDEV-NOTE: Reused every frame so drawing doesn't allocate once they have grown: the bytes
+    // written to the terminal, and the cells of the row being built
+    screen_buffer: String,
+    row_cells: Vec<Cell>,
+    // AIDEV-NOTE: The frame on screen, drawn again only when something over or around it changed
+    // (input, a settled resize, a cleared error, a status marker); otherwise the terminal waits for
+    // the GPU thread's next frame instead of redrawing the same one
+    last_frame: Option<FrameData>,
+    redraw: bool,
+    // Only with --dither and a palette color mode
+    ditherer: Option<Ditherer>,
+    // Brightness of the render, faded by demo transitions
+    fade: Fade,
+    // Window opened with W, following this render's clock and parameters
+    mirror_window: Option<MirrorWindow>,
+}
+
+// A shader or project that can't be loaded (e.g. a missing import) fails like a compile error
+fn load_failed(shader_file: &Path, error: ProjectError) -> String {
+    let message = error.to_string();
+    events::emit(|| EditorEvent::CompileFailed {
+        shader: shader_file.to_path_buf(),
+        diagnostic: Diagnostic::unlocated(shader_file, message.clone()),
+    });
+    message
 }
 
 impl TerminalRenderer {
-    pub fn new(width: u32, height: u32) -> Self {
+    pub fn new(
+        columns: u32,
+        height: u32,
+        options: TerminalOptions,
+        recording: RecordingOptions,
+    ) -> Self {
+        let (x_offset, width) = Self::layout(columns as u16, options.edit);
+        let ditherer = Ditherer::new(options.dither, options.color_mode);
         Self {
             width,
             height,
+            x_offset,
+            options,
+            pending_resize_since: None,
+            input_latency: None,
+            measured_input_generation: 0,
             error_state: None,
             displayed_error: None,
+            title: None,
+            editor: None,
+            recording,
+            recorder: None,
+            recordings: 0,
+            notice: None,
+            compiling: None,
+            shader_failed: false,
+            param_panel: false,
+            zoom_selection: None,
+            cell_writer: CellWriter::new(),
+            screen_buffer: String::new(),
+            last_frame: None,
+            redraw: false,
+            row_cells: Vec::new(),
+            ditherer,
+            fade: Fade::new(),
+            mirror_window: None,
         }
     }
 
-    // AIDEV-NOTE: Helper function for RGB conversion with gamma correction
-    fn float_rgb_to_u8(&self, r: f32, g: f32, b: f32) -> (u8, u8, u8) {
-        // Apply gamma correction to match window renderer (linear -> sRGB)
-        let r = (r.powf(1.0 / 2.2) * 255.0) as u8;
-        let g = (g.powf(1.0 / 2.2) * 255.0) as u8;
-        let b = (b.powf(1.0 / 2.2) * 255.0) as u8;
-        (r, g, b)
+    // AIDEV-NOTE: Split terminal columns into (render region offset, render width). With --edit the
+    // editor takes about two fifths of the columns plus a separator column
+    pub fn layout(columns: u16, edit: bool) -> (u32, u32) {
+        if !edit {
+            return (0, columns as u32);
+        }
+        let editor_columns = (columns * 2 / 5)
+            .max(MIN_EDITOR_COLUMNS)
+            .min(columns.saturating_sub(MIN_RENDER_COLUMNS + 1));
+        let x_offset = editor_columns + 1;
+        (
+            x_offset as u32,
+            columns.saturating_sub(x_offset).max(1) as u32,
+        )
     }
 
-    // AIDEV-NOTE: Handle file change and request shader reload, return dependency info
+    fn notify_compiled(&self, shader_file: &Path) -> io::Result<()> {
+        let name = shader_file.file_name().unwrap_or(shader_file.as_os_str());
+        let message = msg!("notice.compiled", shader = name.to_string_lossy());
+        let caps = TerminalCapabilities::detect();
+        let mut stdout = stdout();
+        stdout.write_all(notification::terminal_sequence(&caps, &message).as_bytes())?;
+        stdout.flush()
+    }
+
+    // AIDEV-NOTE: Handle file change and request shader reload
     fn handle_file_change(
+        &mut self,
         shader_file: &Path,
+        file_watcher: &mut MultiFileWatcher,
         shared_uniforms: &SharedUniformsHandle,
-    ) -> Result<DependencyInfo, String> {
-        match fs::read_to_string(shader_file) {
-            Ok(raw_shader_source) => {
-                // Process imports before reloading
-                match process_imports(shader_file, &raw_shader_source) {
-                    Ok((processed_shader_source, deps)) => {
-                        // Validate user shader before requesting reload
-                        match crate::utils::validation::validate_user_shader_for_reload(
-                            &processed_shader_source,
-                            crate::utils::shader_shell::ShellType::Terminal,
-                        ) {
-                            Ok(()) => {
-                                // Request shader reload via shared uniforms
-                                {
-                                    let mut uniforms = shared_uniforms.lock().unwrap();
-                                    uniforms.request_shader_reload(processed_shader_source);
-                                }
-                                Ok(deps)
-                            }
-                            Err(e) => Err(format!("Shader validation error: {e}")),
-                        }
+        shell_options: &ShellOptions,
+    ) -> Result<(), String> {
+        // Reload the shader (or every pass of a project) with imports processed
+        events::emit(|| EditorEvent::ReloadStarted {
+            shader: shader_file.to_path_buf(),
+        });
+        let project = ShaderProject::load(shader_file).map_err(|e| load_failed(shader_file, e))?;
+        self.request_project_reload(
+            shader_file,
+            project,
+            file_watcher,
+            shared_uniforms,
+            shell_options,
+        )
+    }
+
+    // AIDEV-NOTE: Compile the editor buffer without touching the file on disk
+    fn handle_editor_change(
+        &mut self,
+        shader_file: &Path,
+        source: &str,
+        file_watcher: &mut MultiFileWatcher,
+        shared_uniforms: &SharedUniformsHandle,
+        shell_options: &ShellOptions,
+    ) -> Result<(), String> {
+        events::emit(|| EditorEvent::ReloadStarted {
+            shader: shader_file.to_path_buf(),
+        });
+        let project = ShaderProject::from_source(shader_file, source)
+            .map_err(|e| load_failed(shader_file, e))?;
+        self.request_project_reload(
+            shader_file,
+            project,
+            file_watcher,
+            shared_uniforms,
+            shell_options,
+        )
+    }
+
+    // AIDEV-NOTE: Imports are watched as soon as the project loads, like in windowed mode, so a
+    // validation error in a newly imported file reloads once that file is fixed
+    fn request_project_reload(
+        &mut self,
+        shader_file: &Path,
+        project: ShaderProject,
+        file_watcher: &mut MultiFileWatcher,
+        shared_uniforms: &SharedUniformsHandle,
+        shell_options: &ShellOptions,
+    ) -> Result<(), String> {
+        file_watcher
+            .update_watched_files(&project.all_files)
+            .map_err(|e| msg!("error.file_watcher", error = e))?;
+
+        // Validate all passes before requesting reload
+        match check_project(&project, ShellType::Terminal, shell_options) {
+            Ok(()) => {
+                self.set_title(project.metadata.display_title());
+                // Request shader reload via shared uniforms
+                {
+                    let mut uniforms = shared_uniforms.lock().unwrap();
+                    uniforms.request_shader_reload(project);
+                }
+                Ok(())
+            }
+            Err(failure) => {
+                events::emit(|| EditorEvent::CompileFailed {
+                    shader: shader_file.to_path_buf(),
+                    diagnostic: failure.diagnostic(shader_file),
+                });
+                let error = ShadertuiError::from(failure);
+                Err(msg!("error.shader_validation", error = error))
+            }
+        }
+    }
+
+    fn set_title(&mut self, title: Option<String>) {
+        let terminal_title = match &title {
+            Some(title) => msg!("title.with_shader", title = title),
+            None => msg!("title.app"),
+        };
+        let _ = execute!(stdout(), SetTitle(terminal_title));
+        self.title = title;
+    }
+
+    // AIDEV-NOTE: `r` starts a recording and stops it again; the saved path is shown as a notice
+    fn toggle_recording(&mut self) {
+        let message = match self.recorder.take() {
+            Some(recorder) => match recorder.finish() {
+                Ok(path) => msg!("notice.saved", path = path.display()),
+                Err(e) => msg!("recording.failed", error = e),
+            },
+            None => {
+                self.recordings += 1;
+                match Recorder::start(&self.recording, self.recordings) {
+                    Ok(recorder) => {
+                        self.recorder = Some(recorder);
+                        return;
                     }
-                    Err(e) => Err(format!("Import processing error: {e}")),
+                    Err(e) => msg!("recording.failed", error = e),
                 }
             }
-            Err(e) => Err(format!("File read error: {e}")),
+        };
+        self.notice = Some((message, Instant::now()));
+    }
+
+    // AIDEV-NOTE: `w` opens the shader in a window and closes it again
+    fn toggle_mirror_window(&mut self, shader_file: &Path) {
+        let message = if self.mirror_window.take().is_some() {
+            msg!("notice.window_closed")
+        } else {
+            match MirrorWindow::open(&self.options.window_args, shader_file.to_path_buf()) {
+                Ok(window) => {
+                    self.mirror_window = Some(window);
+                    msg!("notice.window_opened")
+                }
+                Err(e) => msg!("notice.window_failed", error = e),
+            }
+        };
+        self.notice = Some((message, Instant::now()));
+    }
+
+    fn sync_mirror_window(
+        &mut self,
+        shader_file: &Path,
+        shared_uniforms: &SharedUniformsHandle,
+        current_time: f32,
+    ) {
+        let Some(window) = &mut self.mirror_window else {
+            return;
+        };
+        if let Some(closed) = window.closed() {
+            self.mirror_window = None;
+            if let Err(e) = closed {
+                let message = msg!("notice.window_failed", error = e);
+                self.notice = Some((message, Instant::now()));
+            }
+            return;
+        }
+        let state = {
+            let uniforms = shared_uniforms.lock().unwrap();
+            MirrorState {
+                shader_file: shader_file.to_path_buf(),
+                time: uniforms.shader_time(current_time),
+                paused: uniforms.time_paused,
+                time_scale: uniforms.time_scale,
+                params: uniforms.param_values.assignments(),
+            }
+        };
+        window.sync(&state);
+    }
+
+    // AIDEV-NOTE: Recording indicator, background compile or recent notice, right-aligned on the top
+    // row of the render. Returns whether anything was drawn
+    fn push_status_marker(&self, screen_content: &mut String) -> bool {
+        let (text, style) = if self.recorder.is_some() {
+            (msg!("status.recording"), "\x1b[1;31m")
+        } else if self.compiling.is_some() {
+            (msg!("status.compiling"), "\x1b[2;7m")
+        } else if let Some((message, _)) = self
+            .notice
+            .as_ref()
+            .filter(|(_, shown_at)| shown_at.elapsed() < NOTICE_DURATION)
+        {
+            (message.clone(), "\x1b[7m")
+        } else {
+            return false;
+        };
+        let text: String = text.chars().take(self.width as usize).collect();
+        let column = self.x_offset + self.width - text.chars().count() as u32;
+        screen_content.push_str(&format!("\x1b[1;{}H{style}{text}\x1b[0m", column + 1));
+        true
+    }
+
+    // AIDEV-NOTE: Parameter panel in the top-left corner of the render, below the status row: a
+    // key hint, then a slider per parameter with the selected one highlighted
+    fn push_param_panel(&self, screen_content: &mut String, values: &ParamValues, top: u32) {
+        let hint = msg!("panel.param_hint");
+        let lines = std::iter::once(hint).chain(values.panel_lines());
+        let rows = self.height.saturating_sub(top) as usize;
+        for (index, line) in lines.enumerate().take(rows) {
+            let style = if index == values.selected() + 1 {
+                "\x1b[7m"
+            } else {
+                "\x1b[40;97m"
+            };
+            let line: String = line.chars().take(self.width as usize).collect();
+            screen_content.push_str(&format!(
+                "\x1b[{};{}H{style}{line}\x1b[0m",
+                top as usize + index + 1,
+                self.x_offset + 1
+            ));
+        }
+    }
+
+    // AIDEV-NOTE: Outline of the rectangle being selected for the zoom inspector
+    fn push_zoom_selection(&self, screen_content: &mut String) {
+        let Some([start, end]) = self.zoom_selection else {
+            return;
+        };
+        let (left, right) = (start.0.min(end.0), start.0.max(end.0));
+        let (top, bottom) = (start.1.min(end.1), start.1.max(end.1));
+        for row in top..=bottom {
+            for column in left..=right {
+                let corner = match (row == top, row == bottom, column == left, column == right) {
+                    (true, true, _, _) => '─',
+                    (_, _, true, true) => '│',
+                    (true, _, true, _) => '┌',
+                    (true, _, _, true) => '┐',
+                    (_, true, true, _) => '└',
+                    (_, true, _, true) => '┘',
+                    (true, _, _, _) | (_, true, _, _) => '─',
+                    (_, _, true, _) | (_, _, _, true) => '│',
+                    _ => continue,
+                };
+                screen_content.push_str(&format!(
+                    "\x1b[{};{}H\x1b[1;97m{corner}\x1b[0m",
+                    row + 1,
+                    column + 1
+                ));
+            }
         }
     }
 
-    // AIDEV-NOTE: Format performance overlay string for top row display
+    // AIDEV-NOTE: Finish a right-drag: zoom into the selected cells (as fractions of the render,
+    // Y=0 at the bottom) unless it was a plain click
+    fn finish_zoom_selection(&mut self, shared_uniforms: &SharedUniformsHandle) {
+        let Some([start, end]) = self.zoom_selection.take() else {
+            return;
+        };
+        if start == end {
+            return;
+        }
+        let left = start.0.min(end.0) as u32 - self.x_offset;
+        let bottom = start.1.max(end.1) as u32;
+        let columns = start.0.abs_diff(end.0) as u32 + 1;
+        let rows = start.1.abs_diff(end.1) as u32 + 1;
+        let selection = [
+            left as f32 / self.width as f32,
+            (self.height - 1 - bottom) as f32 / self.height as f32,
+            columns as f32 / self.width as f32,
+            rows as f32 / self.height as f32,
+        ];
+        let mut uniforms = shared_uniforms.lock().unwrap();
+        let zoom = zoom_into(uniforms.zoom, selection);
+        uniforms.set_zoom(Some(zoom));
+        let message = msg!("notice.zoom", factor = format!("{:.1}", 1.0 / zoom[2]));
+        self.notice = Some((message, Instant::now()));
+    }
+
+    // AIDEV-NOTE: --low-power keeps the GPU's estimated cost in the bottom-right corner, dimmed so
+    // it doesn't distract from the shader
+    fn push_telemetry(&self, screen_content: &mut String, frame_buffer: &SharedFrameBufferHandle) {
+        let summary = frame_buffer.lock().unwrap().telemetry.summary();
+        let text: String = summary.chars().take(self.width as usize).collect();
+        let column = self.x_offset + self.width - text.chars().count() as u32;
+        screen_content.push_str(&format!(
+            "\x1b[{};{}H\x1b[2m{text}\x1b[0m",
+            self.height,
+            column + 1
+        ));
+    }
+
+    // AIDEV-NOTE: Format performance overlay string for top row display. The detailed layout is used
+    // when it fits the terminal width, otherwise a compact one with the frame rates and the p95
+    // frame time
     fn format_performance_overlay(
+        &self,
+        frame_data: &crate::utils::threading::FrameData,
         performance_tracker: &Option<DualPerformanceTrackerHandle>,
         frame_buffer: &SharedFrameBufferHandle,
     ) -> Option<String> {
         if let Some(ref tracker) = performance_tracker {
-            let (gpu_fps, term_fps, frames_dropped) = {
+            let (gpu_fps, term_fps, gpu_time, frame_times, frames_dropped, gpu_busy) = {
                 let perf = tracker.lock().unwrap();
                 let frame_buf = frame_buffer.lock().unwrap();
                 (
                     perf.get_gpu_fps(),
                     perf.get_terminal_fps(),
+                    perf.gpu_time,
+                    perf.terminal_frame_time_stats(DETAILED_SPARKLINE_WIDTH),
                     frame_buf.get_frames_dropped(),
+                    frame_buf.telemetry.utilization(),
                 )
             };
-            Some(format!(
-                "GPU: {gpu_fps:.1} | Term: {term_fps:.1} | Dropped: {frames_dropped}"
-            ))
+            let ms = |duration: Duration| format!("{:.1}", duration.as_secs_f32() * 1000.0);
+            let gpu_fps = msg!("status.gpu_fps", fps = format!("{gpu_fps:.1}"));
+            let term_fps = msg!("status.term_fps", fps = format!("{term_fps:.1}"));
+
+            // The shader's title first, if it has one
+            let mut stats: Vec<String> = self.title.iter().cloned().collect();
+            let software = self.options.software.then(|| msg!("status.software"));
+            stats.extend(software.clone());
+            stats.extend([
+                msg!(
+                    "status.frame",
+                    frame = frame_data.frame,
+                    time = format!("{:.2}", frame_data.time)
+                ),
+                gpu_fps.clone(),
+                term_fps.clone(),
+            ]);
+            if let Some(gpu_time) = gpu_time {
+                stats.push(msg!(
+                    "status.gpu_time",
+                    ms = format!("{:.2}", gpu_time.as_secs_f32() * 1000.0)
+                ));
+            }
+            if let Some(frame_times) = &frame_times {
+                stats.push(format!(
+                    "{} {}",
+                    msg!(
+                        "status.frame_times",
+                        p50 = ms(frame_times.p50),
+                        p95 = ms(frame_times.p95),
+                        p99 = ms(frame_times.p99)
+                    ),
+                    frame_times.sparkline
+                ));
+            }
+            stats.push(msg!("status.dropped", count = frames_dropped));
+            if let Some(max_fps) = self.options.max_fps {
+                stats.push(msg!("status.cap", fps = max_fps));
+            }
+            if let Some(gpu_busy) = gpu_busy {
+                stats.push(msg!(
+                    "status.busy",
+                    percent = format!("{:.0}", gpu_busy * 100.0)
+                ));
+            }
+            stats.push(msg!(
+                "status.readback",
+                ms = format!("{:.1}", frame_data.readback_latency.as_secs_f32() * 1000.0),
+                count = frame_data.readbacks_in_flight
+            ));
+            if self.options.low_latency {
+                stats.push(match self.input_latency {
+                    Some(latency) => msg!(
+                        "status.latency",
+                        ms = format!("{:.1}", latency.as_secs_f32() * 1000.0)
+                    ),
+                    None => msg!("status.latency_unknown"),
+                });
+            }
+            let detailed = stats.join(" | ");
+            if detailed.chars().count() <= self.width as usize {
+                return Some(detailed);
+            }
+
+            let mut compact: Vec<String> = software.into_iter().collect();
+            compact.extend([gpu_fps, term_fps]);
+            if let Some(frame_times) = &frame_times {
+                let sparkline = frame_times.sparkline.chars();
+                let skip = sparkline
+                    .clone()
+                    .count()
+                    .saturating_sub(COMPACT_SPARKLINE_WIDTH);
+                compact.push(format!(
+                    "{} {}",
+                    msg!("status.frame_time_p95", ms = ms(frame_times.p95)),
+                    sparkline.skip(skip).collect::<String>()
+                ));
+            }
+            Some(compact.join(" | "))
         } else {
             None
         }
     }
 
-    // AIDEV-NOTE: Build complete screen directly from GPU data for maximum performance
+    // AIDEV-NOTE: Build the screen update for a frame directly from GPU data. Cells go through the
+    // CellWriter, so only what changed since the last frame is written
     fn build_full_screen_from_gpu_data(
-        &self,
+        &mut self,
+        screen_content: &mut String,
         frame_data: &crate::utils::threading::FrameData,
         performance_tracker: &Option<DualPerformanceTrackerHandle>,
         frame_buffer: &SharedFrameBufferHandle,
-    ) -> String {
-        let mut screen_content = String::new();
+    ) {
         let gpu_data = &frame_data.gpu_data;
         let gpu_width = frame_data.width;
 
         // Handle performance overlay if enabled - reserve first row
-        if let Some(perf_text) = Self::format_performance_overlay(performance_tracker, frame_buffer)
+        if let Some(perf_text) =
+            self.format_performance_overlay(frame_data, performance_tracker, frame_buffer)
         {
-            // Create performance overlay on first row
-            let clear_line = " ".repeat(self.width as usize - perf_text.len());
+            // Create performance overlay on first row, truncated to the terminal width
+            self.push_row_start(screen_content, 0);
+            let perf_text: String = perf_text.chars().take(self.width as usize).collect();
+            let clear_line = " ".repeat(self.width as usize - perf_text.chars().count());
             screen_content.push_str(&perf_text);
             screen_content.push_str(&clear_line);
         }
@@ -125,63 +663,153 @@ impl TerminalRenderer {
         // Determine starting row for GPU data (skip row 0 if performance monitoring enabled)
         let start_row = if performance_tracker.is_some() { 1 } else { 0 };
 
+        if let Some(image_output) = &self.options.image_output {
+            self.push_image(screen_content, image_output, frame_data, start_row);
+            return;
+        }
+
         // Build each terminal row from GPU data
-        for term_y in start_row..self.height as usize {
-            for term_x in 0..self.width as usize {
-                // Calculate GPU pixel rows for top and bottom halves of this terminal cell
+        let (width, height) = (self.width as usize, self.height as usize);
+        self.cell_writer.begin_frame(screen_content, width, height);
+        let mut row = std::mem::take(&mut self.row_cells);
+        let brightness = self.fade.level();
+        if let Some(ditherer) = &mut self.ditherer {
+            ditherer.begin_frame();
+        }
+        for term_y in start_row..height {
+            row.clear();
+            for term_x in 0..width {
                 // AIDEV-NOTE: Flip Y-axis to match window renderer coordinate system (Y=0 at bottom)
-                let flipped_term_y = (self.height as usize - 1) - term_y;
-                let top_pixel_y = flipped_term_y * 2 + 1;
-                let bottom_pixel_y = flipped_term_y * 2;
-
-                // Get top half color
-                let top_idx = (top_pixel_y * gpu_width as usize + term_x) * 4;
-                let (top_r, top_g, top_b) = if top_idx + 2 < gpu_data.len() {
-                    (
-                        gpu_data[top_idx],
-                        gpu_data[top_idx + 1],
-                        gpu_data[top_idx + 2],
-                    )
-                } else {
-                    (0.0, 0.0, 0.0)
-                };
+                let flipped_term_y = (height - 1) - term_y;
+                row.push(match &self.options.glyph_ramp {
+                    Some(ramp) => ramp_cell(
+                        gpu_data,
+                        gpu_width as usize,
+                        term_x,
+                        flipped_term_y,
+                        ramp,
+                        self.options.cell_mode == CellMode::Foreground,
+                    ),
+                    None => pixel_cell(
+                        gpu_data,
+                        gpu_width as usize,
+                        term_x,
+                        flipped_term_y,
+                        self.options.cell_mode,
+                    ),
+                });
+            }
+            if brightness < 1.0 {
+                row.iter_mut()
+                    .for_each(|cell| *cell = cell.dimmed(brightness));
+            }
+            if let Some(ditherer) = &mut self.ditherer {
+                ditherer.dither_row(term_y, &mut row);
+            }
+            self.cell_writer.push_row(
+                screen_content,
+                term_y,
+                self.x_offset as usize,
+                &row,
+                self.options.color_mode,
+            );
+        }
+        self.cell_writer.end_frame(screen_content);
+        self.row_cells = row;
+    }
 
-                // Get bottom half color
-                let bottom_idx = (bottom_pixel_y * gpu_width as usize + term_x) * 4;
-                let (bottom_r, bottom_g, bottom_b) = if bottom_idx + 2 < gpu_data.len() {
-                    (
-                        gpu_data[bottom_idx],
-                        gpu_data[bottom_idx + 1],
-                        gpu_data[bottom_idx + 2],
-                    )
-                } else {
-                    (0.0, 0.0, 0.0)
-                };
+    // AIDEV-NOTE: The whole frame as one image below the status row. For sixel, terminals that
+    // support scrolling mode 8452 keep the cursor beside the image, so an image touching the bottom
+    // row doesn't scroll the screen
+    fn push_image(
+        &self,
+        screen_content: &mut String,
+        image_output: &ImageOutput,
+        frame_data: &crate::utils::threading::FrameData,
+        start_row: usize,
+    ) {
+        let width = frame_data.width;
+        let height = frame_data.gpu_data.pixel_count() as u32 / width;
+        let mut image = frame_data.gpu_data.to_image(width, height);
+        let skipped_rows = (start_row as u32 * image_output.cell_size.1).min(height);
+        image.pixels.drain(..(skipped_rows * width * 4) as usize);
+        image.height -= skipped_rows;
 
-                // Convert to 0-255 range
-                let (top_r, top_g, top_b) = self.float_rgb_to_u8(top_r, top_g, top_b);
-                let (bottom_r, bottom_g, bottom_b) =
-                    self.float_rgb_to_u8(bottom_r, bottom_g, bottom_b);
-
-                // Create styled character: ▀ with top color as foreground, bottom as background
-                // Optimize: use push_str with pre-built components instead of format!
-                screen_content.push_str("\x1b[38;2;");
-                screen_content.push_str(&top_r.to_string());
-                screen_content.push(';');
-                screen_content.push_str(&top_g.to_string());
-                screen_content.push(';');
-                screen_content.push_str(&top_b.to_string());
-                screen_content.push_str("m\x1b[48;2;");
-                screen_content.push_str(&bottom_r.to_string());
-                screen_content.push(';');
-                screen_content.push_str(&bottom_g.to_string());
-                screen_content.push(';');
-                screen_content.push_str(&bottom_b.to_string());
-                screen_content.push_str("m▀\x1b[0m");
+        let encoded = match image_output.protocol {
+            ImageProtocol::Sixel => {
+                screen_content.push_str("\x1b[?8452h");
+                sixel::encode(&image)
             }
+            ImageProtocol::Kitty => kitty::encode(&image),
+        };
+        screen_content.push_str(&format!("\x1b[{};{}H", start_row + 1, self.x_offset + 1));
+        screen_content.push_str(&image_output.caps.wrap_passthrough(&encoded));
+    }
+
+    // AIDEV-NOTE: Rows wrap naturally across the full width; with an offset each row is positioned
+    fn push_row_start(&self, screen_content: &mut String, term_y: usize) {
+        if self.x_offset > 0 {
+            screen_content.push_str(&format!("\x1b[{};{}H", term_y + 1, self.x_offset + 1));
+        }
+    }
+
+    // AIDEV-NOTE: Hand the terminal to $VISUAL/$EDITOR and take it back afterwards. The GPU thread
+    // keeps running, and saved changes arrive through the file watcher like any other edit
+    fn open_external_editor(&mut self, shader_file: &Path) -> std::io::Result<()> {
+        let mut stdout = stdout();
+        execute!(stdout, DisableMouseCapture, Show, LeaveAlternateScreen)?;
+        crossterm_terminal::disable_raw_mode()?;
+
+        let result = external_editor::open_blocking(shader_file);
+
+        crossterm_terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+        if !self.options.screensaver {
+            execute!(stdout, EnableMouseCapture)?;
+        }
+        self.displayed_error = None;
+        self.cell_writer.invalidate();
+        self.redraw = true;
+
+        if let Err(e) = result {
+            self.error_state = Some(format!(
+                "{}\n{}",
+                msg!(
+                    "error.editor_launch",
+                    editor = external_editor::editor_name(),
+                    error = e
+                ),
+                msg!("error.editor_hint")
+            ));
+        }
+        Ok(())
+    }
+
+    // AIDEV-NOTE: The GPU pixel at the center of a terminal cell, Y=0 at the bottom like the window
+    // renderer. Cells outside the render region (the --edit pane) have no pixel
+    fn mouse_to_pixel(&self, column: u16, row: u16) -> Option<[i32; 2]> {
+        let column = (column as u32).checked_sub(self.x_offset)?;
+        if column >= self.width || row as u32 >= self.height {
+            return None;
         }
+        let (pixels_x, pixels_y) = self.options.pixels_per_cell();
+        let x = column * pixels_x + pixels_x / 2;
+        let y = (self.height - 1 - row as u32) * pixels_y + pixels_y / 2;
+        Some([x as i32, y as i32])
+    }
 
-        screen_content
+    // AIDEV-NOTE: Draw the editor pane and park the (visible) cursor at the editing position
+    fn push_editor(&mut self, screen_content: &mut String) {
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        screen_content.push_str("\x1b[?25l");
+        let (column, row) = editor.draw(
+            screen_content,
+            self.x_offset as usize - 1,
+            self.height as usize,
+        );
+        screen_content.push_str(&format!("\x1b[{};{}H\x1b[?25h", row + 1, column + 1));
     }
 
     // AIDEV-NOTE: Main terminal thread function - handles input, file watching, and display
@@ -194,45 +822,159 @@ impl TerminalRenderer {
         error_receiver: ErrorReceiver,
         shader_file: &Path,
         performance_tracker: Option<DualPerformanceTrackerHandle>,
-        max_fps: Option<u32>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        shell_options: ShellOptions,
+        control: Option<ControlReceiver>,
+    ) -> Result<(), ShadertuiError> {
+        // Switched by `shadertui ctl load` in daemon mode
+        let mut shader_file = shader_file.to_path_buf();
+
         // Set up multi-file watcher for main shader and dependencies
-        let mut file_watcher = MultiFileWatcher::new(shader_file)?;
+        let mut file_watcher = MultiFileWatcher::new(&shader_file)?;
 
-        // Initial dependency scan to watch all imported files
-        if let Ok(raw_content) = fs::read_to_string(shader_file) {
-            if let Ok((_, deps)) = process_imports(shader_file, &raw_content) {
-                let _ = file_watcher.update_watched_files(&deps.all_files);
-            }
+        // Initial dependency scan to watch all imported files (and pass shaders of a project)
+        if let Ok(project) = ShaderProject::load(&shader_file) {
+            let _ = file_watcher.update_watched_files(&project.all_files);
+            self.set_title(project.metadata.display_title());
+        }
+
+        if self.options.edit {
+            self.editor = Some(EditorPane::open(&shader_file)?);
         }
 
         // Enter alternate screen and setup terminal
         execute!(stdout(), EnterAlternateScreen, Hide)?;
         crossterm_terminal::enable_raw_mode()?;
         execute!(stdout(), Clear(ClearType::All))?;
+        if !self.options.screensaver {
+            execute!(stdout(), EnableMouseCapture)?;
+        }
 
         let mut stdout = stdout();
         let start_time = Instant::now();
+        let (pixels_x, pixels_y) = self.options.pixels_per_cell();
+        let stop = stop_on_signals();
 
-        // Calculate frame time for FPS limiting
-        let frame_time = max_fps.map(|fps| Duration::from_millis(1000 / fps as u64));
-        let mut last_frame_time = Instant::now();
+        let mut pacer = FramePacer::new(self.options.max_fps);
+        // Frame rates for --json-events
+        let mut event_stats = events::enabled().then(StatsInterval::new);
+        let mut adaptive = self
+            .options
+            .adaptive
+            .then(|| AdaptiveScale::new(self.options.max_fps));
 
         // Terminal rendering loop
-        loop {
-            // Check for file changes (any watched file)
-            if file_watcher.check_for_changes().is_some() {
-                match Self::handle_file_change(shader_file, &shared_uniforms) {
-                    Ok(deps) => {
-                        // Update watched files with new dependency info
-                        if let Err(e) = file_watcher.update_watched_files(&deps.all_files) {
-                            self.error_state = Some(format!("File watcher update error: {e}"));
-                        } else {
-                            // Clear error state on successful reload request
-                            self.error_state = None;
+        'render: loop {
+            // Being killed (e.g. the terminal closing) quits like `q` does
+            if stop.load(Ordering::Relaxed) {
+                send_error(&[&error_sender], ThreadError::Shutdown);
+                break 'render;
+            }
+
+            // Daemon and demo commands: switching shaders is a reload of a different file. Everything
+            // queued is applied at once, so a demo event's commands land on the same frame
+            let mut switched = false;
+            for command in control.iter().flat_map(|control| control.try_iter()) {
+                match command {
+                    ControlCommand::Load(path) => {
+                        file_watcher = MultiFileWatcher::new(&path)?;
+                        shader_file = path;
+                        switched = true;
+                    }
+                    ControlCommand::Skip(shaders) => match sibling_shader(&shader_file, shaders) {
+                        Ok(path) => {
+                            file_watcher = MultiFileWatcher::new(&path)?;
+                            shader_file = path;
+                            switched = true;
+                        }
+                        Err(e) => self.error_state = Some(e),
+                    },
+                    ControlCommand::Texture(index, path) => match load_image(&path) {
+                        Ok(image) => shared_uniforms
+                            .lock()
+                            .unwrap()
+                            .request_texture(index, Arc::new(image)),
+                        Err(e) => self.error_state = Some(e),
+                    },
+                    ControlCommand::Param(name, value) => {
+                        shared_uniforms.lock().unwrap().set_param(&name, value);
+                    }
+                    ControlCommand::Zoom(zoom) => shared_uniforms.lock().unwrap().set_zoom(zoom),
+                    ControlCommand::Fade(brightness, seconds) => {
+                        self.fade.start(brightness, seconds);
+                    }
+                    ControlCommand::Pause(paused) => {
+                        let current_time = start_time.elapsed().as_secs_f32();
+                        let mut uniforms = shared_uniforms.lock().unwrap();
+                        match paused {
+                            Some(paused) => uniforms.set_paused(paused, current_time),
+                            None => uniforms.toggle_pause(current_time),
                         }
                     }
+                    ControlCommand::Seek(seconds) => {
+                        let current_time = start_time.elapsed().as_secs_f32();
+                        shared_uniforms.lock().unwrap().seek(seconds, current_time);
+                    }
+                    ControlCommand::Time(seconds) => {
+                        let current_time = start_time.elapsed().as_secs_f32();
+                        shared_uniforms
+                            .lock()
+                            .unwrap()
+                            .set_time(seconds, current_time);
+                    }
+                    ControlCommand::Speed(time_scale) => {
+                        let current_time = start_time.elapsed().as_secs_f32();
+                        shared_uniforms
+                            .lock()
+                            .unwrap()
+                            .set_time_scale(time_scale, current_time);
+                    }
+                    ControlCommand::Window => self.toggle_mirror_window(&shader_file),
+                    ControlCommand::Quit => {
+                        send_error(&[&error_sender], ThreadError::Shutdown);
+                        break 'render;
+                    }
+                }
+            }
+
+            // Check for file changes (any watched file)
+            if file_watcher.check_for_changes().is_some() || switched {
+                match self.handle_file_change(
+                    &shader_file,
+                    &mut file_watcher,
+                    &shared_uniforms,
+                    &shell_options,
+                ) {
+                    // Clear error state on successful reload request
+                    Ok(()) => self.error_state = None,
+                    Err(error_msg) => {
+                        self.shader_failed = true;
+                        self.error_state = Some(error_msg);
+                    }
+                }
+            }
+
+            self.sync_mirror_window(
+                &shader_file,
+                &shared_uniforms,
+                start_time.elapsed().as_secs_f32(),
+            );
+
+            // Recompile the editor buffer once typing pauses
+            if let Some(source) = self
+                .editor
+                .as_mut()
+                .and_then(|editor| editor.take_pending_source())
+            {
+                match self.handle_editor_change(
+                    &shader_file,
+                    &source,
+                    &mut file_watcher,
+                    &shared_uniforms,
+                    &shell_options,
+                ) {
+                    Ok(()) => self.error_state = None,
                     Err(error_msg) => {
+                        self.shader_failed = true;
                         self.error_state = Some(error_msg);
                     }
                 }
@@ -242,14 +984,44 @@ impl TerminalRenderer {
             if let Ok(thread_error) = error_receiver.try_recv() {
                 match thread_error {
                     ThreadError::ShaderCompilationError(msg) => {
-                        self.error_state = Some(format!("Shader compilation error: {msg}"));
+                        events::emit(|| EditorEvent::CompileFailed {
+                            shader: shader_file.clone(),
+                            diagnostic: Diagnostic::unlocated(&shader_file, msg.clone()),
+                        });
+                        self.compiling = None;
+                        self.shader_failed = true;
+                        self.error_state = Some(msg!("error.shader_compilation", error = msg));
                     }
+                    ThreadError::ShaderCompiling => self.compiling = Some(Instant::now()),
                     ThreadError::ShaderReloadSuccess => {
                         // Clear error state on successful shader reload
+                        let compile_time = self.compiling.take().map(|since| since.elapsed());
+                        events::emit(|| EditorEvent::CompileSucceeded {
+                            shader: shader_file.clone(),
+                            seconds: compile_time.map(|time| time.as_secs_f64()),
+                        });
+                        if self.options.notify
+                            && notification::should_notify(self.shader_failed, compile_time)
+                        {
+                            self.notify_compiled(&shader_file)?;
+                        }
+                        self.shader_failed = false;
                         self.error_state = None;
                     }
                     ThreadError::GpuError(msg) => {
-                        self.error_state = Some(format!("GPU error: {msg}"));
+                        self.error_state = Some(msg!("error.gpu", error = msg));
+                    }
+                    ThreadError::RenderingStopped(msg) => {
+                        self.error_state = Some(msg!(
+                            "error.rendering_stopped",
+                            attempts = MAX_RETRIES,
+                            error = msg
+                        ));
+                    }
+                    ThreadError::DeviceRecovered => {
+                        self.compiling = None;
+                        self.error_state = None;
+                        self.notice = Some((msg!("notice.gpu_recovered"), Instant::now()));
                     }
                     ThreadError::Shutdown => {
                         break;
@@ -257,106 +1029,433 @@ impl TerminalRenderer {
                 }
             }
 
-            // Check for input events (non-blocking)
-            if event::poll(Duration::from_millis(1))? {
-                // ~60 FPS input polling
-                if let Event::Key(key_event) = event::read()? {
+            // Check for input events, draining everything queued this iteration. Under --max-fps
+            // the wait for the first event lasts until the next frame is due
+            let mut poll_timeout = if self.options.low_latency {
+                Duration::ZERO
+            } else {
+                pacer.time_until_next().max(Duration::from_millis(1))
+            };
+            while event::poll(poll_timeout)? {
+                poll_timeout = Duration::ZERO;
+                let event = event::read()?;
+                self.redraw = true;
+                if let Event::Resize(..) = event {
+                    // AIDEV-NOTE: Resize events also arrive from Windows consoles via crossterm.
+                    // Restart the debounce window on every event in a burst
+                    self.pending_resize_since = Some(Instant::now());
+                }
+                if let Event::Mouse(mouse_event) = event {
+                    // AIDEV-NOTE: The right button selects a region for the zoom inspector; corners
+                    // are clamped to the render so the drag can leave it
+                    let column = (mouse_event.column as u32)
+                        .clamp(self.x_offset, self.x_offset + self.width - 1)
+                        as u16;
+                    let cell = (column, mouse_event.row.min(self.height as u16 - 1));
+                    match mouse_event.kind {
+                        MouseEventKind::Down(MouseButton::Right) => {
+                            self.zoom_selection = Some([cell, cell]);
+                            continue;
+                        }
+                        MouseEventKind::Drag(MouseButton::Right) => {
+                            if let Some(selection) = &mut self.zoom_selection {
+                                selection[1] = cell;
+                            }
+                            continue;
+                        }
+                        MouseEventKind::Up(MouseButton::Right) => {
+                            self.finish_zoom_selection(&shared_uniforms);
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    let Some(position) = self.mouse_to_pixel(mouse_event.column, mouse_event.row)
+                    else {
+                        continue;
+                    };
+                    let mut uniforms = shared_uniforms.lock().unwrap();
+                    match mouse_event.kind {
+                        MouseEventKind::Down(MouseButton::Left) => uniforms.mouse_press(position),
+                        MouseEventKind::Drag(MouseButton::Left) => uniforms.mouse_drag(position),
+                        MouseEventKind::Up(MouseButton::Left) => uniforms.mouse_release(),
+                        _ => {}
+                    }
+                }
+                if let Event::Key(key_event) = event {
+                    if self.options.screensaver && key_event.kind != KeyEventKind::Release {
+                        send_error(&[&error_sender], ThreadError::Shutdown);
+                        break 'render;
+                    }
+                    // AIDEV-NOTE: In --edit mode the editor gets every key except a few Ctrl chords
+                    if let Some(editor) = &mut self.editor {
+                        if key_event.kind == KeyEventKind::Release {
+                            continue;
+                        }
+                        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+                        match key_event.code {
+                            KeyCode::Char('c') | KeyCode::Char('q') if ctrl => {
+                                send_error(&[&error_sender], ThreadError::Shutdown);
+                                break 'render;
+                            }
+                            KeyCode::Char('p') if ctrl => {
+                                let current_time = start_time.elapsed().as_secs_f32();
+                                let mut uniforms = shared_uniforms.lock().unwrap();
+                                uniforms.toggle_pause(current_time);
+                            }
+                            KeyCode::Char('r') if ctrl => self.toggle_recording(),
+                            _ => {
+                                editor.handle_key(key_event, self.height as usize);
+                            }
+                        }
+                        continue;
+                    }
+                    // AIDEV-NOTE: Windows consoles also report key releases, so every action key
+                    // checks `pressed` (presses and repeats) to run once per keypress
+                    let pressed = key_event.kind != KeyEventKind::Release;
                     match key_event.code {
-                        KeyCode::Char('q') | KeyCode::Char('Q') => {
-                            let _ = error_sender.send(ThreadError::Shutdown);
-                            break;
+                        KeyCode::Char('q') | KeyCode::Char('Q') if pressed => {
+                            send_error(&[&error_sender], ThreadError::Shutdown);
+                            break 'render;
                         }
                         KeyCode::Char('c')
-                            if key_event.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                            if pressed
+                                && key_event.modifiers.contains(event::KeyModifiers::CONTROL) =>
                         {
-                            let _ = error_sender.send(ThreadError::Shutdown);
-                            break;
+                            send_error(&[&error_sender], ThreadError::Shutdown);
+                            break 'render;
+                        }
+                        // AIDEV-NOTE: Tab opens the `// @uniform` panel, where the arrows pick and
+                        // change parameters instead of moving the cursor (Shift for ten steps).
+                        // +/- change the selected parameter either way, outside the panel with the
+                        // value shown as a notice
+                        KeyCode::Tab | KeyCode::Esc if self.param_panel && pressed => {
+                            self.param_panel = false;
+                        }
+                        // Escape cancels a zoom selection or leaves the zoomed view
+                        KeyCode::Esc if pressed => {
+                            let cancelled = self.zoom_selection.take().is_some();
+                            let mut uniforms = shared_uniforms.lock().unwrap();
+                            if !cancelled && uniforms.zoom.is_some() {
+                                uniforms.set_zoom(None);
+                            }
+                        }
+                        KeyCode::Tab if pressed => {
+                            if shared_uniforms.lock().unwrap().param_values.is_empty() {
+                                let message = msg!("notice.no_params");
+                                self.notice = Some((message, Instant::now()));
+                            } else {
+                                self.param_panel = true;
+                            }
+                        }
+                        KeyCode::Up | KeyCode::Down if self.param_panel && pressed => {
+                            let mut uniforms = shared_uniforms.lock().unwrap();
+                            if key_event.code == KeyCode::Up {
+                                uniforms.select_previous_param();
+                            } else {
+                                uniforms.select_next_param();
+                            }
                         }
-                        KeyCode::Up => {
+                        KeyCode::Left | KeyCode::Right if self.param_panel && pressed => {
+                            let steps = if key_event.modifiers.contains(KeyModifiers::SHIFT) {
+                                10
+                            } else {
+                                1
+                            };
+                            let direction = if key_event.code == KeyCode::Left {
+                                -1
+                            } else {
+                                1
+                            };
+                            shared_uniforms
+                                .lock()
+                                .unwrap()
+                                .adjust_param(direction * steps);
+                        }
+                        KeyCode::Char('+' | '=' | '-') if pressed => {
+                            let direction = if key_event.code == KeyCode::Char('-') {
+                                -1
+                            } else {
+                                1
+                            };
+                            let label = shared_uniforms.lock().unwrap().adjust_param(direction);
+                            if let Some(label) = label.filter(|_| !self.param_panel) {
+                                self.notice = Some((label, Instant::now()));
+                            }
+                        }
+                        KeyCode::Up if !self.param_panel && pressed => {
                             let mut uniforms = shared_uniforms.lock().unwrap();
                             // AIDEV-NOTE: Flip Y movement to match window renderer (Y=0 at bottom)
                             uniforms.move_cursor(0, 1);
                         }
-                        KeyCode::Down => {
+                        KeyCode::Down if !self.param_panel && pressed => {
                             let mut uniforms = shared_uniforms.lock().unwrap();
                             // AIDEV-NOTE: Flip Y movement to match window renderer (Y=0 at bottom)
                             uniforms.move_cursor(0, -1);
                         }
-                        KeyCode::Left => {
+                        KeyCode::Left if !self.param_panel && pressed => {
                             let mut uniforms = shared_uniforms.lock().unwrap();
                             uniforms.move_cursor(-1, 0);
                         }
-                        KeyCode::Right => {
+                        KeyCode::Right if !self.param_panel && pressed => {
                             let mut uniforms = shared_uniforms.lock().unwrap();
                             uniforms.move_cursor(1, 0);
                         }
-                        KeyCode::Char(' ') => {
+                        KeyCode::Char(' ') if pressed => {
                             let current_time = start_time.elapsed().as_secs_f32();
                             let mut uniforms = shared_uniforms.lock().unwrap();
                             uniforms.toggle_pause(current_time);
                         }
+                        KeyCode::Char(',') | KeyCode::Char('.') if pressed => {
+                            let current_time = start_time.elapsed().as_secs_f32();
+                            let frames = if key_event.code == KeyCode::Char('.') {
+                                1
+                            } else {
+                                -1
+                            };
+                            let mut uniforms = shared_uniforms.lock().unwrap();
+                            uniforms.step_frames(frames, current_time);
+                        }
+                        KeyCode::Char('[') | KeyCode::Char(']') if pressed => {
+                            let current_time = start_time.elapsed().as_secs_f32();
+                            let mut uniforms = shared_uniforms.lock().unwrap();
+                            let faster = key_event.code == KeyCode::Char(']');
+                            let time_scale = step_time_scale(uniforms.time_scale, faster);
+                            uniforms.set_time_scale(time_scale, current_time);
+                            let message = msg!("notice.time_scale", scale = time_scale);
+                            self.notice = Some((message, Instant::now()));
+                        }
+                        KeyCode::Char('0') if pressed => {
+                            let current_time = start_time.elapsed().as_secs_f32();
+                            shared_uniforms.lock().unwrap().reset_clock(current_time);
+                        }
+                        KeyCode::Char('s') | KeyCode::Char('S') if pressed => {
+                            let enabled = shared_uniforms.lock().unwrap().toggle_supersample();
+                            let message = if enabled {
+                                msg!("notice.supersampling_on")
+                            } else {
+                                msg!("notice.supersampling_off")
+                            };
+                            self.notice = Some((message, Instant::now()));
+                        }
+                        KeyCode::Char('t') | KeyCode::Char('T') if pressed => {
+                            let enabled = shared_uniforms.lock().unwrap().toggle_reset_on_reload();
+                            let message = if enabled {
+                                msg!("notice.reset_on_reload_on")
+                            } else {
+                                msg!("notice.reset_on_reload_off")
+                            };
+                            self.notice = Some((message, Instant::now()));
+                        }
+                        KeyCode::Char('e') | KeyCode::Char('E') if pressed => {
+                            self.open_external_editor(&shader_file)?;
+                        }
+                        KeyCode::Char('w') | KeyCode::Char('W') if pressed => {
+                            self.toggle_mirror_window(&shader_file);
+                        }
+                        KeyCode::Char('g') | KeyCode::Char('G') if pressed => {
+                            shared_uniforms.lock().unwrap().request_restart();
+                        }
+                        KeyCode::Char('r') | KeyCode::Char('R')
+                            if key_event.kind == KeyEventKind::Press =>
+                        {
+                            self.toggle_recording();
+                        }
                         _ => {}
                     }
                 }
             }
 
+            // Apply a settled resize, re-querying the size since multiplexers may report it late
+            if self
+                .pending_resize_since
+                .is_some_and(|since| since.elapsed() >= self.options.resize_debounce)
+            {
+                self.pending_resize_since = None;
+                let (columns, rows) = crossterm_terminal::size()?;
+                (self.x_offset, self.width) = Self::layout(columns, self.options.edit);
+                self.height = rows as u32;
+                if let Some(editor) = &mut self.editor {
+                    editor.invalidate();
+                }
+                self.displayed_error = None;
+                self.cell_writer.invalidate();
+                self.redraw = true;
+                execute!(stdout, Clear(ClearType::All))?;
+                shared_uniforms
+                    .lock()
+                    .unwrap()
+                    .request_resize(self.width * pixels_x, self.height * pixels_y);
+            }
+
             // Check for thread errors
             // This is handled by the main thread coordination
 
-            // If we're in an error state, display error only if it changed
-            if let Some(ref error_msg) = self.error_state {
+            // Updated before the error screen skips the rest of the loop, so it reports no frames drawn
+            if let Some(event_stats) = &mut event_stats {
+                let (frames_dropped, gpu_fps) = {
+                    let buffer = frame_buffer.lock().unwrap();
+                    (
+                        buffer.get_frames_dropped(),
+                        buffer.telemetry.frames_per_second(),
+                    )
+                };
+                event_stats.update(frames_dropped, gpu_fps);
+            }
+
+            // The editor shows errors in its status line and keeps the last good frame on screen
+            if let Some(editor) = &mut self.editor {
+                editor.set_error(self.error_state.clone());
+            } else if let Some(ref error_msg) = self.error_state {
                 // Only redraw if this is a new error or we haven't displayed it yet
                 if self.displayed_error.as_ref() != Some(error_msg) {
                     execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
-                    stdout.write_all(format!("{error_msg}\nPress 'q' to quit").as_bytes())?;
+                    self.cell_writer.invalidate();
+                    // Raw mode doesn't return the cursor on a bare newline
+                    let error_msg = error_msg.replace('\n', "\r\n");
+                    let quit_hint = msg!("error.quit_hint");
+                    stdout.write_all(format!("{error_msg}\r\n{quit_hint}").as_bytes())?;
                     stdout.flush()?;
                     self.displayed_error = Some(error_msg.clone());
                 }
                 std::thread::sleep(Duration::from_millis(16));
                 continue;
-            } else {
-                // Clear displayed error when we exit error state
-                self.displayed_error = None;
+            } else if self.displayed_error.take().is_some() {
+                // Draw the render over the error once we exit error state
+                self.redraw = true;
             }
 
-            // Update from latest GPU frame and render full screen
-            if let Some(frame_data) = {
-                let mut buffer = frame_buffer.lock().unwrap();
-                buffer.read_frame()
-            } {
+            // In low-latency mode, keep the current screen until a frame reflects the latest input
+            let min_input_generation = if self.options.low_latency {
+                shared_uniforms.lock().unwrap().input_generation
+            } else {
+                0
+            };
+
+            // Update from latest GPU frame and render full screen, once the next frame is due
+            if pacer.is_due() {
+                if let Some(frame_data) = frame_buffer.lock().unwrap().read_frame() {
+                    self.last_frame = Some(frame_data);
+                    self.redraw = true;
+                }
+            }
+            if let Some(frame_data) = self.last_frame.take_if(|frame_data| {
+                self.redraw
+                    && pacer.is_due()
+                    && frame_data.width == self.width * self.options.pixels_per_cell().0
+                    && frame_data.input_generation >= min_input_generation
+            }) {
                 // Build complete screen content directly from GPU data
-                let screen_content = self.build_full_screen_from_gpu_data(
+                let mut screen_content = std::mem::take(&mut self.screen_buffer);
+                screen_content.clear();
+                self.build_full_screen_from_gpu_data(
+                    &mut screen_content,
                     &frame_data,
                     &performance_tracker,
                     &frame_buffer,
                 );
+                if self.param_panel {
+                    let values = shared_uniforms.lock().unwrap().param_values.clone();
+                    // A reload may have removed every parameter
+                    self.param_panel = !values.is_empty();
+                    let top = performance_tracker.is_some() as u32;
+                    self.push_param_panel(&mut screen_content, &values, top);
+                }
+                if self.options.low_power {
+                    self.push_telemetry(&mut screen_content, &frame_buffer);
+                    self.cell_writer.invalidate_row(self.height as usize - 1);
+                }
+                self.push_zoom_selection(&mut screen_content);
+                // Markers are drawn again until they go away (notices expire, compiling ends)
+                self.redraw = self.push_status_marker(&mut screen_content);
+                if self.redraw {
+                    self.cell_writer.invalidate_row(0);
+                }
+                // The next frame redraws whatever the overlays covered
+                if self.param_panel || self.zoom_selection.is_some() {
+                    self.cell_writer.invalidate();
+                }
+                self.push_editor(&mut screen_content);
 
                 // Single write operation for the entire screen
                 execute!(stdout, MoveTo(0, 0))?;
                 stdout.write_all(screen_content.as_bytes())?;
                 stdout.flush()?;
+                self.screen_buffer = screen_content;
+
+                if let Some(recorder) = self.recorder.as_mut().filter(|r| r.wants_frame()) {
+                    let height = frame_data.gpu_data.pixel_count() as u32 / frame_data.width;
+                    recorder.push_frame(
+                        frame_data.gpu_data.to_image(frame_data.width, height),
+                        frame_data.time,
+                        frame_data.frame,
+                    );
+                }
+
+                // First time a frame reflecting new input reaches the screen
+                if frame_data.input_generation > self.measured_input_generation {
+                    self.measured_input_generation = frame_data.input_generation;
+                    self.input_latency = frame_data.input_at.map(|input_at| input_at.elapsed());
+                }
 
                 // Record terminal frame for performance tracking
                 if let Some(ref tracker) = performance_tracker {
                     let mut perf = tracker.lock().unwrap();
                     perf.record_terminal_frame();
                 }
+                if let Some(adaptive) = &mut adaptive {
+                    adaptive.record_frame();
+                }
+                if let Some(event_stats) = &mut event_stats {
+                    event_stats.record_frame();
+                }
+                pacer.advance();
+                self.last_frame = Some(frame_data);
             }
 
-            // Apply FPS limiting if max_fps is specified
-            if let Some(target_frame_time) = frame_time {
-                let elapsed = last_frame_time.elapsed();
-                if elapsed < target_frame_time {
-                    std::thread::sleep(target_frame_time - elapsed);
+            if let Some(adaptive) = &mut adaptive {
+                let (frames_dropped, gpu_fps) = {
+                    let buffer = frame_buffer.lock().unwrap();
+                    (
+                        buffer.get_frames_dropped(),
+                        buffer.telemetry.frames_per_second(),
+                    )
+                };
+                if let Some(scale) = adaptive.update(frames_dropped, gpu_fps) {
+                    shared_uniforms.lock().unwrap().set_adaptive_scale(scale);
+                    let message = if scale < 1.0 {
+                        msg!("notice.adaptive_reduced")
+                    } else {
+                        msg!("notice.adaptive_restored")
+                    };
+                    self.notice = Some((message, Instant::now()));
                 }
-                last_frame_time = Instant::now();
+            }
+
+            // Keep typing responsive even when no new frame arrives (e.g. while paused)
+            if self.editor.as_ref().is_some_and(EditorPane::needs_redraw) {
+                let mut screen_content = String::new();
+                self.push_editor(&mut screen_content);
+                stdout.write_all(screen_content.as_bytes())?;
+                stdout.flush()?;
             }
         }
 
+        // Quitting mid-recording still saves it, even when the terminal is already gone
+        let recording = self.recorder.take().map(Recorder::finish);
+
         // Cleanup
-        execute!(stdout, Show, LeaveAlternateScreen)?;
+        if let Some(image_output) = &self.options.image_output {
+            stdout.write_all(image_output.cleanup().as_bytes())?;
+        }
+        execute!(stdout, DisableMouseCapture, Show, LeaveAlternateScreen)?;
         crossterm_terminal::disable_raw_mode()?;
 
+        match recording {
+            Some(Ok(path)) => println!("{}", msg!("recording.saved", path = path.display())),
+            Some(Err(e)) => eprintln!("{}", msg!("recording.failed", error = e)),
+            None => {}
+        }
+
         Ok(())
     }
 }
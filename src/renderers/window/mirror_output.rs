@@ -0,0 +1,200 @@
+use wgpu;
+use wgpu::util::DeviceExt;
+
+use crate::error::ShadertuiError;
+use crate::gpu::GpuDevice;
+
+use super::SurfaceManager;
+
+const MIRROR_DISPLAY_SHADER: &str = include_str!("../../shaders/mirror_display.wgsl");
+
+// AIDEV-NOTE: --mirror draws every frame the terminal's GPU thread computes into a window too. The
+// window's surface shares the terminal's device, so the display pass reads the output buffer the
+// readback copies from, and each frame is computed once for both. Presenting happens on the GPU
+// thread; the main thread only owns the winit window and passes on its size
+pub struct MirrorOutput {
+    surface_manager: SurfaceManager,
+    // Built by set_device() for the device the renderer creates on adapter()
+    display: Option<MirrorDisplay>,
+    width: u32,
+    height: u32,
+}
+
+struct MirrorDisplay {
+    pipeline: wgpu::RenderPipeline,
+    // Output size and window size for the display shader
+    frame_buffer: wgpu::Buffer,
+}
+
+impl MirrorOutput {
+    // `adapter` has to be able to present to `surface` (see surface_adapter)
+    pub fn new(
+        surface: wgpu::Surface<'static>,
+        adapter: wgpu::Adapter,
+        window_size: (u32, u32),
+    ) -> Result<Self, ShadertuiError> {
+        if !adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::FRAGMENT_STORAGE)
+        {
+            let name = adapter.get_info().name;
+            return Err(ShadertuiError::Surface(format!(
+                "{name} can't read storage buffers when drawing, which --mirror needs"
+            )));
+        }
+        let (width, height) = window_size;
+        Ok(Self {
+            surface_manager: SurfaceManager::new(surface, adapter),
+            display: None,
+            width,
+            height,
+        })
+    }
+
+    fn create_display(surface_manager: &SurfaceManager, gpu_device: &GpuDevice) -> MirrorDisplay {
+        let device = &gpu_device.device;
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mirror Shader"),
+            source: wgpu::ShaderSource::Wgsl(MIRROR_DISPLAY_SHADER.into()),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mirror Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_manager.get_optimal_format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let frame_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mirror Frame Buffer"),
+            contents: &[0; 16],
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        MirrorDisplay {
+            pipeline,
+            frame_buffer,
+        }
+    }
+
+    pub fn adapter(&self) -> &wgpu::Adapter {
+        self.surface_manager.adapter()
+    }
+
+    // Whenever the renderer gets a device on adapter(), the first one included
+    pub fn set_device(&mut self, gpu_device: &GpuDevice) {
+        self.display = Some(Self::create_display(&self.surface_manager, gpu_device));
+        self.surface_manager
+            .configure(&gpu_device.device, self.width, self.height);
+    }
+
+    pub fn resize(&mut self, gpu_device: &GpuDevice, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        self.surface_manager
+            .configure(&gpu_device.device, width, height);
+    }
+
+    // Draw `output` (`width` x `height` RGBA floats, see HeadlessRenderer::frame_output). Frames
+    // the surface can't take right now are skipped like the window's
+    pub fn present(
+        &self,
+        gpu_device: &GpuDevice,
+        output: &wgpu::Buffer,
+        width: u32,
+        height: u32,
+    ) -> Result<(), ShadertuiError> {
+        let Some(display) = &self.display else {
+            return Ok(());
+        };
+        let surface_texture = match self.surface_manager.get_current_texture() {
+            Ok(surface_texture) => surface_texture,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface_manager
+                    .configure(&gpu_device.device, self.width, self.height);
+                return Ok(());
+            }
+            Err(wgpu::SurfaceError::Timeout) => return Ok(()),
+            Err(e) => return Err(ShadertuiError::Surface(e.to_string())),
+        };
+        let view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let frame: [u32; 4] = [
+            width,
+            height,
+            (self.width as f32).to_bits(),
+            (self.height as f32).to_bits(),
+        ];
+        gpu_device
+            .queue
+            .write_buffer(&display.frame_buffer, 0, bytemuck::cast_slice(&frame));
+        // Output buffers change with resizes and reloads, so the bind group is made per frame
+        let bind_group = gpu_device
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mirror Bind Group"),
+                layout: &display.pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: output.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: display.frame_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        let mut encoder =
+            gpu_device
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Mirror Encoder"),
+                });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mirror Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&display.pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        gpu_device.queue.submit(Some(encoder.finish()));
+        surface_texture.present();
+        Ok(())
+    }
+}
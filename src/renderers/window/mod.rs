@@ -1,8 +1,10 @@
+pub mod mirror_output;
 pub mod pipeline;
 pub mod resources;
 pub mod state;
 pub mod surfaces;
 
+pub use mirror_output::MirrorOutput;
 pub use pipeline::PipelineFactory;
 pub use resources::GpuResourceManager;
 pub use state::WindowState;
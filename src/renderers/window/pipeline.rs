@@ -1,4 +1,9 @@
-use crate::utils::shader_shell::{get_window_display_shader, inject_user_shader, ShellType};
+use crate::error::ShadertuiError;
+use crate::gpu::{state_layout_entry, Accumulation};
+use crate::utils::project::ShaderProject;
+use crate::utils::shader_shell::{
+    get_window_display_shader, inject_user_shader, ShellOptions, ShellType,
+};
 use wgpu;
 
 // AIDEV-NOTE: Extracted pipeline creation from WindowRenderer for better organization
@@ -8,7 +13,10 @@ impl PipelineFactory {
     pub fn create_compute_pipeline(
         device: &wgpu::Device,
         shader_source: &str,
-    ) -> Result<(wgpu::ComputePipeline, wgpu::BindGroupLayout), Box<dyn std::error::Error>> {
+        channel_layout: Option<&wgpu::BindGroupLayout>,
+        with_state: bool,
+        with_accumulation: bool,
+    ) -> Result<(wgpu::ComputePipeline, wgpu::BindGroupLayout), ShadertuiError> {
         // Create shader module
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Compute Shader"),
@@ -16,38 +24,45 @@ impl PipelineFactory {
         });
 
         // Create bind group layout
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Compute Bind Group Layout"),
-            entries: &[
-                // Storage texture for output
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::StorageTexture {
-                        access: wgpu::StorageTextureAccess::WriteOnly,
-                        format: wgpu::TextureFormat::Rgba8Unorm,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                    },
-                    count: None,
+        let mut entries = vec![
+            // Storage texture for output
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    view_dimension: wgpu::TextureViewDimension::D2,
                 },
-                // Uniform buffer
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
+                count: None,
+            },
+            // Uniform buffer
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
                 },
-            ],
+                count: None,
+            },
+        ];
+        entries.extend(with_state.then(state_layout_entry));
+        if with_accumulation {
+            entries.extend(Accumulation::layout_entries());
+        }
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Compute Bind Group Layout"),
+            entries: &entries,
         });
 
-        // Create pipeline layout
+        // Create pipeline layout, with multi-pass channels in group 1 when present
+        let mut bind_group_layouts = vec![&bind_group_layout];
+        bind_group_layouts.extend(channel_layout);
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Compute Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
+            bind_group_layouts: &bind_group_layouts,
             push_constant_ranges: &[],
         });
 
@@ -67,7 +82,7 @@ impl PipelineFactory {
     pub fn create_render_pipeline(
         device: &wgpu::Device,
         surface_format: wgpu::TextureFormat,
-    ) -> Result<(wgpu::RenderPipeline, wgpu::BindGroupLayout), Box<dyn std::error::Error>> {
+    ) -> Result<(wgpu::RenderPipeline, wgpu::BindGroupLayout), ShadertuiError> {
         // Use the window display shader from template file
         let shader_source = get_window_display_shader();
 
@@ -149,9 +164,24 @@ impl PipelineFactory {
 
     pub fn create_compute_pipeline_with_user_shader(
         device: &wgpu::Device,
-        user_shader_source: &str,
-    ) -> Result<(wgpu::ComputePipeline, wgpu::BindGroupLayout), Box<dyn std::error::Error>> {
-        let complete_shader = inject_user_shader(user_shader_source, ShellType::Window)?;
-        Self::create_compute_pipeline(device, &complete_shader)
+        project: &ShaderProject,
+        shell_options: &ShellOptions,
+        channel_layout: Option<&wgpu::BindGroupLayout>,
+    ) -> Result<(wgpu::ComputePipeline, wgpu::BindGroupLayout), ShadertuiError> {
+        let output_pass = &project.output;
+        let output_options = ShellOptions {
+            channel_count: output_pass.inputs.len(),
+            params: project.params.clone(),
+            ..shell_options.clone()
+        };
+        let complete_shader =
+            inject_user_shader(&output_pass.source, ShellType::Window, &output_options)?;
+        Self::create_compute_pipeline(
+            device,
+            &complete_shader,
+            channel_layout,
+            shell_options.state_size.is_some(),
+            shell_options.accumulate,
+        )
     }
 }
@@ -1,4 +1,4 @@
-use crate::gpu::UniformBuffer;
+use crate::gpu::{state_bind_group_entry, Accumulation, UniformBuffer};
 use std::sync::Arc;
 use wgpu;
 
@@ -24,7 +24,10 @@ impl GpuResourceManager {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            // COPY_SRC for reading frames back while recording
+            usage: wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         })
     }
@@ -47,20 +50,27 @@ impl GpuResourceManager {
         layout: &wgpu::BindGroupLayout,
         storage_texture_view: &wgpu::TextureView,
         uniform_buffer: &UniformBuffer,
+        state: Option<&wgpu::Buffer>,
+        accumulation: Option<&Accumulation>,
     ) -> wgpu::BindGroup {
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(storage_texture_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: uniform_buffer.buffer.as_entire_binding(),
+            },
+        ];
+        entries.extend(state.map(state_bind_group_entry));
+        if let Some(accumulation) = accumulation {
+            entries.extend(accumulation.bind_group_entries());
+        }
         self.device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Compute Bind Group"),
             layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(storage_texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: uniform_buffer.buffer.as_entire_binding(),
-                },
-            ],
+            entries: &entries,
         })
     }
 
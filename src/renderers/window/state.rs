@@ -1,64 +1,173 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use crate::gpu::MouseState;
+use crate::utils::frame_pacing::{DeltaSmoother, DeltaSmoothing};
+use crate::utils::timeline::FRAME_STEP;
+use crate::utils::uniform_params::{ParamValues, UniformParam};
 
 // AIDEV-NOTE: Extracted window state management from WindowRenderer for better organization
 pub struct WindowState {
     pub cursor_position: [f32; 2],
+    pub mouse: MouseState,
+    pub param_values: ParamValues,
     pub is_paused: bool,
     pub paused_time: f32,
     pub frame_count: u32,
+    // `uniforms.time` of the last rendered frame (frame_count is its frame)
+    pub rendered_time: f32,
+    // Frames averaged into the output since the last restart (--accumulate)
+    pub sample_index: u32,
     pub start_time: Instant,
+    // Speed of the clock, see utils::timeline
+    pub time_scale: f32,
     pub last_frame_time: Instant,
+    delta_smoother: DeltaSmoother,
+    // Seconds after which the shader's time wraps around (--loop)
+    pub loop_duration: Option<f32>,
 }
 
 impl WindowState {
-    pub fn new() -> Self {
+    pub fn new(
+        params: &[UniformParam],
+        loop_duration: Option<f32>,
+        delta_smoothing: DeltaSmoothing,
+    ) -> Self {
         let now = Instant::now();
         Self {
             cursor_position: [0.0, 0.0],
+            mouse: MouseState::default(),
+            param_values: ParamValues::new(params),
             is_paused: false,
             paused_time: 0.0,
             frame_count: 0,
+            rendered_time: 0.0,
+            sample_index: 0,
             start_time: now,
+            time_scale: 1.0,
             last_frame_time: now,
+            delta_smoother: DeltaSmoother::new(delta_smoothing),
+            loop_duration,
         }
     }
 
     // AIDEV-NOTE: Public methods for controlling renderer state from event loop
     pub fn update_cursor_position(&mut self, x: f32, y: f32, height: u32) {
         // Store cursor in pixel coordinates, flipping Y axis (window Y=0 at top, shader Y=0 at bottom)
-        self.cursor_position = [x, height as f32 - y];
+        let cursor_position = [x, height as f32 - y];
+        if cursor_position != self.cursor_position {
+            self.restart_accumulation();
+        }
+        self.cursor_position = cursor_position;
+        self.mouse.drag(cursor_position);
         // println!(
         //     "Updated cursor: ({x:.3}, {y:.3}) -> flipped: ({:.3}, {:.3})",
         //     self.cursor_position[0], self.cursor_position[1]
         // );
     }
 
+    // AIDEV-NOTE: The scene changed (cursor, size, shader), so the average starts over
+    pub fn restart_accumulation(&mut self) {
+        self.sample_index = 0;
+    }
+
+    pub fn next_sample_index(&mut self) -> u32 {
+        let sample_index = self.sample_index;
+        self.sample_index = self.sample_index.saturating_add(1);
+        sample_index
+    }
+
     pub fn toggle_pause(&mut self) {
         if self.is_paused {
-            // Resume: adjust start time to account for pause duration
-            let pause_duration = Instant::now().duration_since(self.last_frame_time);
-            self.start_time += pause_duration;
+            // Resume: move the start so the clock continues from the paused time
+            self.set_elapsed(self.paused_time);
             self.is_paused = false;
         } else {
             // Pause: store current time
-            self.paused_time = Instant::now().duration_since(self.start_time).as_secs_f32();
+            self.paused_time = self.elapsed();
             self.is_paused = true;
         }
     }
 
-    pub fn get_current_time(&self) -> f32 {
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused != self.is_paused {
+            self.toggle_pause();
+        }
+    }
+
+    // Move the clock by `seconds`, never below zero
+    pub fn seek(&mut self, seconds: f32) {
         if self.is_paused {
+            self.paused_time = (self.paused_time + seconds).max(0.0);
+        } else {
+            self.set_elapsed(self.elapsed() + seconds);
+        }
+    }
+
+    // Set the clock to `seconds`, keeping it paused if it was
+    pub fn set_time(&mut self, seconds: f32) {
+        if self.is_paused {
+            self.paused_time = seconds.max(0.0);
+        } else {
+            self.set_elapsed(seconds);
+        }
+    }
+
+    // Pause and move `frames` frames of timeline::FRAME_STEP
+    pub fn step_frames(&mut self, frames: i32) {
+        self.set_paused(true);
+        self.seek(frames as f32 * FRAME_STEP);
+    }
+
+    // The time stays where it is and continues at the new speed
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        let elapsed = self.elapsed();
+        self.time_scale = time_scale;
+        if !self.is_paused {
+            self.set_elapsed(elapsed);
+        }
+    }
+
+    // Back to time 0 and frame 0, keeping the clock paused if it was
+    pub fn reset_clock(&mut self) {
+        self.frame_count = 0;
+        if self.is_paused {
+            self.paused_time = 0.0;
+        } else {
+            self.set_elapsed(0.0);
+        }
+    }
+
+    // Seconds on the running clock, before --loop wraps them
+    fn elapsed(&self) -> f32 {
+        Instant::now().duration_since(self.start_time).as_secs_f32() * self.time_scale
+    }
+
+    fn set_elapsed(&mut self, seconds: f32) {
+        let now = Instant::now();
+        self.start_time = now
+            .checked_sub(Duration::from_secs_f32(seconds.max(0.0) / self.time_scale))
+            .unwrap_or(now);
+    }
+
+    pub fn get_current_time(&self) -> f32 {
+        let time = if self.is_paused {
             self.paused_time
         } else {
-            Instant::now().duration_since(self.start_time).as_secs_f32()
+            self.elapsed()
+        };
+        match self.loop_duration {
+            Some(loop_duration) => time % loop_duration,
+            None => time,
         }
     }
 
     pub fn update_frame_timing(&mut self) -> f32 {
         let current_time = Instant::now();
-        let delta_time = current_time
-            .duration_since(self.last_frame_time)
-            .as_secs_f32();
+        let delta_time = self.delta_smoother.smooth(
+            current_time
+                .duration_since(self.last_frame_time)
+                .as_secs_f32(),
+        );
         self.last_frame_time = current_time;
         self.frame_count += 1;
         delta_time
@@ -16,6 +16,10 @@ impl SurfaceManager {
         self.surface.configure(device, &surface_config);
     }
 
+    pub fn adapter(&self) -> &wgpu::Adapter {
+        &self.adapter
+    }
+
     pub fn get_optimal_format(&self) -> wgpu::TextureFormat {
         let surface_caps = self.surface.get_capabilities(&self.adapter);
         surface_caps
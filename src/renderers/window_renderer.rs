@@ -1,8 +1,21 @@
 use std::sync::Arc;
+use std::time::Duration;
 use wgpu;
 
-use crate::gpu::{GpuDevice, UniformBuffer, Uniforms};
-use crate::utils::threading::PerformanceTracker;
+use crate::error::ShadertuiError;
+use crate::gpu::{
+    create_state_buffer, pack_params, surface_adapter, Accumulation, GpuDevice, GpuTimer,
+    PassChain, UniformBuffer, Uniforms, IDENTITY_ZOOM,
+};
+use crate::renderers::headless_renderer::DEFAULT_GPU_TIMEOUT;
+use crate::utils::antialias::scaled_size;
+use crate::utils::frame_pacing::DeltaSmoothing;
+use crate::utils::image_export::RgbaImage;
+use crate::utils::project::ShaderProject;
+use crate::utils::shader_shell::ShellOptions;
+use crate::utils::threading::{FrameTimeStats, PerformanceTracker};
+use crate::utils::timeline::step_time_scale;
+use crate::utils::uniform_params::{UniformParam, MAX_PARAMS};
 
 use super::window::{GpuResourceManager, PipelineFactory, SurfaceManager, WindowState};
 
@@ -12,10 +25,16 @@ pub struct WindowRenderer {
     resource_manager: GpuResourceManager,
 
     // Compute stage: user's shader writes to storage texture
+    storage_texture: wgpu::Texture,
     compute_pipeline: wgpu::ComputePipeline,
     compute_bind_group: wgpu::BindGroup,
     compute_bind_group_layout: wgpu::BindGroupLayout,
     uniform_buffer: UniformBuffer,
+    pass_chain: PassChain,
+    state_buffer: Option<wgpu::Buffer>,
+    accumulation: Option<Accumulation>,
+    // `// @uniform` parameters of the compiled shader, valued from state.param_values
+    params: Vec<UniformParam>,
 
     // Render stage: simple fragment shader samples from storage texture
     render_pipeline: wgpu::RenderPipeline,
@@ -26,25 +45,45 @@ pub struct WindowRenderer {
     state: WindowState,
     width: u32,
     height: u32,
+    // Fraction of the window size rendered, stretched back up by the display pass (--scale)
+    scale: f32,
 
-    // Performance tracking
+    // Performance tracking, with GPU time per frame where timestamp queries are supported
     performance_tracker: Option<PerformanceTracker>,
+    timer: Option<GpuTimer>,
+    gpu_time: Option<Duration>,
+
+    shell_options: ShellOptions,
+}
+
+// AIDEV-NOTE: What a renderer on a new device takes over from one whose device was lost: the
+// clock, cursor and parameter values, and textures swapped in with `shadertui ctl texture`
+pub struct RetainedState {
+    state: WindowState,
+    shell_options: ShellOptions,
+}
+
+impl RetainedState {
+    pub fn shell_options(&self) -> ShellOptions {
+        self.shell_options.clone()
+    }
 }
 
 impl WindowRenderer {
+    #[expect(clippy::too_many_arguments)]
     pub fn new(
         instance: wgpu::Instance,
         surface: wgpu::Surface<'static>,
         window_size: (u32, u32),
-        shader_source: &str,
+        project: &ShaderProject,
+        shell_options: ShellOptions,
+        scale: f32,
+        loop_duration: Option<f32>,
+        delta_smoothing: DeltaSmoothing,
         enable_performance_tracking: bool,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+    ) -> Result<Self, ShadertuiError> {
         // Get adapter compatible with the surface
-        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        }))?;
+        let adapter = surface_adapter(&instance, &surface)?;
 
         // Create device and queue
         let (device, queue) =
@@ -56,9 +95,10 @@ impl WindowRenderer {
                 trace: Default::default(),
             }))?;
 
-        let gpu_device = GpuDevice { device, queue };
+        let gpu_device = GpuDevice::from_device(device, queue);
         let width = window_size.0;
         let height = window_size.1;
+        let (render_width, render_height) = scaled_size(width, height, scale);
 
         // Initialize utility managers
         let surface_manager = SurfaceManager::new(surface, adapter);
@@ -71,26 +111,47 @@ impl WindowRenderer {
         // Create uniform buffer
         let uniform_buffer = UniformBuffer::new(&gpu_device.device);
         let uniforms = Uniforms {
-            resolution: [width as f32, height as f32],
+            resolution: [render_width as f32, render_height as f32],
             cursor: [0.0, 0.0],
             time: 0.0,
             frame: 0,
             delta_time: 0.0,
-            _padding: 0.0,
+            sample_index: 0,
+            mouse: [0.0; 4],
+            zoom: IDENTITY_ZOOM,
+            subsamples: 1,
+            params: [0; MAX_PARAMS],
         };
         uniform_buffer.update(&gpu_device.queue, &uniforms);
 
         // Create GPU resources
-        let storage_texture = resource_manager.create_storage_texture(width, height);
+        let storage_texture = resource_manager.create_storage_texture(render_width, render_height);
         let storage_texture_view =
             storage_texture.create_view(&wgpu::TextureViewDescriptor::default());
         let sampler = resource_manager.create_sampler();
 
+        let state_buffer = create_state_buffer(&gpu_device.device, shell_options.state_size)?;
+        let accumulation = shell_options
+            .accumulate
+            .then(|| Accumulation::new(&gpu_device.device, render_width, render_height));
+
         // Create pipelines
+        let pass_chain = PassChain::new(
+            &gpu_device.device,
+            &gpu_device.queue,
+            gpu_device.pipeline_cache(),
+            project,
+            render_width,
+            render_height,
+            &shell_options,
+            state_buffer.as_ref(),
+        )?;
         let (compute_pipeline, compute_bind_group_layout) =
             PipelineFactory::create_compute_pipeline_with_user_shader(
                 &gpu_device.device,
-                shader_source,
+                project,
+                &shell_options,
+                pass_chain.output_channel_layout(),
             )?;
         let (render_pipeline, render_bind_group_layout) =
             PipelineFactory::create_render_pipeline(&gpu_device.device, surface_format)?;
@@ -100,6 +161,8 @@ impl WindowRenderer {
             &compute_bind_group_layout,
             &storage_texture_view,
             &uniform_buffer,
+            state_buffer.as_ref(),
+            accumulation.as_ref(),
         );
         let render_bind_group = resource_manager.create_render_bind_group(
             &render_bind_group_layout,
@@ -107,25 +170,38 @@ impl WindowRenderer {
             &sampler,
         );
 
+        let timer = enable_performance_tracking
+            .then(|| GpuTimer::new(&gpu_device.device, &gpu_device.queue))
+            .flatten();
+
         Ok(Self {
             surface_manager,
             resource_manager,
+            storage_texture,
             compute_pipeline,
             compute_bind_group,
             compute_bind_group_layout,
             uniform_buffer,
+            pass_chain,
+            state_buffer,
+            accumulation,
+            params: project.params.clone(),
             render_pipeline,
             render_bind_group,
             render_bind_group_layout,
             gpu_device,
-            state: WindowState::new(),
+            state: WindowState::new(&project.params, loop_duration, delta_smoothing),
             width,
             height,
+            scale,
             performance_tracker: if enable_performance_tracking {
                 Some(PerformanceTracker::new())
             } else {
                 None
             },
+            timer,
+            gpu_time: None,
+            shell_options,
         })
     }
 
@@ -134,29 +210,128 @@ impl WindowRenderer {
         self.state.update_cursor_position(x, y, self.height);
     }
 
+    pub fn set_mouse_button(&mut self, pressed: bool) {
+        self.state.restart_accumulation();
+        if pressed {
+            self.state.mouse.press(self.state.cursor_position);
+        } else {
+            self.state.mouse.release();
+        }
+    }
+
     pub fn toggle_pause(&mut self) {
         self.state.toggle_pause();
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn set_paused(&mut self, paused: bool) {
+        self.state.set_paused(paused);
+    }
+
+    pub fn seek(&mut self, seconds: f32) {
+        self.state.restart_accumulation();
+        self.state.seek(seconds);
+    }
+
+    pub fn set_time(&mut self, seconds: f32) {
+        self.state.restart_accumulation();
+        self.state.set_time(seconds);
+    }
+
+    pub fn step_frames(&mut self, frames: i32) {
+        self.state.restart_accumulation();
+        self.state.step_frames(frames);
+    }
+
+    // Halves or doubles the clock's speed and returns the new one
+    pub fn step_time_scale(&mut self, faster: bool) -> f32 {
+        let time_scale = step_time_scale(self.state.time_scale, faster);
+        self.state.set_time_scale(time_scale);
+        time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.state.set_time_scale(time_scale);
+    }
+
+    pub fn reset_clock(&mut self) {
+        self.state.restart_accumulation();
+        self.state.reset_clock();
+    }
+
+    // AIDEV-NOTE: Tab and +/- tweak `// @uniform` parameters; both return a label for the title
+    pub fn select_next_param(&mut self) -> Option<String> {
+        self.state.param_values.select_next()
+    }
+
+    pub fn adjust_param(&mut self, steps: i32) -> Option<String> {
+        self.state.restart_accumulation();
+        self.state.param_values.adjust(steps)
+    }
+
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        self.state.restart_accumulation();
+        self.state.param_values.set(name, value);
+    }
+
+    // AIDEV-NOTE: Rebind --texture number `index`; later reloads keep using the new image
+    pub fn set_texture(&mut self, index: usize, image: Arc<RgbaImage>) -> Result<(), String> {
+        self.pass_chain.set_image(
+            &self.gpu_device.device,
+            &self.gpu_device.queue,
+            index,
+            &image,
+        )?;
+        self.shell_options.textures[index] = image;
+        self.state.restart_accumulation();
+        Ok(())
+    }
+
+    pub fn device_lost(&self) -> bool {
+        self.gpu_device.is_lost()
+    }
+
+    pub fn retain(self) -> RetainedState {
+        RetainedState {
+            state: self.state,
+            shell_options: self.shell_options,
+        }
+    }
+
+    // Continue from a lost renderer; the average starts over since the buffers are new
+    pub fn restore(&mut self, retained: RetainedState) {
+        self.state = retained.state;
+        self.state.restart_accumulation();
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), ShadertuiError> {
         self.width = width;
         self.height = height;
 
         // Reconfigure surface
         self.surface_manager
             .configure(&self.gpu_device.device, width, height);
+        let (width, height) = self.render_size();
+        self.pass_chain
+            .resize(&self.gpu_device.device, width, height);
 
         // Recreate GPU resources with new size
         let storage_texture = self.resource_manager.create_storage_texture(width, height);
         let storage_texture_view =
             storage_texture.create_view(&wgpu::TextureViewDescriptor::default());
         let sampler = self.resource_manager.create_sampler();
+        self.storage_texture = storage_texture;
+        if self.shell_options.accumulate {
+            self.accumulation = Some(Accumulation::new(&self.gpu_device.device, width, height));
+        }
+        self.state.restart_accumulation();
 
         // Update bind groups with new texture
         self.compute_bind_group = self.resource_manager.create_compute_bind_group(
             &self.compute_bind_group_layout,
             &storage_texture_view,
             &self.uniform_buffer,
+            self.state_buffer.as_ref(),
+            self.accumulation.as_ref(),
         );
         self.render_bind_group = self.resource_manager.create_render_bind_group(
             &self.render_bind_group_layout,
@@ -167,6 +342,11 @@ impl WindowRenderer {
         Ok(())
     }
 
+    // Size of the compute output, which the display pass stretches over the window
+    fn render_size(&self) -> (u32, u32) {
+        scaled_size(self.width, self.height, self.scale)
+    }
+
     // AIDEV-NOTE: Performance tracking methods for window title display
     pub fn get_fps(&self) -> Option<f32> {
         self.performance_tracker
@@ -174,35 +354,97 @@ impl WindowRenderer {
             .map(|tracker| tracker.get_fps())
     }
 
-    // AIDEV-NOTE: Hot reload method for shader recompilation
+    pub fn get_frame_time_stats(&self, sparkline_width: usize) -> Option<FrameTimeStats> {
+        self.performance_tracker
+            .as_ref()?
+            .frame_time_stats(sparkline_width)
+    }
+
+    pub fn get_gpu_time(&self) -> Option<Duration> {
+        self.gpu_time
+    }
+
+    // AIDEV-NOTE: Hot reload method for shader recompilation. Buffer passes laid out as before are
+    // merged into the running chain (see PassChain::reload), otherwise every pass is rebuilt
+    // AIDEV-NOTE: `reset` (--reset-on-reload) starts the new shader over at time 0 and frame 0
+    // with zeroed state, even with --keep-state
     pub fn reload_shader(
         &mut self,
-        user_shader_source: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Create new compute pipeline with injected user shader
+        project: &ShaderProject,
+        reset: bool,
+    ) -> Result<(), ShadertuiError> {
+        // The state buffer starts zeroed on each reload unless --keep-state asks otherwise
+        let state_buffer = if self.shell_options.keep_state && !reset {
+            self.state_buffer.clone()
+        } else {
+            create_state_buffer(&self.gpu_device.device, self.shell_options.state_size)?
+        };
+
+        // Create new pass chain and compute pipeline with injected user shaders
+        let (width, height) = self.render_size();
+        let new_pass_chain = if self
+            .pass_chain
+            .layout_matches(project, state_buffer.is_some())
+        {
+            None
+        } else {
+            Some(PassChain::new(
+                &self.gpu_device.device,
+                &self.gpu_device.queue,
+                self.gpu_device.pipeline_cache(),
+                project,
+                width,
+                height,
+                &self.shell_options,
+                state_buffer.as_ref(),
+            )?)
+        };
         let (new_compute_pipeline, new_compute_bind_group_layout) =
             PipelineFactory::create_compute_pipeline_with_user_shader(
                 &self.gpu_device.device,
-                user_shader_source,
+                project,
+                &self.shell_options,
+                new_pass_chain
+                    .as_ref()
+                    .unwrap_or(&self.pass_chain)
+                    .output_channel_layout(),
             )?;
 
         // Update compute pipeline and layout
+        match new_pass_chain {
+            Some(pass_chain) => self.pass_chain = pass_chain,
+            None => self.pass_chain.reload(
+                &self.gpu_device.device,
+                self.gpu_device.pipeline_cache(),
+                project,
+                &self.shell_options,
+                state_buffer.as_ref(),
+            )?,
+        }
         self.compute_pipeline = new_compute_pipeline;
         self.compute_bind_group_layout = new_compute_bind_group_layout;
+        self.state_buffer = state_buffer;
+        self.params = project.params.clone();
+        self.state.param_values.set_params(&project.params);
+        self.state.restart_accumulation();
+        if reset {
+            self.state.reset_clock();
+        }
 
         // Recreate GPU resources
-        let storage_texture = self
-            .resource_manager
-            .create_storage_texture(self.width, self.height);
+        let storage_texture = self.resource_manager.create_storage_texture(width, height);
         let storage_texture_view =
             storage_texture.create_view(&wgpu::TextureViewDescriptor::default());
         let sampler = self.resource_manager.create_sampler();
+        self.storage_texture = storage_texture;
 
         // Update bind groups with new resources
         self.compute_bind_group = self.resource_manager.create_compute_bind_group(
             &self.compute_bind_group_layout,
             &storage_texture_view,
             &self.uniform_buffer,
+            self.state_buffer.as_ref(),
+            self.accumulation.as_ref(),
         );
         self.render_bind_group = self.resource_manager.create_render_bind_group(
             &self.render_bind_group_layout,
@@ -213,24 +455,116 @@ impl WindowRenderer {
         Ok(())
     }
 
-    pub fn render(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    // Time and frame number of the last rendered frame
+    pub fn rendered_frame(&self) -> (f32, u32) {
+        (self.state.rendered_time, self.state.frame_count)
+    }
+
+    // AIDEV-NOTE: Read back the last computed frame (Y=0 at the bottom, like the terminal path's
+    // readback) for recording. Blocks until the GPU has finished it
+    pub fn capture_frame(&self) -> Result<RgbaImage, ShadertuiError> {
+        let device = &self.gpu_device.device;
+        let (width, height) = self.render_size();
+        let padded_row = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture Buffer"),
+            size: (padded_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            self.storage_texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: None,
+                },
+            },
+            self.storage_texture.size(),
+        );
+        self.gpu_device
+            .queue
+            .submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = flume::bounded(1);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::PollType::Wait)?;
+        receiver
+            .recv()
+            .map_err(|_| ShadertuiError::Gpu("the frame capture was dropped".to_string()))??;
+
+        // The texture holds linear 8-bit color; go through the float path for the sRGB conversion
+        let data = slice.get_mapped_range();
+        let gpu_data: Vec<f32> = data
+            .chunks_exact(padded_row as usize)
+            .flat_map(|row| &row[..width as usize * 4])
+            .map(|&value| value as f32 / 255.0)
+            .collect();
+        Ok(RgbaImage::from_gpu_data(&gpu_data, width, height))
+    }
+
+    // AIDEV-NOTE: While paused, a shader that depends on nothing but its uniforms (no buffer
+    // passes, state, --accumulate or --audio) shows the same image until input changes it. Every
+    // input restarts the accumulation (see WindowState::restart_accumulation), so a frame past the
+    // first sample at the paused time has nothing new to compute and the last output is presented
+    // again
+    fn paused_and_current(&self, time: f32) -> bool {
+        self.state.is_paused
+            && self.state.sample_index > 0
+            && time == self.state.rendered_time
+            && self.pass_chain.is_empty()
+            && self.state_buffer.is_none()
+            && self.accumulation.is_none()
+            && self.shell_options.audio.is_none()
+    }
+
+    pub fn render(&mut self) -> Result<(), ShadertuiError> {
         // Update time and uniforms using state manager
         let delta_time = self.state.update_frame_timing();
         let time = self.state.get_current_time();
+        let compute = !self.paused_and_current(time);
+        self.state.rendered_time = time;
 
-        // Update uniform buffer
+        // Update uniform buffer, scaling window positions to the render size
+        let (width, height) = self.render_size();
+        let scale = width as f32 / self.width as f32;
+        self.state.param_values.animate(time);
         let uniforms = Uniforms {
-            resolution: [self.width as f32, self.height as f32],
-            cursor: self.state.cursor_position,
+            resolution: [width as f32, height as f32],
+            cursor: self.state.cursor_position.map(|c| c * scale),
             time,
             frame: self.state.frame_count,
-            delta_time,
-            _padding: 0.0,
+            delta_time: delta_time * self.state.time_scale,
+            sample_index: self.state.next_sample_index(),
+            mouse: self.state.mouse.take_uniform().map(|c| c * scale),
+            zoom: IDENTITY_ZOOM,
+            subsamples: 1,
+            params: pack_params(&self.params, &self.state.param_values),
         };
         self.uniform_buffer
             .update(&self.gpu_device.queue, &uniforms);
 
-        let output = self.surface_manager.get_current_texture()?;
+        // A lost or outdated surface (display change, GPU switch) is configured again and the
+        // frame skipped; so is one that timed out
+        let output = match self.surface_manager.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface_manager
+                    .configure(&self.gpu_device.device, self.width, self.height);
+                return Ok(());
+            }
+            Err(wgpu::SurfaceError::Timeout) => return Ok(()),
+            Err(e) => return Err(ShadertuiError::Surface(e.to_string())),
+        };
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
@@ -242,20 +576,36 @@ impl WindowRenderer {
                     label: Some("Window Render Encoder"),
                 });
 
-        // Stage 1: Compute pass - run user's shader to generate output texture
-        {
-            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                label: Some("Compute Pass"),
-                timestamp_writes: None,
-            });
-
-            compute_pass.set_pipeline(&self.compute_pipeline);
-            compute_pass.set_bind_group(0, &self.compute_bind_group, &[]);
+        // Stage 1: Compute passes - run buffer passes, then user's output shader to generate output texture
+        if compute {
+            if let Some(timer) = &self.timer {
+                timer.begin(&mut encoder);
+            }
+            self.pass_chain
+                .encode(&self.gpu_device.queue, &mut encoder, &uniforms);
+            {
+                let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Compute Pass"),
+                    timestamp_writes: self.timer.as_ref().map(GpuTimer::end_writes),
+                });
 
-            // Dispatch compute shader with 8x8 workgroup size
-            let workgroup_count_x = self.width.div_ceil(8);
-            let workgroup_count_y = self.height.div_ceil(8);
-            compute_pass.dispatch_workgroups(workgroup_count_x, workgroup_count_y, 1);
+                compute_pass.set_pipeline(&self.compute_pipeline);
+                compute_pass.set_bind_group(0, &self.compute_bind_group, &[]);
+                if let Some(channel_bind_group) = self.pass_chain.output_channel_bind_group() {
+                    compute_pass.set_bind_group(1, channel_bind_group, &[]);
+                }
+
+                // Dispatch compute shader with 8x8 workgroup size
+                let workgroup_count_x = width.div_ceil(8);
+                let workgroup_count_y = height.div_ceil(8);
+                compute_pass.dispatch_workgroups(workgroup_count_x, workgroup_count_y, 1);
+            }
+            if let Some(timer) = &mut self.timer {
+                timer.resolve(&mut encoder);
+            }
+            if let Some(accumulation) = &self.accumulation {
+                accumulation.copy_reset_to_readback(&mut encoder);
+            }
         }
 
         // Stage 2: Render pass - sample from storage texture and present to surface
@@ -289,6 +639,17 @@ impl WindowRenderer {
             .queue
             .submit(std::iter::once(encoder.finish()));
         output.present();
+        if let Some(timer) = &mut self.timer {
+            timer.submitted();
+            self.gpu_time = timer.gpu_time(&self.gpu_device.device);
+        }
+
+        // A shader that called restart_accumulation() starts the average over on the next frame
+        if let Some(accumulation) = &self.accumulation {
+            if accumulation.read_reset_blocking(&self.gpu_device.device, DEFAULT_GPU_TIMEOUT)? {
+                self.state.restart_accumulation();
+            }
+        }
 
         // Record frame for performance tracking
         if let Some(ref mut tracker) = self.performance_tracker {
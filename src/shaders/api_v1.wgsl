@@ -0,0 +1,29 @@
+// Shader API v1: compute_color returns an opaque RGB color
+
+struct Uniforms {
+    resolution: vec2<f32>,    // Output resolution in pixels
+    cursor: vec2<f32>,       // Cursor position (x, y)
+    time: f32,               // Seconds since start
+    frame: u32,              // Frame number
+    delta_time: f32,         // Time since last frame
+    sample_index: u32,       // Frames averaged so far with --accumulate (0 after a restart)
+    mouse: vec4<f32>,        // Shadertoy iMouse: xy = drag position, zw = click position,
+                             // z < 0 once released, w < 0 after the click frame (see README)
+    zoom: vec4<f32>,         // Terminal zoom inspector: pixels are shaded at coords * zw + xy
+    subsamples: u32,         // Samples per axis averaged into each terminal pixel (supersampling)
+    // SHADERTUI_PARAMS
+}
+
+// Shades the output pixel at `coords`, averaging subsamples x subsamples points spread over it
+fn shadertui_color(coords: vec2<f32>) -> vec4<f32> {
+    let samples = max(uniforms.subsamples, 1u);
+    var color = vec4<f32>(0.0);
+    for (var y = 0u; y < samples; y++) {
+        for (var x = 0u; x < samples; x++) {
+            let offset = (vec2<f32>(f32(x), f32(y)) + 0.5) / f32(samples) - 0.5;
+            let position = (coords + offset) * uniforms.zoom.zw + uniforms.zoom.xy;
+            color += vec4<f32>(compute_color(position), 1.0);
+        }
+    }
+    return color / f32(samples * samples);
+}
@@ -0,0 +1,22 @@
+@group(0) @binding(0) var output_texture: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(1) var<uniform> uniforms: Uniforms;
+
+// Uniforms struct and shadertui_color() are provided by the shader API prelude
+
+// USER_SHADER_INJECTION_POINT
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let coords = vec2<f32>(f32(id.x), f32(id.y));
+    
+    // Skip if we're outside the bounds
+    if (coords.x >= uniforms.resolution.x || coords.y >= uniforms.resolution.y) {
+        return;
+    }
+    
+    // Call user's compute_color function (via the API shim) with unnormalized coordinates
+    let final_color = shadertui_color(coords);
+    
+    // Write to this pass's buffer texture, which later passes (and next frame) can sample
+    textureStore(output_texture, vec2<i32>(i32(coords.x), i32(coords.y)), final_color);
+}
\ No newline at end of file
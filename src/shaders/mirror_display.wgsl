@@ -0,0 +1,45 @@
+// Display stage for --mirror: draws the terminal's output buffer (one vec4<f32> per pixel, bottom
+// row first) into the window, scaled to fit with black bars and without filtering
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+}
+
+struct Frame {
+    // Output pixels
+    size: vec2<u32>,
+    // Window pixels
+    surface: vec2<f32>,
+}
+
+@group(0) @binding(0) var<storage, read> pixels: array<vec4<f32>>;
+@group(0) @binding(1) var<uniform> frame: Frame;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    // Fullscreen triangle
+    var vertices = array<vec2<f32>, 3>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>( 3.0, -1.0),
+        vec2<f32>(-1.0,  3.0)
+    );
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(vertices[vertex_index], 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(frame.size);
+    let scale = min(frame.surface.x / size.x, frame.surface.y / size.y);
+    let offset = (frame.surface - size * scale) * 0.5;
+    // Output pixel under this fragment, from the top left
+    let position = (in.clip_position.xy - offset) / scale;
+    if (any(position < vec2<f32>(0.0)) || any(position >= size)) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    let x = u32(position.x);
+    let y = frame.size.y - 1u - u32(position.y);
+    let pixel = clamp(pixels[y * frame.size.x + x], vec4<f32>(0.0), vec4<f32>(1.0));
+    return vec4<f32>(pixel.rgb, 1.0);
+}
@@ -0,0 +1,20 @@
+// Packing stage for terminal readback: one u32 per output pixel instead of a vec4<f32>. The color
+// is gamma-encoded (2.2, like the terminal displays it) before it is quantized, so dark shades keep
+// their precision; see gpu::packing
+
+@group(0) @binding(0) var<storage, read> pixels: array<vec4<f32>>;
+@group(0) @binding(1) var<storage, read_write> packed: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    // Large frames are dispatched as several rows of workgroups
+    let index = id.y * groups.x * 64u + id.x;
+    if (index >= arrayLength(&packed)) {
+        return;
+    }
+    let pixel = clamp(pixels[index], vec4<f32>(0.0), vec4<f32>(1.0));
+    packed[index] = pack4x8unorm(vec4<f32>(pow(pixel.rgb, vec3<f32>(1.0 / 2.2)), pixel.a));
+}
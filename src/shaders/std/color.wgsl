@@ -0,0 +1,47 @@
+// Color space conversions and palettes; RGB values are linear unless a name says sRGB
+fn hsv_to_rgb(hsv: vec3<f32>) -> vec3<f32> {
+    let k = vec3<f32>(1.0, 2.0 / 3.0, 1.0 / 3.0);
+    let p = abs(fract(vec3<f32>(hsv.x) + k) * 6.0 - 3.0);
+    return hsv.z * mix(vec3<f32>(1.0), clamp(p - 1.0, vec3<f32>(0.0), vec3<f32>(1.0)), hsv.y);
+}
+
+fn rgb_to_hsv(rgb: vec3<f32>) -> vec3<f32> {
+    let k = vec4<f32>(0.0, -1.0 / 3.0, 2.0 / 3.0, -1.0);
+    let p = select(vec4<f32>(rgb.gb, k.xy), vec4<f32>(rgb.bg, k.wz), rgb.g < rgb.b);
+    let q = select(vec4<f32>(rgb.r, p.yzx), vec4<f32>(p.xyw, rgb.r), rgb.r < p.x);
+    let d = q.x - min(q.w, q.y);
+    let e = 1.0e-10;
+    return vec3<f32>(abs(q.z + (q.w - q.y) / (6.0 * d + e)), d / (q.x + e), q.x);
+}
+
+fn srgb_to_linear(srgb: vec3<f32>) -> vec3<f32> {
+    let low = srgb / 12.92;
+    let high = pow((srgb + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, srgb <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(rgb: vec3<f32>) -> vec3<f32> {
+    let low = rgb * 12.92;
+    let high = 1.055 * pow(rgb, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, rgb <= vec3<f32>(0.0031308));
+}
+
+fn luminance(rgb: vec3<f32>) -> f32 {
+    return dot(rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+// Cosine gradient a + b * cos(2π(c * t + d)), see iquilezles.org/articles/palettes
+fn cosine_palette(t: f32, a: vec3<f32>, b: vec3<f32>, c: vec3<f32>, d: vec3<f32>) -> vec3<f32> {
+    return a + b * cos(6.28318530718 * (c * t + d));
+}
+
+// A ready-made rainbow from cosine_palette
+fn rainbow(t: f32) -> vec3<f32> {
+    return cosine_palette(
+        t,
+        vec3<f32>(0.5),
+        vec3<f32>(0.5),
+        vec3<f32>(1.0),
+        vec3<f32>(0.0, 0.33, 0.67),
+    );
+}
@@ -0,0 +1,53 @@
+// Easing curves mapping t in [0, 1] to [0, 1]
+const EASING_PI: f32 = 3.14159265358979;
+
+fn ease_in_quad(t: f32) -> f32 {
+    return t * t;
+}
+
+fn ease_out_quad(t: f32) -> f32 {
+    return 1.0 - (1.0 - t) * (1.0 - t);
+}
+
+fn ease_in_out_quad(t: f32) -> f32 {
+    return select(1.0 - pow(-2.0 * t + 2.0, 2.0) / 2.0, 2.0 * t * t, t < 0.5);
+}
+
+fn ease_in_cubic(t: f32) -> f32 {
+    return t * t * t;
+}
+
+fn ease_out_cubic(t: f32) -> f32 {
+    return 1.0 - pow(1.0 - t, 3.0);
+}
+
+fn ease_in_out_cubic(t: f32) -> f32 {
+    return select(1.0 - pow(-2.0 * t + 2.0, 3.0) / 2.0, 4.0 * t * t * t, t < 0.5);
+}
+
+fn ease_in_out_sine(t: f32) -> f32 {
+    return -(cos(EASING_PI * t) - 1.0) / 2.0;
+}
+
+fn ease_out_elastic(t: f32) -> f32 {
+    if t <= 0.0 || t >= 1.0 {
+        return clamp(t, 0.0, 1.0);
+    }
+    return pow(2.0, -10.0 * t) * sin((t * 10.0 - 0.75) * (2.0 * EASING_PI / 3.0)) + 1.0;
+}
+
+fn ease_out_bounce(t: f32) -> f32 {
+    let n = 7.5625;
+    let d = 2.75;
+    if t < 1.0 / d {
+        return n * t * t;
+    } else if t < 2.0 / d {
+        let u = t - 1.5 / d;
+        return n * u * u + 0.75;
+    } else if t < 2.5 / d {
+        let u = t - 2.25 / d;
+        return n * u * u + 0.9375;
+    }
+    let u = t - 2.625 / d;
+    return n * u * u + 0.984375;
+}
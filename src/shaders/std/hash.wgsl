@@ -0,0 +1,30 @@
+// Hashes of integer lattice points to pseudo-random values in [0, 1), based on PCG
+fn pcg_hash(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn hash_to_unit(h: u32) -> f32 {
+    return f32(h) / 4294967296.0;
+}
+
+fn hash11(p: f32) -> f32 {
+    return hash_to_unit(pcg_hash(bitcast<u32>(i32(floor(p)))));
+}
+
+fn hash21(p: vec2<f32>) -> f32 {
+    let q = bitcast<vec2<u32>>(vec2<i32>(floor(p)));
+    return hash_to_unit(pcg_hash(q.x + pcg_hash(q.y)));
+}
+
+fn hash22(p: vec2<f32>) -> vec2<f32> {
+    let q = bitcast<vec2<u32>>(vec2<i32>(floor(p)));
+    let h = pcg_hash(q.x + pcg_hash(q.y));
+    return vec2<f32>(hash_to_unit(h), hash_to_unit(pcg_hash(h)));
+}
+
+fn hash31(p: vec3<f32>) -> f32 {
+    let q = bitcast<vec3<u32>>(vec3<i32>(floor(p)));
+    return hash_to_unit(pcg_hash(q.x + pcg_hash(q.y + pcg_hash(q.z))));
+}
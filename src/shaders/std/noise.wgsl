@@ -0,0 +1,61 @@
+// Smooth noise in [0, 1) and fractal sums of it
+// @import "std/hash"
+
+fn value_noise(p: vec2<f32>) -> f32 {
+    let cell = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let a = hash21(cell);
+    let b = hash21(cell + vec2<f32>(1.0, 0.0));
+    let c = hash21(cell + vec2<f32>(0.0, 1.0));
+    let d = hash21(cell + vec2<f32>(1.0, 1.0));
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+fn value_noise3(p: vec3<f32>) -> f32 {
+    let cell = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let x0 = mix(
+        mix(hash31(cell), hash31(cell + vec3<f32>(1.0, 0.0, 0.0)), u.x),
+        mix(hash31(cell + vec3<f32>(0.0, 1.0, 0.0)), hash31(cell + vec3<f32>(1.0, 1.0, 0.0)), u.x),
+        u.y,
+    );
+    let x1 = mix(
+        mix(hash31(cell + vec3<f32>(0.0, 0.0, 1.0)), hash31(cell + vec3<f32>(1.0, 0.0, 1.0)), u.x),
+        mix(hash31(cell + vec3<f32>(0.0, 1.0, 1.0)), hash31(cell + vec3<f32>(1.0, 1.0, 1.0)), u.x),
+        u.y,
+    );
+    return mix(x0, x1, u.z);
+}
+
+// Perlin-style gradient noise, remapped from [-1, 1] to [0, 1]
+fn gradient_noise(p: vec2<f32>) -> f32 {
+    let cell = floor(p);
+    let f = fract(p);
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let g00 = hash22(cell) * 2.0 - 1.0;
+    let g10 = hash22(cell + vec2<f32>(1.0, 0.0)) * 2.0 - 1.0;
+    let g01 = hash22(cell + vec2<f32>(0.0, 1.0)) * 2.0 - 1.0;
+    let g11 = hash22(cell + vec2<f32>(1.0, 1.0)) * 2.0 - 1.0;
+    let n = mix(
+        mix(dot(g00, f), dot(g10, f - vec2<f32>(1.0, 0.0)), u.x),
+        mix(dot(g01, f - vec2<f32>(0.0, 1.0)), dot(g11, f - vec2<f32>(1.0, 1.0)), u.x),
+        u.y,
+    );
+    return n * 0.5 + 0.5;
+}
+
+// Fractal Brownian motion: `octaves` layers of value noise, each at twice the frequency and half
+// the amplitude of the previous one
+fn fbm(p: vec2<f32>, octaves: i32) -> f32 {
+    var sum = 0.0;
+    var amplitude = 0.5;
+    var q = p;
+    for (var i = 0; i < octaves; i++) {
+        sum += amplitude * value_noise(q);
+        q = q * 2.0 + vec2<f32>(17.0, 31.0);
+        amplitude *= 0.5;
+    }
+    return sum;
+}
@@ -0,0 +1,54 @@
+// Sphere tracing scaffolding. Define `fn scene_sdf(p: vec3<f32>) -> f32` in your shader (e.g. with
+// std/sdf) and these march rays through it
+const RAYMARCH_MAX_STEPS: i32 = 128;
+const RAYMARCH_MAX_DISTANCE: f32 = 100.0;
+const RAYMARCH_EPSILON: f32 = 0.001;
+
+// Direction through `uv` (centered, aspect-corrected, e.g. in [-1, 1] vertically) of a camera at
+// `eye` looking at `look_at`; a larger `zoom` narrows the field of view
+fn camera_ray(uv: vec2<f32>, eye: vec3<f32>, look_at: vec3<f32>, zoom: f32) -> vec3<f32> {
+    let forward = normalize(look_at - eye);
+    let right = normalize(cross(vec3<f32>(0.0, 1.0, 0.0), forward));
+    let up = cross(forward, right);
+    return normalize(uv.x * right + uv.y * up + zoom * forward);
+}
+
+// Distance along the ray to the first surface, or -1.0 if nothing was hit
+fn raymarch(origin: vec3<f32>, direction: vec3<f32>) -> f32 {
+    var distance = 0.0;
+    for (var i = 0; i < RAYMARCH_MAX_STEPS; i++) {
+        let step = scene_sdf(origin + direction * distance);
+        if step < RAYMARCH_EPSILON * distance {
+            return distance;
+        }
+        distance += step;
+        if distance > RAYMARCH_MAX_DISTANCE {
+            break;
+        }
+    }
+    return -1.0;
+}
+
+fn scene_normal(p: vec3<f32>) -> vec3<f32> {
+    let e = vec2<f32>(RAYMARCH_EPSILON, 0.0);
+    return normalize(vec3<f32>(
+        scene_sdf(p + e.xyy) - scene_sdf(p - e.xyy),
+        scene_sdf(p + e.yxy) - scene_sdf(p - e.yxy),
+        scene_sdf(p + e.yyx) - scene_sdf(p - e.yyx),
+    ));
+}
+
+// Penumbra factor in [0, 1] for light arriving along `direction`; larger `k` gives harder shadows
+fn soft_shadow(origin: vec3<f32>, direction: vec3<f32>, k: f32) -> f32 {
+    var result = 1.0;
+    var distance = 0.02;
+    for (var i = 0; i < 64 && distance < RAYMARCH_MAX_DISTANCE; i++) {
+        let step = scene_sdf(origin + direction * distance);
+        if step < RAYMARCH_EPSILON {
+            return 0.0;
+        }
+        result = min(result, k * step / distance);
+        distance += step;
+    }
+    return clamp(result, 0.0, 1.0);
+}
@@ -1,14 +1,7 @@
 @group(0) @binding(0) var<storage, read_write> output: array<vec4<f32>>;
 @group(0) @binding(1) var<uniform> uniforms: Uniforms;
 
-struct Uniforms {
-    resolution: vec2<f32>,    // Terminal resolution (cols, rows*2)
-    cursor: vec2<f32>,       // Cursor position (x, y)
-    time: f32,               // Seconds since start
-    frame: u32,              // Frame number
-    delta_time: f32,         // Time since last frame
-    _padding: f32,           // Ensure 16-byte alignment
-}
+// Uniforms struct and shadertui_color() are provided by the shader API prelude
 
 // USER_SHADER_INJECTION_POINT
 
@@ -21,10 +14,10 @@ fn main(@builtin(global_invocation_id) id: vec3<u32>) {
         return;
     }
     
-    // Call user's compute_color function with unnormalized coordinates
-    let final_color = compute_color(coords);
+    // Call user's compute_color function (via the API shim) with unnormalized coordinates
+    let final_color = shadertui_color(coords);
     
     // Write to output buffer
     let index = u32(coords.y * uniforms.resolution.x + coords.x);
-    output[index] = vec4<f32>(final_color, 1.0);
+    output[index] = final_color;
 }
\ No newline at end of file
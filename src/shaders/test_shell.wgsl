@@ -0,0 +1,25 @@
+@group(0) @binding(0) var<storage, read_write> output: array<vec4<f32>>;
+@group(0) @binding(1) var<uniform> uniforms: Uniforms;
+
+// Uniforms struct and shadertui_color() are provided by the shader API prelude
+
+// USER_SHADER_INJECTION_POINT
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.y != 0u) {
+        return;
+    }
+
+    // Run the test at this index; the cases are generated from the `// @test` functions
+    var passed = false;
+    switch id.x {
+        // SHADERTUI_TEST_CASES
+        default: {
+            return;
+        }
+    }
+
+    // Red is the result, green marks the test as run
+    output[id.x] = vec4<f32>(select(0.0, 1.0, passed), 1.0, 0.0, 1.0);
+}
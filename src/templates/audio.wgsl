@@ -0,0 +1,29 @@
+// @title Audio
+// @tags starter, audio
+//
+// Spectrum bars over the waveform of the audio input. Run with --audio (a build with
+// `--features audio`); channel0 is then a 512x2 texture: the spectrum at v = 0.25 and the
+// waveform at v = 0.75, with silence at 0.5.
+
+fn spectrum(x: f32) -> f32 {
+    return textureSampleLevel(channel0, channel_sampler, vec2<f32>(x, 0.25), 0.0).r;
+}
+
+fn waveform(x: f32) -> f32 {
+    return textureSampleLevel(channel0, channel_sampler, vec2<f32>(x, 0.75), 0.0).r;
+}
+
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let uv = coords / uniforms.resolution;
+
+    // Low frequencies hold most of the energy, so give them more of the width
+    let bars = 32.0;
+    let bar = floor(pow(uv.x, 1.5) * bars) / bars;
+    let level = spectrum(bar + 0.5 / bars);
+    let hue = vec3<f32>(0.0, 0.33, 0.67) + bar + uniforms.time * 0.1;
+    var color = (0.5 + 0.5 * cos(6.2831853 * hue)) * step(uv.y, level);
+
+    let line = 1.0 - smoothstep(0.0, 0.02, abs(uv.y - waveform(uv.x)));
+    color = max(color, vec3<f32>(line));
+    return color;
+}
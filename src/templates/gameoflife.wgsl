@@ -0,0 +1,43 @@
+// @title Game of Life
+// @tags starter, feedback, multi-pass
+//
+// Conway's Game of Life, one cell per pixel. The `cells` pass reads its own previous frame
+// (channel0) and the `image` pass colors it. Click or drag to draw live cells.
+
+// @pass cells
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let cell = vec2<i32>(coords);
+    let size = vec2<i32>(uniforms.resolution);
+
+    // A random soup on the first frame
+    if (uniforms.frame == 0u) {
+        let h = fract(sin(dot(coords, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+        return vec3<f32>(step(0.7, h));
+    }
+
+    let previous = textureLoad(channel0, cell, 0).r;
+    var neighbors = 0;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            if (x == 0 && y == 0) {
+                continue;
+            }
+            // The edges wrap around
+            let neighbor = (cell + vec2<i32>(x, y) + size) % size;
+            neighbors += i32(textureLoad(channel0, neighbor, 0).r > 0.5);
+        }
+    }
+
+    var alive = neighbors == 3 || (previous > 0.5 && neighbors == 2);
+    if (uniforms.mouse.z > 0.0 && distance(coords, uniforms.mouse.xy) < 3.0) {
+        alive = true;
+    }
+    // Green keeps a fading trail of where cells died
+    return vec3<f32>(f32(alive), max(f32(alive), textureLoad(channel0, cell, 0).g * 0.95), 0.0);
+}
+
+// @pass image
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let cell = textureLoad(channel0, vec2<i32>(coords), 0);
+    return mix(vec3<f32>(0.05, 0.1, 0.2) * cell.g, vec3<f32>(0.9, 1.0, 0.6), cell.r);
+}
@@ -0,0 +1,23 @@
+// @title Plasma
+// @tags starter
+//
+// Classic plasma: a few sine waves summed and mapped through a color palette.
+// `coords` is the pixel position (0 to uniforms.resolution, Y up). Edit and save to hot reload.
+
+// @uniform speed: f32 = 1.0 (0.0 .. 4.0)
+// @uniform scale: f32 = 8.0 (1.0 .. 32.0)
+
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    // Centered and aspect-corrected, about -1 to 1 vertically
+    let uv = (coords - uniforms.resolution * 0.5) / uniforms.resolution.y * 2.0;
+    let t = uniforms.time * uniforms.speed;
+    let p = uv * uniforms.scale;
+
+    var v = sin(p.x + t);
+    v += sin((p.y + t) * 0.5);
+    v += sin((p.x + p.y + t) * 0.5);
+    v += sin(length(p + vec2<f32>(sin(t * 0.3), cos(t * 0.5)) * 4.0) + t);
+    v *= 0.25;
+
+    return 0.5 + 0.5 * cos(6.2831853 * (v + vec3<f32>(0.0, 0.33, 0.67)));
+}
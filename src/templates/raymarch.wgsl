@@ -0,0 +1,41 @@
+// @title Raymarch
+// @tags starter, 3d
+//
+// A sphere-traced scene built from the standard library's distance functions.
+// Change scene_sdf to change the scene; drag the mouse to orbit the camera.
+
+// @import "std/sdf"
+// @import "std/raymarch"
+
+fn scene_sdf(p: vec3<f32>) -> f32 {
+    let ball = sd_sphere(p - vec3<f32>(0.0, 0.2 + 0.2 * sin(uniforms.time), 0.0), 0.6);
+    let ring = sd_torus(p, vec2<f32>(1.0, 0.15));
+    let ground = sd_plane(p, vec3<f32>(0.0, 1.0, 0.0), 0.8);
+    return op_union(op_smooth_union(ball, ring, 0.3), ground);
+}
+
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    let uv = (coords - uniforms.resolution * 0.5) / uniforms.resolution.y * 2.0;
+
+    // Orbit with the mouse while a button is down, slowly on its own otherwise
+    var angle = uniforms.time * 0.3;
+    if (uniforms.mouse.z > 0.0) {
+        angle = uniforms.mouse.x / uniforms.resolution.x * 6.2831853;
+    }
+    let eye = vec3<f32>(3.5 * sin(angle), 1.5, 3.5 * cos(angle));
+    let direction = camera_ray(uv, eye, vec3<f32>(0.0), 1.5);
+
+    let sky = mix(vec3<f32>(0.6, 0.7, 0.9), vec3<f32>(0.2, 0.3, 0.6), uv.y * 0.5 + 0.5);
+    let hit = raymarch(eye, direction);
+    if (hit < 0.0) {
+        return sky;
+    }
+
+    let p = eye + direction * hit;
+    let normal = scene_normal(p);
+    let light = normalize(vec3<f32>(0.6, 0.8, 0.4));
+    let diffuse = max(dot(normal, light), 0.0) * soft_shadow(p + normal * 0.01, light, 8.0);
+    let albedo = select(vec3<f32>(0.9, 0.5, 0.3), vec3<f32>(0.7), p.y < -0.79);
+    let color = albedo * (0.15 + 0.85 * diffuse);
+    return mix(color, sky, 1.0 - exp(-0.02 * hit * hit));
+}
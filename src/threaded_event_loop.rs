@@ -1,22 +1,58 @@
+use std::sync::mpsc::{RecvError, TryRecvError};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
-use crate::renderers::{GpuRenderer, TerminalRenderer};
+use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+use winit::window::{Window, WindowId};
+
+use crate::error::ShadertuiError;
+use crate::gpu::{check_compatibility, gpu_instance, surface_adapter, RenderSize};
+use crate::renderers::window::MirrorOutput;
+use crate::renderers::{
+    GpuRenderer, ImageOutput, ImageProtocol, TerminalOptions, TerminalRenderer,
+};
+use crate::utils::cli::OutputMode;
+use crate::utils::control::ControlReceiver;
+use crate::utils::events;
+use crate::utils::messages::msg;
+use crate::utils::project::{Domain, ShaderProject};
+use crate::utils::terminal_caps::TerminalCapabilities;
+use crate::utils::threading::SharedUniformsHandle;
+use crate::utils::trace;
 use crate::utils::{
-    Cli, DualPerformanceTracker, ErrorReceiver, SharedFrameBuffer, SharedUniforms, ThreadError,
+    get_centered_window_position, get_window_size, Cli, DualPerformanceTracker, ErrorReceiver,
+    SharedFrameBuffer, SharedUniforms, ThreadError,
 };
 
+// How long the --mirror window's events are waited for between checks for a shutdown
+const MIRROR_POLL_INTERVAL: Duration = Duration::from_millis(16);
+
 // AIDEV-NOTE: Multi-threaded event loop with independent GPU and Terminal threads
 pub fn run_threaded_event_loop(
     cli: Cli,
-    shader_source: String,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Get terminal size
-    let (width, height) = crossterm::terminal::size()?;
+    project: ShaderProject,
+    control: Option<ControlReceiver>,
+) -> Result<(), ShadertuiError> {
+    if let Some(path) = &cli.trace {
+        trace::start(path, cli.shader_file())?;
+    }
+
+    // Get terminal size, leaving room for the editor pane in --edit mode
+    let (columns, height) = crossterm::terminal::size()?;
+    let (_, width) = TerminalRenderer::layout(columns, cli.edit);
 
     // Create shared state
     let frame_buffer = Arc::new(Mutex::new(SharedFrameBuffer::new()));
-    let shared_uniforms = Arc::new(Mutex::new(SharedUniforms::new()));
+    let shared_uniforms = Arc::new(Mutex::new(SharedUniforms {
+        supersample: cli.supersample,
+        reset_on_reload: cli.reset_on_reload,
+        ..SharedUniforms::new(&project.params)
+    }));
     let performance_tracker = if cli.perf {
         Some(Arc::new(Mutex::new(DualPerformanceTracker::new())))
     } else {
@@ -28,14 +64,71 @@ pub fn run_threaded_event_loop(
     let (terminal_error_sender, terminal_error_receiver): (_, ErrorReceiver) =
         std::sync::mpsc::channel();
 
-    // Initialize GPU renderer BEFORE starting threads to catch early shader errors
-    let gpu_renderer = match GpuRenderer::new(width as u32, height as u32, &shader_source) {
-        Ok(renderer) => renderer,
-        Err(e) => {
-            eprintln!("Shader compilation error: {e}");
-            std::process::exit(1);
+    // Resolve display options up front, since the cell mode sets the GPU resolution
+    let caps = TerminalCapabilities::detect();
+    let terminal_options = TerminalOptions {
+        cell_mode: cli
+            .cell_mode
+            .as_ref()
+            .map_or_else(|| caps.default_cell_mode(), |arg| arg.mode),
+        glyph_ramp: cli.cell_mode.as_ref().and_then(|arg| arg.ramp.clone()),
+        color_mode: cli.color_mode.unwrap_or_else(|| caps.default_color_mode()),
+        dither: cli.dither,
+        resize_debounce: caps.resize_debounce(),
+        low_latency: cli.low_latency,
+        low_power: cli.low_power,
+        adaptive: !cli.no_adaptive,
+        max_fps: cli.frame_rate_cap(),
+        edit: cli.edit,
+        screensaver: cli.screensaver,
+        image_output: match cli.output {
+            OutputMode::Cells => None,
+            OutputMode::Sixel => Some(ImageProtocol::Sixel),
+            OutputMode::Kitty => Some(ImageProtocol::Kitty),
         }
+        .map(ImageOutput::detect)
+        .transpose()?,
+        software: cli.software_adapter,
+        notify: cli.notify,
+        window_args: cli.mirror_args.clone(),
     };
+    let (pixels_x, pixels_y) = terminal_options.pixels_per_cell();
+    let gpu_options = cli.gpu_options();
+    let (gpu_width, gpu_height) =
+        GpuRenderer::gpu_size(width * pixels_x, height as u32 * pixels_y, &gpu_options);
+    check_compatibility(
+        &project,
+        &cli.shell_options,
+        Some(RenderSize {
+            width: gpu_width,
+            height: gpu_height,
+            scale: gpu_options.scale,
+        }),
+    )?;
+
+    // The --mirror window opens first, since the GPU device has to be able to present to it
+    let mut mirror = if cli.mirror {
+        if project.domain == Domain::Samples {
+            return Err(msg!("mirror.sample_domain").into());
+        }
+        Some(MirrorApp::open(&project, Arc::clone(&shared_uniforms))?)
+    } else {
+        None
+    };
+    let mirror_output = mirror.as_ref().map(MirrorApp::output).transpose()?;
+
+    // Initialize GPU renderer BEFORE starting threads to catch early shader errors
+    let gpu_renderer = GpuRenderer::new(
+        width * pixels_x,
+        height as u32 * pixels_y,
+        &project,
+        cli.shell_options.clone(),
+        gpu_options,
+        mirror_output,
+    )?;
+    if let Some(path) = &cli.json_events {
+        events::start(path, cli.shader_file())?;
+    }
 
     // Clone handles for threads
     let gpu_frame_buffer = Arc::clone(&frame_buffer);
@@ -49,38 +142,49 @@ pub fn run_threaded_event_loop(
     let terminal_main_error_sender = main_error_sender.clone();
     let terminal_performance_tracker = performance_tracker.as_ref().map(Arc::clone);
 
-    // Spawn GPU compute thread
-    let _gpu_thread = thread::spawn(move || {
-        gpu_renderer.run_compute_thread(
-            gpu_frame_buffer,
-            gpu_shared_uniforms,
-            gpu_main_error_sender,
-            gpu_terminal_error_sender,
-            gpu_performance_tracker,
-        );
-    });
+    // Spawn GPU compute thread. Both threads are named for --trace
+    let _gpu_thread = thread::Builder::new()
+        .name("gpu".to_string())
+        .spawn(move || {
+            gpu_renderer.run_compute_thread(
+                gpu_frame_buffer,
+                gpu_shared_uniforms,
+                gpu_main_error_sender,
+                gpu_terminal_error_sender,
+                gpu_performance_tracker,
+            );
+        })?;
 
     // Spawn Terminal render thread
-    let shader_file_path = cli.shader_file.clone();
-    let max_fps = cli.max_fps;
-    let terminal_thread = thread::spawn(move || {
-        let terminal_renderer = TerminalRenderer::new(width as u32, height as u32);
-        if let Err(e) = terminal_renderer.run_terminal_thread(
-            terminal_frame_buffer,
-            terminal_shared_uniforms,
-            terminal_main_error_sender,
-            terminal_error_receiver,
-            &shader_file_path,
-            terminal_performance_tracker,
-            max_fps,
-        ) {
-            eprintln!("Terminal thread error: {e}");
-        }
-    });
+    let shader_file_path = cli.shader_file().to_path_buf();
+    let shell_options = cli.shell_options.clone();
+    let recording = cli.recording_options();
+    let terminal_thread = thread::Builder::new()
+        .name("terminal".to_string())
+        .spawn(move || {
+            let terminal_renderer =
+                TerminalRenderer::new(columns as u32, height as u32, terminal_options, recording);
+            if let Err(e) = terminal_renderer.run_terminal_thread(
+                terminal_frame_buffer,
+                terminal_shared_uniforms,
+                terminal_main_error_sender,
+                terminal_error_receiver,
+                &shader_file_path,
+                terminal_performance_tracker,
+                shell_options,
+                control,
+            ) {
+                eprintln!("{}", msg!("error.terminal_thread", error = e));
+            }
+        })?;
 
-    // Main thread handles error coordination and shutdown
+    // Main thread handles error coordination and shutdown, and the --mirror window's events
     loop {
-        match main_error_receiver.recv() {
+        let message = match &mut mirror {
+            Some(app) => app.recv(&main_error_receiver),
+            None => main_error_receiver.recv(),
+        };
+        match message {
             Ok(ThreadError::Shutdown) => {
                 // User requested quit - threads will naturally exit
                 break;
@@ -89,10 +193,19 @@ pub fn run_threaded_event_loop(
                 // Shader compilation errors are now handled by the terminal thread
                 // and displayed in the UI, so we just continue here
             }
+            Ok(ThreadError::ShaderCompiling) => {
+                // Shown by the terminal thread until the reload finishes
+            }
             Ok(ThreadError::ShaderReloadSuccess) => {
                 // Shader reload success is handled by the terminal thread
                 // and clears the error state, so we just continue here
             }
+            Ok(ThreadError::RenderingStopped(_)) => {
+                // Shown by the terminal thread, which also takes the restart key
+            }
+            Ok(ThreadError::DeviceRecovered) => {
+                // Device recovery is shown by the terminal thread as a notice
+            }
             Ok(ThreadError::GpuError(_)) => {
                 // GPU errors are now handled by the terminal thread
                 // and displayed in the UI, so we just continue here
@@ -108,6 +221,114 @@ pub fn run_threaded_event_loop(
     // Note: GPU thread runs in infinite loop, so we don't join it
     // The process exit will clean it up
     let _ = terminal_thread.join();
+    events::emit(|| events::EditorEvent::Exit);
 
     Ok(())
 }
+
+// AIDEV-NOTE: The main thread's side of --mirror. winit windows can only be created and served on
+// the main thread, which otherwise just waits for the other threads, so it pumps the window's
+// events between checks for a shutdown. The window is drawn by the GPU thread (see
+// renderers::window::MirrorOutput); resizes and closing are passed on through the shared uniforms
+struct MirrorApp {
+    event_loop: EventLoop<()>,
+    handler: MirrorHandler,
+}
+
+struct MirrorHandler {
+    window: Option<Arc<Window>>,
+    title: String,
+    shared_uniforms: SharedUniformsHandle,
+    error: Option<String>,
+}
+
+impl MirrorApp {
+    fn open(
+        project: &ShaderProject,
+        shared_uniforms: SharedUniformsHandle,
+    ) -> Result<Self, ShadertuiError> {
+        let title = match project.metadata.display_title() {
+            Some(title) => msg!("title.with_shader", title = title),
+            None => msg!("title.app"),
+        };
+        let mut app = Self {
+            event_loop: EventLoop::new()?,
+            handler: MirrorHandler {
+                window: None,
+                title,
+                shared_uniforms,
+                error: None,
+            },
+        };
+        // The window is created once the event loop resumes, on the first pump
+        while app.handler.window.is_none() {
+            if let Some(error) = app.handler.error.take() {
+                return Err(ShadertuiError::Surface(error));
+            }
+            if let PumpStatus::Exit(_) = app
+                .event_loop
+                .pump_app_events(Some(Duration::ZERO), &mut app.handler)
+            {
+                return Err(ShadertuiError::Surface(msg!("mirror.closed")));
+            }
+        }
+        Ok(app)
+    }
+
+    // The window's surface on an adapter that can present to it, for the GPU thread
+    fn output(&self) -> Result<MirrorOutput, ShadertuiError> {
+        let window = self.handler.window.clone().expect("opened with a window");
+        let instance = gpu_instance();
+        let size = window.inner_size();
+        let surface = instance.create_surface(window)?;
+        let adapter = surface_adapter(&instance, &surface)?;
+        MirrorOutput::new(surface, adapter, (size.width, size.height))
+    }
+
+    // Like Receiver::recv(), serving the window while waiting
+    fn recv(&mut self, receiver: &ErrorReceiver) -> Result<ThreadError, RecvError> {
+        loop {
+            match receiver.try_recv() {
+                Ok(message) => return Ok(message),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {}
+            }
+            let _ = self
+                .event_loop
+                .pump_app_events(Some(MIRROR_POLL_INTERVAL), &mut self.handler);
+        }
+    }
+}
+
+impl ApplicationHandler for MirrorHandler {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let (width, height) = get_window_size();
+        let window_attributes = Window::default_attributes()
+            .with_title(&self.title)
+            .with_inner_size(PhysicalSize::new(width, height))
+            .with_position(get_centered_window_position(event_loop))
+            .with_resizable(true);
+        match event_loop.create_window(window_attributes) {
+            Ok(window) => self.window = Some(Arc::new(window)),
+            Err(e) => self.error = Some(msg!("mirror.open_failed", error = e)),
+        }
+    }
+
+    fn window_event(&mut self, _event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::Resized(size) => {
+                let mut uniforms = self.shared_uniforms.lock().unwrap();
+                uniforms.request_mirror_resize(size.width, size.height);
+            }
+            // Only the window closes; the terminal keeps rendering
+            WindowEvent::CloseRequested => {
+                self.shared_uniforms.lock().unwrap().close_mirror();
+                self.window = None;
+            }
+            _ => {}
+        }
+    }
+}
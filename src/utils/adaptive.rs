@@ -0,0 +1,177 @@
+use std::time::{Duration, Instant};
+
+// How long AdaptiveScale measures before each decision
+const WINDOW: Duration = Duration::from_secs(1);
+// Frame rate the terminal should reach without --max-fps
+const DEFAULT_TARGET_FPS: f32 = 30.0;
+// Behind: drawing under this share of the target while frames are dropped, or while the GPU
+// renders over 1 / FAR_BELOW_GPU times as many
+const BEHIND_RATIO: f32 = 0.75;
+const FAR_BELOW_GPU: f32 = 0.5;
+// Headroom: the target is reached at the reduced resolution
+const HEADROOM_RATIO: f32 = 0.95;
+// Reducing has to beat the frame rate it started from by this much to be kept
+const MIN_GAIN: f32 = 1.1;
+// Windows in a row before reducing and restoring; restoring waits twice as long after each
+// restore that was followed by falling behind again within RESTORE_GRACE windows
+const WINDOWS_TO_REDUCE: u32 = 2;
+const WINDOWS_TO_RESTORE: u32 = 3;
+const MAX_WINDOWS_TO_RESTORE: u32 = 48;
+const RESTORE_GRACE: u32 = 5;
+
+// Fraction of the resolution rendered while the terminal can't keep up
+pub const REDUCED_SCALE: f32 = 0.5;
+
+// AIDEV-NOTE: Adaptive resolution in the terminal (off with --no-adaptive). The terminal thread
+// feeds it every frame it draws plus the frame buffer's drop count, and once per WINDOW it decides
+// whether the terminal is falling behind: under its target frame rate (--max-fps, or
+// DEFAULT_TARGET_FPS) while frames keep being dropped or the GPU renders far more than are drawn.
+// A change goes to the GPU thread through SharedUniforms::set_adaptive_scale, which renders at
+// REDUCED_SCALE on top of --scale and stretches frames back up like --scale does. Full resolution
+// returns once the target is met again, or when reducing didn't speed the terminal up; in that
+// case the terminal itself is the bottleneck and it stops adapting
+#[derive(Debug)]
+pub struct AdaptiveScale {
+    target_fps: f32,
+    window_start: Instant,
+    frames_drawn: u32,
+    dropped_at_start: u64,
+    behind_windows: u32,
+    headroom_windows: u32,
+    windows_to_restore: u32,
+    windows_since_restore: u32,
+    // Terminal frame rate when the resolution was reduced, None at full resolution
+    reduced_from: Option<f32>,
+    gave_up: bool,
+}
+
+impl AdaptiveScale {
+    pub fn new(max_fps: Option<u32>) -> Self {
+        Self {
+            target_fps: max_fps.map_or(DEFAULT_TARGET_FPS, |fps| fps as f32),
+            window_start: Instant::now(),
+            frames_drawn: 0,
+            dropped_at_start: 0,
+            behind_windows: 0,
+            headroom_windows: 0,
+            windows_to_restore: WINDOWS_TO_RESTORE,
+            windows_since_restore: u32::MAX,
+            reduced_from: None,
+            gave_up: false,
+        }
+    }
+
+    pub fn record_frame(&mut self) {
+        self.frames_drawn += 1;
+    }
+
+    // Call every iteration; returns the new scale when it changes. `gpu_fps` is the GPU thread's
+    // rate where known (GpuTelemetry)
+    pub fn update(&mut self, frames_dropped: u64, gpu_fps: Option<f32>) -> Option<f32> {
+        let elapsed = self.window_start.elapsed();
+        if elapsed < WINDOW {
+            return None;
+        }
+        let seconds = elapsed.as_secs_f32();
+        let terminal_fps = self.frames_drawn as f32 / seconds;
+        let dropped_per_second =
+            frames_dropped.saturating_sub(self.dropped_at_start) as f32 / seconds;
+        self.window_start = Instant::now();
+        self.frames_drawn = 0;
+        self.dropped_at_start = frames_dropped;
+        self.observe(terminal_fps, dropped_per_second, gpu_fps)
+    }
+
+    // One window's measurements
+    fn observe(
+        &mut self,
+        terminal_fps: f32,
+        dropped_per_second: f32,
+        gpu_fps: Option<f32>,
+    ) -> Option<f32> {
+        match self.reduced_from {
+            None => {
+                self.windows_since_restore = self.windows_since_restore.saturating_add(1);
+                let behind = terminal_fps < self.target_fps * BEHIND_RATIO
+                    && (dropped_per_second >= 1.0
+                        || gpu_fps.is_some_and(|gpu_fps| terminal_fps < gpu_fps * FAR_BELOW_GPU));
+                self.behind_windows = if behind { self.behind_windows + 1 } else { 0 };
+                if self.gave_up || self.behind_windows < WINDOWS_TO_REDUCE {
+                    return None;
+                }
+                if self.windows_since_restore <= RESTORE_GRACE {
+                    self.windows_to_restore =
+                        (self.windows_to_restore * 2).min(MAX_WINDOWS_TO_RESTORE);
+                }
+                self.behind_windows = 0;
+                self.headroom_windows = 0;
+                self.reduced_from = Some(terminal_fps);
+                Some(REDUCED_SCALE)
+            }
+            Some(reduced_from) => {
+                let headroom = terminal_fps >= self.target_fps * HEADROOM_RATIO;
+                let helped = terminal_fps >= reduced_from * MIN_GAIN;
+                self.headroom_windows = if headroom || !helped {
+                    self.headroom_windows + 1
+                } else {
+                    0
+                };
+                if self.headroom_windows < self.windows_to_restore {
+                    return None;
+                }
+                self.gave_up = !headroom && !helped;
+                self.reduced_from = None;
+                self.windows_since_restore = 0;
+                Some(1.0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive_scale() {
+        let mut adaptive = AdaptiveScale::new(Some(60));
+        // Keeping up, or slow without dropping frames
+        assert_eq!(adaptive.observe(58.0, 400.0, Some(460.0)), None);
+        assert_eq!(adaptive.observe(20.0, 0.0, Some(20.0)), None);
+        assert_eq!(adaptive.observe(20.0, 0.0, Some(22.0)), None);
+
+        // Falling behind for two windows in a row
+        assert_eq!(adaptive.observe(20.0, 200.0, None), None);
+        assert_eq!(
+            adaptive.observe(20.0, 0.0, Some(300.0)),
+            Some(REDUCED_SCALE)
+        );
+
+        // Back at the target for three windows
+        assert_eq!(adaptive.observe(59.0, 100.0, None), None);
+        assert_eq!(adaptive.observe(59.0, 100.0, None), None);
+        assert_eq!(adaptive.observe(59.0, 100.0, None), Some(1.0));
+
+        // Behind again right away, so the next restore waits twice as long
+        adaptive.observe(20.0, 200.0, None);
+        assert_eq!(adaptive.observe(20.0, 200.0, None), Some(REDUCED_SCALE));
+        for _ in 0..5 {
+            assert_eq!(adaptive.observe(59.0, 100.0, None), None);
+        }
+        assert_eq!(adaptive.observe(59.0, 100.0, None), Some(1.0));
+    }
+
+    #[test]
+    fn test_adaptive_scale_gives_up() {
+        let mut adaptive = AdaptiveScale::new(None);
+        adaptive.observe(10.0, 100.0, None);
+        assert_eq!(adaptive.observe(10.0, 100.0, None), Some(REDUCED_SCALE));
+        // Half the pixels didn't make the terminal any faster
+        adaptive.observe(10.5, 100.0, None);
+        adaptive.observe(10.5, 100.0, None);
+        assert_eq!(adaptive.observe(10.5, 100.0, None), Some(1.0));
+        for _ in 0..10 {
+            assert_eq!(adaptive.observe(10.0, 100.0, None), None);
+        }
+    }
+}
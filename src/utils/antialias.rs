@@ -0,0 +1,94 @@
+use crate::utils::options::AntiAliasing;
+
+// The GPU renders this many pixels per output pixel along each axis with --aa
+pub const SUPERSAMPLE: u32 = 2;
+
+// AIDEV-NOTE: Filter a frame rendered at SUPERSAMPLE times the output size (RGBA f32, rows of
+// `width * SUPERSAMPLE` pixels) down to `width` x `height`. Box averages each output pixel's own
+// 2x2 block; Gauss weights a 4x4 footprint by [1, 3, 3, 1] per axis, reaching into neighbors
+pub fn downsample(data: &[f32], width: u32, height: u32, filter: AntiAliasing) -> Vec<f32> {
+    let (src_width, src_height) = (width * SUPERSAMPLE, height * SUPERSAMPLE);
+    let taps: &[(i32, f32)] = match filter {
+        AntiAliasing::Box => &[(0, 0.5), (1, 0.5)],
+        AntiAliasing::Gauss => &[(-1, 0.125), (0, 0.375), (1, 0.375), (2, 0.125)],
+    };
+
+    let mut output = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let mut pixel = [0.0; 4];
+            for &(dy, weight_y) in taps {
+                let src_y = (y as i32 * SUPERSAMPLE as i32 + dy).clamp(0, src_height as i32 - 1);
+                for &(dx, weight_x) in taps {
+                    let src_x = (x as i32 * SUPERSAMPLE as i32 + dx).clamp(0, src_width as i32 - 1);
+                    let index = (src_y as usize * src_width as usize + src_x as usize) * 4;
+                    let Some(sample) = data.get(index..index + 4) else {
+                        continue;
+                    };
+                    for (channel, value) in pixel.iter_mut().zip(sample) {
+                        *channel += value * weight_x * weight_y;
+                    }
+                }
+            }
+            output.extend_from_slice(&pixel);
+        }
+    }
+    output
+}
+
+// AIDEV-NOTE: Resolution rendered with --scale, at least one pixel along each axis
+pub fn scaled_size(width: u32, height: u32, scale: f32) -> (u32, u32) {
+    let scaled = |size: u32| ((size as f32 * scale).round() as u32).max(1);
+    (scaled(width), scaled(height))
+}
+
+// Nearest-neighbor stretch of a `src_width` x `src_height` frame to `width` x `height`, with
+// `channels` values per pixel (4 for RGBA f32, 1 for packed RGBA8)
+pub fn stretch<T: Copy + Default>(
+    data: &[T],
+    channels: usize,
+    src_width: u32,
+    src_height: u32,
+    width: u32,
+    height: u32,
+) -> Vec<T> {
+    let mut output = Vec::with_capacity((width * height) as usize * channels);
+    for y in 0..height {
+        let src_y = (y * src_height / height) as usize;
+        for x in 0..width {
+            let src_x = (x * src_width / width) as usize;
+            let index = (src_y * src_width as usize + src_x) * channels;
+            match data.get(index..index + channels) {
+                Some(pixel) => output.extend_from_slice(pixel),
+                None => output.extend(std::iter::repeat_n(T::default(), channels)),
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downsample() {
+        // 4x2 source: left half black, right half white
+        let mut data = Vec::new();
+        for _ in 0..2 {
+            for x in 0..4 {
+                let value = if x < 2 { 0.0 } else { 1.0 };
+                data.extend_from_slice(&[value, value, value, 1.0]);
+            }
+        }
+
+        let boxed = downsample(&data, 2, 1, AntiAliasing::Box);
+        assert_eq!(boxed, vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+
+        // The wider filter blends across the edge but keeps constant areas and alpha intact
+        let gauss = downsample(&data, 2, 1, AntiAliasing::Gauss);
+        assert_eq!(gauss[0], 0.125);
+        assert_eq!(gauss[4], 0.875);
+        assert_eq!((gauss[3], gauss[7]), (1.0, 1.0));
+    }
+}
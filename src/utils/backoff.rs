@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+// Wait after the first failure, doubling with every further one up to MAX_DELAY
+const INITIAL_DELAY: Duration = Duration::from_millis(16);
+const MAX_DELAY: Duration = Duration::from_secs(2);
+// Failures in a row before giving up, about 8 seconds of retrying
+pub const MAX_RETRIES: u32 = 10;
+
+// AIDEV-NOTE: Retry schedule for the GPU thread's render errors. Persistent errors (a driver stuck
+// resetting, a broken device) used to be retried every frame forever with an error each time;
+// now the waits grow, and after MAX_RETRIES failed frames in a row rendering stops until the user
+// asks for a restart (G in the terminal) or saves the shader. A rendered frame resets the count
+#[derive(Debug, Default)]
+pub struct Backoff {
+    failures: u32,
+}
+
+impl Backoff {
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    // Counts a failure: the wait before the next attempt, or None once it is time to give up
+    pub fn failure(&mut self) -> Option<Duration> {
+        self.failures += 1;
+        if self.failures > MAX_RETRIES {
+            return None;
+        }
+        let delay = INITIAL_DELAY.saturating_mul(1 << (self.failures - 1).min(16));
+        Some(delay.min(MAX_DELAY))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::default();
+        let delays: Vec<_> = (0..MAX_RETRIES).map(|_| backoff.failure()).collect();
+        assert_eq!(delays[0], Some(INITIAL_DELAY));
+        assert_eq!(delays[1], Some(INITIAL_DELAY * 2));
+        assert_eq!(delays.last(), Some(&Some(MAX_DELAY)));
+        assert_eq!(backoff.failure(), None);
+        assert_eq!(backoff.failures(), MAX_RETRIES + 1);
+
+        backoff.reset();
+        assert_eq!(backoff.failure(), Some(INITIAL_DELAY));
+    }
+}
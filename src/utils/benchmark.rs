@@ -0,0 +1,146 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+// AIDEV-NOTE: What `shadertui bench --save` writes and `--baseline` reads back. The raw frame times
+// are kept so later versions can compare other statistics of old runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchResults {
+    pub shader: String,
+    pub width: u32,
+    pub height: u32,
+    pub frame_times_ms: Vec<f64>,
+}
+
+impl BenchResults {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read '{}': {e}", path.display()))?;
+        serde_json::from_str(&content).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json + "\n")
+            .map_err(|e| format!("Could not write '{}': {e}", path.display()))
+    }
+
+    pub fn summary(&self) -> FrameTimeSummary {
+        FrameTimeSummary::new(&self.frame_times_ms)
+    }
+}
+
+// Frame time statistics in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTimeSummary {
+    pub mean: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl FrameTimeSummary {
+    pub fn new(frame_times: &[f64]) -> Self {
+        let mut sorted = frame_times.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let mean = sorted.iter().sum::<f64>() / sorted.len().max(1) as f64;
+        Self {
+            mean,
+            median: percentile(&sorted, 50.0),
+            p95: percentile(&sorted, 95.0),
+            p99: percentile(&sorted, 99.0),
+            min: sorted.first().copied().unwrap_or_default(),
+            max: sorted.last().copied().unwrap_or_default(),
+        }
+    }
+
+    // (name, value) in the order they are printed
+    pub fn metrics(&self) -> [(&'static str, f64); 6] {
+        [
+            ("mean", self.mean),
+            ("median", self.median),
+            ("p95", self.p95),
+            ("p99", self.p99),
+            ("min", self.min),
+            ("max", self.max),
+        ]
+    }
+}
+
+// Nearest-rank percentile of sorted values
+pub fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+// Metrics that decide whether a run passes against its baseline. The tails and extremes are
+// printed too but vary too much between runs to gate on
+pub const GATED_METRICS: &[&str] = &["mean", "median", "p95"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricChange {
+    pub name: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+    // Positive when frames got slower
+    pub percent: f64,
+    pub regressed: bool,
+}
+
+// AIDEV-NOTE: A gated metric regresses when its frame time grew by more than `threshold` percent
+pub fn compare(
+    baseline: &FrameTimeSummary,
+    current: &FrameTimeSummary,
+    threshold: f64,
+) -> Vec<MetricChange> {
+    baseline
+        .metrics()
+        .into_iter()
+        .zip(current.metrics())
+        .map(|((name, baseline), (_, current))| {
+            let percent = if baseline > 0.0 {
+                (current - baseline) / baseline * 100.0
+            } else {
+                0.0
+            };
+            MetricChange {
+                name,
+                baseline,
+                current,
+                percent,
+                regressed: GATED_METRICS.contains(&name) && percent > threshold,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_and_compare() {
+        let times: Vec<f64> = (1..=100).map(f64::from).collect();
+        let summary = FrameTimeSummary::new(&times);
+        assert_eq!(summary.median, 50.0);
+        assert_eq!(summary.p95, 95.0);
+        assert_eq!(summary.p99, 99.0);
+        assert_eq!(summary.mean, 50.5);
+
+        let slower: Vec<f64> = times.iter().map(|t| t * 1.1).collect();
+        let changes = compare(&summary, &FrameTimeSummary::new(&slower), 5.0);
+        let median = changes.iter().find(|c| c.name == "median").unwrap();
+        assert!((median.percent - 10.0).abs() < 1e-9);
+        assert!(median.regressed);
+        // Only gated metrics fail a run
+        assert!(!changes.iter().find(|c| c.name == "max").unwrap().regressed);
+        assert!(compare(&summary, &summary, 5.0)
+            .iter()
+            .all(|change| !change.regressed));
+    }
+}
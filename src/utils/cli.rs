@@ -1,87 +1,975 @@
-use std::fs;
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
+use crate::audio::AudioCapture;
+use crate::error::ShadertuiError;
+use crate::gpu::{select_adapters, AdapterChoice, AdapterSelection, AdapterSupport};
+use crate::renderers::{headless_renderer::EXPORT_FPS, GpuOptions};
+use crate::utils::messages::msg;
+pub use crate::utils::options::{
+    parse_fps, parse_loop, parse_scale, parse_seconds, AntiAliasing, CellMode, ColorMode, Dither,
+    SampleView,
+};
 use crate::utils::{
-    shader_import::process_imports,
-    shader_shell::{inject_user_shader, ShellType},
-    validation::validate_shader,
+    frame_pacing::DeltaSmoothing,
+    image_import::load_image,
+    lfo::{self, Lfo},
+    metadata::ShaderSettings,
+    project::{is_manifest_path, ShaderProject},
+    recording::RecordingOptions,
+    screensaver::pick_shader,
+    shader_shell::{ShellOptions, ShellType},
+    validation::validate_project,
+    watermark::Watermark,
 };
 
+// Frame rate of --low-power without --max-fps
+const LOW_POWER_FPS: u32 = 10;
+// Default --scale on software adapters
+const SOFTWARE_SCALE: f32 = 0.5;
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(after_help = "EXAMPLES:
+    shadertui new my.wgsl --run               # Start a new shader from a template
     shadertui example.wgsl                    # Basic usage
     shadertui --perf example.wgsl             # With performance monitoring
     shadertui --max-fps 30 example.wgsl       # Limit terminal refresh to 30 FPS
+    shadertui --scale 0.5 example.wgsl        # Render at half resolution for heavy shaders
     shadertui --window example.wgsl           # Render in a window instead of terminal
-    shadertui --window --perf shader.wgsl     # Windowed mode with performance monitoring")]
+    shadertui --cell-mode foreground ex.wgsl  # Glyphs only, for light terminal themes
+    shadertui --cell-mode braille ex.wgsl     # 2x4 braille dots per cell, finer detail
+    shadertui --edit example.wgsl             # Live-code in an embedded editor pane
+    shadertui --sample-view spectrogram shaders/oscillator.wgsl
+    shadertui --window --perf shader.wgsl     # Windowed mode with performance monitoring
+    shadertui --shell my_shell.wgsl ex.wgsl   # Wrap the shader in a custom shell template
+    shadertui --texture photo.jpg ex.wgsl     # Sample an image as channel0
+    shadertui --audio visualizer.wgsl         # React to the default audio input
+    shadertui --screensaver --idle-delay 5m shaders/  # Random shader after 5 idle minutes
+    shadertui project.toml                    # Run a multi-pass project
+    shadertui thumb ex.wgsl -o thumb.png --at 3.5s --size 320x180
+    shadertui sheet ex.wgsl --times 0,2,4,6 --cols 2 -o sheet.png
+    shadertui render ex.wgsl --frames 300 --fps 60 --out frames/
+    shadertui --backend gl --force-fallback thumb ex.wgsl  # Software rendering on a GPU-less server
+    shadertui --adapter nvidia example.wgsl   # Pin one GPU (see --list-gpus)
+    shadertui daemon example.wgsl             # Keep a renderer running in this terminal
+    shadertui ctl load other.wgsl             # ...and switch it to another shader
+    shadertui cache size                      # Show disk usage of cached files
+    shadertui doctor                          # Diagnose terminal and GPU issues")]
+#[command(subcommand_negates_reqs = true)]
 pub struct Cli {
-    /// Path to the WGSL shader file
-    pub shader_file: PathBuf,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Path to the WGSL or Shadertoy GLSL shader file, or a project.toml describing multiple passes (with
+    /// --screensaver, a directory to pick a random shader from)
+    #[arg(required_unless_present = "list_gpus")]
+    pub shader_file: Option<PathBuf>,
 
     /// Enable performance monitoring display
     #[arg(short, long)]
     pub perf: bool,
 
-    /// Maximum terminal frame rate (frames per second)
-    #[arg(long, value_name = "FPS")]
+    /// Maximum frame rate, for both rendering and drawing to the terminal (frames per second)
+    #[arg(long, value_name = "FPS", value_parser = parse_fps)]
     pub max_fps: Option<u32>,
 
+    /// Render at this fraction of the resolution (e.g. 0.5) and stretch the result, for heavy shaders
+    #[arg(long, value_name = "FACTOR", value_parser = parse_scale)]
+    pub scale: Option<f32>,
+
+    /// Wrap `uniforms.time` around after this long, for shaders that loop (e.g. 8s)
+    #[arg(long = "loop", value_name = "TIME", value_parser = parse_loop)]
+    pub loop_duration: Option<f32>,
+
+    /// Clamp `uniforms.delta_time` to at most this long, so a stall doesn't hand simulations one
+    /// huge step (e.g. 100ms)
+    #[arg(long, value_name = "TIME", value_parser = parse_max_delta)]
+    pub max_delta: Option<f32>,
+
+    /// Average `uniforms.delta_time` over the last N frames, evening out uneven frame pacing
+    #[arg(long, value_name = "FRAMES", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub smooth_delta: u32,
+
+    /// Prioritize input responsiveness and show input-to-photon latency with --perf
+    #[arg(long)]
+    pub low_latency: bool,
+
+    /// Ambient mode for running all day: render only displayed frames, at 10 FPS unless --max-fps
+    /// says otherwise, and show the GPU's estimated load in a corner
+    #[arg(long, conflicts_with_all = ["window", "low_latency"])]
+    pub low_power: bool,
+
+    /// Always render at full resolution, instead of halving it while the terminal can't keep up
+    #[arg(long, conflicts_with = "window")]
+    pub no_adaptive: bool,
+
+    /// Treat a frame that takes longer than this on the GPU as a hung shader (e.g. 5s, 500ms)
+    #[arg(long, value_name = "TIME", default_value = "5s", value_parser = parse_duration)]
+    pub gpu_timeout: Duration,
+
+    /// Debugging: log every message between the GPU and terminal threads to FILE (JSON lines), for
+    /// `shadertui replay` and race condition reports
+    #[arg(long, value_name = "FILE", conflicts_with = "window")]
+    pub trace: Option<PathBuf>,
+
+    /// Write reloads, compile errors with their locations and frame rates to FILE as JSON lines, for
+    /// editor plugins (e.g. /dev/fd/3)
+    #[arg(long, value_name = "FILE", conflicts_with = "window")]
+    pub json_events: Option<PathBuf>,
+
     /// Render in a window instead of terminal
     #[arg(short, long)]
     pub window: bool,
+
+    // A window opened from the terminal with W, following the commands it sends on stdin
+    #[arg(long, hide = true)]
+    pub follow: bool,
+
+    /// Also show the terminal's render in a window, drawn from the same GPU frames (e.g. on a
+    /// projector while you drive it from the terminal)
+    #[arg(long, conflicts_with = "window")]
+    pub mirror: bool,
+
+    /// Experimental: edit the shader in a pane next to the render, recompiling as you type
+    #[arg(long, conflicts_with = "window")]
+    pub edit: bool,
+
+    /// Run as a screensaver: exit on any key, and pick a random shader if given a directory
+    #[arg(long, conflicts_with = "edit")]
+    pub screensaver: bool,
+
+    /// With --screensaver, wait until no key has been pressed for this long before starting
+    #[arg(long, value_name = "TIME", default_value = "0s", value_parser = parse_duration, requires = "screensaver")]
+    pub idle_delay: Duration,
+
+    /// How terminal cells are drawn: half-block, foreground, braille or ascii. foreground and ascii
+    /// take glyphs from dark to bright after a colon, e.g. ascii:" .oO@" [default: half-block, or
+    /// foreground on legacy Windows consoles]
+    #[arg(long, value_name = "MODE[:RAMP]", alias = "chars", value_parser = parse_cell_mode)]
+    pub cell_mode: Option<CellModeArg>,
+
+    /// Terminal color palette [default: truecolor, ansi256 on legacy Windows consoles, ansi16 on
+    /// the Linux console]
+    #[arg(long, value_enum)]
+    pub color_mode: Option<ColorMode>,
+
+    /// Dither colors that ansi256/ansi16 can't show, trading banding for a fine pattern
+    #[arg(long, value_enum, default_value_t = Dither::None)]
+    pub dither: Dither,
+
+    /// Draw the terminal image as text cells, or as sixel or kitty graphics at full pixel resolution
+    #[arg(long, value_enum, default_value_t = OutputMode::Cells, conflicts_with = "window")]
+    pub output: OutputMode,
+
+    /// Render the terminal image at 2x and filter it down to smooth jagged edges
+    #[arg(long, value_enum, conflicts_with = "window")]
+    pub aa: Option<AntiAliasing>,
+
+    /// Start with shader supersampling on: every pixel averages 2x2 samples (toggle with S)
+    #[arg(long, conflicts_with = "window")]
+    pub supersample: bool,
+
+    /// How `// @domain 1d` shaders are drawn in the terminal
+    #[arg(long, value_enum, default_value_t = SampleView::Scope)]
+    pub sample_view: SampleView,
+
+    /// Custom shell template to wrap the shader in (must contain the injection marker)
+    #[arg(long, value_name = "PATH", global = true)]
+    pub shell: Option<PathBuf>,
+
+    /// PNG or JPEG image to bind as a texture channel after the pass inputs (repeatable)
+    #[arg(long = "texture", value_name = "PATH", global = true)]
+    pub textures: Vec<PathBuf>,
+
+    /// Capture audio input as a spectrum + waveform texture channel (--audio=NAME picks a device)
+    #[arg(long, value_name = "DEVICE", num_args = 0..=1, require_equals = true, default_missing_value = "")]
+    pub audio: Option<String>,
+
+    /// Let media keys pause, seek and switch shaders (MPRIS, Linux)
+    #[arg(long)]
+    pub mpris: bool,
+
+    /// How `r` records the live render
+    #[arg(long, value_enum, default_value_t = RecordFormat::Gif)]
+    pub record_format: RecordFormat,
+
+    /// File for recordings started with `r` (later ones get -2, -3, ...) [default:
+    /// shadertui-<timestamp>-<n>.gif or .mp4 in the current directory]
+    #[arg(long, value_name = "PATH")]
+    pub record_output: Option<PathBuf>,
+
+    /// Text drawn into the corner of recordings and of frames from thumb, sheet and render, e.g.
+    /// "time={time:.2}s frame={frame}"
+    #[arg(long, value_name = "FORMAT", global = true, value_parser = Watermark::parse)]
+    pub watermark: Option<Watermark>,
+
+    /// Bytes of zeroed storage kept across frames, declared as `state: array<f32>` in every pass
+    #[arg(long, value_name = "BYTES", global = true, value_parser = parse_state_size)]
+    pub state_size: Option<u32>,
+
+    /// Animate a `// @uniform` parameter with an oscillator over shader time (sine, triangle, saw
+    /// or square), e.g. speed:sine:0.25hz:0..2 or mix:triangle:4s. Repeatable; without a range
+    /// the parameter's declared one is swept
+    #[arg(long, value_name = "PARAM:SHAPE:RATE[:MIN..MAX]", global = true, value_parser = Lfo::parse)]
+    pub lfo: Vec<Lfo>,
+
+    /// Keep the `state` contents when the shader is reloaded instead of zeroing them
+    #[arg(long, global = true, requires = "state_size")]
+    pub keep_state: bool,
+
+    /// Start a reloaded shader over at time 0 and frame 0 with zeroed `state`, instead of
+    /// continuing where the old one was (toggle with T)
+    #[arg(long)]
+    pub reset_on_reload: bool,
+
+    /// Ring the bell and show a desktop notification (terminals that support OSC 9) or flash the
+    /// window when a failing shader compiles again, or after a compile of 5 seconds or more
+    #[arg(long)]
+    pub notify: bool,
+
+    /// Show the running average of all frames since the last reload, resize or cursor move, for
+    /// progressive renderers like path tracers (`uniforms.sample_index` counts the frames)
+    #[arg(long)]
+    pub accumulate: bool,
+
+    /// Cap loop iterations and clamp coordinates/colors in the shader, so mistakes can't hang the GPU
+    #[arg(long, global = true)]
+    pub safe: bool,
+
+    /// Graphics API to render with [default: try every backend, then OpenGL, then a software adapter]
+    #[arg(long, value_enum, global = true)]
+    pub backend: Option<GpuBackend>,
+
+    /// Only use a software adapter (llvmpipe, lavapipe, WARP), e.g. on servers without a GPU
+    #[arg(long, visible_alias = "software", global = true)]
+    pub force_fallback: bool,
+
+    /// Render on this adapter: its number in --list-gpus, or part of its name (e.g. nvidia)
+    #[arg(
+        long,
+        value_name = "INDEX|NAME",
+        global = true,
+        conflicts_with = "force_fallback"
+    )]
+    pub adapter: Option<AdapterChoice>,
+
+    /// Print the GPU adapters on the selected backends, numbered for --adapter, and exit
+    #[arg(long)]
+    pub list_gpus: bool,
+
+    #[arg(skip)]
+    pub shell_options: ShellOptions,
+
+    // Command line for a window opened with W, see utils::mirror
+    #[arg(skip)]
+    pub mirror_args: Vec<OsString>,
+
+    // Set by apply_adapter when the adapter renders on the CPU
+    #[arg(skip)]
+    pub software_adapter: bool,
 }
 
-impl Cli {
-    pub fn parse_and_load() -> Result<(Self, String), Box<dyn std::error::Error>> {
-        // Parse command line arguments
-        let cli = Self::parse();
-
-        // Load shader file with import processing
-        let raw_shader_source = match fs::read_to_string(&cli.shader_file) {
-            Ok(content) => content,
-            Err(e) => {
-                eprintln!(
-                    "Error reading shader file '{}': {}",
-                    cli.shader_file.display(),
-                    e
-                );
-                std::process::exit(1);
-            }
+// A --cell-mode value, with the glyph ramp given after `foreground:` or `ascii:`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellModeArg {
+    pub mode: CellMode,
+    pub ramp: Option<Vec<char>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GpuBackend {
+    Vulkan,
+    Metal,
+    Dx12,
+    /// OpenGL, through EGL on Linux (works without a display)
+    Gl,
+}
+
+impl GpuBackend {
+    pub fn backends(self) -> wgpu::Backends {
+        match self {
+            GpuBackend::Vulkan => wgpu::Backends::VULKAN,
+            GpuBackend::Metal => wgpu::Backends::METAL,
+            GpuBackend::Dx12 => wgpu::Backends::DX12,
+            GpuBackend::Gl => wgpu::Backends::GL,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputMode {
+    /// Unicode cells colored with escape codes (see --cell-mode)
+    Cells,
+    /// Sixel images (xterm -ti vt340, foot, mlterm, WezTerm, ...)
+    Sixel,
+    /// Kitty graphics protocol images (kitty, WezTerm, Ghostty, Konsole, ...)
+    Kitty,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RecordFormat {
+    /// Animated GIF, encoded by shadertui
+    Gif,
+    /// Raw frames piped to ffmpeg, which picks the container from the file extension
+    Ffmpeg,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Write a starter shader from a template, optionally opening it right away
+    New(NewArgs),
+    /// Render a single frame offscreen and save it as a PNG thumbnail
+    Thumb(ThumbArgs),
+    /// Render frames at several times into one PNG grid (contact sheet)
+    Sheet(SheetArgs),
+    /// Render an animation offscreen into numbered PNG frames
+    Render(RenderArgs),
+    /// Measure a shader's frame times offscreen, optionally against a saved baseline
+    Bench(BenchArgs),
+    /// List the shaders in a directory with a small inline preview of each
+    List(ListArgs),
+    /// Inspect or clear files shadertui keeps in its cache directory
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Check the terminal and GPU setup and suggest flags
+    Doctor(DoctorArgs),
+    /// Browse the WGSL helpers built in as `// @import "std/..."`
+    Stdlib {
+        #[command(subcommand)]
+        action: StdlibAction,
+    },
+    /// Render a shader and keep running, switching shaders on `shadertui ctl` commands
+    Daemon(DaemonArgs),
+    /// Play a demo script: a timed show of shaders, parameters, zooms and fades
+    Demo(DemoArgs),
+    /// Control a running `shadertui daemon`
+    Ctl {
+        #[command(subcommand)]
+        action: CtlAction,
+    },
+    /// Re-render the frames of a --trace file with the uniforms they were computed with
+    Replay(ReplayArgs),
+    /// Run the functions marked `// @test` in a shader on the GPU and report which pass
+    Test(TestArgs),
+    /// Compile shaders without rendering or a GPU, for CI and editors; exits 1 on errors
+    Check(CheckArgs),
+    /// Print one frame as ANSI text, optionally within a byte budget (chat bots, MOTDs)
+    Print(PrintArgs),
+}
+
+#[derive(Args)]
+pub struct NewArgs {
+    /// Where to write the shader (.wgsl is added when there is no extension)
+    pub path: PathBuf,
+
+    /// Which starter to write
+    #[arg(short, long, value_enum, default_value_t = Template::Plasma)]
+    pub template: Template,
+
+    /// Open the new shader once it is written
+    #[arg(long)]
+    pub run: bool,
+
+    /// Replace the file if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Template {
+    /// Animated sine-wave plasma with two parameters
+    Plasma,
+    /// Sphere-traced 3D scene using the std/ library
+    Raymarch,
+    /// Conway's Game of Life as a feedback pass you can draw into
+    #[value(name = "gameoflife")]
+    GameOfLife,
+    /// Spectrum and waveform of the --audio input
+    #[cfg(feature = "audio")]
+    Audio,
+}
+
+impl Template {
+    // AIDEV-NOTE: The audio template reads the --audio channel, so it only exists in builds that
+    // can capture audio; elsewhere it would never compile
+    pub fn needs_audio(self) -> bool {
+        #[cfg(feature = "audio")]
+        if self == Template::Audio {
+            return true;
+        }
+        false
+    }
+}
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Also check that this shader or project.toml fits the GPU at the current terminal size
+    pub shader_file: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct DaemonArgs {
+    /// Shader or project.toml to start with
+    pub shader_file: PathBuf,
+
+    /// Render in a window instead of this terminal
+    #[arg(short, long)]
+    pub window: bool,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Trace written with --trace
+    pub trace: PathBuf,
+
+    /// Shader to render instead of the one the trace was recorded with
+    #[arg(long, value_name = "PATH")]
+    pub shader: Option<PathBuf>,
+
+    /// Write the frames the terminal was sent as DIR/frame_<n>.png
+    #[arg(short, long, value_name = "DIR")]
+    pub out: Option<PathBuf>,
+
+    /// Print every event of the trace in order, not just the summary
+    #[arg(long)]
+    pub timeline: bool,
+}
+
+#[derive(Args)]
+pub struct TestArgs {
+    /// WGSL shader (or library of helpers) with `// @test` functions returning bool
+    pub shader_file: PathBuf,
+}
+
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Shaders or project.toml files to check
+    #[arg(required = true)]
+    pub shader_files: Vec<PathBuf>,
+
+    /// How problems are reported
+    #[arg(long, value_enum, default_value_t = CheckFormat::Text)]
+    pub format: CheckFormat,
+}
+
+#[derive(Args)]
+pub struct PrintArgs {
+    /// Path to the WGSL or Shadertoy GLSL shader file, or a project.toml describing multiple passes
+    pub shader_file: PathBuf,
+
+    /// Shader time to print (e.g. 3.5s, 500ms, plain seconds, or a frame number like 210f)
+    #[arg(long, value_name = "TIME", default_value = "0s", value_parser = parse_seconds)]
+    pub at: f32,
+
+    /// Size in terminal cells
+    #[arg(long, value_name = "WxH", default_value = "40x12", value_parser = parse_size)]
+    pub cells: (u32, u32),
+
+    /// Largest output in bytes; colors get coarser until the frame fits
+    #[arg(long, value_name = "BYTES")]
+    pub max_bytes: Option<usize>,
+
+    /// Color escape codes to start from
+    #[arg(long, value_enum, default_value_t = ColorMode::Truecolor)]
+    pub color_mode: ColorMode,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CheckFormat {
+    /// Compiler errors with the offending line, as a normal run prints them
+    Text,
+    /// A JSON array of {file, line, column, length, message}, empty when everything compiles
+    Json,
+}
+
+#[derive(Args)]
+pub struct DemoArgs {
+    /// Demo script (TOML) listing the shaders and timed events of the show
+    pub script: PathBuf,
+}
+
+#[derive(Subcommand)]
+pub enum CtlAction {
+    /// Switch the daemon to another shader or project.toml
+    Load { shader_file: PathBuf },
+    /// Bind another image in place of a --texture the daemon was started with
+    Texture {
+        /// Which --texture to replace, counting from 0 in command-line order
+        index: usize,
+        /// PNG or JPEG image to bind instead
+        image: PathBuf,
+    },
+    /// Stop the shader's clock
+    Pause,
+    /// Restart the shader's clock where it was paused
+    Resume,
+    /// Move the shader's clock forwards or backwards, e.g. 10s or -2.5s
+    Seek {
+        #[arg(allow_hyphen_values = true, value_parser = parse_offset)]
+        offset: f32,
+    },
+    /// Switch to the next shader file in the current shader's directory
+    Next,
+    /// Switch to the previous shader file in the current shader's directory
+    Previous,
+    /// Open a window showing the daemon's terminal render at full resolution, or close it
+    Window,
+    /// Stop the daemon
+    Quit,
+}
+
+#[derive(Subcommand)]
+pub enum StdlibAction {
+    /// List the modules and the functions each one defines
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Delete all cached files
+    Clear,
+    /// List cache entries and their sizes
+    Ls,
+    /// Print the total size of the cache
+    Size,
+}
+
+#[derive(Args)]
+pub struct ThumbArgs {
+    /// Path to the WGSL or Shadertoy GLSL shader file, or a project.toml describing multiple passes
+    pub shader_file: PathBuf,
+
+    /// Output PNG path
+    #[arg(short, long, value_name = "PATH")]
+    pub output: PathBuf,
+
+    /// Also write the alpha channel as a grayscale matte PNG (white = opaque)
+    #[arg(long, value_name = "PATH")]
+    pub matte: Option<PathBuf>,
+
+    /// Shader time to render (e.g. 3.5s, 500ms, plain seconds, or a frame number like 210f)
+    #[arg(long, value_name = "TIME", default_value = "0s", value_parser = parse_seconds)]
+    pub at: f32,
+
+    /// Thumbnail size in pixels
+    #[arg(long, value_name = "WxH", default_value = "320x180", value_parser = parse_size)]
+    pub size: (u32, u32),
+}
+
+#[derive(Args)]
+pub struct RenderArgs {
+    /// Path to the WGSL or Shadertoy GLSL shader file, or a project.toml describing multiple passes
+    pub shader_file: PathBuf,
+
+    /// Output directory for frame_0000.png, frame_0001.png, ... (created if missing)
+    #[arg(short, long, value_name = "DIR")]
+    pub out: PathBuf,
+
+    /// Number of frames to render, starting at --from. A shader that calls request_exit(code) stops
+    /// sooner, and shadertui exits with that code
+    #[arg(long, default_value_t = 60, conflicts_with = "to", value_parser = clap::value_parser!(u32).range(1..))]
+    pub frames: u32,
+
+    /// Frame rate; frame N is rendered at shader time N / FPS
+    #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u32).range(1..))]
+    pub fps: u32,
+
+    /// Shader time of the first frame (e.g. 3.5s, 500ms, plain seconds, or a frame number like 210f)
+    #[arg(long, value_name = "TIME", default_value = "0s", value_parser = parse_seconds)]
+    pub from: f32,
+
+    /// End of the segment (exclusive), instead of counting --frames
+    #[arg(long, value_name = "TIME", value_parser = parse_seconds)]
+    pub to: Option<f32>,
+
+    /// Also write each frame's alpha channel as a grayscale matte_NNNN.png (white = opaque)
+    #[arg(long)]
+    pub matte: bool,
+
+    /// Frame size in pixels
+    #[arg(long, value_name = "WxH", default_value = "640x360", value_parser = parse_size)]
+    pub size: (u32, u32),
+}
+
+impl RenderArgs {
+    // AIDEV-NOTE: Frame numbers to export. --from and --to are snapped to the --fps timestep, and
+    // frames keep their number from t=0 so segments rendered separately line up
+    pub fn frame_range(&self) -> Result<Range<u32>, ShadertuiError> {
+        let fps = self.fps as f32;
+        let first = (self.from * fps).round() as u32;
+        let end = match self.to {
+            Some(to) => (to * fps).round() as u32,
+            None => first.saturating_add(self.frames),
         };
+        if end <= first {
+            return Err(msg!("error.render_range").into());
+        }
+        Ok(first..end)
+    }
+}
 
-        let user_shader_source = match process_imports(&cli.shader_file, &raw_shader_source) {
-            Ok((processed, _deps)) => processed,
-            Err(e) => {
-                eprintln!("Import processing error: {e}");
-                std::process::exit(1);
-            }
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Path to the WGSL or Shadertoy GLSL shader file, or a project.toml describing multiple passes
+    pub shader_file: PathBuf,
+
+    /// Number of frames to time
+    #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u32).range(1..))]
+    pub frames: u32,
+
+    /// Frames rendered before timing starts, to let the GPU and driver settle
+    #[arg(long, default_value_t = 30)]
+    pub warmup: u32,
+
+    /// Frame size in pixels
+    #[arg(long, value_name = "WxH", default_value = "640x360", value_parser = parse_size)]
+    pub size: (u32, u32),
+
+    /// Write the results as JSON, for use as a later --baseline
+    #[arg(long, value_name = "PATH")]
+    pub save: Option<PathBuf>,
+
+    /// Compare against results saved with --save and fail if the shader got slower
+    #[arg(long, value_name = "PATH")]
+    pub baseline: Option<PathBuf>,
+
+    /// Slowdown in percent of the mean, median or 95th percentile frame time that fails --baseline
+    #[arg(long, value_name = "PERCENT", default_value_t = 5.0, value_parser = parse_threshold)]
+    pub threshold: f64,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Directory of shaders (subdirectories with a project.toml are listed too)
+    pub dir: PathBuf,
+
+    /// Only print names and titles
+    #[arg(long)]
+    pub no_preview: bool,
+
+    /// Shader time to preview (e.g. 3.5s, 500ms, plain seconds, or a frame number like 210f)
+    #[arg(long, value_name = "TIME", default_value = "0s", value_parser = parse_seconds)]
+    pub at: f32,
+
+    /// Preview size in terminal cells
+    #[arg(long, value_name = "WxH", default_value = "40x12", value_parser = parse_size)]
+    pub cells: (u32, u32),
+
+    /// Color escape codes for the previews [default: detected from the terminal]
+    #[arg(long, value_enum)]
+    pub color_mode: Option<ColorMode>,
+}
+
+#[derive(Args)]
+pub struct SheetArgs {
+    /// Path to the WGSL or Shadertoy GLSL shader file, or a project.toml describing multiple passes
+    pub shader_file: PathBuf,
+
+    /// Output PNG path
+    #[arg(short, long, value_name = "PATH")]
+    pub output: PathBuf,
+
+    /// Also write the alpha channel as a grayscale matte PNG (white = opaque)
+    #[arg(long, value_name = "PATH")]
+    pub matte: Option<PathBuf>,
+
+    /// Comma-separated shader times, one cell each in reading order (e.g. 0,2s,4.5s,300f)
+    #[arg(long, value_name = "TIMES", required_unless_present = "from", conflicts_with = "from", value_delimiter = ',', value_parser = parse_seconds)]
+    pub times: Vec<f32>,
+
+    /// Start of a segment to sample evenly instead of listing --times
+    #[arg(long, value_name = "TIME", requires = "to", value_parser = parse_seconds)]
+    pub from: Option<f32>,
+
+    /// End of the segment (inclusive)
+    #[arg(long, value_name = "TIME", requires = "from", value_parser = parse_seconds)]
+    pub to: Option<f32>,
+
+    /// Number of frames to sample between --from and --to
+    #[arg(long, default_value_t = 4, requires = "from", value_parser = clap::value_parser!(u32).range(1..))]
+    pub count: u32,
+
+    /// Number of columns in the grid
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+    pub cols: u32,
+
+    /// Size of each cell in pixels
+    #[arg(long, value_name = "WxH", default_value = "320x180", value_parser = parse_size)]
+    pub size: (u32, u32),
+}
+
+impl SheetArgs {
+    // AIDEV-NOTE: Cell times in reading order. A --from/--to segment is sampled evenly and snapped to
+    // the export timestep so every cell is an exact frame
+    pub fn cell_times(&self) -> Vec<f32> {
+        let (Some(from), Some(to)) = (self.from, self.to) else {
+            return self.times.clone();
         };
 
-        // Inject user shader into terminal shell for validation (use terminal as default)
-        let complete_shader_for_validation =
-            match inject_user_shader(&user_shader_source, ShellType::Terminal) {
-                Ok(complete) => complete,
-                Err(e) => {
-                    eprintln!("Shader shell injection error: {e}");
-                    std::process::exit(1);
+        let steps = self.count.saturating_sub(1).max(1) as f32;
+        (0..self.count)
+            .map(|i| {
+                let time = from + (to - from) * i as f32 / steps;
+                (time * EXPORT_FPS).round() / EXPORT_FPS
+            })
+            .collect()
+    }
+}
+
+impl Cli {
+    pub fn parse_args() -> Result<Self, ShadertuiError> {
+        // Parse command line arguments
+        let mut cli = Self::parse();
+        select_adapters(AdapterSelection {
+            backends: cli.backend.map(GpuBackend::backends),
+            force_fallback: cli.force_fallback,
+            adapter: cli.adapter.clone(),
+        });
+        lfo::bind(cli.lfo.clone());
+
+        // Load the custom shell template, if any, before any injection happens
+        cli.shell_options =
+            ShellOptions::load(cli.shell.as_deref()).map_err(|e| msg!("error.shell", error = e))?;
+        cli.shell_options.safe_mode = cli.safe;
+        cli.shell_options.state_size = cli.state_size;
+        cli.shell_options.keep_state = cli.keep_state;
+        cli.shell_options.accumulate = cli.accumulate;
+
+        // Images are decoded once and shared by every renderer and reload
+        for path in &cli.textures {
+            let image = load_image(path)?;
+            cli.shell_options.textures.push(Arc::new(image));
+        }
+
+        if let Some(device) = &cli.audio {
+            let device = (!device.is_empty()).then_some(device.as_str());
+            let capture =
+                AudioCapture::start(device).map_err(|e| msg!("error.audio_capture", error = e))?;
+            cli.shell_options.audio = Some(capture);
+        }
+
+        // A screensaver given a directory shows a random shader from it
+        if cli.screensaver && cli.shader_file.as_deref().is_some_and(Path::is_dir) {
+            let dir = cli.shader_file().to_path_buf();
+            match pick_shader(&dir, &cli.shell_options) {
+                Ok(Some(path)) => cli.shader_file = Some(path),
+                Ok(None) => {
+                    return Err(msg!("error.no_working_shaders", dir = dir.display()).into())
                 }
-            };
+                Err(e) => return Err(msg!("error.read_dir", dir = dir.display(), error = e).into()),
+            }
+        }
 
-        // Validate the complete injected shader
-        if let Err(e) = validate_shader(&complete_shader_for_validation) {
-            eprintln!("Shader compilation error: {e}");
-            std::process::exit(1);
+        // The editor pane holds a single source file, so projects can't be live-edited yet
+        if cli.edit && cli.shader_file.as_deref().is_some_and(is_manifest_path) {
+            return Err(msg!("error.edit_manifest").into());
         }
 
-        // Return the original user shader source (not the injected version)
-        // Renderers will do their own injection with appropriate shell type
-        Ok((cli, user_shader_source))
+        Ok(cli)
+    }
+
+    // AIDEV-NOTE: clap guarantees a shader file whenever no subcommand is given
+    pub fn shader_file(&self) -> &Path {
+        self.shader_file
+            .as_deref()
+            .expect("shader file is required without a subcommand")
+    }
+
+    // AIDEV-NOTE: Frame rate cap for the GPU and terminal threads, which --low-power lowers by default
+    pub fn frame_rate_cap(&self) -> Option<u32> {
+        self.max_fps.or(self.low_power.then_some(LOW_POWER_FPS))
     }
 
     pub fn is_windowed_mode(&self) -> bool {
-        self.window
+        self.window || self.follow
+    }
+
+    // AIDEV-NOTE: Fill options not given on the command line from the shader's `// @fps`, `// @scale`
+    // and `// @loop` header pragmas
+    pub fn apply_settings(&mut self, settings: &ShaderSettings) {
+        self.max_fps = self.max_fps.or(settings.fps);
+        self.scale = self.scale.or(settings.scale);
+        self.loop_duration = self.loop_duration.or(settings.loop_duration);
+    }
+
+    // AIDEV-NOTE: Software adapters (llvmpipe, lavapipe, WARP) render at SOFTWARE_SCALE unless
+    // --scale or `// @scale` says otherwise, and --perf flags them, so a slow CPU render over SSH
+    // isn't mistaken for a slow shader
+    pub fn apply_adapter(&mut self, support: Option<&AdapterSupport>) {
+        self.software_adapter = support.is_some_and(|support| support.software);
+        if self.software_adapter {
+            self.scale = self.scale.or(Some(SOFTWARE_SCALE));
+        }
+    }
+
+    pub fn gpu_options(&self) -> GpuOptions {
+        GpuOptions {
+            gpu_timeout: self.gpu_timeout,
+            sample_view: self.sample_view,
+            aa: self.aa,
+            scale: self.scale.unwrap_or(1.0),
+            loop_duration: self.loop_duration,
+            max_fps: self.frame_rate_cap(),
+            pipelined_readback: !self.low_latency,
+            gpu_timing: self.perf,
+            delta_smoothing: self.delta_smoothing(),
+        }
+    }
+
+    pub fn delta_smoothing(&self) -> DeltaSmoothing {
+        DeltaSmoothing {
+            max_delta: self.max_delta,
+            frames: self.smooth_delta as usize,
+        }
+    }
+
+    pub fn recording_options(&self) -> RecordingOptions {
+        RecordingOptions {
+            format: self.record_format,
+            output: self.record_output.clone(),
+            watermark: self.watermark.clone(),
+        }
+    }
+}
+
+// AIDEV-NOTE: Load a shader or project and validate every pass
+pub fn load_validated_project(
+    shader_file: &Path,
+    shell_options: &ShellOptions,
+) -> Result<ShaderProject, ShadertuiError> {
+    // Load shader file (or project manifest) with import processing
+    let project = ShaderProject::load(shader_file)?;
+
+    // Validate every pass injected into its shell (use terminal as default output shell)
+    validate_project(&project, ShellType::Terminal, shell_options)?;
+
+    // Return the original user sources (not the injected versions)
+    // Renderers will do their own injection with appropriate shell type
+    Ok(project)
+}
+
+// AIDEV-NOTE: Everything after the first colon is the ramp, so it may contain colons itself
+fn parse_cell_mode(value: &str) -> Result<CellModeArg, String> {
+    let (name, ramp) = match value.split_once(':') {
+        Some((name, ramp)) => (name, Some(ramp.chars().collect::<Vec<_>>())),
+        None => (value, None),
+    };
+    let mode = CellMode::from_str(name, true)?;
+    match &ramp {
+        Some(_) if !matches!(mode, CellMode::Foreground | CellMode::Ascii) => Err(format!(
+            "{name} cells don't take a glyph ramp (only foreground and ascii do)"
+        )),
+        Some(glyphs) if glyphs.len() < 2 => Err(format!(
+            "glyph ramp '{}' needs at least two characters, from dark to bright",
+            glyphs.iter().collect::<String>()
+        )),
+        _ => Ok(CellModeArg { mode, ramp }),
+    }
+}
+
+fn parse_max_delta(value: &str) -> Result<f32, String> {
+    match parse_seconds(value)? {
+        time if time > 0.0 => Ok(time),
+        _ => Err(format!(
+            "invalid maximum delta '{value}' (must be longer than 0)"
+        )),
+    }
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    parse_seconds(value)
+        .and_then(|seconds| Duration::try_from_secs_f32(seconds).map_err(|e| e.to_string()))
+}
+
+// A time that may be negative, for seeking backwards
+fn parse_offset(value: &str) -> Result<f32, String> {
+    match value.strip_prefix('-') {
+        Some(time) => parse_seconds(time).map(|seconds| -seconds),
+        None => parse_seconds(value.strip_prefix('+').unwrap_or(value)),
+    }
+}
+
+fn parse_threshold(value: &str) -> Result<f64, String> {
+    match value.trim_end_matches('%').parse::<f64>() {
+        Ok(percent) if percent >= 0.0 => Ok(percent),
+        _ => Err(format!(
+            "invalid threshold '{value}' (expected a percentage, e.g. 5 or 2.5%)"
+        )),
+    }
+}
+
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let parsed = value
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)));
+
+    match parsed {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(format!(
+            "invalid size '{value}' (expected WIDTHxHEIGHT, e.g. 320x180)"
+        )),
+    }
+}
+
+// AIDEV-NOTE: `state` is an array<f32>, so its size must be a whole number of elements
+fn parse_state_size(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(bytes) if bytes > 0 && bytes % 4 == 0 => Ok(bytes),
+        _ => Err(format!(
+            "invalid state size '{value}' (expected a positive multiple of 4 bytes)"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cell_mode() {
+        assert_eq!(
+            parse_cell_mode("braille"),
+            Ok(CellModeArg {
+                mode: CellMode::Braille,
+                ramp: None
+            })
+        );
+        assert_eq!(
+            parse_cell_mode("ascii: .:oO@"),
+            Ok(CellModeArg {
+                mode: CellMode::Ascii,
+                ramp: Some(vec![' ', '.', ':', 'o', 'O', '@'])
+            })
+        );
+        assert!(parse_cell_mode("half-block:.#").is_err());
+        assert!(parse_cell_mode("ascii:#").is_err());
+        assert!(parse_cell_mode("dots").is_err());
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_seconds("3.5s"), Ok(3.5));
+        assert_eq!(parse_seconds("500ms"), Ok(0.5));
+        assert_eq!(parse_seconds("2"), Ok(2.0));
+        assert_eq!(parse_seconds("90f"), Ok(1.5));
+        assert!(parse_seconds("-1s").is_err());
+        assert!(parse_seconds("soon").is_err());
+        assert!(parse_seconds("inf").is_err());
+        assert!(parse_duration("1e30s").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("320x180"), Ok((320, 180)));
+        assert!(parse_size("0x180").is_err());
+        assert!(parse_size("320").is_err());
     }
 }
@@ -0,0 +1,160 @@
+use std::fmt::Write;
+
+use crate::utils::options::ColorMode;
+
+// AIDEV-NOTE: Levels of the 6x6x6 cube in the xterm 256-color palette (indices 16..=231)
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// AIDEV-NOTE: xterm's default colors for the 16 basic ANSI colors. Terminals theme these freely, so
+// the nearest entry is a guess at what the user will see
+const ANSI16_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+// AIDEV-NOTE: Nearest xterm-256 palette index, choosing between the color cube and the gray ramp
+// (indices 232..=255, 8 + 10 * n)
+pub fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = |value: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &level)| (level as i32 - value as i32).abs())
+            .map(|(index, _)| index as u8)
+            .unwrap()
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube_color = (
+        CUBE_LEVELS[ri as usize],
+        CUBE_LEVELS[gi as usize],
+        CUBE_LEVELS[bi as usize],
+    );
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = ((average.saturating_sub(3)) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+
+    if distance_sq((r, g, b), (gray_level, gray_level, gray_level))
+        < distance_sq((r, g, b), cube_color)
+    {
+        232 + gray_index
+    } else {
+        16 + 36 * ri + 6 * gi + bi
+    }
+}
+
+// Nearest of the 16 basic ANSI colors; 0..=7 are the normal colors, 8..=15 the bright ones
+pub fn rgb_to_ansi16(r: u8, g: u8, b: u8) -> u8 {
+    (0..16)
+        .min_by_key(|&index| distance_sq((r, g, b), ANSI16_PALETTE[index]))
+        .unwrap() as u8
+}
+
+// The color of an xterm-256 palette index
+pub fn ansi256_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16_PALETTE[index as usize],
+        16..=231 => {
+            let cube = index - 16;
+            (
+                CUBE_LEVELS[(cube / 36) as usize],
+                CUBE_LEVELS[(cube / 6 % 6) as usize],
+                CUBE_LEVELS[(cube % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+// AIDEV-NOTE: The color the terminal shows for `rgb` in `mode`, which dithering diffuses the
+// difference to
+pub fn palette_color((r, g, b): (u8, u8, u8), mode: ColorMode) -> (u8, u8, u8) {
+    match mode {
+        ColorMode::Truecolor => (r, g, b),
+        ColorMode::Ansi256 => ansi256_rgb(rgb_to_ansi256(r, g, b)),
+        ColorMode::Ansi16 => ANSI16_PALETTE[rgb_to_ansi16(r, g, b) as usize],
+    }
+}
+
+fn distance_sq(a: (u8, u8, u8), b: (u8, u8, u8)) -> i32 {
+    let dr = a.0 as i32 - b.0 as i32;
+    let dg = a.1 as i32 - b.1 as i32;
+    let db = a.2 as i32 - b.2 as i32;
+    dr * dr + dg * dg + db * db
+}
+
+// AIDEV-NOTE: SGR color escape for `layer` ("38" foreground, "48" background) in the given mode.
+// Written in place, without allocating, since it runs for most cells of every frame
+pub fn push_ansi_color(out: &mut String, layer: &str, (r, g, b): (u8, u8, u8), mode: ColorMode) {
+    let _ = match mode {
+        ColorMode::Truecolor => write!(out, "\x1b[{layer};2;{r};{g};{b}m"),
+        ColorMode::Ansi256 => write!(out, "\x1b[{layer};5;{}m", rgb_to_ansi256(r, g, b)),
+        ColorMode::Ansi16 => {
+            // 30-37 and 90-97 for foregrounds, 40-47 and 100-107 for backgrounds
+            let index = rgb_to_ansi16(r, g, b);
+            let code = if index < 8 { 30 + index } else { 82 + index };
+            let code = if layer == "48" { code + 10 } else { code };
+            write!(out, "\x1b[{code}m")
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rgb_to_ansi256() {
+        assert_eq!(rgb_to_ansi256(0, 0, 0), 16);
+        assert_eq!(rgb_to_ansi256(255, 255, 255), 231);
+        assert_eq!(rgb_to_ansi256(255, 0, 0), 196);
+        // Mid grays prefer the finer gray ramp
+        assert_eq!(rgb_to_ansi256(128, 128, 128), 244);
+    }
+
+    #[test]
+    fn test_palette_color() {
+        assert_eq!(rgb_to_ansi16(250, 10, 10), 9);
+        assert_eq!(rgb_to_ansi16(100, 100, 110), 8);
+        // Palette colors map to themselves
+        for index in 16..=255 {
+            assert_eq!(
+                rgb_to_ansi256(
+                    ansi256_rgb(index).0,
+                    ansi256_rgb(index).1,
+                    ansi256_rgb(index).2
+                ),
+                index
+            );
+        }
+        assert_eq!(palette_color((250, 10, 10), ColorMode::Ansi16), (255, 0, 0));
+        assert_eq!(palette_color((1, 2, 3), ColorMode::Truecolor), (1, 2, 3));
+    }
+
+    #[test]
+    fn test_push_ansi_color() {
+        let mut out = String::new();
+        push_ansi_color(&mut out, "38", (255, 0, 7), ColorMode::Truecolor);
+        push_ansi_color(&mut out, "48", (255, 0, 0), ColorMode::Ansi256);
+        push_ansi_color(&mut out, "38", (0, 200, 0), ColorMode::Ansi16);
+        push_ansi_color(&mut out, "48", (255, 255, 255), ColorMode::Ansi16);
+        assert_eq!(out, "\x1b[38;2;255;0;7m\x1b[48;5;196m\x1b[32m\x1b[107m");
+    }
+}
@@ -0,0 +1,327 @@
+use std::env;
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use crate::utils::paths::cache_dir;
+
+const SOCKET_NAME: &str = "shadertui.sock";
+
+// AIDEV-NOTE: Requests from `shadertui ctl` to a running `shadertui daemon`, one line each:
+//   load <absolute path>
+//   texture <index> <absolute path>
+//   param <name> <value>
+//   zoom <x> <y> <width> <height> | zoom off
+//   fade <brightness> <seconds>
+//   pause | resume | toggle-pause
+//   seek <seconds>
+//   time <seconds>
+//   speed <factor>
+//   skip <shaders>
+//   window
+//   quit
+// `shadertui demo` and --mpris send the same commands from a thread instead of a socket, and a
+// mirror window (utils::mirror) reads them from stdin
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    Load(PathBuf),
+    // Rebind --texture number `index` (0-based) to another image
+    Texture(usize, PathBuf),
+    // Set a `// @uniform` parameter by name, kept for shaders loaded later
+    Param(String, f32),
+    // Region to show as in the zoom inspector, see gpu::uniforms::zoom_into
+    Zoom(Option<[f32; 4]>),
+    // Move the terminal's brightness to `brightness` (0 black, 1 normal) over `seconds`
+    Fade(f32, f32),
+    // Stop (true) or restart (false) the shader's clock, or flip it with None
+    Pause(Option<bool>),
+    // Move the shader's clock by seconds, backwards when negative
+    Seek(f32),
+    // Set the shader's clock to this many seconds
+    Time(f32),
+    // Run the shader's clock at this speed, see utils::timeline
+    Speed(f32),
+    // Load the shader file this many places after the current one in its directory, see
+    // project::sibling_shader
+    Skip(i32),
+    // Open a window mirroring the terminal render, or close it
+    Window,
+    Quit,
+}
+
+impl ControlCommand {
+    pub fn to_line(&self) -> String {
+        match self {
+            ControlCommand::Load(path) => format!("load {}\n", path.display()),
+            ControlCommand::Texture(index, path) => {
+                format!("texture {index} {}\n", path.display())
+            }
+            ControlCommand::Param(name, value) => format!("param {name} {value}\n"),
+            ControlCommand::Zoom(Some([x, y, width, height])) => {
+                format!("zoom {x} {y} {width} {height}\n")
+            }
+            ControlCommand::Zoom(None) => "zoom off\n".to_string(),
+            ControlCommand::Fade(brightness, seconds) => format!("fade {brightness} {seconds}\n"),
+            ControlCommand::Pause(Some(true)) => "pause\n".to_string(),
+            ControlCommand::Pause(Some(false)) => "resume\n".to_string(),
+            ControlCommand::Pause(None) => "toggle-pause\n".to_string(),
+            ControlCommand::Seek(seconds) => format!("seek {seconds}\n"),
+            ControlCommand::Time(seconds) => format!("time {seconds}\n"),
+            ControlCommand::Speed(factor) => format!("speed {factor}\n"),
+            ControlCommand::Skip(shaders) => format!("skip {shaders}\n"),
+            ControlCommand::Window => "window\n".to_string(),
+            ControlCommand::Quit => "quit\n".to_string(),
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        match line.split_once(' ') {
+            Some(("load", path)) if !path.is_empty() => Some(ControlCommand::Load(path.into())),
+            Some(("texture", rest)) => {
+                let (index, path) = rest.split_once(' ')?;
+                let index = index.parse().ok()?;
+                (!path.is_empty()).then(|| ControlCommand::Texture(index, path.into()))
+            }
+            Some(("param", rest)) => {
+                let (name, value) = rest.split_once(' ')?;
+                let value = value.parse().ok()?;
+                (!name.is_empty()).then(|| ControlCommand::Param(name.to_string(), value))
+            }
+            Some(("zoom", "off")) => Some(ControlCommand::Zoom(None)),
+            Some(("zoom", rest)) => {
+                let region: Vec<f32> = rest
+                    .split(' ')
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .ok()?;
+                Some(ControlCommand::Zoom(Some(region.try_into().ok()?)))
+            }
+            Some(("fade", rest)) => {
+                let (brightness, seconds) = rest.split_once(' ')?;
+                Some(ControlCommand::Fade(
+                    brightness.parse().ok()?,
+                    seconds.parse().ok()?,
+                ))
+            }
+            Some(("seek", seconds)) => Some(ControlCommand::Seek(seconds.parse().ok()?)),
+            Some(("time", seconds)) => Some(ControlCommand::Time(seconds.parse().ok()?)),
+            Some(("speed", factor)) => {
+                let factor: f32 = factor.parse().ok()?;
+                (factor > 0.0).then_some(ControlCommand::Speed(factor))
+            }
+            Some(("skip", shaders)) => Some(ControlCommand::Skip(shaders.parse().ok()?)),
+            None => match line {
+                "pause" => Some(ControlCommand::Pause(Some(true))),
+                "resume" => Some(ControlCommand::Pause(Some(false))),
+                "toggle-pause" => Some(ControlCommand::Pause(None)),
+                "window" => Some(ControlCommand::Window),
+                "quit" => Some(ControlCommand::Quit),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+pub type ControlReceiver = flume::Receiver<ControlCommand>;
+
+// Commands from several sources (e.g. a daemon with --mpris) in the order they arrive
+pub fn merge(sources: Vec<ControlReceiver>) -> Option<ControlReceiver> {
+    if sources.len() <= 1 {
+        return sources.into_iter().next();
+    }
+    let (sender, commands) = flume::unbounded();
+    for source in sources {
+        let sender = sender.clone();
+        std::thread::spawn(move || {
+            for command in source.iter() {
+                if sender.send(command).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+    Some(commands)
+}
+
+// Commands one per line until `reader` ends, which quits; unknown lines are skipped
+pub fn read_commands(reader: impl BufRead + Send + 'static) -> ControlReceiver {
+    let (sender, commands) = flume::unbounded();
+    std::thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Some(command) = ControlCommand::parse(&line) {
+                if sender.send(command).is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = sender.send(ControlCommand::Quit);
+    });
+    commands
+}
+
+// AIDEV-NOTE: One daemon per user: the socket lives in $XDG_RUNTIME_DIR (private to the user),
+// falling back to the cache directory
+pub fn socket_path() -> Option<PathBuf> {
+    env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(cache_dir)
+        .map(|dir| dir.join(SOCKET_NAME))
+}
+
+#[cfg(unix)]
+pub use unix::{send_command, ControlServer};
+
+#[cfg(unix)]
+mod unix {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::PathBuf;
+
+    use super::{socket_path, ControlCommand, ControlReceiver};
+
+    // AIDEV-NOTE: Accepts control connections on a background thread and forwards each command to
+    // the render loop, which polls `commands`. `texture_count` is the number of --texture images,
+    // so bad indices are rejected to the caller. The socket file is removed on drop
+    pub struct ControlServer {
+        path: PathBuf,
+        pub commands: ControlReceiver,
+    }
+
+    impl ControlServer {
+        pub fn start(texture_count: usize) -> Result<Self, String> {
+            let path = socket_path().ok_or("Could not determine where to put the daemon socket")?;
+            if UnixStream::connect(&path).is_ok() {
+                return Err(format!(
+                    "A shadertui daemon is already running ({})",
+                    path.display()
+                ));
+            }
+            // Left behind by a daemon that didn't exit cleanly
+            let _ = fs::remove_file(&path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Could not create '{}': {e}", parent.display()))?;
+            }
+            let listener = UnixListener::bind(&path)
+                .map_err(|e| format!("Could not listen on '{}': {e}", path.display()))?;
+
+            let (sender, commands) = flume::unbounded();
+            std::thread::spawn(move || {
+                for mut stream in listener.incoming().flatten() {
+                    let mut line = String::new();
+                    if BufReader::new(&stream).read_line(&mut line).is_err() {
+                        continue;
+                    }
+                    let reply = match ControlCommand::parse(&line) {
+                        Some(ControlCommand::Texture(index, _)) if index >= texture_count => {
+                            format!(
+                                "error: there is no texture {index} ({texture_count} --texture \
+                                 images are bound)\n"
+                            )
+                        }
+                        Some(command) => match sender.send(command) {
+                            Ok(()) => "ok\n".to_string(),
+                            Err(_) => "error: the daemon is shutting down\n".to_string(),
+                        },
+                        None => "error: unknown command\n".to_string(),
+                    };
+                    let _ = stream.write_all(reply.as_bytes());
+                }
+            });
+
+            Ok(Self { path, commands })
+        }
+    }
+
+    impl Drop for ControlServer {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    pub fn send_command(command: &ControlCommand) -> Result<(), String> {
+        let path = socket_path().ok_or("Could not determine where the daemon socket is")?;
+        let mut stream = UnixStream::connect(&path)
+            .map_err(|_| "No shadertui daemon is running (start one with `shadertui daemon`)")?;
+        stream
+            .write_all(command.to_line().as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        let mut reply = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut reply)
+            .map_err(|e| e.to_string())?;
+        match reply.trim_end().strip_prefix("error: ") {
+            Some(error) => Err(format!("Daemon error: {error}")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub use fallback::{send_command, ControlServer};
+
+#[cfg(not(unix))]
+mod fallback {
+    use super::{ControlCommand, ControlReceiver};
+
+    const UNSUPPORTED: &str = "Daemon mode needs Unix domain sockets and isn't available here";
+
+    pub struct ControlServer {
+        pub commands: ControlReceiver,
+    }
+
+    impl ControlServer {
+        pub fn start(_texture_count: usize) -> Result<Self, String> {
+            Err(UNSUPPORTED.to_string())
+        }
+    }
+
+    pub fn send_command(_command: &ControlCommand) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_round_trip() {
+        let load = ControlCommand::Load(PathBuf::from("/shaders/my plasma.wgsl"));
+        assert_eq!(ControlCommand::parse(&load.to_line()), Some(load));
+        assert_eq!(
+            ControlCommand::parse(&ControlCommand::Quit.to_line()),
+            Some(ControlCommand::Quit)
+        );
+        let texture = ControlCommand::Texture(1, PathBuf::from("/images/b.png"));
+        assert_eq!(ControlCommand::parse(&texture.to_line()), Some(texture));
+        assert_eq!(ControlCommand::parse("texture one /images/b.png\n"), None);
+        for command in [
+            ControlCommand::Param("speed".to_string(), 2.5),
+            ControlCommand::Zoom(Some([0.25, 0.5, 0.125, 0.125])),
+            ControlCommand::Zoom(None),
+            ControlCommand::Fade(0.0, 1.5),
+            ControlCommand::Pause(Some(true)),
+            ControlCommand::Pause(Some(false)),
+            ControlCommand::Pause(None),
+            ControlCommand::Seek(-2.5),
+            ControlCommand::Time(12.25),
+            ControlCommand::Speed(0.5),
+            ControlCommand::Skip(-1),
+            ControlCommand::Window,
+        ] {
+            assert_eq!(ControlCommand::parse(&command.to_line()), Some(command));
+        }
+        assert_eq!(ControlCommand::parse("zoom 0.5 0.5\n"), None);
+        assert_eq!(ControlCommand::parse("load \n"), None);
+        assert_eq!(ControlCommand::parse("speed 0\n"), None);
+        assert_eq!(ControlCommand::parse("reboot\n"), None);
+    }
+}
@@ -0,0 +1,168 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::utils::validation::Diagnostic;
+
+// AIDEV-NOTE: --json-events FILE streams what editor plugins show next to the shader being edited:
+// reloads, compile results located in the user's files, and the frame rates once a second. One JSON
+// object per line with seconds since the start, like --trace, but a small stable format for other
+// programs rather than a debugging log. FILE may be /dev/fd/N for a pipe the editor opened. Set once
+// at startup; emit() costs one atomic load when off
+struct EventSink {
+    file: Mutex<File>,
+    start: Instant,
+}
+
+static SINK: OnceLock<EventSink> = OnceLock::new();
+
+// How often Stats events are written
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn start(path: &Path, shader: &Path) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Could not open event stream '{}': {e}", path.display()))?;
+    let _ = SINK.set(EventSink {
+        file: Mutex::new(file),
+        start: Instant::now(),
+    });
+    emit(|| EditorEvent::Start {
+        shader: shader.to_path_buf(),
+    });
+    Ok(())
+}
+
+pub fn enabled() -> bool {
+    SINK.get().is_some()
+}
+
+// The event is only built when the stream is on
+pub fn emit(event: impl FnOnce() -> EditorEvent) {
+    let Some(sink) = SINK.get() else {
+        return;
+    };
+    let record = EventRecord {
+        t: sink.start.elapsed().as_secs_f64(),
+        event: event(),
+    };
+    let Ok(mut line) = serde_json::to_string(&record) else {
+        return;
+    };
+    line.push('\n');
+    // Flushed line by line, since editors read the stream while shadertui runs
+    let mut file = sink.file.lock().unwrap();
+    let _ = file.write_all(line.as_bytes());
+    let _ = file.flush();
+}
+
+#[derive(Debug, Serialize)]
+struct EventRecord {
+    // Seconds since the stream started
+    t: f64,
+    #[serde(flatten)]
+    event: EditorEvent,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EditorEvent {
+    // The shader compiled and rendering starts
+    Start {
+        shader: PathBuf,
+    },
+    // A watched file or the --edit buffer changed
+    ReloadStarted {
+        shader: PathBuf,
+    },
+    // The reload is on screen; `seconds` is how long the GPU took to compile it
+    CompileSucceeded {
+        shader: PathBuf,
+        seconds: Option<f64>,
+    },
+    // The reload failed and the last working shader keeps rendering
+    CompileFailed {
+        shader: PathBuf,
+        #[serde(flatten)]
+        diagnostic: Diagnostic,
+    },
+    // Frames drawn per second, frames the GPU rendered per second and frames rendered but never
+    // drawn, over the last STATS_INTERVAL
+    Stats {
+        fps: f32,
+        gpu_fps: Option<f32>,
+        dropped: u64,
+    },
+    Exit,
+}
+
+// Frames drawn since the last Stats event
+#[derive(Debug)]
+pub struct StatsInterval {
+    since: Instant,
+    frames: u32,
+    dropped: u64,
+}
+
+impl StatsInterval {
+    pub fn new() -> Self {
+        Self {
+            since: Instant::now(),
+            frames: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn record_frame(&mut self) {
+        self.frames += 1;
+    }
+
+    // Emits Stats once STATS_INTERVAL has passed; `dropped` is the total since startup
+    pub fn update(&mut self, dropped: u64, gpu_fps: Option<f32>) {
+        let elapsed = self.since.elapsed();
+        if elapsed < STATS_INTERVAL {
+            return;
+        }
+        let fps = self.frames as f32 / elapsed.as_secs_f32();
+        let dropped_since = dropped.saturating_sub(self.dropped);
+        emit(|| EditorEvent::Stats {
+            fps,
+            gpu_fps,
+            dropped: dropped_since,
+        });
+        *self = Self {
+            since: Instant::now(),
+            frames: 0,
+            dropped,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_format() {
+        let record = EventRecord {
+            t: 1.5,
+            event: EditorEvent::CompileFailed {
+                shader: PathBuf::from("main.wgsl"),
+                diagnostic: Diagnostic {
+                    file: PathBuf::from("helpers.wgsl"),
+                    line: Some(2),
+                    column: Some(12),
+                    length: Some(7),
+                    message: "no definition in scope for identifier: `missing`".to_string(),
+                },
+            },
+        };
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"t":1.5,"event":"compile_failed","shader":"main.wgsl","file":"helpers.wgsl","line":2,"column":12,"length":7,"message":"no definition in scope for identifier: `missing`"}"#
+        );
+    }
+}
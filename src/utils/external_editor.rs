@@ -0,0 +1,61 @@
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+// AIDEV-NOTE: $VISUAL wins over $EDITOR; either may carry arguments (e.g. "code --wait")
+fn editor_argv(visual: Option<String>, editor: Option<String>) -> Vec<String> {
+    let command = [visual, editor]
+        .into_iter()
+        .flatten()
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+    command.split_whitespace().map(str::to_string).collect()
+}
+
+fn editor_command(path: &Path) -> Command {
+    let argv = editor_argv(env::var("VISUAL").ok(), env::var("EDITOR").ok());
+    let mut command = Command::new(&argv[0]);
+    command.args(&argv[1..]).arg(path);
+    command
+}
+
+pub fn editor_name() -> String {
+    editor_argv(env::var("VISUAL").ok(), env::var("EDITOR").ok()).join(" ")
+}
+
+// AIDEV-NOTE: Blocks until the editor exits; the caller must hand the terminal over first
+pub fn open_blocking(path: &Path) -> io::Result<ExitStatus> {
+    editor_command(path).status()
+}
+
+// AIDEV-NOTE: For window mode - the editor runs alongside the window and is reaped on a thread
+pub fn spawn_detached(path: &Path) -> io::Result<()> {
+    let mut child = editor_command(path).spawn()?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_argv() {
+        assert_eq!(
+            editor_argv(Some("code --wait".into()), Some("vim".into())),
+            vec!["code", "--wait"]
+        );
+        assert_eq!(
+            editor_argv(Some(" ".into()), Some("nano".into())),
+            vec!["nano"]
+        );
+        assert_eq!(editor_argv(None, None).len(), 1);
+    }
+}
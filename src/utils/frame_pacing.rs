@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// AIDEV-NOTE: Deadline-based pacing for --max-fps, shared by the GPU and terminal threads.
+// Deadlines advance by a fixed interval, so the average rate holds when single frames run late.
+// After a stall of more than one interval the schedule restarts from now instead of rushing to
+// catch up. Without a cap every frame is due immediately
+#[derive(Debug)]
+pub struct FramePacer {
+    interval: Option<Duration>,
+    next_frame: Instant,
+}
+
+impl FramePacer {
+    pub fn new(max_fps: Option<u32>) -> Self {
+        Self {
+            interval: max_fps.map(|fps| Duration::from_secs(1) / fps),
+            next_frame: Instant::now(),
+        }
+    }
+
+    pub fn time_until_next(&self) -> Duration {
+        self.next_frame.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_due(&self) -> bool {
+        self.time_until_next().is_zero()
+    }
+
+    // Schedules the next frame after one was produced
+    pub fn advance(&mut self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let now = Instant::now();
+        self.next_frame += interval;
+        if self.next_frame + interval < now {
+            self.next_frame = now;
+        }
+    }
+
+    // Blocks until the next frame is due, then schedules the one after it
+    pub fn wait(&mut self) {
+        std::thread::sleep(self.time_until_next());
+        self.advance();
+    }
+}
+
+// AIDEV-NOTE: `uniforms.delta_time` as shaders see it. After dropped frames or a sleeping thread,
+// a raw delta hands simulations one huge step: --max-delta clamps each delta and --smooth-delta
+// averages the last few. Both apply to real time, before the playback speed; `uniforms.time` is
+// never affected
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeltaSmoothing {
+    // Longest delta in seconds (--max-delta)
+    pub max_delta: Option<f32>,
+    // Deltas averaged, 1 or 0 for none (--smooth-delta)
+    pub frames: usize,
+}
+
+#[derive(Debug)]
+pub struct DeltaSmoother {
+    smoothing: DeltaSmoothing,
+    recent: VecDeque<f32>,
+}
+
+impl DeltaSmoother {
+    pub fn new(smoothing: DeltaSmoothing) -> Self {
+        Self {
+            smoothing,
+            recent: VecDeque::new(),
+        }
+    }
+
+    // The delta to hand the shader for a frame `delta` seconds after the previous one
+    pub fn smooth(&mut self, delta: f32) -> f32 {
+        let delta = match self.smoothing.max_delta {
+            Some(max_delta) => delta.min(max_delta),
+            None => delta,
+        };
+        self.recent.push_back(delta);
+        while self.recent.len() > self.smoothing.frames.max(1) {
+            self.recent.pop_front();
+        }
+        self.recent.iter().sum::<f32>() / self.recent.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_pacer() {
+        let mut uncapped = FramePacer::new(None);
+        uncapped.advance();
+        assert!(uncapped.is_due());
+
+        let mut pacer = FramePacer::new(Some(1));
+        assert!(pacer.is_due());
+        pacer.advance();
+        assert!(!pacer.is_due());
+        assert!(pacer.time_until_next() <= Duration::from_secs(1));
+
+        // A stall restarts the schedule rather than producing a burst of overdue frames
+        pacer.next_frame -= Duration::from_secs(5);
+        pacer.advance();
+        assert!(pacer.is_due());
+        pacer.advance();
+        assert!(!pacer.is_due());
+    }
+
+    #[test]
+    fn test_delta_smoother() {
+        let mut raw = DeltaSmoother::new(DeltaSmoothing::default());
+        assert_eq!(raw.smooth(0.5), 0.5);
+
+        let mut smoother = DeltaSmoother::new(DeltaSmoothing {
+            max_delta: Some(0.5),
+            frames: 2,
+        });
+        assert_eq!(smoother.smooth(0.25), 0.25);
+        // A stall is clamped, then averaged with the frame before it
+        assert_eq!(smoother.smooth(3.0), 0.375);
+        assert_eq!(smoother.smooth(0.25), 0.375);
+        assert_eq!(smoother.smooth(0.25), 0.25);
+    }
+}
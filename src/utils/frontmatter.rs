@@ -0,0 +1,131 @@
+use std::borrow::Cow;
+
+const OPEN: &str = "/* ---";
+const CLOSE: &str = "--- */";
+
+// AIDEV-NOTE: A TOML block between `/* ---` and `--- */` lines declares compile-time constants:
+//   /* ---
+//   GRID = 8
+//   SPEED = 1.5
+//   TINT = "#ff8040"
+//   --- */
+// Every key becomes a WGSL `const` in place of the block (ints and floats stay abstract, so GRID
+// works as i32, u32 or f32; arrays of 2-4 numbers and "#rrggbb" colors become vec2-4<f32>), padded
+// to the block's line count so line numbers after it don't move. Applied to every file as imports
+// are processed, so reloads pick up changed values like any other edit
+pub fn expand_frontmatter(source: &str) -> Result<Cow<'_, str>, String> {
+    let lines: Vec<&str> = source.lines().collect();
+    let Some(open) = lines.iter().position(|line| line.trim() == OPEN) else {
+        return Ok(Cow::Borrowed(source));
+    };
+    let close = lines[open + 1..]
+        .iter()
+        .position(|line| line.trim() == CLOSE)
+        .map(|offset| open + 1 + offset)
+        .ok_or_else(|| {
+            format!(
+                "the frontmatter opened on line {} has no '{CLOSE}'",
+                open + 1
+            )
+        })?;
+
+    let table: toml::Table = lines[open + 1..close]
+        .join("\n")
+        .parse()
+        .map_err(|e: toml::de::Error| format!("invalid frontmatter: {}", e.message()))?;
+    let mut declarations = table
+        .iter()
+        .map(|(name, value)| const_declaration(name, value))
+        .collect::<Result<Vec<_>, _>>()?;
+    declarations.resize((close - open + 1).max(declarations.len()), String::new());
+
+    let mut expanded: Vec<&str> = lines[..open].to_vec();
+    expanded.extend(declarations.iter().map(String::as_str));
+    expanded.extend(&lines[close + 1..]);
+    Ok(Cow::Owned(expanded.join("\n") + "\n"))
+}
+
+fn const_declaration(name: &str, value: &toml::Value) -> Result<String, String> {
+    let is_identifier = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_identifier {
+        return Err(format!("frontmatter key '{name}' is not a WGSL identifier"));
+    }
+    let unsupported = || {
+        format!(
+            "frontmatter value of '{name}' is not a number, boolean, array of 2 to 4 numbers or \
+             \"#rrggbb\" color"
+        )
+    };
+
+    let expression = match value {
+        toml::Value::Integer(int) => int.to_string(),
+        toml::Value::Float(float) => float_literal(*float).ok_or_else(unsupported)?,
+        toml::Value::Boolean(boolean) => boolean.to_string(),
+        toml::Value::Array(items) if (2..=4).contains(&items.len()) => {
+            let components = items
+                .iter()
+                .map(|item| match item {
+                    toml::Value::Integer(int) => float_literal(*int as f64),
+                    toml::Value::Float(float) => float_literal(*float),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(unsupported)?;
+            vector(&components)
+        }
+        toml::Value::String(color) => {
+            let components = hex_color(color).ok_or_else(unsupported)?;
+            vector(&components.map(|c| float_literal(c as f64 / 255.0).unwrap()))
+        }
+        _ => return Err(unsupported()),
+    };
+    Ok(format!("const {name} = {expression};"))
+}
+
+// Finite floats always written with a decimal point, so WGSL reads them as floats
+fn float_literal(value: f64) -> Option<String> {
+    value.is_finite().then(|| format!("{value:?}"))
+}
+
+fn vector(components: &[String]) -> String {
+    format!("vec{}<f32>({})", components.len(), components.join(", "))
+}
+
+fn hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_frontmatter() {
+        let source = "// @title Grid\n/* ---\nGRID = 8\nSPEED = 1.5\nTINT = \"#ff8000\"\nUV = [1, 0.5]\nDEBUG = false\n--- */\nfn f() {}\n";
+        let expanded = expand_frontmatter(source).unwrap();
+        assert_eq!(
+            expanded,
+            "// @title Grid\n\
+             const DEBUG = false;\n\
+             const GRID = 8;\n\
+             const SPEED = 1.5;\n\
+             const TINT = vec3<f32>(1.0, 0.5019607843137255, 0.0);\n\
+             const UV = vec2<f32>(1.0, 0.5);\n\
+             \n\
+             \n\
+             fn f() {}\n"
+        );
+        assert_eq!(expanded.lines().count(), source.lines().count());
+
+        assert!(matches!(
+            expand_frontmatter("fn f() {}"),
+            Ok(Cow::Borrowed(_))
+        ));
+        assert!(expand_frontmatter("/* ---\nA = 1\n").is_err());
+        assert!(expand_frontmatter("/* ---\nA = \"blue\"\n--- */").is_err());
+        assert!(expand_frontmatter("/* ---\n[table]\nA = 1\n--- */").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::error::ShadertuiError;
+use crate::utils::metadata::ShaderMetadata;
+
+// AIDEV-NOTE: 8-bit RGBA image with the top row first, built from GPU readback data (bottom row first)
+#[derive(Debug, Clone)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    pub fn from_gpu_data(gpu_data: &[f32], width: u32, height: u32) -> Self {
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for y in (0..height as usize).rev() {
+            let row_start = y * width as usize * 4;
+            let row = &gpu_data[row_start..row_start + width as usize * 4];
+            for texel in row.chunks_exact(4) {
+                pixels.push(linear_to_srgb_u8(texel[0]));
+                pixels.push(linear_to_srgb_u8(texel[1]));
+                pixels.push(linear_to_srgb_u8(texel[2]));
+                pixels.push((texel[3].clamp(0.0, 1.0) * 255.0) as u8);
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // AIDEV-NOTE: Opaque grayscale image of the alpha channel, for compositing in video editors
+    pub fn alpha_matte(&self) -> Self {
+        let pixels = self
+            .pixels
+            .chunks_exact(4)
+            .flat_map(|texel| [texel[3], texel[3], texel[3], 255])
+            .collect();
+
+        Self {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+
+    // AIDEV-NOTE: Copy `image` with its top-left corner at (x, y); it must fit inside this image
+    pub fn blit(&mut self, image: &RgbaImage, x: u32, y: u32) {
+        let row_len = image.width as usize * 4;
+        for row in 0..image.height as usize {
+            let src = row * row_len;
+            let dst = ((y as usize + row) * self.width as usize + x as usize) * 4;
+            self.pixels[dst..dst + row_len].copy_from_slice(&image.pixels[src..src + row_len]);
+        }
+    }
+
+    // AIDEV-NOTE: Shader metadata is stored as standard PNG text chunks (Title, Author, Copyright, ...)
+    pub fn write_png(&self, path: &Path, metadata: &ShaderMetadata) -> Result<(), ShadertuiError> {
+        let file = File::create(path)
+            .map_err(|e| format!("Could not create '{}': {e}", path.display()))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let text_chunks = [
+            ("Title", metadata.title.clone()),
+            ("Author", metadata.author.clone()),
+            ("Copyright", metadata.license.clone()),
+            (
+                "Keywords",
+                (!metadata.tags.is_empty()).then(|| metadata.tags.join(", ")),
+            ),
+            ("Software", Some("shadertui".to_string())),
+        ];
+        for (keyword, text) in text_chunks {
+            if let Some(text) = text {
+                encoder.add_itxt_chunk(keyword.to_string(), text)?;
+            }
+        }
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+}
+
+// AIDEV-NOTE: Same linear -> sRGB approximation (gamma 2.2) the terminal renderer uses, so exports
+// match what is shown on screen
+pub fn linear_to_srgb_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0).powf(1.0 / 2.2) * 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_gpu_data_flips_rows() {
+        // 1x2 image: bottom row red, top row blue
+        let gpu_data = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.5];
+        let image = RgbaImage::from_gpu_data(&gpu_data, 1, 2);
+
+        assert_eq!(image.pixels, vec![0, 0, 255, 127, 255, 0, 0, 255]);
+        assert_eq!(
+            image.alpha_matte().pixels,
+            vec![127, 127, 127, 255, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_blit_places_image() {
+        let mut sheet = RgbaImage::new(2, 2);
+        let cell = RgbaImage {
+            width: 1,
+            height: 1,
+            pixels: vec![1, 2, 3, 4],
+        };
+        sheet.blit(&cell, 1, 1);
+
+        assert_eq!(&sheet.pixels[12..], &[1, 2, 3, 4]);
+        assert!(sheet.pixels[..12].iter().all(|&p| p == 0));
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::utils::image_export::RgbaImage;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG";
+const JPEG_SIGNATURE: &[u8] = b"\xff\xd8";
+
+// AIDEV-NOTE: Decode a PNG or JPEG (detected from its first bytes, not the extension) into 8-bit
+// RGBA with the top row first
+pub fn load_image(path: &Path) -> Result<RgbaImage, String> {
+    let error = |message: String| format!("Could not load image '{}': {message}", path.display());
+
+    let data = fs::read(path).map_err(|e| error(e.to_string()))?;
+    if data.starts_with(PNG_SIGNATURE) {
+        decode_png(&data).map_err(error)
+    } else if data.starts_with(JPEG_SIGNATURE) {
+        decode_jpeg(&data).map_err(error)
+    } else {
+        Err(error("only PNG and JPEG images are supported".to_string()))
+    }
+}
+
+fn decode_png(data: &[u8]) -> Result<RgbaImage, String> {
+    let mut decoder = png::Decoder::new(Cursor::new(data));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size().ok_or("image is too large")?];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+
+    let frame = &buffer[..info.buffer_size()];
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => frame.to_vec(),
+        png::ColorType::Rgb => expand_pixels(frame, 3, |p| [p[0], p[1], p[2], 255]),
+        png::ColorType::GrayscaleAlpha => expand_pixels(frame, 2, |p| [p[0], p[0], p[0], p[1]]),
+        png::ColorType::Grayscale => expand_pixels(frame, 1, |p| [p[0], p[0], p[0], 255]),
+        png::ColorType::Indexed => return Err("unexpanded indexed colors".to_string()),
+    };
+
+    Ok(RgbaImage {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+fn decode_jpeg(data: &[u8]) -> Result<RgbaImage, String> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let decoded = decoder.decode().map_err(|e| e.to_string())?;
+    let info = decoder.info().ok_or("missing image header")?;
+
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => expand_pixels(&decoded, 3, |p| [p[0], p[1], p[2], 255]),
+        jpeg_decoder::PixelFormat::L8 => expand_pixels(&decoded, 1, |p| [p[0], p[0], p[0], 255]),
+        // Big-endian 16-bit gray; keep the high byte
+        jpeg_decoder::PixelFormat::L16 => expand_pixels(&decoded, 2, |p| [p[0], p[0], p[0], 255]),
+        jpeg_decoder::PixelFormat::CMYK32 => expand_pixels(&decoded, 4, |p| {
+            let k = 255 - p[3] as u32;
+            let channel = |c: u8| ((255 - c as u32) * k / 255) as u8;
+            [channel(p[0]), channel(p[1]), channel(p[2]), 255]
+        }),
+    };
+
+    Ok(RgbaImage {
+        width: info.width as u32,
+        height: info.height as u32,
+        pixels,
+    })
+}
+
+fn expand_pixels(data: &[u8], channels: usize, to_rgba: impl Fn(&[u8]) -> [u8; 4]) -> Vec<u8> {
+    data.chunks_exact(channels).flat_map(to_rgba).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::metadata::ShaderMetadata;
+
+    #[test]
+    fn test_load_png_round_trip() {
+        let image = RgbaImage {
+            width: 2,
+            height: 1,
+            pixels: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        };
+        let path = std::env::temp_dir().join("shadertui_test_load_png_round_trip.png");
+        image.write_png(&path, &ShaderMetadata::default()).unwrap();
+
+        let loaded = load_image(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!((loaded.width, loaded.height), (2, 1));
+        assert_eq!(loaded.pixels, image.pixels);
+        assert!(load_image(Path::new("Cargo.toml")).is_err());
+    }
+}
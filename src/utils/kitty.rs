@@ -0,0 +1,101 @@
+use crate::utils::image_export::RgbaImage;
+
+// AIDEV-NOTE: Every frame is transmitted as this image id and placement, so the terminal replaces the
+// previous frame in place instead of stacking a new image per frame
+const IMAGE_ID: u32 = 1;
+const PLACEMENT_ID: u32 = 1;
+
+// The protocol limits each escape sequence to 4096 bytes of payload
+const CHUNK_SIZE: usize = 4096;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// AIDEV-NOTE: Transmit and display an image at the cursor as 24-bit RGB (alpha is dropped, like the
+// cell output). q=2 silences the terminal's replies, which would otherwise arrive as key input, and
+// C=1 keeps the cursor still so an image touching the bottom row doesn't scroll the screen
+pub fn encode(image: &RgbaImage) -> String {
+    let rgb: Vec<u8> = image
+        .pixels
+        .chunks_exact(4)
+        .flat_map(|texel| [texel[0], texel[1], texel[2]])
+        .collect();
+    let payload = base64(&rgb);
+
+    let mut out = String::with_capacity(payload.len() + payload.len() / CHUNK_SIZE * 16 + 64);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(CHUNK_SIZE).collect();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        out.push_str("\x1b_G");
+        if index == 0 {
+            out.push_str(&format!(
+                "a=T,f=24,s={},v={},i={IMAGE_ID},p={PLACEMENT_ID},q=2,C=1,",
+                image.width, image.height
+            ));
+        }
+        out.push_str(&format!("m={more};"));
+        // Base64 output is ASCII
+        out.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        out.push_str("\x1b\\");
+    }
+    out
+}
+
+// Removes the frame image (and frees its data) when shadertui exits
+pub fn delete_image() -> String {
+    format!("\x1b_Ga=d,d=I,i={IMAGE_ID},q=2\x1b\\")
+}
+
+fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for position in 0..4 {
+            if position <= chunk.len() {
+                let index = (bits >> (18 - position * 6)) & 0x3F;
+                out.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+
+        // 2x1: red then blue, alpha dropped
+        let image = RgbaImage {
+            width: 2,
+            height: 1,
+            pixels: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        };
+        assert_eq!(
+            encode(&image),
+            "\x1b_Ga=T,f=24,s=2,v=1,i=1,p=1,q=2,C=1,m=0;/wAAAAD/\x1b\\"
+        );
+
+        // Large frames are split into chunks, all but the last flagged m=1
+        let large = RgbaImage {
+            width: 64,
+            height: 64,
+            pixels: vec![0; 64 * 64 * 4],
+        };
+        let encoded = encode(&large);
+        assert_eq!(encoded.matches("m=1;").count(), 3);
+        assert_eq!(encoded.matches("m=0;").count(), 1);
+    }
+}
@@ -0,0 +1,138 @@
+use std::f32::consts::TAU;
+use std::sync::OnceLock;
+
+// AIDEV-NOTE: --lfo binds a `// @uniform` parameter to an oscillator over shader time, evaluated on
+// the CPU every frame, to animate a value for testing without editing the shader:
+//   --lfo speed:sine:0.25hz:0..2
+// The rate is a frequency (0.25hz) or a period (4s); without a range the parameter's declared one is
+// swept, or 0..1 without that. Set once at startup like the adapter selection, and applied by
+// ParamValues::animate wherever parameters are packed, so the terminal, the window and the export
+// commands all agree. Manual changes to a bound parameter are overridden on the next frame
+static BINDINGS: OnceLock<Vec<Lfo>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lfo {
+    pub param: String,
+    pub shape: LfoShape,
+    // Cycles per second of shader time
+    pub frequency: f32,
+    pub range: Option<(f32, f32)>,
+}
+
+pub fn bind(lfos: Vec<Lfo>) {
+    let _ = BINDINGS.set(lfos);
+}
+
+pub fn bindings() -> &'static [Lfo] {
+    BINDINGS.get().map_or(&[], Vec::as_slice)
+}
+
+impl Lfo {
+    // `declared` is the parameter's own range, swept when the binding gives none
+    pub fn value(&self, time: f32, declared: Option<(f32, f32)>) -> f32 {
+        let phase = (time * self.frequency).rem_euclid(1.0);
+        // 0..1; sine starts in the middle going up, the others at the bottom
+        let level = match self.shape {
+            LfoShape::Sine => 0.5 + 0.5 * (phase * TAU).sin(),
+            LfoShape::Triangle => 1.0 - (2.0 * phase - 1.0).abs(),
+            LfoShape::Saw => phase,
+            LfoShape::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        };
+        let (min, max) = self.range.or(declared).unwrap_or((0.0, 1.0));
+        min + level * (max - min)
+    }
+
+    // PARAM:SHAPE:RATE[:MIN..MAX]
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = value.split(':').map(str::trim).collect();
+        let (param, shape, rate, range) = match parts.as_slice() {
+            [param, shape, rate] => (param, shape, rate, None),
+            [param, shape, rate, range] => (param, shape, rate, Some(range)),
+            _ => {
+                return Err(format!(
+                    "expected PARAM:SHAPE:RATE[:MIN..MAX] (e.g. speed:sine:0.25hz:0..2), got \
+                     '{value}'"
+                ))
+            }
+        };
+        if param.is_empty() {
+            return Err("missing the parameter name".to_string());
+        }
+        let shape = match shape.to_lowercase().as_str() {
+            "sine" | "sin" => LfoShape::Sine,
+            "triangle" | "tri" => LfoShape::Triangle,
+            "saw" => LfoShape::Saw,
+            "square" => LfoShape::Square,
+            _ => {
+                return Err(format!(
+                    "unknown shape '{shape}' (expected sine, triangle, saw or square)"
+                ))
+            }
+        };
+        let rate_lower = rate.to_lowercase();
+        let frequency = match (rate_lower.strip_suffix("hz"), rate_lower.strip_suffix('s')) {
+            (Some(hz), _) => hz.trim().parse::<f32>().ok(),
+            (None, Some(seconds)) => seconds.trim().parse::<f32>().ok().map(|s| 1.0 / s),
+            (None, None) => None,
+        }
+        .filter(|frequency| frequency.is_finite() && *frequency > 0.0)
+        .ok_or_else(|| format!("invalid rate '{rate}' (expected e.g. 0.25hz or 4s)"))?;
+        let range = range
+            .map(|range| {
+                let (min, max) = range
+                    .split_once("..")
+                    .ok_or_else(|| format!("invalid range '{range}' (expected MIN..MAX)"))?;
+                match (min.trim().parse::<f32>(), max.trim().parse::<f32>()) {
+                    (Ok(min), Ok(max)) => Ok((min, max)),
+                    _ => Err(format!("invalid range '{range}' (expected MIN..MAX)")),
+                }
+            })
+            .transpose()?;
+        Ok(Self {
+            param: param.to_string(),
+            shape,
+            frequency,
+            range,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lfo() {
+        let lfo = Lfo::parse("speed:sine:0.25hz:0..2").unwrap();
+        assert_eq!(lfo.param, "speed");
+        assert_eq!(lfo.frequency, 0.25);
+        assert_eq!(lfo.range, Some((0.0, 2.0)));
+        assert!((lfo.value(0.0, None) - 1.0).abs() < 1e-5);
+        assert!((lfo.value(1.0, None) - 2.0).abs() < 1e-5);
+
+        let lfo = Lfo::parse("mix:Triangle:4s").unwrap();
+        assert_eq!(lfo.frequency, 0.25);
+        assert_eq!(lfo.value(2.0, None), 1.0);
+        assert_eq!(lfo.value(2.0, Some((10.0, 20.0))), 20.0);
+        assert_eq!(lfo.value(-1.0, None), 0.5);
+
+        assert!(Lfo::parse("speed:sine").is_err());
+        assert!(Lfo::parse("speed:wobble:1hz").is_err());
+        assert!(Lfo::parse("speed:sine:0hz").is_err());
+        assert!(Lfo::parse("speed:sine:1hz:2").is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::utils::paths::config_dir;
+
+// English is built in and fills in whatever a translation leaves out
+const ENGLISH: &str = include_str!("../locales/en.toml");
+// Translations shipped in the binary, by locale name
+const BUNDLED: &[(&str, &str)] = &[("en", ENGLISH)];
+
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+// AIDEV-NOTE: Message catalog for the TUI and window frontends. Strings live in TOML files keyed by
+// section ("notice.saved"); code refers to them with msg!, never with literal text. Translations
+// are `<locale>.toml` files with the same keys, looked up in $SHADERTUI_LOCALE_DIR, the config
+// dir's `locales/` and the bundled set, for $SHADERTUI_LANG or else the usual POSIX variables
+macro_rules! msg {
+    ($key:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::utils::messages::format_message(
+            $key,
+            &[$((stringify!($name), &$value as &dyn std::fmt::Display)),*],
+        )
+    };
+}
+pub(crate) use msg;
+
+// Load the catalog now, so problems with a translation are reported before the TUI takes the
+// screen
+pub fn init() {
+    catalog();
+}
+
+pub fn format_message(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let template = catalog().get(key).map_or(key, String::as_str);
+    fill_placeholders(template, args)
+}
+
+fn catalog() -> &'static HashMap<String, String> {
+    CATALOG.get_or_init(|| {
+        let mut messages = parse_catalog(ENGLISH).expect("built-in English messages are valid");
+        let Some(locale) = requested_locale() else {
+            return messages;
+        };
+        for candidate in locale_candidates(&locale) {
+            match load_translation(&candidate) {
+                Some(Ok(translation)) => {
+                    messages.extend(translation);
+                    break;
+                }
+                Some(Err(e)) => {
+                    eprintln!("Warning: ignoring the '{candidate}' translation: {e}");
+                    break;
+                }
+                None => {}
+            }
+        }
+        messages
+    })
+}
+
+// $SHADERTUI_LANG picks a language for shadertui alone; otherwise the POSIX precedence applies
+fn requested_locale() -> Option<String> {
+    ["SHADERTUI_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))
+}
+
+// "pt_BR.UTF-8@euro" is looked up as pt_BR, then pt. C and POSIX mean untranslated
+fn locale_candidates(locale: &str) -> Vec<String> {
+    let name = locale.split(['.', '@']).next().unwrap_or_default();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return Vec::new();
+    }
+    let mut candidates = vec![name.to_string()];
+    if let Some((language, _)) = name.split_once(['_', '-']) {
+        candidates.push(language.to_string());
+    }
+    candidates
+}
+
+fn translation_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env::var_os("SHADERTUI_LOCALE_DIR")
+        .map(PathBuf::from)
+        .into_iter()
+        .collect();
+    dirs.extend(config_dir().map(|dir| dir.join("locales")));
+    if cfg!(unix) {
+        // Where distributions install translations they ship
+        dirs.push(PathBuf::from("/usr/share/shadertui/locales"));
+    }
+    dirs
+}
+
+// A translation file on disk takes precedence over a bundled one of the same name
+fn load_translation(locale: &str) -> Option<Result<HashMap<String, String>, String>> {
+    let file_name = format!("{locale}.toml");
+    for dir in translation_dirs() {
+        let path = dir.join(&file_name);
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            return Some(parse_catalog(&content).map_err(|e| format!("{}: {e}", path.display())));
+        }
+    }
+    BUNDLED
+        .iter()
+        .find(|(name, _)| *name == locale)
+        .map(|(_, content)| parse_catalog(content))
+}
+
+// Flatten the TOML tables into dotted keys
+fn parse_catalog(content: &str) -> Result<HashMap<String, String>, String> {
+    fn flatten(
+        prefix: &str,
+        table: &toml::Table,
+        messages: &mut HashMap<String, String>,
+    ) -> Result<(), String> {
+        for (name, value) in table {
+            let key = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{prefix}.{name}")
+            };
+            match value {
+                toml::Value::String(text) => {
+                    messages.insert(key, text.clone());
+                }
+                toml::Value::Table(table) => flatten(&key, table, messages)?,
+                _ => return Err(format!("'{key}' must be a string")),
+            }
+        }
+        Ok(())
+    }
+
+    let table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    let mut messages = HashMap::new();
+    flatten("", &table, &mut messages)?;
+    Ok(messages)
+}
+
+// Replace `{name}` placeholders in one pass; unknown ones are left as written
+fn fill_placeholders(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                output.push_str(&value.to_string());
+                rest = &after[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_placeholders() {
+        let path = "a {b}.gif";
+        assert_eq!(
+            fill_placeholders("Saved {path} ({size})", &[("path", &path), ("size", &3)]),
+            "Saved a {b}.gif (3)"
+        );
+        assert_eq!(fill_placeholders("{missing} {", &[]), "{missing} {");
+    }
+
+    #[test]
+    fn test_locale_candidates() {
+        assert_eq!(locale_candidates("pt_BR.UTF-8@euro"), ["pt_BR", "pt"]);
+        assert_eq!(locale_candidates("de"), ["de"]);
+        assert!(locale_candidates("C.UTF-8").is_empty());
+    }
+
+    // Every key the code asks for exists in the English catalog
+    #[test]
+    fn test_message_keys_exist() {
+        let english = parse_catalog(ENGLISH).unwrap();
+        let key_pattern = regex::Regex::new(r#"msg!\(\s*"([^"]+)""#).unwrap();
+        let mut pending = vec![PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src"))];
+        while let Some(path) = pending.pop() {
+            if path.is_dir() {
+                pending.extend(std::fs::read_dir(&path).unwrap().map(|e| e.unwrap().path()));
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                let source = std::fs::read_to_string(&path).unwrap();
+                for key in key_pattern.captures_iter(&source) {
+                    assert!(
+                        english.contains_key(&key[1]),
+                        "{}: unknown message '{}'",
+                        path.display(),
+                        &key[1]
+                    );
+                }
+            }
+        }
+    }
+}
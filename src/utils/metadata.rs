@@ -0,0 +1,147 @@
+use crate::utils::options::{parse_fps, parse_loop, parse_scale, parse_seconds};
+
+// AIDEV-NOTE: Optional header in the leading comment block of a shader, one `// @key value` per line:
+//   // @title Plasma Waves
+//   // @author Jane Doe
+//   // @license CC-BY-4.0
+//   // @tags plasma, waves
+//   // @duration 10s
+// Unknown keys (e.g. the `@shadertui` version pragma) are ignored
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShaderMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub license: Option<String>,
+    pub tags: Vec<String>,
+    pub duration: Option<f32>,
+}
+
+impl ShaderMetadata {
+    pub fn parse(source: &str) -> Self {
+        let mut metadata = Self::default();
+
+        for (key, value) in header_fields(source) {
+            match key {
+                "title" => metadata.title = Some(value.to_string()),
+                "author" => metadata.author = Some(value.to_string()),
+                "license" => metadata.license = Some(value.to_string()),
+                "tags" => {
+                    metadata.tags = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }
+                "duration" => metadata.duration = parse_seconds(value).ok(),
+                _ => {}
+            }
+        }
+
+        metadata
+    }
+
+    // AIDEV-NOTE: Short label for status bars and window titles, e.g. "Plasma Waves by Jane Doe"
+    pub fn display_title(&self) -> Option<String> {
+        match (&self.title, &self.author) {
+            (Some(title), Some(author)) => Some(format!("{title} by {author}")),
+            (Some(title), None) => Some(title.clone()),
+            (None, _) => None,
+        }
+    }
+}
+
+// `// @key value` lines of the leading comment block
+fn header_fields(source: &str) -> impl Iterator<Item = (&str, &str)> {
+    source
+        .lines()
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with("//"))
+        .filter_map(|line| {
+            let field = line.strip_prefix("//")?.trim();
+            let (key, value) = field.strip_prefix('@')?.split_once(' ')?;
+            Some((key, value.trim()))
+        })
+}
+
+// AIDEV-NOTE: Runtime defaults a shader ships with, from the same header as the metadata:
+//   // @fps 30      (--max-fps)
+//   // @scale 0.5   (--scale)
+//   // @loop 8s     (--loop)
+// Options given on the command line take precedence. Unlike metadata, invalid values are errors
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ShaderSettings {
+    pub fps: Option<u32>,
+    pub scale: Option<f32>,
+    pub loop_duration: Option<f32>,
+}
+
+impl ShaderSettings {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut settings = Self::default();
+
+        for (key, value) in header_fields(source) {
+            let result = match key {
+                "fps" => parse_fps(value).map(|fps| settings.fps = Some(fps)),
+                "scale" => parse_scale(value).map(|scale| settings.scale = Some(scale)),
+                "loop" => parse_loop(value).map(|time| settings.loop_duration = Some(time)),
+                _ => Ok(()),
+            };
+            result.map_err(|message| format!("'// @{key} {value}': {message}"))?;
+        }
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        let source = "// @shadertui 2\n\
+                      // @title Plasma Waves\n\
+                      // @author Jane Doe\n\
+                      //\n\
+                      // @tags plasma, waves,\n\
+                      // @duration 10s\n\
+                      \n\
+                      fn compute_color(coords: vec2<f32>) -> vec4<f32> {}\n\
+                      // @license MIT\n";
+        let metadata = ShaderMetadata::parse(source);
+
+        assert_eq!(metadata.title.as_deref(), Some("Plasma Waves"));
+        assert_eq!(metadata.tags, vec!["plasma", "waves"]);
+        assert_eq!(metadata.duration, Some(10.0));
+        // Only the leading comment block is a header
+        assert_eq!(metadata.license, None);
+        assert_eq!(
+            metadata.display_title().as_deref(),
+            Some("Plasma Waves by Jane Doe")
+        );
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        let source = "// @title Tunnel\n\
+                      // @fps 30\n\
+                      // @scale 0.5\n\
+                      // @loop 8s\n";
+        assert_eq!(
+            ShaderSettings::parse(source),
+            Ok(ShaderSettings {
+                fps: Some(30),
+                scale: Some(0.5),
+                loop_duration: Some(8.0),
+            })
+        );
+        assert_eq!(
+            ShaderSettings::parse("fn main() {}\n// @fps 30\n"),
+            Ok(ShaderSettings::default())
+        );
+        assert!(ShaderSettings::parse("// @scale 2").is_err());
+        assert!(ShaderSettings::parse("// @fps 0").is_err());
+        assert!(ShaderSettings::parse("// @loop 0s").is_err());
+    }
+}
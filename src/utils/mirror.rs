@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::time::Instant;
+
+use crate::utils::control::ControlCommand;
+
+// Seconds the window's clock may be off from the terminal's before it is set again
+const TIME_TOLERANCE: f32 = 0.1;
+
+// AIDEV-NOTE: W in the terminal (or `shadertui ctl window`) shows the shader in a window: a
+// `shadertui --follow` child process with the terminal's command line, taking control commands on
+// stdin. It is a separate process because winit's event loop can only run once, on the main
+// thread. The terminal sends its clock, pause state, speed, parameters and shader switches when they
+// change, so the window shows the same moment at full resolution. Dropping this closes the window
+pub struct MirrorWindow {
+    child: Child,
+    stdin: ChildStdin,
+    // What the window was last told, starting with the shader from the command line
+    sent: MirrorState,
+    // When `sent.time` was sent
+    sent_at: Instant,
+    time_sent: bool,
+}
+
+// What the window follows
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirrorState {
+    pub shader_file: PathBuf,
+    // The shader's time before --loop wraps it
+    pub time: f32,
+    pub paused: bool,
+    pub time_scale: f32,
+    pub params: BTreeMap<String, f32>,
+}
+
+impl MirrorWindow {
+    // `args` is the command line without the program, naming `shader_file`
+    pub fn open(args: &[OsString], shader_file: PathBuf) -> Result<Self, String> {
+        let program =
+            std::env::current_exe().map_err(|e| format!("Could not find shadertui: {e}"))?;
+        // The window's output would draw over the terminal render
+        let mut child = Command::new(program)
+            .arg("--follow")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        Ok(Self {
+            child,
+            stdin,
+            sent: MirrorState {
+                shader_file,
+                time: 0.0,
+                paused: false,
+                time_scale: 1.0,
+                params: BTreeMap::new(),
+            },
+            sent_at: Instant::now(),
+            time_sent: false,
+        })
+    }
+
+    // Once the window has been closed: Err if it couldn't open or failed
+    pub fn closed(&mut self) -> Option<Result<(), String>> {
+        match self.child.try_wait() {
+            Ok(None) => None,
+            Ok(Some(status)) if !status.success() => Some(Err(status.to_string())),
+            Ok(Some(_)) => Some(Ok(())),
+            Err(e) => Some(Err(e.to_string())),
+        }
+    }
+
+    // Sends what changed since the last call
+    pub fn sync(&mut self, state: &MirrorState) {
+        let elapsed = self.sent_at.elapsed().as_secs_f32();
+        let commands = changes(&self.sent, self.time_sent.then_some(elapsed), state);
+        if commands.is_empty() {
+            return;
+        }
+        let mut lines = String::new();
+        for command in &commands {
+            lines.push_str(&command.to_line());
+            if let ControlCommand::Time(time) = command {
+                self.sent.time = *time;
+                self.sent_at = Instant::now();
+                self.time_sent = true;
+            }
+        }
+        let _ = self.stdin.write_all(lines.as_bytes());
+        self.sent = MirrorState {
+            time: self.sent.time,
+            ..state.clone()
+        };
+    }
+}
+
+impl Drop for MirrorWindow {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// Commands that take the window from `sent` to `state`. `elapsed` is the time since `sent.time` was
+// sent, or None before the first time
+fn changes(sent: &MirrorState, elapsed: Option<f32>, state: &MirrorState) -> Vec<ControlCommand> {
+    let mut commands = Vec::new();
+    if state.shader_file != sent.shader_file {
+        commands.push(ControlCommand::Load(state.shader_file.clone()));
+    }
+    if state.time_scale != sent.time_scale {
+        commands.push(ControlCommand::Speed(state.time_scale));
+    }
+    if state.paused != sent.paused {
+        commands.push(ControlCommand::Pause(Some(state.paused)));
+    }
+    let expected_time = elapsed.map(|elapsed| {
+        if sent.paused {
+            sent.time
+        } else {
+            sent.time + elapsed * sent.time_scale
+        }
+    });
+    let clock_changed = state.paused != sent.paused || state.time_scale != sent.time_scale;
+    if clock_changed
+        || expected_time.is_none_or(|expected| (state.time - expected).abs() > TIME_TOLERANCE)
+    {
+        commands.push(ControlCommand::Time(state.time));
+    }
+    for (name, &value) in &state.params {
+        if sent.params.get(name) != Some(&value) {
+            commands.push(ControlCommand::Param(name.clone(), value));
+        }
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_changes() {
+        let sent = MirrorState {
+            shader_file: PathBuf::from("plasma.wgsl"),
+            time: 2.0,
+            paused: false,
+            time_scale: 1.0,
+            params: BTreeMap::from([("speed".to_string(), 1.0)]),
+        };
+        // The first sync sets the clock
+        assert_eq!(changes(&sent, None, &sent), vec![ControlCommand::Time(2.0)]);
+        // A running clock is left alone while it matches
+        let running = MirrorState {
+            time: 3.0,
+            ..sent.clone()
+        };
+        assert!(changes(&sent, Some(1.0), &running).is_empty());
+
+        let changed = MirrorState {
+            shader_file: PathBuf::from("/shaders/tunnel.wgsl"),
+            time: 10.0,
+            paused: true,
+            params: BTreeMap::from([("speed".to_string(), 2.0)]),
+            ..sent.clone()
+        };
+        assert_eq!(
+            changes(&sent, Some(1.0), &changed),
+            vec![
+                ControlCommand::Load(PathBuf::from("/shaders/tunnel.wgsl")),
+                ControlCommand::Pause(Some(true)),
+                ControlCommand::Time(10.0),
+                ControlCommand::Param("speed".to_string(), 2.0),
+            ]
+        );
+    }
+}
@@ -1,13 +1,87 @@
+#[cfg(feature = "cli")]
+pub mod adaptive;
+pub mod antialias;
+#[cfg(feature = "cli")]
+pub mod backoff;
+#[cfg(feature = "cli")]
+pub mod benchmark;
+#[cfg(feature = "cli")]
 pub mod cli;
+pub mod color;
+#[cfg(feature = "cli")]
+pub mod control;
+#[cfg(feature = "cli")]
+pub mod events;
+#[cfg(feature = "cli")]
+pub mod external_editor;
+#[cfg(feature = "cli")]
+pub mod font;
+#[cfg(feature = "cli")]
+pub mod frame_pacing;
+pub mod frontmatter;
+pub mod image_export;
+pub mod image_import;
+#[cfg(feature = "cli")]
+pub mod kitty;
+pub mod lfo;
+#[cfg(feature = "cli")]
+pub mod messages;
+pub mod metadata;
+#[cfg(feature = "cli")]
+pub mod mirror;
+#[cfg(feature = "cli")]
+pub mod mpris;
+#[cfg(feature = "cli")]
 pub mod multi_file_watcher;
+#[cfg(feature = "cli")]
+pub mod notification;
+pub mod options;
+pub mod palette;
+pub mod paths;
+pub mod project;
+#[cfg(feature = "cli")]
+pub mod recording;
+pub mod safe_mode;
+pub mod sample_view;
+#[cfg(feature = "cli")]
 pub mod screen;
+#[cfg(feature = "cli")]
+pub mod screensaver;
+#[cfg(feature = "cli")]
+pub mod sequencer;
 pub mod shader_import;
 pub mod shader_shell;
+#[cfg(feature = "cli")]
+pub mod shader_tests;
+pub mod shadertoy;
+#[cfg(feature = "cli")]
+pub mod signals;
+#[cfg(feature = "cli")]
+pub mod sixel;
+pub mod source_map;
+pub mod stdlib;
+#[cfg(feature = "cli")]
+pub mod templates;
+#[cfg(feature = "cli")]
+pub mod terminal_caps;
+#[cfg(feature = "cli")]
+pub mod text_buffer;
+#[cfg(feature = "cli")]
 pub mod threading;
+#[cfg(feature = "cli")]
+pub mod timeline;
+#[cfg(feature = "cli")]
+pub mod trace;
+pub mod uniform_params;
 pub mod validation;
+#[cfg(feature = "cli")]
+pub mod watermark;
 
+#[cfg(feature = "cli")]
 pub use cli::Cli;
+#[cfg(feature = "cli")]
 pub use screen::{get_centered_window_position, get_window_size};
+#[cfg(feature = "cli")]
 pub use threading::{
     DualPerformanceTracker, ErrorReceiver, SharedFrameBuffer, SharedUniforms, ThreadError,
 };
@@ -0,0 +1,263 @@
+use std::path::Path;
+
+use crate::utils::control::ControlReceiver;
+
+// AIDEV-NOTE: --mpris registers shadertui as a media player on the D-Bus session bus, so media keys
+// and desktop widgets can pause, resume, seek and switch shaders. Their requests become the same
+// commands `shadertui ctl` sends. Next and previous step through the shader files next to the
+// current one (project::sibling_shader). The playback status and title reported back are what the
+// player last asked for; pausing with the keyboard or loading with `shadertui ctl` doesn't update them
+pub struct MprisPlayer {
+    pub commands: ControlReceiver,
+    // Owns the bus name; the player disappears from the desktop when this is dropped
+    _connection: dbus::Connection,
+}
+
+impl MprisPlayer {
+    pub fn start(shader: &Path) -> Result<Self, String> {
+        let shader = shader
+            .canonicalize()
+            .map_err(|e| format!("Could not resolve '{}': {e}", shader.display()))?;
+        let (sender, commands) = flume::unbounded();
+        let connection = dbus::register(sender, shader)
+            .map_err(|e| format!("Could not register the MPRIS player: {e}"))?;
+        Ok(Self {
+            commands,
+            _connection: connection,
+        })
+    }
+}
+
+#[cfg(all(feature = "mpris", target_os = "linux"))]
+mod dbus {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use zbus::blocking::connection;
+    use zbus::interface;
+    use zbus::object_server::SignalEmitter;
+    use zbus::zvariant::{ObjectPath, Value};
+
+    use crate::utils::control::ControlCommand;
+    use crate::utils::project::sibling_shader;
+
+    pub use zbus::blocking::Connection;
+
+    type CommandSender = flume::Sender<ControlCommand>;
+
+    const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+    // Shaders aren't tracks, but clients expect every player to report one
+    const TRACK_ID: &str = "/org/shadertui/CurrentShader";
+
+    // The bus name carries the process id, as MPRIS asks of players that can run more than once
+    pub fn register(sender: CommandSender, shader: PathBuf) -> zbus::Result<Connection> {
+        let root = Root {
+            sender: sender.clone(),
+        };
+        let player = Player {
+            sender,
+            shader,
+            paused: false,
+        };
+        connection::Builder::session()?
+            .name(format!(
+                "org.mpris.MediaPlayer2.shadertui.instance{}",
+                std::process::id()
+            ))?
+            .serve_at(OBJECT_PATH, root)?
+            .serve_at(OBJECT_PATH, player)?
+            .build()
+    }
+
+    struct Root {
+        sender: CommandSender,
+    }
+
+    #[interface(name = "org.mpris.MediaPlayer2")]
+    impl Root {
+        fn raise(&self) {}
+
+        fn quit(&self) {
+            let _ = self.sender.send(ControlCommand::Quit);
+        }
+
+        #[zbus(property)]
+        fn can_quit(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_raise(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn has_track_list(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn identity(&self) -> &str {
+            "shadertui"
+        }
+
+        #[zbus(property)]
+        fn supported_uri_schemes(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        #[zbus(property)]
+        fn supported_mime_types(&self) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    struct Player {
+        sender: CommandSender,
+        shader: PathBuf,
+        paused: bool,
+    }
+
+    impl Player {
+        async fn set_paused(&mut self, paused: bool, emitter: &SignalEmitter<'_>) {
+            let _ = self.sender.send(ControlCommand::Pause(Some(paused)));
+            self.paused = paused;
+            let _ = self.playback_status_changed(emitter).await;
+        }
+
+        async fn skip(&mut self, shaders: i32, emitter: &SignalEmitter<'_>) {
+            // Without another shader to switch to, the current one keeps running
+            let Ok(shader) = sibling_shader(&self.shader, shaders) else {
+                return;
+            };
+            let _ = self.sender.send(ControlCommand::Load(shader.clone()));
+            self.shader = shader;
+            let _ = self.metadata_changed(emitter).await;
+        }
+    }
+
+    #[interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl Player {
+        async fn next(&mut self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+            self.skip(1, &emitter).await;
+        }
+
+        async fn previous(&mut self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+            self.skip(-1, &emitter).await;
+        }
+
+        async fn pause(&mut self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+            self.set_paused(true, &emitter).await;
+        }
+
+        async fn play_pause(&mut self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+            self.set_paused(!self.paused, &emitter).await;
+        }
+
+        // A shader can't be stopped, only paused
+        async fn stop(&mut self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+            self.set_paused(true, &emitter).await;
+        }
+
+        async fn play(&mut self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+            self.set_paused(false, &emitter).await;
+        }
+
+        // `offset` is in microseconds
+        fn seek(&self, offset: i64) {
+            let _ = self
+                .sender
+                .send(ControlCommand::Seek(offset as f32 / 1_000_000.0));
+        }
+
+        #[zbus(property)]
+        fn playback_status(&self) -> &str {
+            if self.paused {
+                "Paused"
+            } else {
+                "Playing"
+            }
+        }
+
+        #[zbus(property)]
+        fn metadata(&self) -> HashMap<&'static str, Value<'static>> {
+            let title = self
+                .shader
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            HashMap::from([
+                (
+                    "mpris:trackid",
+                    Value::from(ObjectPath::from_static_str_unchecked(TRACK_ID)),
+                ),
+                ("xesam:title", Value::from(title)),
+            ])
+        }
+
+        #[zbus(property)]
+        fn rate(&self) -> f64 {
+            1.0
+        }
+
+        #[zbus(property)]
+        fn minimum_rate(&self) -> f64 {
+            1.0
+        }
+
+        #[zbus(property)]
+        fn maximum_rate(&self) -> f64 {
+            1.0
+        }
+
+        #[zbus(property)]
+        fn can_go_next(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_go_previous(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_play(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_pause(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_seek(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_control(&self) -> bool {
+            true
+        }
+    }
+}
+
+#[cfg(not(all(feature = "mpris", target_os = "linux")))]
+mod dbus {
+    use std::path::PathBuf;
+
+    use crate::utils::control::ControlCommand;
+
+    pub struct Connection;
+
+    pub fn register(
+        _sender: flume::Sender<ControlCommand>,
+        _shader: PathBuf,
+    ) -> Result<Connection, String> {
+        if cfg!(target_os = "linux") {
+            Err("shadertui was built without MPRIS support (rebuild with --features mpris)".into())
+        } else {
+            Err("MPRIS is only available on Linux".into())
+        }
+    }
+}
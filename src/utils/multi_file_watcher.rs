@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
+use crate::error::ShadertuiError;
 use notify::{RecursiveMode, Watcher};
 
 pub struct MultiFileWatcher {
@@ -15,7 +16,7 @@ pub struct MultiFileWatcher {
 }
 
 impl MultiFileWatcher {
-    pub fn new(main_file: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(main_file: &Path) -> Result<Self, ShadertuiError> {
         let (tx, rx) = mpsc::channel();
         let mut watcher = Self {
             main_file: main_file.to_path_buf(),
@@ -31,7 +32,7 @@ impl MultiFileWatcher {
         Ok(watcher)
     }
 
-    fn add_file_to_watch(&mut self, file_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fn add_file_to_watch(&mut self, file_path: &Path) -> Result<(), ShadertuiError> {
         let canonical_path = file_path.canonicalize()?;
 
         if self.watched_files.contains(&canonical_path) {
@@ -68,7 +69,7 @@ impl MultiFileWatcher {
     pub fn update_watched_files(
         &mut self,
         all_files: &HashSet<PathBuf>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), ShadertuiError> {
         // Always include the main file
         let mut target_files = all_files.clone();
         target_files.insert(self.main_file.clone());
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use crate::utils::terminal_caps::TerminalCapabilities;
+
+// Compiles at least this long notify even when the previous shader was fine
+const LONG_COMPILE: Duration = Duration::from_secs(5);
+
+// AIDEV-NOTE: --notify tells a user who switched away that the shader is back: after a reload that
+// follows a failed one, or one that compiled for LONG_COMPILE or more. `compile_time` is None for
+// reloads that weren't timed
+pub fn should_notify(was_failing: bool, compile_time: Option<Duration>) -> bool {
+    was_failing || compile_time.is_some_and(|time| time >= LONG_COMPILE)
+}
+
+// The bell, then an OSC 9 desktop notification for terminals that show them (iTerm2, WezTerm,
+// Windows Terminal, Ghostty, ...). Others ignore it; multiplexers need it passed through
+pub fn terminal_sequence(caps: &TerminalCapabilities, message: &str) -> String {
+    let message: String = message.chars().filter(|c| !c.is_control()).collect();
+    format!(
+        "\x07{}",
+        caps.wrap_passthrough(&format!("\x1b]9;{message}\x07"))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_notify() {
+        assert!(should_notify(true, None));
+        assert!(!should_notify(false, None));
+        assert!(!should_notify(false, Some(Duration::from_secs(1))));
+        assert!(should_notify(false, Some(LONG_COMPILE)));
+
+        let caps = TerminalCapabilities::default();
+        assert_eq!(
+            terminal_sequence(&caps, "a\x1bb"),
+            "\x07\x1b]9;ab\x07".to_string()
+        );
+    }
+}
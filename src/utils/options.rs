@@ -0,0 +1,126 @@
+use clap::ValueEnum;
+
+use crate::renderers::headless_renderer::EXPORT_FPS;
+
+// AIDEV-NOTE: Option values the rendering core shares with the command line: how pixels become
+// cells and colors, and the times, rates and scales shaders can also set in their header (see
+// utils::metadata). utils::cli re-exports them, so they build without the `cli` feature
+
+// AIDEV-NOTE: Terminal cell rendering strategies; `pixels_per_cell` sets the GPU resolution
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CellMode {
+    /// ▀ with foreground and background colors (two pixels per cell)
+    HalfBlock,
+    /// Density glyphs in the foreground color only, keeping the terminal's own background
+    /// (better on light themes)
+    Foreground,
+    /// Unicode braille dots, 2x4 pixels per cell in a single foreground color
+    Braille,
+    /// Plain characters picked by brightness, without any color escapes
+    Ascii,
+    /// Quadrant blocks (▘▝▖▗▚▞...), 2x2 pixels per cell in two colors
+    Quadrant,
+    /// Sextant blocks from Unicode 13, 2x3 pixels per cell in two colors (needs a font with them,
+    /// e.g. Cascadia Code, Iosevka or a Nerd Font)
+    Sextant,
+}
+
+impl CellMode {
+    // AIDEV-NOTE: GPU pixels (columns, rows) drawn by one terminal cell
+    pub fn pixels_per_cell(self) -> (u32, u32) {
+        match self {
+            CellMode::HalfBlock | CellMode::Foreground | CellMode::Ascii => (1, 2),
+            CellMode::Quadrant => (2, 2),
+            CellMode::Sextant => (2, 3),
+            CellMode::Braille => (2, 4),
+        }
+    }
+}
+
+// AIDEV-NOTE: Downfilters for --aa; see utils::antialias
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AntiAliasing {
+    /// Average of the 2x2 samples inside each pixel
+    Box,
+    /// Gaussian-like 4x4 footprint, softer and with less shimmer in motion
+    Gauss,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// 24-bit RGB escape codes
+    Truecolor,
+    /// Nearest color of the xterm 256-color palette
+    #[value(alias = "256")]
+    Ansi256,
+    /// Nearest of the 16 basic ANSI colors
+    #[value(alias = "16")]
+    Ansi16,
+}
+
+// AIDEV-NOTE: Spreads the error of snapping colors to the ansi256/ansi16 palette over neighboring
+// cells; see utils::dither
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Dither {
+    None,
+    /// A fixed 4x4 Bayer pattern, stable while the image moves
+    Ordered,
+    /// Floyd-Steinberg error diffusion: smoother, but the pattern shimmers in animation
+    FloydSteinberg,
+}
+
+// AIDEV-NOTE: Built-in displays for 1D shaders, which compute samples instead of pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SampleView {
+    /// Oscilloscope trace of the samples from -1 (bottom) to 1 (top)
+    Scope,
+    /// Frequency spectrum of every frame, scrolling left with low frequencies at the bottom
+    Spectrogram,
+}
+
+// AIDEV-NOTE: Frame numbers (`120f`) are on the export timestep, so they land exactly on a frame
+pub fn parse_seconds(value: &str) -> Result<f32, String> {
+    let (number, units_per_second) = if let Some(frames) = value.strip_suffix('f') {
+        (frames, EXPORT_FPS)
+    } else if let Some(ms) = value.strip_suffix("ms") {
+        (ms, 1000.0)
+    } else if let Some(secs) = value.strip_suffix('s') {
+        (secs, 1.0)
+    } else {
+        (value, 1.0)
+    };
+
+    match number.trim().parse::<f32>() {
+        Ok(time) if time.is_finite() && time >= 0.0 => Ok(time / units_per_second),
+        _ => Err(format!(
+            "invalid time '{value}' (expected e.g. 3.5s, 500ms or 120f)"
+        )),
+    }
+}
+
+pub fn parse_fps(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(fps) if fps > 0 => Ok(fps),
+        _ => Err(format!(
+            "invalid frame rate '{value}' (expected a positive number)"
+        )),
+    }
+}
+
+pub fn parse_scale(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(scale) if scale > 0.0 && scale <= 1.0 => Ok(scale),
+        _ => Err(format!(
+            "invalid scale '{value}' (expected a factor above 0 and at most 1, e.g. 0.5)"
+        )),
+    }
+}
+
+pub fn parse_loop(value: &str) -> Result<f32, String> {
+    match parse_seconds(value)? {
+        time if time > 0.0 => Ok(time),
+        _ => Err(format!(
+            "invalid loop length '{value}' (must be longer than 0)"
+        )),
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+// AIDEV-NOTE: A palette file is a bare list of colors or an object with a `colors` list. Colors are
+// "#rrggbb"/"#rgb" hex strings (sRGB, converted to linear to match the renderers' output gamma) or
+// [r, g, b] floats taken as linear values
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PaletteFile {
+    List(Vec<PaletteColor>),
+    Object { colors: Vec<PaletteColor> },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PaletteColor {
+    Hex(String),
+    Rgb([f32; 3]),
+}
+
+// AIDEV-NOTE: Constant name when the pragma has no `as NAME`, e.g. "sunset-2.json" -> PALETTE_SUNSET_2
+pub fn default_palette_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let sanitized: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("PALETTE_{sanitized}")
+}
+
+// AIDEV-NOTE: Emits `const NAME_SIZE: u32` and `const NAME: array<vec3<f32>, N>`
+pub fn palette_to_wgsl(name: &str, json: &str) -> Result<String, String> {
+    let colors = match serde_json::from_str(json).map_err(|e| e.to_string())? {
+        PaletteFile::List(colors) | PaletteFile::Object { colors } => colors,
+    };
+    if colors.is_empty() {
+        return Err("palette has no colors".to_string());
+    }
+
+    let entries = colors
+        .iter()
+        .map(|color| {
+            let [r, g, b] = match color {
+                PaletteColor::Hex(hex) => parse_hex(hex)?,
+                PaletteColor::Rgb(rgb) => *rgb,
+            };
+            Ok(format!("vec3<f32>({r:.6}, {g:.6}, {b:.6})"))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let count = entries.len();
+    Ok(format!(
+        "const {name}_SIZE: u32 = {count}u;\nconst {name} = array<vec3<f32>, {count}>(\n    {}\n);",
+        entries.join(",\n    ")
+    ))
+}
+
+fn parse_hex(hex: &str) -> Result<[f32; 3], String> {
+    let digits = hex.trim().trim_start_matches('#');
+    let expanded: String = match digits.len() {
+        3 => digits.chars().flat_map(|c| [c, c]).collect(),
+        6 => digits.to_string(),
+        _ => return Err(format!("invalid hex color '{hex}'")),
+    };
+
+    let mut rgb = [0.0; 3];
+    for (i, channel) in rgb.iter_mut().enumerate() {
+        let value = u8::from_str_radix(&expanded[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("invalid hex color '{hex}'"))?;
+        *channel = (value as f32 / 255.0).powf(2.2);
+    }
+    Ok(rgb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_to_wgsl() {
+        let wgsl =
+            palette_to_wgsl("PALETTE_TEST", r##"{"colors": ["#fff", [0.5, 0.25, 0]]}"##).unwrap();
+        assert!(wgsl.contains("const PALETTE_TEST_SIZE: u32 = 2u;"));
+        assert!(wgsl.contains("vec3<f32>(1.000000, 1.000000, 1.000000)"));
+        assert!(wgsl.contains("vec3<f32>(0.500000, 0.250000, 0.000000)"));
+
+        assert!(palette_to_wgsl("P", "[]").is_err());
+        assert!(palette_to_wgsl("P", r##"["#12345"]"##).is_err());
+        assert_eq!(
+            default_palette_name(Path::new("dir/sunset-2.json")),
+            "PALETTE_SUNSET_2"
+        );
+    }
+}
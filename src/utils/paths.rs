@@ -0,0 +1,69 @@
+use std::env;
+use std::path::PathBuf;
+
+const APP_DIR_NAME: &str = "shadertui";
+
+// AIDEV-NOTE: Single home for on-disk artifacts (gpu::pipeline_cache's pipelines/, ...). Features
+// that write files should use a subdirectory of this instead of picking their own location
+pub fn cache_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        return home_dir().map(|home| home.join("Library/Caches").join(APP_DIR_NAME));
+    }
+    if cfg!(windows) {
+        return env::var_os("LOCALAPPDATA")
+            .map(|local| PathBuf::from(local).join(APP_DIR_NAME).join("cache"));
+    }
+    xdg_dir(env::var_os("XDG_CACHE_HOME"), home_dir(), ".cache")
+}
+
+// User configuration, such as translations (see messages.rs)
+pub fn config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        return home_dir().map(|home| home.join("Library/Application Support").join(APP_DIR_NAME));
+    }
+    if cfg!(windows) {
+        return env::var_os("APPDATA").map(|roaming| PathBuf::from(roaming).join(APP_DIR_NAME));
+    }
+    xdg_dir(env::var_os("XDG_CONFIG_HOME"), home_dir(), ".config")
+}
+
+// AIDEV-NOTE: XDG base directory rule - use the variable if it is an absolute path, otherwise fall
+// back to $HOME/<default>
+fn xdg_dir(
+    xdg_value: Option<std::ffi::OsString>,
+    home: Option<PathBuf>,
+    home_default: &str,
+) -> Option<PathBuf> {
+    let base = xdg_value
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| home.map(|home| home.join(home_default)))?;
+    Some(base.join(APP_DIR_NAME))
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xdg_dir_resolution() {
+        let home = Some(PathBuf::from("/home/user"));
+
+        assert_eq!(
+            xdg_dir(Some("/xdg/cache".into()), home.clone(), ".cache"),
+            Some(PathBuf::from("/xdg/cache/shadertui"))
+        );
+        // Relative values are invalid per the spec and ignored
+        assert_eq!(
+            xdg_dir(Some("relative".into()), home.clone(), ".cache"),
+            Some(PathBuf::from("/home/user/.cache/shadertui"))
+        );
+        assert_eq!(xdg_dir(None, None, ".cache"), None);
+    }
+}
@@ -0,0 +1,782 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::utils::metadata::{ShaderMetadata, ShaderSettings};
+use crate::utils::sample_view::sample_adapter;
+use crate::utils::shader_import::{process_imports, ImportError};
+use crate::utils::shader_shell::ShaderApiVersion;
+use crate::utils::shadertoy::{is_glsl_path, translate_shadertoy};
+use crate::utils::source_map::SourceMap;
+use crate::utils::uniform_params::{parse_params, UniformParam};
+
+pub const OUTPUT_PASS_NAME: &str = "image";
+// AIDEV-NOTE: Upper bound on a buffer pass's resolution scale, to keep allocations sane
+const MAX_PASS_SCALE: f32 = 4.0;
+const ITERATIONS_PRAGMA: &str = "// @iterations ";
+const PASS_PRAGMA: &str = "// @pass ";
+const MAX_PASS_ITERATIONS: u32 = 64;
+const DOMAIN_PRAGMA: &str = "// @domain ";
+// AIDEV-NOTE: Samples computed per frame by a `// @domain 1d` shader
+pub const SAMPLE_COUNT: u32 = 1024;
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Manifest {
+        path: PathBuf,
+        message: String,
+    },
+    Import {
+        pass: String,
+        source: ImportError,
+    },
+    // `// @pass` sections of a single shader file that don't form a valid project
+    Sections {
+        path: PathBuf,
+        message: String,
+    },
+    Pragma {
+        path: PathBuf,
+        message: String,
+    },
+    // A .glsl/.frag file that doesn't translate as a Shadertoy shader
+    Glsl {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectError::Io { path, source } => {
+                write!(f, "Error reading '{}': {}", path.display(), source)
+            }
+            ProjectError::Manifest { path, message } => {
+                write!(
+                    f,
+                    "Invalid project manifest '{}': {}",
+                    path.display(),
+                    message
+                )
+            }
+            ProjectError::Import { pass, source } => {
+                write!(f, "Import processing error in pass '{pass}': {source}")
+            }
+            ProjectError::Sections { path, message } => {
+                write!(
+                    f,
+                    "Invalid @pass sections in '{}': {message}",
+                    path.display()
+                )
+            }
+            ProjectError::Pragma { path, message } => {
+                write!(f, "Invalid pragma in '{}': {message}", path.display())
+            }
+            ProjectError::Glsl { path, message } => {
+                let lines: Vec<String> = message
+                    .lines()
+                    .map(|line| format!("{}:{line}", path.display()))
+                    .collect();
+                write!(f, "{}", lines.join("\n"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+// AIDEV-NOTE: project.toml schema - passes run in declared order each frame and the last one is the
+// displayed output; `inputs` become channel0..N in that pass's shader
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    #[serde(rename = "pass")]
+    passes: Vec<PassManifest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PassManifest {
+    name: String,
+    shader: PathBuf,
+    #[serde(default)]
+    inputs: Vec<String>,
+    #[serde(default = "default_scale")]
+    scale: f32,
+    #[serde(default)]
+    format: BufferFormat,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+// AIDEV-NOTE: Texture formats a buffer pass can write, named as in WGSL. 32-bit float buffers keep
+// precision for simulations but can't be filtered, so passes reading them sample with nearest
+// filtering (see gpu::passes for the wgpu mapping)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum BufferFormat {
+    #[default]
+    #[serde(rename = "rgba16float")]
+    Rgba16Float,
+    #[serde(rename = "rgba32float")]
+    Rgba32Float,
+    #[serde(rename = "rg32float")]
+    Rg32Float,
+    #[serde(rename = "r32float")]
+    R32Float,
+    #[serde(rename = "rgba8unorm")]
+    Rgba8Unorm,
+}
+
+impl BufferFormat {
+    pub fn wgsl_name(self) -> &'static str {
+        match self {
+            BufferFormat::Rgba16Float => "rgba16float",
+            BufferFormat::Rgba32Float => "rgba32float",
+            BufferFormat::Rg32Float => "rg32float",
+            BufferFormat::R32Float => "r32float",
+            BufferFormat::Rgba8Unorm => "rgba8unorm",
+        }
+    }
+
+    pub fn is_filterable(self) -> bool {
+        !matches!(
+            self,
+            BufferFormat::Rgba32Float | BufferFormat::Rg32Float | BufferFormat::R32Float
+        )
+    }
+}
+
+// AIDEV-NOTE: A single compute pass after import processing; `inputs` are indices into the buffer passes.
+// `scale` and `format` only apply to buffer passes, the output pass always matches the display
+#[derive(Debug, Clone)]
+pub struct PassSource {
+    pub name: String,
+    pub source: String,
+    pub inputs: Vec<usize>,
+    pub scale: f32,
+    pub format: BufferFormat,
+    // Dispatches per frame from `// @iterations N`, each reading the previous one's output
+    pub iterations: u32,
+    pub source_map: SourceMap,
+}
+
+// AIDEV-NOTE: What the output pass computes. `// @domain 1d` shaders implement
+// `fn compute_sample(index: u32) -> f32` over SAMPLE_COUNT samples, and the renderer draws them with
+// a built-in view instead of showing them as pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Domain {
+    #[default]
+    Pixels,
+    Samples,
+}
+
+// AIDEV-NOTE: Everything needed to render one frame - a plain .wgsl file loads as a project with a
+// single output pass, so renderers and hot reload only deal with this type. Metadata comes from the
+// output pass header
+#[derive(Debug, Clone)]
+pub struct ShaderProject {
+    pub buffers: Vec<PassSource>,
+    pub output: PassSource,
+    pub all_files: HashSet<PathBuf>,
+    pub metadata: ShaderMetadata,
+    // Runtime defaults from the output pass header (`// @fps`, `// @scale`, `// @loop`)
+    pub settings: ShaderSettings,
+    pub domain: Domain,
+    // `// @uniform` parameters declared by any pass
+    pub params: Vec<UniformParam>,
+}
+
+impl ShaderProject {
+    pub fn load(path: &Path) -> Result<Self, ProjectError> {
+        let project = if is_manifest_path(path) {
+            Self::load_manifest(path)
+        } else {
+            Self::load_single_shader(path)
+        }?;
+        project
+            .with_domain(path)?
+            .with_params(path)?
+            .with_settings(path)
+    }
+
+    pub fn passes(&self) -> impl Iterator<Item = &PassSource> {
+        self.buffers.iter().chain(std::iter::once(&self.output))
+    }
+
+    // AIDEV-NOTE: Single-shader project from unsaved source (--edit); imports resolve relative to `path`
+    pub fn from_source(path: &Path, raw_source: &str) -> Result<Self, ProjectError> {
+        let (source, source_map, all_files) =
+            process_pass_source(OUTPUT_PASS_NAME, path, raw_source)?;
+        Self::from_shader_file(path, source, source_map, all_files)?
+            .with_domain(path)?
+            .with_params(path)?
+            .with_settings(path)
+    }
+
+    // AIDEV-NOTE: 1D output passes get a compute_color() adapter around compute_sample(), so every
+    // shell and validator sees an ordinary shader. An unsupported API version is left for the
+    // shell to report
+    fn with_domain(mut self, path: &Path) -> Result<Self, ProjectError> {
+        self.domain =
+            parse_domain(&self.output.source).map_err(|message| ProjectError::Pragma {
+                path: path.to_path_buf(),
+                message,
+            })?;
+        if self.domain == Domain::Samples {
+            if let Ok(version) = ShaderApiVersion::from_user_shader(&self.output.source) {
+                self.output.source.push('\n');
+                self.output.source.push_str(&sample_adapter(version));
+            }
+        }
+        Ok(self)
+    }
+
+    fn with_params(mut self, path: &Path) -> Result<Self, ProjectError> {
+        self.params =
+            parse_params(self.passes().map(|pass| pass.source.as_str())).map_err(|message| {
+                ProjectError::Pragma {
+                    path: path.to_path_buf(),
+                    message,
+                }
+            })?;
+        Ok(self)
+    }
+
+    fn with_settings(mut self, path: &Path) -> Result<Self, ProjectError> {
+        self.settings =
+            ShaderSettings::parse(&self.output.source).map_err(|message| ProjectError::Pragma {
+                path: path.to_path_buf(),
+                message,
+            })?;
+        Ok(self)
+    }
+
+    fn load_single_shader(path: &Path) -> Result<Self, ProjectError> {
+        let (source, source_map, all_files) = load_pass_source(OUTPUT_PASS_NAME, path)?;
+        Self::from_shader_file(path, source, source_map, all_files)
+    }
+
+    // AIDEV-NOTE: A .wgsl file is one output pass, unless it is split into Shadertoy-style
+    // `// @pass NAME` sections. Then code before the first section is shared by every pass, the last
+    // section must be the output pass, and every pass gets all buffers as channel0..N in order
+    fn from_shader_file(
+        path: &Path,
+        source: String,
+        source_map: SourceMap,
+        all_files: HashSet<PathBuf>,
+    ) -> Result<Self, ProjectError> {
+        let metadata = ShaderMetadata::parse(&source);
+        let Some(sections) = split_pass_sections(&source) else {
+            return Ok(Self {
+                metadata,
+                buffers: Vec::new(),
+                output: PassSource {
+                    name: OUTPUT_PASS_NAME.to_string(),
+                    source,
+                    inputs: Vec::new(),
+                    scale: 1.0,
+                    format: BufferFormat::default(),
+                    iterations: 1,
+                    source_map,
+                },
+                all_files,
+                settings: ShaderSettings::default(),
+                domain: Domain::Pixels,
+                params: Vec::new(),
+            });
+        };
+
+        let sections_error = |message: String| ProjectError::Sections {
+            path: path.to_path_buf(),
+            message,
+        };
+        let names: Vec<&str> = sections.iter().map(|(name, ..)| name.as_str()).collect();
+        if names.iter().any(|name| name.is_empty()) {
+            return Err(sections_error("'// @pass' needs a pass name".to_string()));
+        }
+        if names.last() != Some(&OUTPUT_PASS_NAME) {
+            return Err(sections_error(format!(
+                "the last section must be '// @pass {OUTPUT_PASS_NAME}'"
+            )));
+        }
+        if let Some(duplicate) = names
+            .iter()
+            .enumerate()
+            .find_map(|(i, name)| names[..i].contains(name).then_some(name))
+        {
+            return Err(sections_error(format!("duplicate pass name '{duplicate}'")));
+        }
+
+        let all_buffers: Vec<usize> = (0..sections.len() - 1).collect();
+        let mut passes = Vec::new();
+        for (name, source, lines) in sections {
+            passes.push(PassSource {
+                iterations: parse_iterations(&name, &source).map_err(sections_error)?,
+                name,
+                source,
+                inputs: all_buffers.clone(),
+                scale: 1.0,
+                format: BufferFormat::default(),
+                source_map: source_map.select(&lines),
+            });
+        }
+
+        let mut output = passes.pop().unwrap();
+        output.iterations = 1;
+        Ok(Self {
+            metadata,
+            buffers: passes,
+            output,
+            all_files,
+            settings: ShaderSettings::default(),
+            domain: Domain::Pixels,
+            params: Vec::new(),
+        })
+    }
+
+    fn load_manifest(path: &Path) -> Result<Self, ProjectError> {
+        let content = fs::read_to_string(path).map_err(|e| ProjectError::Io {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let manifest = parse_manifest(path, &content)?;
+        let project_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+        let mut all_files = HashSet::new();
+        if let Ok(canonical) = path.canonicalize() {
+            all_files.insert(canonical);
+        }
+
+        let names: Vec<&str> = manifest.passes.iter().map(|p| p.name.as_str()).collect();
+        let mut passes = Vec::new();
+        for pass in &manifest.passes {
+            let (source, source_map, files) =
+                load_pass_source(&pass.name, &project_dir.join(&pass.shader))?;
+            all_files.extend(files);
+
+            let inputs = pass
+                .inputs
+                .iter()
+                .map(|input| names.iter().position(|name| name == input).unwrap())
+                .collect();
+            passes.push(PassSource {
+                name: pass.name.clone(),
+                iterations: parse_iterations(&pass.name, &source).map_err(|message| {
+                    ProjectError::Manifest {
+                        path: path.to_path_buf(),
+                        message,
+                    }
+                })?,
+                source,
+                inputs,
+                scale: pass.scale,
+                format: pass.format,
+                source_map,
+            });
+        }
+
+        let output = passes.pop().unwrap();
+        Ok(Self {
+            metadata: ShaderMetadata::parse(&output.source),
+            buffers: passes,
+            output,
+            all_files,
+            settings: ShaderSettings::default(),
+            domain: Domain::Pixels,
+            params: Vec::new(),
+        })
+    }
+}
+
+// AIDEV-NOTE: Returns (name, shared prefix + section body, index in `source` of each of its lines) per
+// `// @pass NAME` line, or None when the file has no sections
+fn split_pass_sections(source: &str) -> Option<Vec<(String, String, Vec<usize>)>> {
+    let mut prefix = (String::new(), Vec::new());
+    let mut sections: Vec<(String, String, Vec<usize>)> = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        if let Some(name) = line.trim().strip_prefix(PASS_PRAGMA) {
+            sections.push((name.trim().to_string(), prefix.0.clone(), prefix.1.clone()));
+            continue;
+        }
+        let (body, lines) = match sections.last_mut() {
+            Some((_, body, lines)) => (body, lines),
+            None => (&mut prefix.0, &mut prefix.1),
+        };
+        body.push_str(line);
+        body.push('\n');
+        lines.push(index);
+    }
+
+    (!sections.is_empty()).then_some(sections)
+}
+
+// AIDEV-NOTE: `// @iterations N` on its own line; only buffer passes iterate (the output pass is
+// always dispatched once)
+fn parse_iterations(pass_name: &str, source: &str) -> Result<u32, String> {
+    let Some(value) = source
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(ITERATIONS_PRAGMA))
+    else {
+        return Ok(1);
+    };
+
+    match value.trim().parse::<u32>() {
+        Ok(iterations) if (1..=MAX_PASS_ITERATIONS).contains(&iterations) => Ok(iterations),
+        _ => Err(format!(
+            "pass '{pass_name}' has invalid @iterations '{}' (expected 1 to {MAX_PASS_ITERATIONS})",
+            value.trim()
+        )),
+    }
+}
+
+fn parse_domain(source: &str) -> Result<Domain, String> {
+    let value = source
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(DOMAIN_PRAGMA));
+    match value.map(str::trim) {
+        None | Some("2d") => Ok(Domain::Pixels),
+        Some("1d") => Ok(Domain::Samples),
+        Some(other) => Err(format!("unknown @domain '{other}' (expected 1d or 2d)")),
+    }
+}
+
+pub fn is_manifest_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+// AIDEV-NOTE: Files that load as a single shader when found in a directory (screensaver, list)
+pub fn is_shader_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "wgsl") || is_glsl_path(path)
+}
+
+// AIDEV-NOTE: The shader file `step` places after `current` in its directory, in name order and
+// wrapping around (media keys' next/previous). A current file that isn't a shader file, like a
+// project.toml, counts as sitting just before the first one
+pub fn sibling_shader(current: &Path, step: i32) -> Result<PathBuf, String> {
+    let dir = match current.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut shaders: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Could not read '{}': {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && is_shader_path(path))
+        .collect();
+    if shaders.is_empty() {
+        return Err(format!("No shader files in '{}'", dir.display()));
+    }
+    shaders.sort();
+
+    let position = match shaders
+        .iter()
+        .position(|path| path.file_name() == current.file_name())
+    {
+        Some(index) => index as i64,
+        None if step > 0 => -1,
+        None => 0,
+    };
+    let index = (position + i64::from(step)).rem_euclid(shaders.len() as i64);
+    Ok(shaders.swap_remove(index as usize))
+}
+
+fn load_pass_source(
+    pass_name: &str,
+    shader_path: &Path,
+) -> Result<(String, SourceMap, HashSet<PathBuf>), ProjectError> {
+    let raw_source = fs::read_to_string(shader_path).map_err(|e| ProjectError::Io {
+        path: shader_path.to_path_buf(),
+        source: e,
+    })?;
+    process_pass_source(pass_name, shader_path, &raw_source)
+}
+
+fn process_pass_source(
+    pass_name: &str,
+    shader_path: &Path,
+    raw_source: &str,
+) -> Result<(String, SourceMap, HashSet<PathBuf>), ProjectError> {
+    // Shadertoy GLSL has no imports; it is translated as a whole
+    if is_glsl_path(shader_path) {
+        let source = translate_shadertoy(raw_source).map_err(|message| ProjectError::Glsl {
+            path: shader_path.to_path_buf(),
+            message,
+        })?;
+        let all_files = shader_path.canonicalize().into_iter().collect();
+        return Ok((source, SourceMap::default(), all_files));
+    }
+
+    let (source, source_map, deps) =
+        process_imports(shader_path, raw_source).map_err(|e| ProjectError::Import {
+            pass: pass_name.to_string(),
+            source: e,
+        })?;
+    Ok((source, source_map, deps.all_files))
+}
+
+// AIDEV-NOTE: Structural checks happen here so the loader can index passes without further validation
+fn parse_manifest(path: &Path, content: &str) -> Result<Manifest, ProjectError> {
+    let manifest_error = |message: String| ProjectError::Manifest {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    let manifest: Manifest = toml::from_str(content).map_err(|e| manifest_error(e.to_string()))?;
+
+    if manifest.passes.is_empty() {
+        return Err(manifest_error(
+            "at least one [[pass]] is required".to_string(),
+        ));
+    }
+
+    let buffer_count = manifest.passes.len() - 1;
+    for (i, pass) in manifest.passes.iter().enumerate() {
+        if i == buffer_count && (pass.scale != 1.0 || pass.format != BufferFormat::default()) {
+            return Err(manifest_error(format!(
+                "the output pass '{}' always renders at display size and format; set scale and format on buffer passes",
+                pass.name
+            )));
+        }
+        if !(pass.scale > 0.0 && pass.scale <= MAX_PASS_SCALE) {
+            return Err(manifest_error(format!(
+                "pass '{}' has scale {}, expected a value in (0, {MAX_PASS_SCALE}]",
+                pass.name, pass.scale
+            )));
+        }
+
+        if manifest.passes[..i].iter().any(|p| p.name == pass.name) {
+            return Err(manifest_error(format!(
+                "duplicate pass name '{}'",
+                pass.name
+            )));
+        }
+
+        for input in &pass.inputs {
+            match manifest.passes.iter().position(|p| &p.name == input) {
+                Some(index) if index < buffer_count => {}
+                Some(_) => {
+                    return Err(manifest_error(format!(
+                        "pass '{}' cannot use the output pass '{input}' as an input",
+                        pass.name
+                    )));
+                }
+                None => {
+                    return Err(manifest_error(format!(
+                        "pass '{}' has unknown input '{input}'",
+                        pass.name
+                    )));
+                }
+            }
+        }
+    }
+
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shader_shell::{ShellOptions, ShellType};
+    use crate::utils::validation::validate_project;
+
+    fn parse(content: &str) -> Result<Manifest, ProjectError> {
+        parse_manifest(Path::new("project.toml"), content)
+    }
+
+    #[test]
+    fn test_parse_manifest_valid() {
+        let manifest = parse(
+            r#"
+            [[pass]]
+            name = "bufferA"
+            shader = "buffer_a.wgsl"
+            inputs = ["bufferA"]
+
+            [[pass]]
+            name = "image"
+            shader = "image.wgsl"
+            inputs = ["bufferA"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.passes.len(), 2);
+        assert_eq!(manifest.passes[1].inputs, vec!["bufferA".to_string()]);
+        assert_eq!(manifest.passes[0].scale, 1.0);
+        assert_eq!(manifest.passes[0].format, BufferFormat::Rgba16Float);
+
+        let sized = parse(
+            r#"
+            [[pass]]
+            name = "velocity"
+            shader = "velocity.wgsl"
+            scale = 0.5
+            format = "rg32float"
+
+            [[pass]]
+            name = "image"
+            shader = "image.wgsl"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(sized.passes[0].scale, 0.5);
+        assert_eq!(sized.passes[0].format, BufferFormat::Rg32Float);
+    }
+
+    #[test]
+    fn test_parse_manifest_rejects_bad_wiring() {
+        let unknown_input = r#"
+            [[pass]]
+            name = "image"
+            shader = "image.wgsl"
+            inputs = ["bufferB"]
+        "#;
+        assert!(matches!(
+            parse(unknown_input),
+            Err(ProjectError::Manifest { .. })
+        ));
+
+        let output_as_input = r#"
+            [[pass]]
+            name = "bufferA"
+            shader = "a.wgsl"
+            inputs = ["image"]
+
+            [[pass]]
+            name = "image"
+            shader = "image.wgsl"
+        "#;
+        assert!(matches!(
+            parse(output_as_input),
+            Err(ProjectError::Manifest { .. })
+        ));
+
+        let scaled_output = r#"
+            [[pass]]
+            name = "image"
+            shader = "image.wgsl"
+            scale = 0.5
+        "#;
+        assert!(matches!(
+            parse(scaled_output),
+            Err(ProjectError::Manifest { .. })
+        ));
+
+        assert!(matches!(
+            parse("pass = []"),
+            Err(ProjectError::Manifest { .. })
+        ));
+    }
+
+    #[test]
+    fn test_split_pass_sections() {
+        assert!(split_pass_sections("fn compute_color() {}").is_none());
+
+        let sections = split_pass_sections(
+            "fn shared() {}\n// @pass bufferA\nfn a() {}\n// @pass image\nfn b() {}\n",
+        )
+        .unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].0, "bufferA");
+        assert_eq!(sections[0].1, "fn shared() {}\nfn a() {}\n");
+        assert_eq!(sections[1].1, "fn shared() {}\nfn b() {}\n");
+    }
+
+    #[test]
+    fn test_load_single_file_sections() {
+        let shader_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders/multipass.wgsl");
+        let project = ShaderProject::load(&shader_path).unwrap();
+
+        assert_eq!(project.buffers.len(), 2);
+        assert_eq!(project.buffers[1].inputs, vec![0, 1]);
+        assert_eq!(project.output.name, OUTPUT_PASS_NAME);
+        assert!(validate_project(&project, ShellType::Terminal, &ShellOptions::default()).is_ok());
+
+        let misordered = "// @pass image\nfn a() {}\n// @pass bufferA\nfn b() {}\n";
+        assert!(matches!(
+            ShaderProject::from_shader_file(
+                &shader_path,
+                misordered.to_string(),
+                SourceMap::default(),
+                HashSet::new()
+            ),
+            Err(ProjectError::Sections { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_iterations() {
+        assert_eq!(parse_iterations("a", "fn f() {}"), Ok(1));
+        assert_eq!(
+            parse_iterations("a", "  // @iterations 8\nfn f() {}"),
+            Ok(8)
+        );
+        assert!(parse_iterations("a", "// @iterations 0").is_err());
+        assert!(parse_iterations("a", "// @iterations lots").is_err());
+    }
+
+    #[test]
+    fn test_sibling_shader() {
+        let dir = std::env::temp_dir().join("shadertui_sibling_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["a.wgsl", "b.frag", "c.wgsl", "notes.txt", "project.toml"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert_eq!(
+            sibling_shader(&dir.join("a.wgsl"), 1),
+            Ok(dir.join("b.frag"))
+        );
+        assert_eq!(
+            sibling_shader(&dir.join("a.wgsl"), -1),
+            Ok(dir.join("c.wgsl"))
+        );
+        assert_eq!(
+            sibling_shader(&dir.join("c.wgsl"), 1),
+            Ok(dir.join("a.wgsl"))
+        );
+        assert_eq!(
+            sibling_shader(&dir.join("project.toml"), 1),
+            Ok(dir.join("a.wgsl"))
+        );
+        assert_eq!(
+            sibling_shader(&dir.join("project.toml"), -1),
+            Ok(dir.join("c.wgsl"))
+        );
+    }
+
+    #[test]
+    fn test_parse_domain() {
+        assert_eq!(parse_domain("fn f() {}"), Ok(Domain::Pixels));
+        assert_eq!(
+            parse_domain("// @domain 1d\nfn f() {}"),
+            Ok(Domain::Samples)
+        );
+        assert!(parse_domain("// @domain 3d").is_err());
+    }
+
+    #[test]
+    fn test_load_example_project() {
+        let manifest_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders/feedback/project.toml");
+        let project = ShaderProject::load(&manifest_path).unwrap();
+
+        assert_eq!(project.buffers.len(), 1);
+        assert_eq!(project.buffers[0].inputs, vec![0]);
+        assert_eq!(project.output.inputs, vec![0]);
+        assert_eq!(project.all_files.len(), 3);
+
+        for shell_type in [ShellType::Terminal, ShellType::Window] {
+            assert!(validate_project(&project, shell_type, &ShellOptions::default()).is_ok());
+        }
+    }
+}
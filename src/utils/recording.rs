@@ -0,0 +1,344 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::utils::cli::RecordFormat;
+use crate::utils::image_export::RgbaImage;
+use crate::utils::watermark::Watermark;
+
+// AIDEV-NOTE: GIF frame delays are whole centiseconds, so 25 FPS plays back at exactly real time
+const RECORD_FPS: u32 = 25;
+// 1 (best) to 30 (fastest) for the GIF palette quantizer
+const GIF_QUANTIZE_SPEED: i32 = 10;
+// Last byte of every GIF file
+const GIF_TRAILER: u8 = 0x3B;
+
+type CapturedFrame = (RgbaImage, Instant);
+
+// AIDEV-NOTE: Where and how `r` records; every recording in a session gets its own file
+#[derive(Debug, Clone)]
+pub struct RecordingOptions {
+    pub format: RecordFormat,
+    pub output: Option<PathBuf>,
+    pub watermark: Option<Watermark>,
+}
+
+impl RecordingOptions {
+    // `--record-output clip.gif` numbers later recordings clip-2.gif, clip-3.gif, ...
+    fn output_path(&self, take: u32) -> PathBuf {
+        let extension = match self.format {
+            RecordFormat::Gif => "gif",
+            RecordFormat::Ffmpeg => "mp4",
+        };
+        let Some(output) = &self.output else {
+            let seconds = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs());
+            return PathBuf::from(format!("shadertui-{seconds}-{take}.{extension}"));
+        };
+        if take <= 1 {
+            return output.clone();
+        }
+        let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+        let file_name = match output.extension() {
+            Some(extension) => format!("{stem}-{take}.{}", extension.to_string_lossy()),
+            None => format!("{stem}-{take}"),
+        };
+        output.with_file_name(file_name)
+    }
+}
+
+// AIDEV-NOTE: A recording in progress. The render loop offers frames at most RECORD_FPS times per
+// second and an encoder thread writes them, repeating frames to fill gaps so playback keeps the
+// live timing. The first frame fixes the size; frames of another size (after a resize) are skipped
+pub struct Recorder {
+    path: PathBuf,
+    frames: flume::Sender<CapturedFrame>,
+    worker: JoinHandle<Result<(), String>>,
+    last_capture: Option<Instant>,
+    watermark: Option<Watermark>,
+}
+
+impl Recorder {
+    pub fn start(options: &RecordingOptions, take: u32) -> Result<Self, String> {
+        if options.format == RecordFormat::Ffmpeg {
+            // Fail now rather than when the recording is stopped
+            Command::new("ffmpeg")
+                .arg("-version")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map_err(|e| format!("Could not run ffmpeg: {e}"))?;
+        }
+
+        let path = options.output_path(take);
+        let (frames, receiver) = flume::unbounded();
+        let worker = {
+            let (format, path) = (options.format, path.clone());
+            std::thread::spawn(move || encode(format, &path, receiver))
+        };
+
+        Ok(Self {
+            path,
+            frames,
+            worker,
+            last_capture: None,
+            watermark: options.watermark.clone(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn wants_frame(&self) -> bool {
+        let interval = Duration::from_secs(1) / RECORD_FPS;
+        self.last_capture.is_none_or(|at| at.elapsed() >= interval)
+    }
+
+    // `time` and `frame` are the uniforms the frame was rendered with, for the watermark
+    pub fn push_frame(&mut self, mut image: RgbaImage, time: f32, frame: u32) {
+        if let Some(watermark) = &self.watermark {
+            watermark.draw(&mut image, time, frame);
+        }
+        let now = Instant::now();
+        self.last_capture = Some(now);
+        let _ = self.frames.send((image, now));
+    }
+
+    // AIDEV-NOTE: Blocks until every queued frame is encoded and the file is complete
+    pub fn finish(self) -> Result<PathBuf, String> {
+        drop(self.frames);
+        self.worker
+            .join()
+            .map_err(|_| "The recording thread panicked".to_string())??;
+        Ok(self.path)
+    }
+}
+
+fn encode(
+    format: RecordFormat,
+    path: &Path,
+    frames: flume::Receiver<CapturedFrame>,
+) -> Result<(), String> {
+    let (mut pending, start) = frames
+        .recv()
+        .map_err(|_| "No frames were recorded".to_string())?;
+    let size = (pending.width, pending.height);
+    let mut encoder = match format {
+        RecordFormat::Gif => Encoder::gif(path, size)?,
+        RecordFormat::Ffmpeg => Encoder::ffmpeg(path, size)?,
+    };
+
+    // Each frame is held until the next one arrives, which decides how long it stays on screen
+    let mut pending_tick = 0;
+    for (image, captured_at) in frames.iter() {
+        if (image.width, image.height) != size {
+            continue;
+        }
+        let tick =
+            (captured_at.duration_since(start).as_secs_f64() * RECORD_FPS as f64).round() as u64;
+        if tick > pending_tick {
+            encoder.write(&pending, tick - pending_tick)?;
+            pending_tick = tick;
+        }
+        pending = image;
+    }
+    encoder.write(&pending, 1)?;
+    encoder.finish()
+}
+
+// AIDEV-NOTE: GIF file that always ends in a trailer. Each flush (after every frame) writes a
+// provisional trailer and moves back onto it, so the next frame overwrites it. A recording cut
+// short by a crash or kill is still a playable GIF up to its last flushed frame
+struct CheckpointedGif {
+    writer: BufWriter<File>,
+}
+
+impl CheckpointedGif {
+    // Flushes without a provisional trailer once the encoder has written the real one
+    fn finish(self) -> io::Result<()> {
+        self.writer
+            .into_inner()
+            .map_err(io::Error::other)?
+            .sync_all()
+    }
+}
+
+impl Write for CheckpointedGif {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        let file = self.writer.get_mut();
+        file.write_all(&[GIF_TRAILER])?;
+        file.seek(SeekFrom::Current(-1))?;
+        Ok(())
+    }
+}
+
+enum Encoder {
+    Gif(gif::Encoder<CheckpointedGif>),
+    Ffmpeg { process: Child, stdin: ChildStdin },
+}
+
+impl Encoder {
+    fn gif(path: &Path, (width, height): (u32, u32)) -> Result<Self, String> {
+        let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(format!("{width}x{height} is too large for a GIF"));
+        };
+        let file = File::create(path)
+            .map_err(|e| format!("Could not create '{}': {e}", path.display()))?;
+        let writer = CheckpointedGif {
+            writer: BufWriter::new(file),
+        };
+        let mut encoder =
+            gif::Encoder::new(writer, width, height, &[]).map_err(|e| e.to_string())?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(|e| e.to_string())?;
+        Ok(Encoder::Gif(encoder))
+    }
+
+    fn ffmpeg(path: &Path, (width, height): (u32, u32)) -> Result<Self, String> {
+        let mut command = Command::new("ffmpeg");
+        command
+            .args([
+                "-y",
+                "-loglevel",
+                "error",
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgba",
+            ])
+            .args(["-s", &format!("{width}x{height}")])
+            .args(["-framerate", &RECORD_FPS.to_string(), "-i", "-"]);
+        // H.264 players expect 4:2:0 chroma, which needs even dimensions. Fragmented output (a
+        // keyframe and fragment every second) stays playable if shadertui dies mid-recording
+        if path
+            .extension()
+            .is_none_or(|extension| extension == "mp4" || extension == "mov")
+        {
+            command.args([
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-pix_fmt",
+                "yuv420p",
+                "-movflags",
+                "+frag_keyframe+empty_moov+default_base_moof",
+            ]);
+            command.args(["-g", &RECORD_FPS.to_string()]);
+        }
+        // Keep Ctrl+C and hangups aimed at shadertui away from ffmpeg, so closing its input
+        // finishes the file rather than a signal cutting it off
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut process = command
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Could not run ffmpeg: {e}"))?;
+        let stdin = process.stdin.take().ok_or("Could not write to ffmpeg")?;
+        Ok(Encoder::Ffmpeg { process, stdin })
+    }
+
+    fn write(&mut self, image: &RgbaImage, ticks: u64) -> Result<(), String> {
+        match self {
+            Encoder::Gif(encoder) => {
+                // GIF only has on/off transparency; record what the screen showed
+                let mut pixels = image.pixels.clone();
+                pixels.iter_mut().skip(3).step_by(4).for_each(|a| *a = 255);
+                let mut frame = gif::Frame::from_rgba_speed(
+                    image.width as u16,
+                    image.height as u16,
+                    &mut pixels,
+                    GIF_QUANTIZE_SPEED,
+                );
+                frame.delay = (ticks * (100 / RECORD_FPS) as u64).min(u16::MAX as u64) as u16;
+                encoder.write_frame(&frame).map_err(|e| e.to_string())?;
+                encoder.get_mut().flush().map_err(|e| e.to_string())
+            }
+            Encoder::Ffmpeg { stdin, .. } => {
+                for _ in 0..ticks {
+                    stdin
+                        .write_all(&image.pixels)
+                        .map_err(|e| format!("ffmpeg stopped accepting frames: {e}"))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            Encoder::Gif(encoder) => {
+                let writer = encoder.into_inner().map_err(|e| e.to_string())?;
+                writer.finish().map_err(|e| e.to_string())
+            }
+            Encoder::Ffmpeg { process, stdin } => {
+                drop(stdin);
+                let output = process.wait_with_output().map_err(|e| e.to_string())?;
+                if output.status.success() {
+                    Ok(())
+                } else {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    Err(format!("ffmpeg failed: {}", stderr.trim()))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gif_recording() {
+        let path = std::env::temp_dir().join("shadertui_test_gif_recording.gif");
+        let options = RecordingOptions {
+            format: RecordFormat::Gif,
+            output: Some(path.clone()),
+            watermark: None,
+        };
+        assert_eq!(
+            options.output_path(3),
+            std::env::temp_dir().join("shadertui_test_gif_recording-3.gif")
+        );
+
+        let mut recorder = Recorder::start(&options, 1).unwrap();
+        for shade in [0, 128, 255] {
+            let mut image = RgbaImage::new(4, 2);
+            image.pixels.fill(shade);
+            recorder.push_frame(image, 0.0, 0);
+        }
+        // A frame of another size (after a resize) is skipped
+        recorder.push_frame(RgbaImage::new(8, 8), 0.0, 0);
+        assert_eq!(recorder.finish().unwrap(), path);
+
+        let data = std::fs::read(&path).unwrap();
+        assert!(data.starts_with(b"GIF89a"));
+        assert_eq!(&data[6..10], &[4, 0, 2, 0]);
+        assert_eq!(data.last(), Some(&GIF_TRAILER));
+
+        // Every written frame leaves a playable file behind, in case finishing never happens
+        let mut encoder = Encoder::gif(&path, (4, 2)).unwrap();
+        encoder.write(&RgbaImage::new(4, 2), 1).unwrap();
+        let partial = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(partial.as_slice())
+            .unwrap();
+        assert!(decoder.read_next_frame().unwrap().is_some());
+        assert!(decoder.read_next_frame().unwrap().is_none());
+        drop(encoder);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::utils::shader_shell::ShaderApiVersion;
+
+// AIDEV-NOTE: Budget shared by every loop in one invocation (pixel); generous enough for ray
+// marchers, small enough that a runaway loop finishes well before the GPU watchdog fires
+pub const MAX_LOOP_ITERATIONS: u32 = 100_000;
+
+const LOOP_KEYWORDS: [&str; 3] = ["loop", "for", "while"];
+const GUARDED_COMPUTE_COLOR: &str = "shadertui_guarded_compute_color";
+
+// AIDEV-NOTE: --safe rewrite of (import-processed) user code: every loop body starts by charging
+// a per-invocation iteration counter and breaks once it runs out, and compute_color is wrapped so
+// coords are clamped to the output and colors to [0, 1]
+pub fn apply_safe_guards(user_shader: &str, version: ShaderApiVersion) -> String {
+    let guard = "shadertui_iterations += 1u; \
+                 if (shadertui_iterations > SHADERTUI_MAX_ITERATIONS) { break; }";
+    let mut guarded = insert_after_loop_braces(user_shader, guard);
+
+    let signature = version.compute_color_signature();
+    guarded = guarded.replacen(
+        signature,
+        &signature.replace("compute_color", GUARDED_COMPUTE_COLOR),
+        1,
+    );
+    let color_type = match version {
+        ShaderApiVersion::V1 => "vec3<f32>",
+        ShaderApiVersion::V2 => "vec4<f32>",
+    };
+
+    format!(
+        "{guarded}\n\n\
+         const SHADERTUI_MAX_ITERATIONS: u32 = {MAX_LOOP_ITERATIONS}u;\n\
+         var<private> shadertui_iterations: u32;\n\n\
+         {signature} {{\n    \
+             let safe_coords = clamp(coords, vec2<f32>(0.0), uniforms.resolution);\n    \
+             return clamp({GUARDED_COMPUTE_COLOR}(safe_coords), {color_type}(0.0), {color_type}(1.0));\n\
+         }}\n"
+    )
+}
+
+// AIDEV-NOTE: Minimal scanner: skips comments, finds loop keywords as whole words and inserts the
+// guard after the first `{` outside parentheses that follows each one
+fn insert_after_loop_braces(source: &str, guard: &str) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let mut output = String::with_capacity(source.len());
+    let mut pending_loops = 0;
+    let mut paren_depth = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        // Line comments
+        if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                output.push(chars[i]);
+                i += 1;
+            }
+            continue;
+        }
+        // Block comments (WGSL allows nesting)
+        if c == '/' && next == Some('*') {
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    output.push_str("/*");
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    output.push_str("*/");
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    output.push(chars[i]);
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            if LOOP_KEYWORDS.contains(&word.as_str()) {
+                pending_loops += 1;
+            }
+            output.push_str(&word);
+            continue;
+        }
+
+        match c {
+            '(' => paren_depth += 1,
+            ')' => paren_depth -= 1,
+            _ => {}
+        }
+        output.push(c);
+        if c == '{' && paren_depth == 0 && pending_loops > 0 {
+            pending_loops -= 1;
+            output.push(' ');
+            output.push_str(guard);
+        }
+        i += 1;
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shader_shell::{inject_user_shader, ShellOptions, ShellType};
+    use crate::utils::validation::validate_shader;
+
+    const LOOPY_SHADER: &str = r#"
+// a for loop in a comment should be ignored {
+fn helper(n: u32) -> f32 {
+    var total = 0.0;
+    for (var i = 0u; i < n; i = i + 1u) { total += 1.0; }
+    var j = 0u;
+    while (j < n) { j += 1u; }
+    loop { break; }
+    return total;
+}
+
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    return vec3<f32>(helper(4u) / 4.0);
+}
+"#;
+
+    #[test]
+    fn test_guards_every_loop() {
+        let guarded = insert_after_loop_braces(LOOPY_SHADER, "GUARD");
+        assert_eq!(guarded.matches("GUARD").count(), 3);
+        assert!(guarded.contains("// a for loop in a comment should be ignored {\n"));
+    }
+
+    #[test]
+    fn test_safe_shader_validates() {
+        let options = ShellOptions {
+            safe_mode: true,
+            ..Default::default()
+        };
+        for shell_type in [ShellType::Terminal, ShellType::Window] {
+            let shader = inject_user_shader(LOOPY_SHADER, shell_type, &options).unwrap();
+            assert!(validate_shader(&shader).is_ok(), "{shader}");
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+use crate::utils::options::SampleView;
+use crate::utils::shader_shell::ShaderApiVersion;
+
+const SCOPE_BACKGROUND: [f32; 3] = [0.02, 0.03, 0.02];
+const SCOPE_AXIS: [f32; 3] = [0.08, 0.2, 0.1];
+const SCOPE_TRACE: [f32; 3] = [0.35, 1.0, 0.45];
+// AIDEV-NOTE: Magnitudes at or below this many dB under full scale draw as black
+const SPECTROGRAM_FLOOR_DB: f32 = -80.0;
+
+// AIDEV-NOTE: compute_color() wrapper for 1D shaders. Sample N lands in output column N, biased into
+// [0, 1] so the --safe color clamp limits samples to [-1, 1]; decode_samples() undoes the bias
+pub fn sample_adapter(version: ShaderApiVersion) -> String {
+    let color_type = match version {
+        ShaderApiVersion::V1 => "vec3<f32>",
+        ShaderApiVersion::V2 => "vec4<f32>",
+    };
+    format!(
+        "{} {{\n    return {color_type}(compute_sample(u32(coords.x)) * 0.5 + 0.5);\n}}\n",
+        version.compute_color_signature()
+    )
+}
+
+pub fn decode_samples(gpu_data: &[f32]) -> Vec<f32> {
+    gpu_data
+        .chunks_exact(4)
+        .map(|pixel| pixel[0] * 2.0 - 1.0)
+        .collect()
+}
+
+// AIDEV-NOTE: Turns one frame of samples into a picture. The spectrogram keeps one column per
+// frame, so it must see every rendered frame in order
+pub struct SampleVisualizer {
+    view: SampleView,
+    history: VecDeque<Vec<f32>>,
+}
+
+impl SampleVisualizer {
+    pub fn new(view: SampleView) -> Self {
+        Self {
+            view,
+            history: VecDeque::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    // AIDEV-NOTE: Returns width x height RGBA floats, bottom row first like the GPU readback
+    pub fn draw(&mut self, samples: &[f32], width: u32, height: u32) -> Vec<f32> {
+        match self.view {
+            SampleView::Scope => draw_scope(samples, width as usize, height as usize),
+            SampleView::Spectrogram => {
+                self.history.push_back(spectrum(samples));
+                while self.history.len() > width as usize {
+                    self.history.pop_front();
+                }
+                self.draw_spectrogram(width as usize, height as usize)
+            }
+        }
+    }
+
+    fn draw_spectrogram(&self, width: usize, height: usize) -> Vec<f32> {
+        let mut image = vec![0.0; width * height * 4];
+        let first_column = width - self.history.len();
+        for (column, magnitudes) in self.history.iter().enumerate() {
+            let bins = magnitudes.len();
+            for y in 0..height {
+                // Every row shows the loudest of the bins it covers, low frequencies at the bottom
+                let start = y * bins / height;
+                let end = ((y + 1) * bins / height).max(start + 1).min(bins);
+                let level = magnitudes[start..end].iter().copied().fold(0.0, f32::max);
+                set_pixel(&mut image, width, first_column + column, y, heat(level));
+            }
+        }
+        image
+    }
+}
+
+fn draw_scope(samples: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let mut image = vec![0.0; width * height * 4];
+    for y in 0..height {
+        let color = if y == height / 2 {
+            SCOPE_AXIS
+        } else {
+            SCOPE_BACKGROUND
+        };
+        for x in 0..width {
+            set_pixel(&mut image, width, x, y, color);
+        }
+    }
+    if samples.is_empty() {
+        return image;
+    }
+
+    let to_row = |sample: f32| {
+        let row = (sample.clamp(-1.0, 1.0) + 1.0) * 0.5 * (height - 1) as f32;
+        row.round() as usize
+    };
+    let mut previous = None;
+    for x in 0..width {
+        // Columns span several samples when zoomed out; joining each to the previous column's
+        // last sample keeps steep edges continuous
+        let start = x * samples.len() / width;
+        let end = ((x + 1) * samples.len() / width).max(start + 1);
+        let column = &samples[start..end];
+        let (mut low, mut high) = column.iter().fold((f32::MAX, f32::MIN), |(low, high), &s| {
+            (low.min(s), high.max(s))
+        });
+        if let Some(last) = previous {
+            low = low.min(last);
+            high = high.max(last);
+        }
+        previous = column.last().copied();
+
+        for y in to_row(low)..=to_row(high) {
+            set_pixel(&mut image, width, x, y, SCOPE_TRACE);
+        }
+    }
+    image
+}
+
+// AIDEV-NOTE: Hann-windowed magnitude spectrum of the largest power-of-two prefix, scaled to
+// [0, 1] between SPECTROGRAM_FLOOR_DB and a full-scale sine
+pub fn spectrum(samples: &[f32]) -> Vec<f32> {
+    if samples.len() < 2 {
+        return vec![0.0];
+    }
+    let n = 1 << samples.len().ilog2();
+    let mut re: Vec<f32> = samples[..n]
+        .iter()
+        .enumerate()
+        .map(|(i, s)| s * (0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos()))
+        .collect();
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im);
+
+    let full_scale = n as f32 / 4.0;
+    (0..n / 2)
+        .map(|bin| {
+            let magnitude = re[bin].hypot(im[bin]) / full_scale;
+            let db = 20.0 * magnitude.max(1e-9).log10();
+            (1.0 - db / SPECTROGRAM_FLOOR_DB).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+// AIDEV-NOTE: In-place iterative radix-2 FFT; the length must be a power of two
+pub fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+// Black through red and yellow to white
+fn heat(level: f32) -> [f32; 3] {
+    [
+        (level * 3.0).min(1.0),
+        (level * 3.0 - 1.0).clamp(0.0, 1.0),
+        (level * 3.0 - 2.0).clamp(0.0, 1.0),
+    ]
+}
+
+fn set_pixel(image: &mut [f32], width: usize, x: usize, y: usize, color: [f32; 3]) {
+    let index = (y * width + x) * 4;
+    image[index..index + 3].copy_from_slice(&color);
+    image[index + 3] = 1.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spectrum_peaks_at_sine_frequency() {
+        let samples: Vec<f32> = (0..256)
+            .map(|i| (2.0 * PI * 16.0 * i as f32 / 256.0).sin())
+            .collect();
+        let magnitudes = spectrum(&samples);
+
+        assert_eq!(magnitudes.len(), 128);
+        let peak = (0..magnitudes.len())
+            .max_by(|&a, &b| magnitudes[a].total_cmp(&magnitudes[b]))
+            .unwrap();
+        assert_eq!(peak, 16);
+        assert!(magnitudes[16] > 0.99);
+        assert!(magnitudes[64] < 0.1);
+    }
+}
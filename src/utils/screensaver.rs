@@ -0,0 +1,67 @@
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+use crate::utils::project::{is_shader_path, ShaderProject};
+use crate::utils::shader_shell::{ShellOptions, ShellType};
+use crate::utils::validation::validate_project;
+
+// AIDEV-NOTE: Random shader file from `dir` that loads and validates, so one broken shader in a
+// collection doesn't stop the screensaver. Candidates are tried in order from a random start
+pub fn pick_shader(dir: &Path, shell_options: &ShellOptions) -> io::Result<Option<PathBuf>> {
+    let mut candidates: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && is_shader_path(path))
+        .collect();
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+    candidates.sort();
+
+    // std's hasher keys are randomly seeded per process, which is all the randomness needed here
+    let start = RandomState::new().build_hasher().finish() as usize % candidates.len();
+    candidates.rotate_left(start);
+
+    Ok(candidates.into_iter().find(|path| {
+        ShaderProject::load(path).is_ok_and(|project| {
+            validate_project(&project, ShellType::Terminal, shell_options).is_ok()
+        })
+    }))
+}
+
+// AIDEV-NOTE: Block until no key has been pressed for `delay`; every key restarts the wait. Returns
+// false if the user pressed Ctrl+C instead
+pub fn wait_for_idle(delay: Duration) -> io::Result<bool> {
+    if delay.is_zero() {
+        return Ok(true);
+    }
+
+    terminal::enable_raw_mode()?;
+    let mut idle_since = Instant::now();
+    let result = loop {
+        let remaining = delay.saturating_sub(idle_since.elapsed());
+        if remaining.is_zero() {
+            break Ok(true);
+        }
+        match event::poll(remaining).and_then(|ready| ready.then(event::read).transpose()) {
+            Ok(Some(Event::Key(key)))
+                if key.code == KeyCode::Char('c')
+                    && key.modifiers.contains(KeyModifiers::CONTROL)
+                    && key.kind == KeyEventKind::Press =>
+            {
+                break Ok(false);
+            }
+            Ok(Some(Event::Key(_))) => idle_since = Instant::now(),
+            Ok(_) => {}
+            Err(e) => break Err(e),
+        }
+    };
+    terminal::disable_raw_mode()?;
+    result
+}
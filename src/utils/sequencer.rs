@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::utils::cli::parse_seconds;
+use crate::utils::control::{ControlCommand, ControlReceiver};
+
+// AIDEV-NOTE: Demo script schema (TOML). `shader` starts the show; each `[[event]]` fires `at` a
+// time after the start and may switch shader, set parameters and zoom in. With a `transition` the
+// screen fades to black over the first half, the event is applied, and it fades back in. The show
+// quits at `end`, or keeps running after the last event without one
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScriptFile {
+    shader: PathBuf,
+    end: Option<String>,
+    #[serde(default, rename = "event")]
+    events: Vec<EventFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EventFile {
+    at: String,
+    shader: Option<PathBuf>,
+    #[serde(default)]
+    params: BTreeMap<String, f32>,
+    // x, y and size as fractions of the frame, Y=0 at the bottom; [0, 0, 1] shows all of it
+    zoom: Option<[f32; 3]>,
+    transition: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemoEvent {
+    pub at: f32,
+    pub transition: f32,
+    pub commands: Vec<ControlCommand>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemoScript {
+    pub shader: PathBuf,
+    pub end: Option<f32>,
+    // Ordered by time
+    pub events: Vec<DemoEvent>,
+}
+
+impl DemoScript {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read '{}': {e}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        Self::parse(&content, base).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    // Shader paths are relative to `base`, the script's directory, and made absolute
+    fn parse(content: &str, base: &Path) -> Result<Self, String> {
+        let file: ScriptFile = toml::from_str(content).map_err(|e| e.to_string())?;
+        let resolve = |shader: &Path| {
+            let path = base.join(shader);
+            path.canonicalize()
+                .map_err(|e| format!("Could not resolve '{}': {e}", path.display()))
+        };
+
+        let mut events = Vec::with_capacity(file.events.len());
+        for event in file.events {
+            let mut commands = Vec::new();
+            if let Some(shader) = &event.shader {
+                commands.push(ControlCommand::Load(resolve(shader)?));
+            }
+            for (name, value) in event.params {
+                commands.push(ControlCommand::Param(name, value));
+            }
+            if let Some([x, y, size]) = event.zoom {
+                if size <= 0.0 || size > 1.0 {
+                    return Err(format!(
+                        "invalid zoom size {size} at {} (expected above 0 and at most 1)",
+                        event.at
+                    ));
+                }
+                let full_frame = size == 1.0 && x == 0.0 && y == 0.0;
+                commands.push(ControlCommand::Zoom(
+                    (!full_frame).then_some([x, y, size, size]),
+                ));
+            }
+            events.push(DemoEvent {
+                at: parse_seconds(&event.at)?,
+                transition: event
+                    .transition
+                    .as_deref()
+                    .map(parse_seconds)
+                    .transpose()?
+                    .unwrap_or(0.0),
+                commands,
+            });
+        }
+        // Stable, so events written for the same time keep their order
+        events.sort_by(|a, b| a.at.total_cmp(&b.at));
+
+        Ok(Self {
+            shader: resolve(&file.shader)?,
+            end: file.end.as_deref().map(parse_seconds).transpose()?,
+            events,
+        })
+    }
+
+    // Every shader the show uses, so all of them can be validated before it starts
+    pub fn shaders(&self) -> impl Iterator<Item = &PathBuf> {
+        let switched = self.events.iter().flat_map(|event| &event.commands);
+        std::iter::once(&self.shader).chain(switched.filter_map(|command| match command {
+            ControlCommand::Load(path) => Some(path),
+            _ => None,
+        }))
+    }
+}
+
+// AIDEV-NOTE: Plays the script on a background thread, feeding the render loop the same commands
+// `shadertui ctl` would. Times are measured from this call; the thread stops early once the
+// receiver is gone
+pub fn start(script: DemoScript) -> ControlReceiver {
+    let (sender, commands) = flume::unbounded();
+    thread::spawn(move || {
+        let start = Instant::now();
+        let wait_until = |seconds: f32| {
+            let due = start + Duration::from_secs_f32(seconds);
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+        };
+        for event in script.events {
+            wait_until(event.at);
+            let half = event.transition / 2.0;
+            if half > 0.0 {
+                if sender.send(ControlCommand::Fade(0.0, half)).is_err() {
+                    return;
+                }
+                wait_until(event.at + half);
+            }
+            for command in event.commands {
+                if sender.send(command).is_err() {
+                    return;
+                }
+            }
+            if half > 0.0 && sender.send(ControlCommand::Fade(1.0, half)).is_err() {
+                return;
+            }
+        }
+        if let Some(end) = script.end {
+            wait_until(end);
+            let _ = sender.send(ControlCommand::Quit);
+        }
+    });
+    commands
+}
+
+// Brightness of a fade between two levels, 1.0 when none is running
+#[derive(Debug, Clone, Copy)]
+pub struct Fade {
+    from: f32,
+    to: f32,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Fade {
+    pub fn new() -> Self {
+        Self {
+            from: 1.0,
+            to: 1.0,
+            started: Instant::now(),
+            duration: Duration::ZERO,
+        }
+    }
+
+    // Continue from the current brightness, so interrupting a fade doesn't jump
+    pub fn start(&mut self, to: f32, seconds: f32) {
+        *self = Self {
+            from: self.level(),
+            to: to.clamp(0.0, 1.0),
+            started: Instant::now(),
+            duration: Duration::from_secs_f32(seconds.max(0.0)),
+        };
+    }
+
+    pub fn level(&self) -> f32 {
+        let elapsed = self.started.elapsed();
+        if elapsed >= self.duration {
+            return self.to;
+        }
+        let progress = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        self.from + (self.to - self.from) * progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_demo_script() {
+        let base = std::env::temp_dir().join("shadertui_demo_script_test");
+        std::fs::create_dir_all(&base).unwrap();
+        for name in ["intro.wgsl", "tunnel.wgsl"] {
+            std::fs::write(base.join(name), "").unwrap();
+        }
+        let base = base.canonicalize().unwrap();
+
+        let script = DemoScript::parse(
+            r#"
+            shader = "intro.wgsl"
+            end = "90s"
+
+            [[event]]
+            at = "30s"
+            shader = "tunnel.wgsl"
+            transition = "2s"
+            params = { speed = 3.0 }
+
+            [[event]]
+            at = "10s"
+            zoom = [0.0, 0.0, 1.0]
+            "#,
+            &base,
+        )
+        .unwrap();
+        assert_eq!(script.shader, base.join("intro.wgsl"));
+        assert_eq!(script.end, Some(90.0));
+        assert_eq!(
+            script.events,
+            [
+                DemoEvent {
+                    at: 10.0,
+                    transition: 0.0,
+                    commands: vec![ControlCommand::Zoom(None)],
+                },
+                DemoEvent {
+                    at: 30.0,
+                    transition: 2.0,
+                    commands: vec![
+                        ControlCommand::Load(base.join("tunnel.wgsl")),
+                        ControlCommand::Param("speed".to_string(), 3.0),
+                    ],
+                },
+            ]
+        );
+        assert_eq!(script.shaders().count(), 2);
+
+        assert!(DemoScript::parse("shader = \"missing.wgsl\"", &base).is_err());
+        let bad_zoom = "shader = \"intro.wgsl\"\n[[event]]\nat = \"1s\"\nzoom = [0.0, 0.0, 2.0]";
+        assert!(DemoScript::parse(bad_zoom, &base).is_err());
+    }
+}
@@ -1,6 +1,12 @@
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::utils::frontmatter::expand_frontmatter;
+use crate::utils::palette::{default_palette_name, palette_to_wgsl};
+use crate::utils::source_map::{LineOrigin, SourceMap};
+use crate::utils::stdlib;
 
 #[derive(Debug)]
 pub enum ImportError {
@@ -18,6 +24,18 @@ pub enum ImportError {
     CircularDependency {
         chain: Vec<PathBuf>,
     },
+    InvalidPalette {
+        path: PathBuf,
+        message: String,
+    },
+    UnknownStdModule {
+        name: String,
+        import_location: String,
+    },
+    InvalidFrontmatter {
+        path: PathBuf,
+        message: String,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -65,6 +83,22 @@ impl std::fmt::Display for ImportError {
                 }
                 Ok(())
             }
+            ImportError::InvalidPalette { path, message } => {
+                write!(f, "Invalid palette '{}': {message}", path.display())
+            }
+            ImportError::UnknownStdModule {
+                name,
+                import_location,
+            } => {
+                write!(
+                    f,
+                    "Unknown standard library module 'std/{name}' (imported from \
+                     {import_location}). Run `shadertui stdlib list` to see the available ones"
+                )
+            }
+            ImportError::InvalidFrontmatter { path, message } => {
+                write!(f, "Error in '{}': {message}", path.display())
+            }
         }
     }
 }
@@ -113,10 +147,16 @@ impl DependencyTracker {
             .push(to.to_path_buf());
     }
 
+    // Standard library modules are built in, so they aren't files to watch
     fn get_dependency_info(&self) -> DependencyInfo {
         DependencyInfo {
             dependencies: self.dependencies.clone(),
-            all_files: self.processed_files.clone(),
+            all_files: self
+                .processed_files
+                .iter()
+                .filter(|path| !stdlib::is_virtual(path))
+                .cloned()
+                .collect(),
         }
     }
 }
@@ -124,11 +164,12 @@ impl DependencyTracker {
 pub fn process_imports(
     shader_path: &Path,
     shader_source: &str,
-) -> Result<(String, DependencyInfo), ImportError> {
+) -> Result<(String, SourceMap, DependencyInfo), ImportError> {
     let mut tracker = DependencyTracker::new();
-    let result = process_imports_recursive(shader_path, shader_source, &mut tracker, 0)?;
+    let (result, source_map) =
+        process_imports_recursive(shader_path, shader_source, &mut tracker, 0)?;
     let deps = tracker.get_dependency_info();
-    Ok((result, deps))
+    Ok((result, source_map, deps))
 }
 
 fn process_imports_recursive(
@@ -136,27 +177,95 @@ fn process_imports_recursive(
     source: &str,
     tracker: &mut DependencyTracker,
     depth: usize,
-) -> Result<String, ImportError> {
+) -> Result<(String, SourceMap), ImportError> {
     if depth > MAX_IMPORT_DEPTH {
         return Err(ImportError::RecursionLimit { depth });
     }
 
-    let canonical_current = current_file
-        .canonicalize()
-        .map_err(|e| ImportError::IoError {
-            path: current_file.to_path_buf(),
-            source: e,
-        })?;
+    let canonical_current = if stdlib::is_virtual(current_file) {
+        current_file.to_path_buf()
+    } else {
+        current_file
+            .canonicalize()
+            .map_err(|e| ImportError::IoError {
+                path: current_file.to_path_buf(),
+                source: e,
+            })?
+    };
 
     tracker.enter_file(canonical_current.clone())?;
 
     let current_dir = current_file.parent().unwrap_or_else(|| Path::new("."));
 
     let import_regex = regex::Regex::new(r#"// @import "([^"]+)""#).unwrap();
+    let palette_regex =
+        regex::Regex::new(r#"// @palette "([^"]+)"(?:\s+as\s+([A-Za-z_][A-Za-z0-9_]*))?"#).unwrap();
+    let source = expand_frontmatter(source).map_err(|message| ImportError::InvalidFrontmatter {
+        path: current_file.to_path_buf(),
+        message,
+    })?;
     let mut result = String::new();
+    let mut source_map = SourceMap::default();
+    let file: Arc<Path> = Arc::from(current_file);
+
+    for (index, line) in source.lines().enumerate() {
+        if let Some(captures) = palette_regex.captures(line) {
+            let palette_path = current_dir.join(&captures[1]);
+            let canonical_palette_path =
+                palette_path
+                    .canonicalize()
+                    .map_err(|_| ImportError::FileNotFound {
+                        path: palette_path.clone(),
+                        import_location: current_file.display().to_string(),
+                    })?;
+
+            // Palettes are tracked like imports so editing one hot reloads the shader
+            tracker.add_dependency(&canonical_current, &canonical_palette_path);
+            if !tracker
+                .processed_files
+                .insert(canonical_palette_path.clone())
+            {
+                continue;
+            }
+
+            let json =
+                fs::read_to_string(&canonical_palette_path).map_err(|e| ImportError::IoError {
+                    path: canonical_palette_path.clone(),
+                    source: e,
+                })?;
+            let name = captures.get(2).map_or_else(
+                || default_palette_name(&palette_path),
+                |m| m.as_str().to_string(),
+            );
+            let wgsl =
+                palette_to_wgsl(&name, &json).map_err(|message| ImportError::InvalidPalette {
+                    path: canonical_palette_path,
+                    message,
+                })?;
 
-    for line in source.lines() {
-        if let Some(captures) = import_regex.captures(line) {
+            result.push_str(&wgsl);
+            result.push('\n');
+            source_map.push_chunk(&wgsl, None);
+        } else if let Some(module) = import_regex
+            .captures(line)
+            .and_then(|captures| stdlib::resolve(&captures[1]))
+        {
+            let module = module.map_err(|name| ImportError::UnknownStdModule {
+                name,
+                import_location: current_file.display().to_string(),
+            })?;
+            let module_path = stdlib::virtual_path(module);
+            tracker.add_dependency(&canonical_current, &module_path);
+            if tracker.processed_files.contains(&module_path) {
+                continue;
+            }
+
+            let (processed_import, import_map) =
+                process_imports_recursive(&module_path, module.source, tracker, depth + 1)?;
+            result.push_str(&processed_import);
+            result.push('\n');
+            source_map.push_chunk(&processed_import, Some(import_map));
+        } else if let Some(captures) = import_regex.captures(line) {
             let import_path_str = &captures[1];
             let import_path = current_dir.join(import_path_str);
 
@@ -188,7 +297,7 @@ fn process_imports_recursive(
                 }
             };
 
-            let processed_import = process_imports_recursive(
+            let (processed_import, import_map) = process_imports_recursive(
                 &canonical_import_path,
                 &import_content,
                 tracker,
@@ -197,9 +306,14 @@ fn process_imports_recursive(
 
             result.push_str(&processed_import);
             result.push('\n');
+            source_map.push_chunk(&processed_import, Some(import_map));
         } else {
             result.push_str(line);
             result.push('\n');
+            source_map.push_line(Some(LineOrigin {
+                file: file.clone(),
+                line: index + 1,
+            }));
         }
     }
 
@@ -209,5 +323,5 @@ fn process_imports_recursive(
         result.pop();
     }
 
-    Ok(result)
+    Ok((result, source_map))
 }
@@ -1,33 +1,194 @@
+use std::borrow::Cow;
 use std::error::Error;
 use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::audio::AudioCapture;
+use crate::utils::image_export::RgbaImage;
+use crate::utils::project::BufferFormat;
+use crate::utils::safe_mode::apply_safe_guards;
+use crate::utils::uniform_params::{struct_fields, UniformParam};
 
 // AIDEV-NOTE: Shell templates for different rendering modes
 const TERMINAL_SHELL: &str = include_str!("../shaders/terminal_shell.wgsl");
 const WINDOW_SHELL: &str = include_str!("../shaders/window_shell.wgsl");
+const BUFFER_SHELL: &str = include_str!("../shaders/buffer_shell.wgsl");
 const WINDOW_DISPLAY_SHADER: &str = include_str!("../shaders/window_display.wgsl");
+// AIDEV-NOTE: Storage format written by the buffer shell, swapped for the pass's declared format
+const BUFFER_SHELL_FORMAT: &str = "rgba16float";
+
+// AIDEV-NOTE: Versioned API preludes declare the Uniforms layout and the shadertui_color() shim
+// that shells call; add a new version here instead of changing an existing one
+const SHADER_API_V1: &str = include_str!("../shaders/api_v1.wgsl");
+const SHADER_API_V2: &str = include_str!("../shaders/api_v2.wgsl");
+// AIDEV-NOTE: Line in the preludes' Uniforms struct that becomes the `// @uniform` fields
+const PARAMS_MARKER: &str = "    // SHADERTUI_PARAMS\n";
+
+// AIDEV-NOTE: Must match gpu::buffer::STATE_BINDING
+const STATE_DECLARATION: &str =
+    "@group(0) @binding(2) var<storage, read_write> state: array<f32>;\n";
+
+// AIDEV-NOTE: --accumulate wraps the prelude's shadertui_color(), which every shell calls, so custom
+// shells get it too. Must match gpu::buffer::ACCUMULATION_BINDING and RESET_BINDING. Without
+// accumulation (and in buffer passes) restart_accumulation() is a no-op, so shaders run either way
+const SAMPLE_COLOR_FN: &str = "fn shadertui_sample_color(";
+const ACCUMULATE_DECLARATION: &str = "
+@group(0) @binding(3) var<storage, read_write> shadertui_accumulation: array<vec4<f32>>;
+@group(0) @binding(4) var<storage, read_write> shadertui_reset: atomic<u32>;
+
+fn restart_accumulation() {
+    atomicStore(&shadertui_reset, 1u);
+}
+
+fn shadertui_color(coords: vec2<f32>) -> vec4<f32> {
+    let index = u32(coords.y) * u32(uniforms.resolution.x) + u32(coords.x);
+    let weight = 1.0 / f32(uniforms.sample_index + 1u);
+    let average = mix(shadertui_accumulation[index], shadertui_sample_color(coords), weight);
+    shadertui_accumulation[index] = average;
+    return average;
+}
+";
+
+const NO_ACCUMULATE_DECLARATION: &str = "fn restart_accumulation() {}\n";
+
+// AIDEV-NOTE: request_exit() stops `shadertui render` after the current frame, exiting with `code`.
+// Must match gpu::buffer::EXIT_BINDING. Everywhere else, including buffer passes, it is a no-op
+const EXIT_DECLARATION: &str = "
+@group(0) @binding(5) var<storage, read_write> shadertui_exit: array<atomic<u32>, 2>;
+
+fn request_exit(code: u32) {
+    atomicStore(&shadertui_exit[1], code);
+    atomicStore(&shadertui_exit[0], 1u);
+}
+";
+
+const NO_EXIT_DECLARATION: &str = "fn request_exit(code: u32) {}\n";
 
 const USER_INJECTION_MARKER: &str = "// USER_SHADER_INJECTION_POINT";
+const API_VERSION_PRAGMA: &str = "// @shadertui ";
 
 #[derive(Debug, Clone, Copy)]
 pub enum ShellType {
     Terminal,
     Window,
+    // Intermediate multi-pass buffer that writes to a sampleable texture
+    Buffer,
+}
+
+// AIDEV-NOTE: Shader API version declared with `// @shadertui N`; shaders without the pragma are V1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderApiVersion {
+    V1,
+    V2,
+}
+
+impl ShaderApiVersion {
+    pub fn from_user_shader(user_shader: &str) -> Result<Self, ShaderShellError> {
+        let pragma = user_shader
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(API_VERSION_PRAGMA));
+
+        match pragma.map(str::trim) {
+            None | Some("1") => Ok(Self::V1),
+            Some("2") => Ok(Self::V2),
+            Some(other) => Err(ShaderShellError::UnsupportedApiVersion(other.to_string())),
+        }
+    }
+
+    pub fn compute_color_signature(self) -> &'static str {
+        match self {
+            Self::V1 => "fn compute_color(coords: vec2<f32>) -> vec3<f32>",
+            Self::V2 => "fn compute_color(coords: vec2<f32>) -> vec4<f32>",
+        }
+    }
+
+    fn prelude(self) -> &'static str {
+        match self {
+            Self::V1 => SHADER_API_V1,
+            Self::V2 => SHADER_API_V2,
+        }
+    }
+}
+
+// AIDEV-NOTE: Options controlling how user code is wrapped, threaded from the CLI to every injection site
+#[derive(Debug, Clone, Default)]
+pub struct ShellOptions {
+    pub custom_shell: Option<String>,
+    // Number of pass inputs declared as channel0..N (set per pass); images follow them
+    pub channel_count: usize,
+    // Inject loop iteration caps and coordinate/color clamps (--safe)
+    pub safe_mode: bool,
+    // Storage format of the texture a buffer pass writes (set per pass)
+    pub buffer_format: BufferFormat,
+    // Images from --texture, bound to every pass as the channels after its inputs
+    pub textures: Vec<Arc<RgbaImage>>,
+    // Input from --audio, bound as the channel after the images
+    pub audio: Option<AudioCapture>,
+    // Bytes of persistent `state` storage (--state-size), and whether it survives reloads
+    pub state_size: Option<u32>,
+    pub keep_state: bool,
+    // Average the output pass over frames (--accumulate); buffer passes are left alone
+    pub accumulate: bool,
+    // Bind request_exit() in the output pass (set by `shadertui render`)
+    pub exit_requests: bool,
+    // `// @uniform` parameters of the project, declared in every pass (set per project)
+    pub params: Vec<UniformParam>,
+}
+
+impl ShellOptions {
+    // AIDEV-NOTE: Custom shells replace the built-in shell for whichever mode is running,
+    // so they must declare the same bindings that renderer provides
+    pub fn load(custom_shell_path: Option<&Path>) -> Result<Self, ShaderShellError> {
+        let custom_shell = match custom_shell_path {
+            Some(path) => {
+                let source = fs::read_to_string(path).map_err(|e| {
+                    ShaderShellError::CustomShellReadError(format!("{}: {e}", path.display()))
+                })?;
+                if !source.contains(USER_INJECTION_MARKER) {
+                    return Err(ShaderShellError::InjectionMarkerNotFound);
+                }
+                Some(source)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            custom_shell,
+            ..Default::default()
+        })
+    }
 }
 
 #[derive(Debug)]
 pub enum ShaderShellError {
-    MissingComputeColorFunction,
+    MissingComputeColorFunction { signature: &'static str },
     InjectionMarkerNotFound,
+    CustomShellReadError(String),
+    UnsupportedApiVersion(String),
 }
 
 impl fmt::Display for ShaderShellError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ShaderShellError::MissingComputeColorFunction => {
-                write!(f, "User shader must contain 'fn compute_color(coords: vec2<f32>) -> vec3<f32>' function")
+            ShaderShellError::MissingComputeColorFunction { signature } => {
+                write!(f, "User shader must contain '{signature}' function")
             }
             ShaderShellError::InjectionMarkerNotFound => {
-                write!(f, "Shell template is missing injection marker")
+                write!(
+                    f,
+                    "Shell template is missing injection marker '{USER_INJECTION_MARKER}'"
+                )
+            }
+            ShaderShellError::CustomShellReadError(msg) => {
+                write!(f, "Error reading custom shell: {msg}")
+            }
+            ShaderShellError::UnsupportedApiVersion(version) => {
+                write!(
+                    f,
+                    "Unsupported shader API version '{version}' (supported: 1, 2)"
+                )
             }
         }
     }
@@ -35,38 +196,148 @@ impl fmt::Display for ShaderShellError {
 
 impl Error for ShaderShellError {}
 
-// AIDEV-NOTE: Validate that user shader contains required compute_color function
-pub fn validate_user_shader(user_shader: &str) -> Result<(), ShaderShellError> {
+// AIDEV-NOTE: Validate that user shader contains the compute_color function for its API version
+pub fn validate_user_shader(user_shader: &str) -> Result<ShaderApiVersion, ShaderShellError> {
+    let version = ShaderApiVersion::from_user_shader(user_shader)?;
+
     // Check for compute_color function signature
-    if !user_shader.contains("fn compute_color(coords: vec2<f32>) -> vec3<f32>") {
-        return Err(ShaderShellError::MissingComputeColorFunction);
+    let signature = version.compute_color_signature();
+    if !user_shader.contains(signature) {
+        return Err(ShaderShellError::MissingComputeColorFunction { signature });
     }
-    Ok(())
+    Ok(version)
 }
 
 // AIDEV-NOTE: Inject user shader code into the appropriate shell template
 pub fn inject_user_shader(
     user_shader: &str,
     shell_type: ShellType,
+    options: &ShellOptions,
 ) -> Result<String, ShaderShellError> {
-    // First validate the user shader
-    validate_user_shader(user_shader)?;
+    // First validate the user shader and determine which API prelude it targets
+    let version = validate_user_shader(user_shader)?;
 
-    // Get the appropriate shell template
-    let shell_template = match shell_type {
-        ShellType::Terminal => TERMINAL_SHELL,
-        ShellType::Window => WINDOW_SHELL,
-    };
+    let shell_template = shell_template(shell_type, options);
+    let shell_template = shell_template.as_ref();
 
     // Check that the injection marker exists
     if !shell_template.contains(USER_INJECTION_MARKER) {
         return Err(ShaderShellError::InjectionMarkerNotFound);
     }
 
-    // Replace the injection marker with user code
-    let complete_shader = shell_template.replace(USER_INJECTION_MARKER, user_shader);
+    let user_shader = if options.safe_mode {
+        apply_safe_guards(user_shader, version)
+    } else {
+        user_shader.to_string()
+    };
+    let (directives, user_shader) = hoist_directives(&user_shader);
+
+    let accumulate = options.accumulate && !matches!(shell_type, ShellType::Buffer);
+    let exit_requests = options.exit_requests && !matches!(shell_type, ShellType::Buffer);
+    let prelude = version
+        .prelude()
+        .replace(PARAMS_MARKER, &struct_fields(&options.params));
+    let prelude = if accumulate {
+        prelude.replace("fn shadertui_color(", SAMPLE_COLOR_FN)
+    } else {
+        prelude
+    };
+
+    // Replace the injection marker with user code followed by the versioned prelude
+    let injected = format!(
+        "{user_shader}\n\n{prelude}\n{}{}{}{}",
+        channel_declarations(
+            options.channel_count + options.textures.len() + usize::from(options.audio.is_some())
+        ),
+        if options.state_size.is_some() {
+            STATE_DECLARATION
+        } else {
+            ""
+        },
+        if accumulate {
+            ACCUMULATE_DECLARATION
+        } else {
+            NO_ACCUMULATE_DECLARATION
+        },
+        if exit_requests {
+            EXIT_DECLARATION
+        } else {
+            NO_EXIT_DECLARATION
+        }
+    );
+    let complete_shader = shell_template.replace(USER_INJECTION_MARKER, &injected);
+
+    Ok(directives + &complete_shader)
+}
 
-    Ok(complete_shader)
+// AIDEV-NOTE: WGSL wants directives like `enable f16;` before every declaration, but user code is
+// injected after the shell's bindings. They move to the start of the shell's first line (blanked
+// where they were), so no line shifts and user_line_offset still holds
+fn hoist_directives(user_shader: &str) -> (String, String) {
+    let mut directives = String::new();
+    let lines: Vec<&str> = user_shader
+        .split('\n')
+        .map(|line| {
+            let trimmed = line.trim();
+            if (trimmed.starts_with("enable ") || trimmed.starts_with("requires "))
+                && trimmed.ends_with(';')
+            {
+                directives.push_str(trimmed);
+                directives.push(' ');
+                ""
+            } else {
+                line
+            }
+        })
+        .collect();
+    (directives, lines.join("\n"))
+}
+
+// Whether a shader enables WGSL's f16 extension, which needs wgpu::Features::SHADER_F16
+pub fn uses_f16(user_shader: &str) -> bool {
+    user_shader.lines().any(|line| {
+        line.trim()
+            .strip_prefix("enable ")
+            .is_some_and(|extensions| extensions.contains("f16"))
+    })
+}
+
+// Get the appropriate shell template, preferring a user-supplied one for the output pass
+fn shell_template(shell_type: ShellType, options: &ShellOptions) -> Cow<'_, str> {
+    match (&options.custom_shell, shell_type) {
+        (_, ShellType::Buffer) => {
+            Cow::Owned(BUFFER_SHELL.replace(BUFFER_SHELL_FORMAT, options.buffer_format.wgsl_name()))
+        }
+        (Some(custom_shell), _) => Cow::Borrowed(custom_shell.as_str()),
+        (None, ShellType::Terminal) => Cow::Borrowed(TERMINAL_SHELL),
+        (None, ShellType::Window) => Cow::Borrowed(WINDOW_SHELL),
+    }
+}
+
+// AIDEV-NOTE: Index of the user shader's first line in the output of inject_user_shader; safe mode
+// keeps user lines in place, so line N of the user shader is line N + offset of the complete one
+pub fn user_line_offset(shell_type: ShellType, options: &ShellOptions) -> usize {
+    let template = shell_template(shell_type, options);
+    template
+        .find(USER_INJECTION_MARKER)
+        .map_or(0, |marker| template[..marker].matches('\n').count())
+}
+
+// AIDEV-NOTE: Channels live in bind group 1 (sampler at binding 0, channelN at binding N + 1);
+// this must match gpu::passes::create_channel_layout
+pub fn channel_declarations(channel_count: usize) -> String {
+    if channel_count == 0 {
+        return String::new();
+    }
+
+    let mut declarations = String::from("@group(1) @binding(0) var channel_sampler: sampler;\n");
+    for i in 0..channel_count {
+        declarations.push_str(&format!(
+            "@group(1) @binding({}) var channel{i}: texture_2d<f32>;\n",
+            i + 1
+        ));
+    }
+    declarations
 }
 
 // AIDEV-NOTE: Get the window display shader for the render pipeline
@@ -77,6 +348,7 @@ pub fn get_window_display_shader() -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::validation::validate_shader;
 
     #[test]
     fn test_validate_user_shader_valid() {
@@ -98,7 +370,7 @@ mod tests {
         "#;
         assert!(matches!(
             validate_user_shader(invalid_shader),
-            Err(ShaderShellError::MissingComputeColorFunction)
+            Err(ShaderShellError::MissingComputeColorFunction { .. })
         ));
     }
 
@@ -111,7 +383,7 @@ mod tests {
             }
         "#;
 
-        let result = inject_user_shader(user_shader, ShellType::Terminal);
+        let result = inject_user_shader(user_shader, ShellType::Terminal, &ShellOptions::default());
         assert!(result.is_ok());
 
         let complete_shader = result.unwrap();
@@ -129,7 +401,7 @@ mod tests {
             }
         "#;
 
-        let result = inject_user_shader(user_shader, ShellType::Window);
+        let result = inject_user_shader(user_shader, ShellType::Window, &ShellOptions::default());
         assert!(result.is_ok());
 
         let complete_shader = result.unwrap();
@@ -138,4 +410,165 @@ mod tests {
         assert!(complete_shader.contains("fn compute_color(coords: vec2<f32>) -> vec3<f32>"));
         assert!(!complete_shader.contains(USER_INJECTION_MARKER));
     }
+
+    #[test]
+    fn test_inject_user_shader_custom_shell() {
+        let user_shader = r#"
+            fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+                return vec3<f32>(coords.x, coords.y, 0.5);
+            }
+        "#;
+        let options = ShellOptions {
+            custom_shell: Some(format!("// my custom shell\n{USER_INJECTION_MARKER}\n")),
+            ..Default::default()
+        };
+
+        let complete_shader =
+            inject_user_shader(user_shader, ShellType::Terminal, &options).unwrap();
+        assert!(complete_shader.starts_with("// my custom shell"));
+        assert!(complete_shader.contains("fn compute_color(coords: vec2<f32>) -> vec3<f32>"));
+        assert!(!complete_shader.contains("var<storage, read_write> output"));
+    }
+
+    #[test]
+    fn test_api_version_pragma() {
+        assert_eq!(
+            ShaderApiVersion::from_user_shader("fn f() {}").unwrap(),
+            ShaderApiVersion::V1
+        );
+        assert_eq!(
+            ShaderApiVersion::from_user_shader("// @shadertui 2\nfn f() {}").unwrap(),
+            ShaderApiVersion::V2
+        );
+        assert!(matches!(
+            ShaderApiVersion::from_user_shader("// @shadertui 9"),
+            Err(ShaderShellError::UnsupportedApiVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_inject_v2_shader_validates_in_both_shells() {
+        let user_shader = r#"
+            // @shadertui 2
+            fn compute_color(coords: vec2<f32>) -> vec4<f32> {
+                let uv = coords / uniforms.resolution;
+                return vec4<f32>(uv.x, uv.y, 0.5, uv.x);
+            }
+        "#;
+
+        for shell_type in [ShellType::Terminal, ShellType::Window] {
+            let complete_shader =
+                inject_user_shader(user_shader, shell_type, &ShellOptions::default()).unwrap();
+            assert!(validate_shader(&complete_shader).is_ok());
+        }
+
+        // A v1 signature is rejected once the shader opts into v2
+        let mismatched = "// @shadertui 2\nfn compute_color(coords: vec2<f32>) -> vec3<f32> {}";
+        assert!(matches!(
+            validate_user_shader(mismatched),
+            Err(ShaderShellError::MissingComputeColorFunction { .. })
+        ));
+    }
+
+    #[test]
+    fn test_inject_buffer_pass_with_channels() {
+        let user_shader = r#"
+            fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+                let previous = textureLoad(channel0, vec2<i32>(coords), 0);
+                let uv = coords / uniforms.resolution;
+                let blurred = textureSampleLevel(channel1, channel_sampler, uv, 0.0);
+                return mix(previous.rgb, blurred.rgb, 0.5);
+            }
+        "#;
+        let options = ShellOptions {
+            channel_count: 2,
+            ..Default::default()
+        };
+
+        for shell_type in [ShellType::Buffer, ShellType::Terminal, ShellType::Window] {
+            let complete_shader = inject_user_shader(user_shader, shell_type, &options).unwrap();
+            assert!(validate_shader(&complete_shader).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_inject_state_buffer() {
+        let user_shader = r#"
+            fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+                let i = u32(coords.y * uniforms.resolution.x + coords.x);
+                state[i] = state[i] * 0.9 + 0.1;
+                return vec3<f32>(state[i]);
+            }
+        "#;
+        let options = ShellOptions {
+            state_size: Some(1024),
+            ..Default::default()
+        };
+
+        for shell_type in [ShellType::Buffer, ShellType::Terminal, ShellType::Window] {
+            let complete_shader = inject_user_shader(user_shader, shell_type, &options).unwrap();
+            assert!(validate_shader(&complete_shader).is_ok());
+        }
+        // Without --state-size there is no `state` to refer to
+        let complete_shader =
+            inject_user_shader(user_shader, ShellType::Terminal, &ShellOptions::default()).unwrap();
+        assert!(validate_shader(&complete_shader).is_err());
+    }
+
+    #[test]
+    fn test_inject_accumulation() {
+        let user_shader = r#"
+            fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+                if uniforms.cursor.x > 10.0 {
+                    restart_accumulation();
+                }
+                return vec3<f32>(f32(uniforms.sample_index % 2u));
+            }
+        "#;
+        // Without --accumulate the restart is a no-op
+        let complete_shader =
+            inject_user_shader(user_shader, ShellType::Terminal, &ShellOptions::default()).unwrap();
+        assert!(validate_shader(&complete_shader).is_ok());
+
+        let options = ShellOptions {
+            accumulate: true,
+            ..Default::default()
+        };
+
+        for shell_type in [ShellType::Buffer, ShellType::Terminal, ShellType::Window] {
+            let complete_shader = inject_user_shader(user_shader, shell_type, &options).unwrap();
+            assert!(validate_shader(&complete_shader).is_ok());
+            // Buffer passes are not averaged
+            assert_eq!(
+                complete_shader.contains("shadertui_accumulation"),
+                !matches!(shell_type, ShellType::Buffer)
+            );
+        }
+    }
+    #[test]
+    fn test_inject_exit_request() {
+        let user_shader = r#"
+            fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+                if uniforms.frame >= 100u {
+                    request_exit(3u);
+                }
+                return vec3<f32>(0.0);
+            }
+        "#;
+        let options = ShellOptions {
+            exit_requests: true,
+            ..Default::default()
+        };
+
+        for shell_type in [ShellType::Buffer, ShellType::Terminal] {
+            for options in [&options, &ShellOptions::default()] {
+                let complete_shader = inject_user_shader(user_shader, shell_type, options).unwrap();
+                assert!(validate_shader(&complete_shader).is_ok());
+                assert_eq!(
+                    complete_shader.contains("shadertui_exit"),
+                    options.exit_requests && !matches!(shell_type, ShellType::Buffer)
+                );
+            }
+        }
+    }
 }
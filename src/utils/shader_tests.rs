@@ -0,0 +1,125 @@
+use crate::utils::shader_shell::{ShaderApiVersion, ShaderShellError};
+
+// AIDEV-NOTE: `shadertui test` runs the functions marked `// @test` in a shader, each one a
+// `fn name() -> bool` returning whether it passed. TEST_SHELL dispatches one invocation per test,
+// which writes its result to the output pixel of the same index (see commands/test.rs)
+pub const TEST_PRAGMA: &str = "// @test";
+const TEST_SHELL: &str = include_str!("../shaders/test_shell.wgsl");
+const TEST_CASES_MARKER: &str = "        // SHADERTUI_TEST_CASES\n";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderTest {
+    pub name: String,
+    // Index of the `fn` line in the source the test was found in
+    pub line_index: usize,
+}
+
+// The tests of a (processed) shader source, in source order
+pub fn discover_tests(source: &str) -> Result<Vec<ShaderTest>, String> {
+    let mut tests = Vec::new();
+    let mut lines = source.lines().enumerate();
+    while let Some((pragma_index, line)) = lines.next() {
+        if line.trim() != TEST_PRAGMA {
+            continue;
+        }
+        // The function follows, possibly after blank lines and other comments
+        let function = lines.find(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with("//")
+        });
+        let test = function.and_then(|(line_index, line)| {
+            let (name, rest) = line.trim().strip_prefix("fn ")?.split_once('(')?;
+            let returns_bool = rest
+                .trim_start()
+                .strip_prefix(')')?
+                .trim_start()
+                .strip_prefix("->")?
+                .trim_start()
+                .starts_with("bool");
+            returns_bool.then(|| ShaderTest {
+                name: name.trim().to_string(),
+                line_index,
+            })
+        });
+        match test {
+            Some(test) => tests.push(test),
+            None => {
+                return Err(format!(
+                    "line {}: `{TEST_PRAGMA}` must be followed by a `fn name() -> bool`",
+                    pragma_index + 1
+                ))
+            }
+        }
+    }
+    Ok(tests)
+}
+
+// AIDEV-NOTE: Libraries of helpers have no compute_color() of their own, but every shell calls it;
+// an empty one is appended after the last line, so line numbers stay the same
+pub fn with_compute_color(source: &str) -> Result<String, ShaderShellError> {
+    let version = ShaderApiVersion::from_user_shader(source)?;
+    let signature = version.compute_color_signature();
+    if source.contains(signature) {
+        return Ok(source.to_string());
+    }
+    let color_type = signature.rsplit("-> ").next().unwrap_or("vec3<f32>");
+    Ok(format!(
+        "{source}\n{signature} {{\n    return {color_type}(0.0);\n}}\n"
+    ))
+}
+
+// The shell running `tests`, for ShellOptions::custom_shell
+pub fn test_shell(tests: &[ShaderTest]) -> String {
+    let cases: String = tests
+        .iter()
+        .enumerate()
+        .map(|(index, test)| {
+            format!(
+                "        case {index}u: {{\n            passed = {}();\n        }}\n",
+                test.name
+            )
+        })
+        .collect();
+    TEST_SHELL.replace(TEST_CASES_MARKER, &cases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_tests() {
+        let source = "\
+fn helper() -> f32 { return 1.0; }
+
+// @test
+fn test_helper() -> bool {
+    return helper() == 1.0;
+}
+
+// @test
+// Comments may sit in between
+fn  test_spacing ( )->bool { return true; }
+";
+        let tests = discover_tests(source).unwrap();
+        assert_eq!(
+            tests,
+            vec![
+                ShaderTest {
+                    name: "test_helper".to_string(),
+                    line_index: 3,
+                },
+                ShaderTest {
+                    name: "test_spacing".to_string(),
+                    line_index: 9,
+                },
+            ]
+        );
+        assert!(test_shell(&tests).contains("case 1u: {\n            passed = test_spacing();"));
+
+        let error = discover_tests("// @test\nfn takes_input(x: f32) -> bool {}").unwrap_err();
+        assert!(error.starts_with("line 1:"), "{error}");
+        assert!(discover_tests("// @test\nfn returns_f32() -> f32 {}").is_err());
+        assert!(discover_tests("// @test").is_err());
+    }
+}
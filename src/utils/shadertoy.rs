@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use naga::back::wgsl::WriterFlags;
+use naga::front::glsl::{Frontend, Options};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use naga::ShaderStage;
+
+// AIDEV-NOTE: Shadertoy's inputs become private globals that shadertoy_image() fills in from its
+// arguments, so mainImage() reads them like the uniforms it expects
+const GLSL_PRELUDE: &str = "#version 450
+vec3 iResolution;
+float iTime;
+float iTimeDelta;
+int iFrame;
+vec4 iMouse;
+";
+
+const GLSL_ENTRY: &str = "
+vec3 shadertoy_image(vec2 fragCoord, vec3 resolution, float time, float timeDelta, int frame, vec4 mouse) {
+    iResolution = resolution;
+    iTime = time;
+    iTimeDelta = timeDelta;
+    iFrame = frame;
+    iMouse = mouse;
+    vec4 color = vec4(0.0, 0.0, 0.0, 1.0);
+    mainImage(color, fragCoord);
+    return color.rgb;
+}
+
+void main() {}
+";
+
+// Shadertoy samples at pixel centers and ignores alpha in the final image
+const WGSL_ADAPTER: &str = "
+fn compute_color(coords: vec2<f32>) -> vec3<f32> {
+    return shadertoy_image(
+        coords + 0.5,
+        vec3<f32>(uniforms.resolution, 1.0),
+        uniforms.time,
+        uniforms.delta_time,
+        i32(uniforms.frame),
+        uniforms.mouse,
+    );
+}
+";
+
+pub fn is_glsl_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "glsl" || ext == "frag")
+}
+
+// AIDEV-NOTE: Translate a Shadertoy-style `void mainImage(out vec4 fragColor, in vec2 fragCoord)`
+// shader to WGSL with an ordinary v1 compute_color(), so shells, validation and passes treat it like
+// any other shader. The leading comment block is kept for the metadata header. Errors are one
+// `LINE:COL: message` per line, positioned in `source`
+pub fn translate_shadertoy(source: &str) -> Result<String, String> {
+    let glsl = format!("{GLSL_PRELUDE}{source}\n{GLSL_ENTRY}");
+    let mut module = Frontend::default()
+        .parse(&Options::from(ShaderStage::Fragment), &glsl)
+        .map_err(|errors| {
+            let messages: Vec<String> = errors
+                .errors
+                .iter()
+                .map(|error| match user_location(source, error.meta.to_range()) {
+                    Some((line, column)) => format!("{line}:{column}: {}", error.kind),
+                    None => format!(" {}", error.kind),
+                })
+                .collect();
+            messages.join("\n")
+        })?;
+
+    // The shell provides the real entry point, named `main` like the GLSL one
+    module.entry_points.clear();
+    for (_, function) in module.functions.iter_mut() {
+        if function.name.as_deref() == Some("main") {
+            function.name = Some("shadertoy_main".to_string());
+        }
+    }
+
+    let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|e| format!(" {e}"))?;
+    let wgsl = naga::back::wgsl::write_string(&module, &info, WriterFlags::empty())
+        .map_err(|e| format!(" {e}"))?;
+
+    let header: String = source
+        .lines()
+        .take_while(|line| line.trim().is_empty() || line.trim().starts_with("//"))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    Ok(format!("{header}{wgsl}{WGSL_ADAPTER}"))
+}
+
+// 1-based line and column in the user's source of a span in the combined GLSL, if it falls there
+fn user_location(source: &str, range: Option<std::ops::Range<usize>>) -> Option<(usize, usize)> {
+    let start = range?.start.checked_sub(GLSL_PRELUDE.len())?;
+    let before = source.get(..start)?;
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Some((
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shader_shell::{inject_user_shader, ShellOptions, ShellType};
+    use crate::utils::validation::validate_shader;
+
+    #[test]
+    fn test_translate_shadertoy() {
+        let source = "// @title Rainbow\n\
+                      void mainImage(out vec4 fragColor, in vec2 fragCoord) {\n\
+                          vec2 uv = fragCoord / iResolution.xy;\n\
+                          fragColor = vec4(0.5 + 0.5 * cos(iTime + uv.xyx + vec3(0, 2, 4)), 1.0);\n\
+                      }\n";
+        let wgsl = translate_shadertoy(source).unwrap();
+        assert!(wgsl.starts_with("// @title Rainbow\n"));
+
+        for shell_type in [ShellType::Terminal, ShellType::Window] {
+            let complete_shader =
+                inject_user_shader(&wgsl, shell_type, &ShellOptions::default()).unwrap();
+            assert!(validate_shader(&complete_shader).is_ok());
+        }
+
+        // Errors point at the user's line, not the prelude
+        let error = translate_shadertoy("void mainImage(out vec4 c, in vec2 p) {\n  c = nope;\n}")
+            .unwrap_err();
+        assert!(error.starts_with("2:7: "), "{error}");
+    }
+}
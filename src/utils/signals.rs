@@ -0,0 +1,28 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+// AIDEV-NOTE: SIGINT, SIGTERM and SIGHUP set the returned flag instead of killing the process, so
+// the render loops can exit normally: restoring the terminal and finishing a recording in
+// progress. A second signal exits immediately, in case the loop is stuck
+pub fn stop_on_signals() -> Arc<AtomicBool> {
+    let stop = Arc::new(AtomicBool::new(false));
+    register(&stop);
+    stop
+}
+
+#[cfg(unix)]
+fn register(stop: &Arc<AtomicBool>) {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    use signal_hook::flag;
+
+    for signal in [SIGINT, SIGTERM, SIGHUP] {
+        // Registered first, so it sees the flag before this signal sets it
+        let _ = flag::register_conditional_shutdown(signal, 1, stop.clone());
+        let _ = flag::register(signal, stop.clone());
+    }
+}
+
+// Elsewhere signals keep their default behavior; recordings still stay playable up to the last
+// written frame
+#[cfg(not(unix))]
+fn register(_stop: &Arc<AtomicBool>) {}
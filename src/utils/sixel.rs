@@ -0,0 +1,103 @@
+use std::fmt::Write;
+
+use crate::utils::image_export::RgbaImage;
+
+// Levels per channel of the fixed color cube; 6^3 = 216 registers fits every sixel terminal that
+// allows 256
+const LEVELS: usize = 6;
+const PALETTE_SIZE: usize = LEVELS * LEVELS * LEVELS;
+
+// AIDEV-NOTE: Encode an image as one sixel DCS sequence. Colors are snapped to a fixed 6x6x6 cube so
+// the palette never has to be computed per frame; each 6-row band draws every color it uses as a
+// run-length encoded pass over the band, returning to the left edge with `$` in between
+pub fn encode(image: &RgbaImage) -> String {
+    let (width, height) = (image.width as usize, image.height as usize);
+    let mut out = String::with_capacity(width * height / 2);
+
+    // P2 = 1 leaves unset pixels alone; raster attributes give the exact size
+    let _ = write!(out, "\x1bP0;1;0q\"1;1;{width};{height}");
+    for index in 0..PALETTE_SIZE {
+        let [r, g, b] = cube_color(index).map(|level| level * 100 / (LEVELS - 1));
+        let _ = write!(out, "#{index};2;{r};{g};{b}");
+    }
+
+    let mut bands: Vec<Option<Vec<u8>>> = vec![None; PALETTE_SIZE];
+    for band_top in (0..height).step_by(6) {
+        for row in 0..6.min(height - band_top) {
+            let y = band_top + row;
+            for x in 0..width {
+                let pixel = &image.pixels[(y * width + x) * 4..];
+                let index = cube_index(pixel[0], pixel[1], pixel[2]);
+                bands[index].get_or_insert_with(|| vec![0; width])[x] |= 1 << row;
+            }
+        }
+
+        let mut first = true;
+        for (index, band) in bands.iter_mut().enumerate() {
+            let Some(bits) = band.take() else {
+                continue;
+            };
+            if !first {
+                out.push('$');
+            }
+            first = false;
+            let _ = write!(out, "#{index}");
+            push_runs(&mut out, &bits);
+        }
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+fn cube_index(r: u8, g: u8, b: u8) -> usize {
+    let level = |c: u8| (c as usize * (LEVELS - 1) + 127) / 255;
+    (level(r) * LEVELS + level(g)) * LEVELS + level(b)
+}
+
+fn cube_color(index: usize) -> [usize; 3] {
+    [
+        index / (LEVELS * LEVELS),
+        index / LEVELS % LEVELS,
+        index % LEVELS,
+    ]
+}
+
+// Sixel characters are 0x3F + the six vertical bits; `!N` repeats the next one N times
+fn push_runs(out: &mut String, bits: &[u8]) {
+    let mut x = 0;
+    while x < bits.len() {
+        let run = bits[x..].iter().take_while(|&&b| b == bits[x]).count();
+        let ch = (0x3F + bits[x]) as char;
+        if run > 3 {
+            let _ = write!(out, "!{run}{ch}");
+        } else {
+            (0..run).for_each(|_| out.push(ch));
+        }
+        x += run;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        // 5x2: red row over a blue row
+        let mut pixels = Vec::new();
+        pixels.extend([255, 0, 0, 255].repeat(5));
+        pixels.extend([0, 0, 255, 255].repeat(5));
+        let image = RgbaImage {
+            width: 5,
+            height: 2,
+            pixels,
+        };
+
+        let sixel = encode(&image);
+        assert!(sixel.starts_with("\x1bP0;1;0q\"1;1;5;2#0;2;0;0;0"));
+        // Blue (register 5) sets the second bit, red (register 180) the first
+        assert!(sixel.ends_with("#5!5A$#180!5@-\x1b\\"), "{sixel}");
+    }
+}
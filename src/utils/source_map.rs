@@ -0,0 +1,46 @@
+use std::path::Path;
+use std::sync::Arc;
+
+// AIDEV-NOTE: Where each line of a processed pass source came from (after imports, @pass sections
+// and translation), so compile errors can point at the file the user edits. Lines shadertui
+// generated itself (palettes, adapters, translated GLSL) have no origin
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    lines: Vec<Option<LineOrigin>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineOrigin {
+    pub file: Arc<Path>,
+    // 1-based
+    pub line: usize,
+}
+
+impl SourceMap {
+    pub fn origin(&self, line_index: usize) -> Option<&LineOrigin> {
+        self.lines.get(line_index).and_then(Option::as_ref)
+    }
+
+    pub fn push_line(&mut self, origin: Option<LineOrigin>) {
+        self.lines.push(origin);
+    }
+
+    // The lines at `line_indices`, in that order (e.g. one `// @pass` section of a file)
+    pub fn select(&self, line_indices: &[usize]) -> SourceMap {
+        SourceMap {
+            lines: line_indices
+                .iter()
+                .map(|&index| self.origin(index).cloned())
+                .collect(),
+        }
+    }
+
+    // Append the map of a chunk that was added to the source followed by a newline, padding or
+    // trimming it to the lines the chunk really occupies
+    pub fn push_chunk(&mut self, chunk: &str, chunk_map: Option<SourceMap>) {
+        let line_count = chunk.matches('\n').count() + 1;
+        let mut lines = chunk_map.map(|map| map.lines).unwrap_or_default();
+        lines.resize(line_count, None);
+        self.lines.extend(lines);
+    }
+}
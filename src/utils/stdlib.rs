@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+// AIDEV-NOTE: WGSL helpers built into the binary and imported as `// @import "std/<name>"` (a
+// `.wgsl` suffix is optional). They resolve to virtual paths under STD_ROOT, which show up in error
+// locations but are never read from disk or watched
+pub const STD_PREFIX: &str = "std/";
+const STD_ROOT: &str = "<std>";
+
+pub struct StdModule {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub source: &'static str,
+}
+
+pub const MODULES: &[StdModule] = &[
+    StdModule {
+        name: "hash",
+        description: "Pseudo-random hashes of lattice points (PCG)",
+        source: include_str!("../shaders/std/hash.wgsl"),
+    },
+    StdModule {
+        name: "noise",
+        description: "Value and gradient noise, fractal Brownian motion",
+        source: include_str!("../shaders/std/noise.wgsl"),
+    },
+    StdModule {
+        name: "sdf",
+        description: "2D and 3D signed distance functions and their combinations",
+        source: include_str!("../shaders/std/sdf.wgsl"),
+    },
+    StdModule {
+        name: "easing",
+        description: "Easing curves for animation",
+        source: include_str!("../shaders/std/easing.wgsl"),
+    },
+    StdModule {
+        name: "color",
+        description: "HSV and sRGB conversions, luminance, cosine palettes",
+        source: include_str!("../shaders/std/color.wgsl"),
+    },
+    StdModule {
+        name: "raymarch",
+        description: "Camera rays, sphere tracing, normals and soft shadows over your scene_sdf()",
+        source: include_str!("../shaders/std/raymarch.wgsl"),
+    },
+];
+
+// The module an import path like "std/noise" or "std/noise.wgsl" names, or Err(name) for an
+// unknown one. None if the path isn't in the standard library
+pub fn resolve(import_path: &str) -> Option<Result<&'static StdModule, String>> {
+    let name = import_path.strip_prefix(STD_PREFIX)?;
+    let name = name.strip_suffix(".wgsl").unwrap_or(name);
+    Some(
+        MODULES
+            .iter()
+            .find(|module| module.name == name)
+            .ok_or_else(|| name.to_string()),
+    )
+}
+
+pub fn virtual_path(module: &StdModule) -> PathBuf {
+    Path::new(STD_ROOT).join(format!("{}.wgsl", module.name))
+}
+
+pub fn is_virtual(path: &Path) -> bool {
+    path.starts_with(STD_ROOT)
+}
+
+// Names of the functions a module defines, for `shadertui stdlib list`
+pub fn function_names(module: &StdModule) -> Vec<&'static str> {
+    module
+        .source
+        .lines()
+        .filter_map(|line| line.strip_prefix("fn "))
+        .filter_map(|rest| rest.split('(').next())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shader_import::process_imports;
+
+    // Every module compiles on its own, pulling in the modules it imports
+    #[test]
+    fn test_modules_compile() {
+        for module in MODULES {
+            let mut source = format!("// @import \"std/{}.wgsl\"\n", module.name);
+            if module.name == "raymarch" {
+                source.push_str("fn scene_sdf(p: vec3<f32>) -> f32 { return length(p) - 1.0; }\n");
+            }
+            let path = std::env::temp_dir().join("shadertui_stdlib_test.wgsl");
+            std::fs::write(&path, &source).unwrap();
+            let (wgsl, _, deps) = process_imports(&path, &source).unwrap();
+            assert!(deps.all_files.iter().all(|file| !is_virtual(file)));
+
+            let parsed = naga::front::wgsl::parse_str(&wgsl)
+                .unwrap_or_else(|e| panic!("std/{}: {}", module.name, e.emit_to_string(&wgsl)));
+            naga::valid::Validator::new(
+                naga::valid::ValidationFlags::all(),
+                naga::valid::Capabilities::empty(),
+            )
+            .validate(&parsed)
+            .unwrap_or_else(|e| panic!("std/{}: {e:?}", module.name));
+        }
+        assert!(matches!(resolve("std/nope"), Some(Err(name)) if name == "nope"));
+        assert!(resolve("utils.wgsl").is_none());
+    }
+}
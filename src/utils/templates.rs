@@ -0,0 +1,44 @@
+use crate::utils::cli::Template;
+
+// AIDEV-NOTE: Starter shaders for `shadertui new`, built into the binary. Each must validate as
+// written (the audio one, only in `--features audio` builds, with its --audio channel bound), since
+// users run them before editing
+pub fn template_source(template: Template) -> &'static str {
+    match template {
+        Template::Plasma => include_str!("../templates/plasma.wgsl"),
+        Template::Raymarch => include_str!("../templates/raymarch.wgsl"),
+        Template::GameOfLife => include_str!("../templates/gameoflife.wgsl"),
+        #[cfg(feature = "audio")]
+        Template::Audio => include_str!("../templates/audio.wgsl"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::ValueEnum;
+
+    use super::*;
+    use crate::utils::project::ShaderProject;
+    use crate::utils::shader_shell::{ShellOptions, ShellType};
+    use crate::utils::validation::validate_project;
+
+    // Every template `shadertui new` offers in this build compiles with the options a plain
+    // `shadertui check` uses
+    #[test]
+    fn test_templates_validate() {
+        let dir = std::env::temp_dir().join("shadertui_template_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        for &template in Template::value_variants() {
+            let path = dir.join(format!("{template:?}.wgsl"));
+            std::fs::write(&path, template_source(template)).unwrap();
+            let project = ShaderProject::load(&path).unwrap();
+
+            let mut shell_options = ShellOptions::default();
+            if template.needs_audio() {
+                shell_options.audio = Some(crate::audio::AudioCapture::silent());
+            }
+            validate_project(&project, ShellType::Terminal, &shell_options)
+                .unwrap_or_else(|e| panic!("{template:?}: {e}"));
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::io::{stdout, IsTerminal, Write};
+use std::time::Duration;
+
+use crossterm::{cursor, execute, terminal};
+
+use crate::utils::cli::{CellMode, ColorMode};
+
+// GNU screen drops DCS strings longer than its internal buffer
+const SCREEN_DCS_CHUNK: usize = 768;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    Tmux,
+    Screen,
+}
+
+// AIDEV-NOTE: What the current terminal can do. Environment-based fields are heuristics (terminals
+// rarely advertise graphics protocols); the probe_* fields need a real TTY and are None otherwise
+#[derive(Debug, Clone, Default)]
+pub struct TerminalCapabilities {
+    pub term: Option<String>,
+    pub term_program: Option<String>,
+    pub truecolor: bool,
+    pub kitty_graphics: bool,
+    pub sixel: bool,
+    pub multiplexer: Option<Multiplexer>,
+    // Classic conhost without a VT-capable host (Windows Terminal, ConEmu, ...)
+    pub legacy_windows_console: bool,
+    pub cell_pixel_size: Option<(u16, u16)>,
+    pub half_block_width: Option<u16>,
+}
+
+impl TerminalCapabilities {
+    pub fn detect() -> Self {
+        Self::from_env(cfg!(windows), |name| std::env::var(name).ok())
+    }
+
+    // AIDEV-NOTE: Used when --color-mode/--cell-mode are not given explicitly
+    pub fn default_color_mode(&self) -> ColorMode {
+        // The Linux virtual console and *-16color terminfo entries only have the basic colors
+        let term = self.term.as_deref().unwrap_or("");
+        if !self.truecolor && (term == "linux" || term.ends_with("16color")) {
+            return ColorMode::Ansi16;
+        }
+        // Multiplexers only forward 24-bit color when configured to, which COLORTERM reflects
+        let limited_host = self.legacy_windows_console || self.multiplexer.is_some();
+        if limited_host && !self.truecolor {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Truecolor
+        }
+    }
+
+    pub fn default_cell_mode(&self) -> CellMode {
+        if self.legacy_windows_console {
+            CellMode::Foreground
+        } else {
+            CellMode::HalfBlock
+        }
+    }
+
+    // AIDEV-NOTE: Also queries the terminal itself (pixel size, rendered width of ▀); only call this
+    // when nothing else is drawing, since it briefly enables raw mode and writes to stdout
+    pub fn probe() -> Self {
+        let mut caps = Self::detect();
+        if !stdout().is_terminal() {
+            return caps;
+        }
+
+        caps.cell_pixel_size = cell_pixel_size();
+        caps.half_block_width = measure_glyph_width("▀");
+
+        caps
+    }
+
+    // AIDEV-NOTE: Multiplexers deliver resizes in bursts (attach, pane/zoom changes) and may report
+    // the final size late, so wait for the burst to settle before resizing the GPU buffers
+    pub fn resize_debounce(&self) -> Duration {
+        if self.multiplexer.is_some() {
+            Duration::from_millis(100)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    // AIDEV-NOTE: Wrap a DCS/APC graphics sequence (sixel, kitty) so the multiplexer forwards it to
+    // the outer terminal. tmux needs `set -g allow-passthrough on`; screen limits DCS length, so the
+    // sequence is split into chunks
+    pub fn wrap_passthrough(&self, sequence: &str) -> String {
+        match self.multiplexer {
+            Some(Multiplexer::Tmux) => {
+                format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+            }
+            Some(Multiplexer::Screen) => {
+                let mut wrapped = String::new();
+                let mut chunk = String::new();
+                for ch in sequence.chars() {
+                    chunk.push(ch);
+                    if chunk.len() >= SCREEN_DCS_CHUNK {
+                        wrapped.push_str(&format!("\x1bP{chunk}\x1b\\"));
+                        chunk.clear();
+                    }
+                }
+                if !chunk.is_empty() {
+                    wrapped.push_str(&format!("\x1bP{chunk}\x1b\\"));
+                }
+                wrapped
+            }
+            None => sequence.to_string(),
+        }
+    }
+
+    fn from_env(is_windows: bool, var: impl Fn(&str) -> Option<String>) -> Self {
+        let term = var("TERM");
+        let term_program = var("TERM_PROGRAM");
+        let term_str = term.as_deref().unwrap_or("");
+        let program = term_program.as_deref().unwrap_or("");
+
+        let truecolor = matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit"))
+            || var("WT_SESSION").is_some()
+            || term_str.contains("direct")
+            || term_str.contains("kitty")
+            || term_str.contains("alacritty")
+            || matches!(
+                program,
+                "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+            );
+        let kitty_graphics = term_str == "xterm-kitty"
+            || var("KITTY_WINDOW_ID").is_some()
+            || matches!(program, "WezTerm" | "ghostty");
+        let sixel = term_str.contains("sixel")
+            || term_str.starts_with("foot")
+            || term_str.starts_with("mlterm")
+            || matches!(program, "WezTerm" | "iTerm.app");
+        let multiplexer = if var("TMUX").is_some() {
+            Some(Multiplexer::Tmux)
+        } else if var("STY").is_some() || term_str.starts_with("screen") {
+            Some(Multiplexer::Screen)
+        } else {
+            None
+        };
+
+        let legacy_windows_console = is_windows
+            && term.is_none()
+            && term_program.is_none()
+            && var("WT_SESSION").is_none()
+            && var("ConEmuANSI").as_deref() != Some("ON");
+
+        Self {
+            term,
+            term_program,
+            truecolor,
+            kitty_graphics,
+            sixel,
+            multiplexer,
+            legacy_windows_console,
+            cell_pixel_size: None,
+            half_block_width: None,
+        }
+    }
+}
+
+// Size of one cell in pixels, when the terminal reports its pixel size (many don't)
+pub fn cell_pixel_size() -> Option<(u16, u16)> {
+    let size = terminal::window_size().ok()?;
+    (size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0)
+        .then(|| (size.width / size.columns, size.height / size.rows))
+}
+
+// AIDEV-NOTE: Print the glyph and compare cursor columns before and after, then erase the line
+fn measure_glyph_width(glyph: &str) -> Option<u16> {
+    terminal::enable_raw_mode().ok()?;
+    let measured = (|| {
+        let mut stdout = stdout();
+        write!(stdout, "\r")?;
+        stdout.flush()?;
+        let (start, _) = cursor::position()?;
+        write!(stdout, "{glyph}")?;
+        stdout.flush()?;
+        let (end, _) = cursor::position()?;
+        execute!(
+            stdout,
+            cursor::MoveToColumn(0),
+            terminal::Clear(terminal::ClearType::CurrentLine)
+        )?;
+        Ok::<_, std::io::Error>(end.saturating_sub(start))
+    })();
+    let _ = terminal::disable_raw_mode();
+
+    measured.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps_from(vars: &[(&str, &str)]) -> TerminalCapabilities {
+        caps_from_os(false, vars)
+    }
+
+    fn caps_from_os(is_windows: bool, vars: &[(&str, &str)]) -> TerminalCapabilities {
+        TerminalCapabilities::from_env(is_windows, |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_detect_from_env() {
+        let kitty = caps_from(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux-1000/default")]);
+        assert!(kitty.truecolor);
+        assert!(kitty.kitty_graphics);
+        assert_eq!(kitty.multiplexer, Some(Multiplexer::Tmux));
+
+        let plain = caps_from(&[("TERM", "xterm-256color")]);
+        assert!(!plain.truecolor);
+        assert!(!plain.sixel);
+        assert_eq!(plain.multiplexer, None);
+
+        let console = caps_from(&[("TERM", "linux")]);
+        assert_eq!(console.default_color_mode(), ColorMode::Ansi16);
+    }
+
+    #[test]
+    fn test_multiplexer_handling() {
+        let tmux = caps_from(&[("TERM", "tmux-256color"), ("TMUX", "/tmp/tmux")]);
+        assert_eq!(tmux.default_color_mode(), ColorMode::Ansi256);
+        assert_eq!(
+            tmux.wrap_passthrough("\x1b_Ga=T\x1b\\"),
+            "\x1bPtmux;\x1b\x1b_Ga=T\x1b\x1b\\\x1b\\"
+        );
+
+        let tmux_truecolor = caps_from(&[("TMUX", "/tmp/tmux"), ("COLORTERM", "truecolor")]);
+        assert_eq!(tmux_truecolor.default_color_mode(), ColorMode::Truecolor);
+    }
+
+    #[test]
+    fn test_legacy_windows_console_fallbacks() {
+        let conhost = caps_from_os(true, &[]);
+        assert!(conhost.legacy_windows_console);
+        assert_eq!(conhost.default_color_mode(), ColorMode::Ansi256);
+        assert_eq!(conhost.default_cell_mode(), CellMode::Foreground);
+
+        let windows_terminal = caps_from_os(true, &[("WT_SESSION", "1234")]);
+        assert!(!windows_terminal.legacy_windows_console);
+        assert_eq!(windows_terminal.default_color_mode(), ColorMode::Truecolor);
+    }
+}
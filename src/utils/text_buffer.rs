@@ -0,0 +1,159 @@
+// AIDEV-NOTE: Line-based text buffer with a char-indexed cursor, used by the --edit pane. Kept free
+// of terminal concerns so editing behaviour can be unit tested
+#[derive(Debug, Clone)]
+pub struct TextBuffer {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+}
+
+impl TextBuffer {
+    pub fn new(text: &str) -> Self {
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        Self {
+            lines,
+            row: 0,
+            col: 0,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        text
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let index = self.byte_index();
+        self.lines[self.row].insert(index, c);
+        self.col += 1;
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.insert_char(c);
+        }
+    }
+
+    // AIDEV-NOTE: New lines keep the indentation of the line they were split from
+    pub fn insert_newline(&mut self) {
+        let index = self.byte_index();
+        let rest = self.lines[self.row].split_off(index);
+        let indent: String = self.lines[self.row]
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+
+        self.col = indent.chars().count();
+        self.row += 1;
+        self.lines.insert(self.row, indent + &rest);
+    }
+
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len();
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.col < self.line_len() {
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len();
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.col < self.line_len() {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    pub fn move_up(&mut self, count: usize) {
+        self.row = self.row.saturating_sub(count);
+        self.col = self.col.min(self.line_len());
+    }
+
+    pub fn move_down(&mut self, count: usize) {
+        self.row = (self.row + count).min(self.lines.len() - 1);
+        self.col = self.col.min(self.line_len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.col = self.line_len();
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        self.lines[self.row]
+            .char_indices()
+            .nth(self.col)
+            .map_or(self.lines[self.row].len(), |(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editing_round_trip() {
+        let mut buffer = TextBuffer::new("fn a() {\n}\n");
+        buffer.move_end();
+        buffer.insert_newline();
+        buffer.insert_str("    let é = 1;");
+        buffer.insert_newline();
+        assert_eq!(buffer.cursor(), (2, 4));
+
+        buffer.backspace();
+        buffer.backspace();
+        buffer.backspace();
+        buffer.backspace();
+        buffer.backspace();
+        assert_eq!(buffer.text(), "fn a() {\n    let é = 1;\n}\n");
+
+        buffer.move_down(5);
+        buffer.move_home();
+        buffer.backspace();
+        assert_eq!(buffer.text(), "fn a() {\n    let é = 1;}\n");
+    }
+}
@@ -1,27 +1,47 @@
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::gpu::{FramePixels, MouseState};
+use crate::utils::benchmark::percentile;
+use crate::utils::image_export::RgbaImage;
+use crate::utils::messages::msg;
+use crate::utils::project::ShaderProject;
+use crate::utils::timeline::FRAME_STEP;
+use crate::utils::trace::{self, TraceEvent};
+use crate::utils::uniform_params::{ParamValues, UniformParam};
 
 // AIDEV-NOTE: Shared frame buffer for GPU→Terminal communication with frame dropping
 #[derive(Debug, Clone)]
 pub struct FrameData {
-    pub gpu_data: Vec<f32>,
+    pub gpu_data: FramePixels,
     pub width: u32,
+    // Uniform values the frame was computed with, so the displayed frame can be identified
+    pub frame: u32,
+    pub time: f32,
+    // Input state the frame was computed with, for latency tracking
+    pub input_generation: u64,
+    pub input_at: Option<Instant>,
+    // From submitting the frame to having it on the CPU, and frames still being read back then
+    pub readback_latency: Duration,
+    pub readbacks_in_flight: usize,
 }
 
 pub struct SharedFrameBuffer {
-    // Double buffering: one frame being written by GPU, one being read by terminal
-    current_frame: Option<FrameData>,
+    // The frame written by the GPU and not yet taken by the terminal
     next_frame: Option<FrameData>,
     frames_dropped: u64,
+    pub telemetry: GpuTelemetry,
 }
 
 impl SharedFrameBuffer {
     pub fn new() -> Self {
         Self {
-            current_frame: None,
             next_frame: None,
             frames_dropped: 0,
+            telemetry: GpuTelemetry::new(),
         }
     }
 
@@ -31,17 +51,33 @@ impl SharedFrameBuffer {
         if self.next_frame.is_some() {
             self.frames_dropped += 1;
         }
+        trace::record(|| TraceEvent::FrameWritten {
+            frame: frame_data.frame,
+            dropped: self.next_frame.as_ref().map(|dropped| dropped.frame),
+        });
         self.next_frame = Some(frame_data);
     }
 
-    // AIDEV-NOTE: Terminal thread reads latest available frame
-    pub fn read_frame(&mut self) -> Option<FrameData> {
-        // Swap next frame to current if available
-        if self.next_frame.is_some() {
-            self.current_frame = self.next_frame.take();
-        }
+    // AIDEV-NOTE: Readback throttling: the GPU thread only reads a frame back once the terminal has
+    // taken the previous one, so readbacks follow the display rate while compute runs free
+    pub fn wants_frame(&self) -> bool {
+        self.next_frame.is_none()
+    }
 
-        self.current_frame.clone()
+    // A frame was computed but never read back for display
+    pub fn skip_frame(&mut self) {
+        self.frames_dropped += 1;
+        trace::record(|| TraceEvent::FrameSkipped);
+    }
+
+    // AIDEV-NOTE: Terminal thread takes the latest frame, None until a new one arrives. The frame
+    // is moved out, so the terminal keeps it for redraws instead of copying it every iteration
+    pub fn read_frame(&mut self) -> Option<FrameData> {
+        let frame_data = self.next_frame.take()?;
+        trace::record(|| TraceEvent::FrameRead {
+            frame: frame_data.frame,
+        });
+        Some(frame_data)
     }
 
     pub fn get_frames_dropped(&self) -> u64 {
@@ -49,50 +85,369 @@ impl SharedFrameBuffer {
     }
 }
 
+// How long GpuTelemetry measures before updating its figures
+const TELEMETRY_WINDOW: Duration = Duration::from_secs(2);
+
+// AIDEV-NOTE: Rough cost of the GPU thread, for --low-power and --perf: the share of wall time it
+// spends rendering (submitting a frame and waiting for it) and frames rendered per minute. Both
+// are None until the first TELEMETRY_WINDOW has passed
+#[derive(Debug)]
+pub struct GpuTelemetry {
+    window_start: Instant,
+    busy: Duration,
+    frames: u32,
+    utilization: Option<f32>,
+    frames_per_minute: Option<f32>,
+}
+
+impl GpuTelemetry {
+    pub fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            busy: Duration::ZERO,
+            frames: 0,
+            utilization: None,
+            frames_per_minute: None,
+        }
+    }
+
+    pub fn record_frame(&mut self, busy: Duration) {
+        self.busy += busy;
+        self.frames += 1;
+
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= TELEMETRY_WINDOW {
+            let seconds = elapsed.as_secs_f32();
+            self.utilization = Some((self.busy.as_secs_f32() / seconds).min(1.0));
+            self.frames_per_minute = Some(self.frames as f32 * 60.0 / seconds);
+            self.window_start = Instant::now();
+            self.busy = Duration::ZERO;
+            self.frames = 0;
+        }
+    }
+
+    pub fn utilization(&self) -> Option<f32> {
+        self.utilization
+    }
+
+    pub fn frames_per_second(&self) -> Option<f32> {
+        self.frames_per_minute.map(|frames| frames / 60.0)
+    }
+
+    // e.g. "GPU busy 3% · 600 frames/min"
+    pub fn summary(&self) -> String {
+        match (self.utilization, self.frames_per_minute) {
+            (Some(utilization), Some(frames_per_minute)) => msg!(
+                "status.gpu_busy",
+                percent = format!("{:.0}", utilization * 100.0),
+                frames = format!("{frames_per_minute:.0}"),
+            ),
+            _ => msg!("status.gpu_busy_unknown"),
+        }
+    }
+}
+
 // AIDEV-NOTE: Shared uniforms for Terminal→GPU communication
 #[derive(Debug, Clone)]
 pub struct SharedUniforms {
     pub cursor: [i32; 2],
+    pub mouse: MouseState,
+    pub param_values: ParamValues,
+    // Region picked with the terminal's zoom inspector, see gpu::uniforms::zoom_transform
+    pub zoom: Option<[f32; 4]>,
+    // Shade SHADER_SUBSAMPLES x SHADER_SUBSAMPLES points per pixel and average them (--supersample)
+    pub supersample: bool,
     pub time_paused: bool,
     pub paused_time: f32,
+    // Seconds added to the GPU thread's clock by seeking and pausing
+    pub time_offset: f32,
+    // Speed of the GPU thread's clock, see utils::timeline
+    pub time_scale: f32,
     pub should_reload_shader: bool,
-    pub new_shader_source: Option<String>,
+    pub new_shader_project: Option<ShaderProject>,
+    // Start reloaded shaders over at time 0 with zeroed state (--reset-on-reload, T)
+    pub reset_on_reload: bool,
+    // Latest terminal size in cells, applied by the GPU thread before its next frame
+    pub pending_resize: Option<(u32, u32)>,
+    // Fraction of the resolution rendered on top of --scale, see utils::adaptive
+    pub adaptive_scale: f32,
+    // Latest size of the --mirror window in pixels and whether it was closed, from the main thread
+    pub pending_mirror_resize: Option<(u32, u32)>,
+    pub mirror_closed: bool,
+    // Asks a GPU thread that stopped after repeated errors to try again (G)
+    pub restart_requested: bool,
+    // --texture images to rebind as (index, image), applied before the next frame
+    pub pending_textures: Vec<(usize, Arc<RgbaImage>)>,
+    // Bumped by input that changes what the shader draws (cursor, mouse, parameters), restarting
+    // --accumulate
+    pub scene_generation: u64,
+    // Bumped on every input change so frames can be matched to the input they reflect
+    pub input_generation: u64,
+    pub last_input_at: Option<Instant>,
 }
 
 impl SharedUniforms {
-    pub fn new() -> Self {
+    pub fn new(params: &[UniformParam]) -> Self {
         Self {
             cursor: [0, 0],
+            mouse: MouseState::default(),
+            param_values: ParamValues::new(params),
+            zoom: None,
+            supersample: false,
             time_paused: false,
             paused_time: 0.0,
+            time_offset: 0.0,
+            time_scale: 1.0,
             should_reload_shader: false,
-            new_shader_source: None,
+            new_shader_project: None,
+            reset_on_reload: false,
+            pending_resize: None,
+            adaptive_scale: 1.0,
+            pending_mirror_resize: None,
+            mirror_closed: false,
+            restart_requested: false,
+            pending_textures: Vec::new(),
+            scene_generation: 0,
+            input_generation: 0,
+            last_input_at: None,
         }
     }
 
     pub fn move_cursor(&mut self, dx: i32, dy: i32) {
         self.cursor[0] += dx;
         self.cursor[1] += dy;
+        self.scene_generation += 1;
+        self.record_input(|uniforms| format!("cursor {:?}", uniforms.cursor));
     }
 
+    // AIDEV-NOTE: Mouse input arrives in GPU pixels (Y=0 at bottom) and also moves the cursor
+    pub fn mouse_press(&mut self, position: [i32; 2]) {
+        self.cursor = position;
+        self.mouse.press(position.map(|c| c as f32));
+        self.scene_generation += 1;
+        self.record_input(|_| format!("mouse press {position:?}"));
+    }
+
+    pub fn mouse_drag(&mut self, position: [i32; 2]) {
+        self.cursor = position;
+        self.mouse.drag(position.map(|c| c as f32));
+        self.scene_generation += 1;
+        self.record_input(|_| format!("mouse drag {position:?}"));
+    }
+
+    pub fn mouse_release(&mut self) {
+        self.mouse.release();
+        self.scene_generation += 1;
+        self.record_input(|_| "mouse release".to_string());
+    }
+
+    pub fn toggle_supersample(&mut self) -> bool {
+        self.supersample = !self.supersample;
+        self.scene_generation += 1;
+        self.record_input(|uniforms| format!("supersample {}", uniforms.supersample));
+        self.supersample
+    }
+
+    pub fn set_zoom(&mut self, zoom: Option<[f32; 4]>) {
+        self.zoom = zoom;
+        self.scene_generation += 1;
+        self.record_input(|_| format!("zoom {zoom:?}"));
+    }
+
+    // AIDEV-NOTE: Tab and +/- tweak `// @uniform` parameters; both return a label to show
+    pub fn select_next_param(&mut self) -> Option<String> {
+        self.param_values.select_next()
+    }
+
+    pub fn select_previous_param(&mut self) -> Option<String> {
+        self.param_values.select_previous()
+    }
+
+    pub fn adjust_param(&mut self, steps: i32) -> Option<String> {
+        let label = self.param_values.adjust(steps)?;
+        self.scene_generation += 1;
+        self.record_input(|_| format!("param {label}"));
+        Some(label)
+    }
+
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        self.param_values.set(name, value);
+        self.scene_generation += 1;
+        self.record_input(|_| format!("param {name} = {value}"));
+    }
+
+    // AIDEV-NOTE: `current_time` is the caller's seconds since start, which the GPU thread's clock
+    // tracks as `current_time * time_scale + time_offset`; resuming shifts the clock so the shader
+    // continues from where it was paused
     pub fn toggle_pause(&mut self, current_time: f32) {
         if self.time_paused {
             self.time_paused = false;
+            self.time_offset = self.paused_time - current_time * self.time_scale;
         } else {
             self.time_paused = true;
-            self.paused_time = current_time;
+            self.paused_time = (current_time * self.time_scale + self.time_offset).max(0.0);
+        }
+        self.record_input(|uniforms| {
+            format!(
+                "paused {} at {current_time:.4} (paused_time {:.4}, time_offset {:.4})",
+                uniforms.time_paused, uniforms.paused_time, uniforms.time_offset
+            )
+        });
+    }
+
+    pub fn set_paused(&mut self, paused: bool, current_time: f32) {
+        if paused != self.time_paused {
+            self.toggle_pause(current_time);
+        }
+    }
+
+    // The shader's time at `current_time`, before --loop wraps it
+    pub fn shader_time(&self, current_time: f32) -> f32 {
+        if self.time_paused {
+            self.paused_time
+        } else {
+            (current_time * self.time_scale + self.time_offset).max(0.0)
         }
     }
 
-    pub fn request_shader_reload(&mut self, shader_source: String) {
+    // Move the shader's time to `seconds`, keeping the clock paused if it was
+    pub fn set_time(&mut self, seconds: f32, current_time: f32) {
+        self.seek(seconds - self.shader_time(current_time), current_time);
+    }
+
+    // The shader's time never goes below zero
+    pub fn seek(&mut self, seconds: f32, current_time: f32) {
+        self.scene_generation += 1;
+        if self.time_paused {
+            self.paused_time = (self.paused_time + seconds).max(0.0);
+        } else {
+            self.time_offset = (self.time_offset + seconds).max(-current_time * self.time_scale);
+        }
+        self.record_input(|uniforms| {
+            format!(
+                "seek {seconds:+.4} at {current_time:.4} (paused_time {:.4}, time_offset {:.4})",
+                uniforms.paused_time, uniforms.time_offset
+            )
+        });
+    }
+
+    // Pause and move `frames` frames of timeline::FRAME_STEP
+    pub fn step_frames(&mut self, frames: i32, current_time: f32) {
+        self.set_paused(true, current_time);
+        self.seek(frames as f32 * FRAME_STEP, current_time);
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32, current_time: f32) {
+        // The shader's time stays where it is and continues at the new speed
+        self.time_offset += current_time * (self.time_scale - time_scale);
+        self.time_scale = time_scale;
+        self.record_input(|uniforms| {
+            format!(
+                "time scale {time_scale} at {current_time:.4} (time_offset {:.4})",
+                uniforms.time_offset
+            )
+        });
+    }
+
+    // Back to time 0, keeping the clock paused if it was
+    pub fn reset_clock(&mut self, current_time: f32) {
+        self.scene_generation += 1;
+        if self.time_paused {
+            self.paused_time = 0.0;
+        } else {
+            self.time_offset = -current_time * self.time_scale;
+        }
+        self.record_input(|_| format!("clock reset at {current_time:.4}"));
+    }
+
+    pub fn toggle_reset_on_reload(&mut self) -> bool {
+        self.reset_on_reload = !self.reset_on_reload;
+        trace::record(|| TraceEvent::Mutation {
+            change: format!("reset on reload {}", self.reset_on_reload),
+        });
+        self.reset_on_reload
+    }
+
+    // `change` describes the new state for --trace
+    fn record_input(&mut self, change: impl FnOnce(&Self) -> String) {
+        self.input_generation += 1;
+        self.last_input_at = Some(Instant::now());
+        trace::record(|| TraceEvent::Mutation {
+            change: format!("{} (input {})", change(self), self.input_generation),
+        });
+    }
+
+    pub fn request_shader_reload(&mut self, project: ShaderProject) {
+        self.param_values.set_params(&project.params);
         self.should_reload_shader = true;
-        self.new_shader_source = Some(shader_source);
+        self.new_shader_project = Some(project);
+        trace::record(|| TraceEvent::Mutation {
+            change: "reload requested".to_string(),
+        });
+    }
+
+    pub fn request_resize(&mut self, width: u32, height: u32) {
+        self.pending_resize = Some((width, height));
+        trace::record(|| TraceEvent::Mutation {
+            change: format!("resize requested: {width}x{height}"),
+        });
+    }
+
+    pub fn request_mirror_resize(&mut self, width: u32, height: u32) {
+        self.pending_mirror_resize = Some((width, height));
+        trace::record(|| TraceEvent::Mutation {
+            change: format!("mirror resize requested: {width}x{height}"),
+        });
+    }
+
+    pub fn close_mirror(&mut self) {
+        self.mirror_closed = true;
+        trace::record(|| TraceEvent::Mutation {
+            change: "mirror closed".to_string(),
+        });
+    }
+
+    pub fn set_adaptive_scale(&mut self, scale: f32) {
+        self.adaptive_scale = scale;
+        trace::record(|| TraceEvent::Mutation {
+            change: format!("adaptive scale {scale}"),
+        });
+    }
+
+    pub fn request_restart(&mut self) {
+        self.restart_requested = true;
+        trace::record(|| TraceEvent::Mutation {
+            change: "restart requested".to_string(),
+        });
+    }
+
+    pub fn consume_restart(&mut self) -> bool {
+        std::mem::take(&mut self.restart_requested)
     }
 
-    pub fn consume_shader_reload(&mut self) -> Option<String> {
+    pub fn request_texture(&mut self, index: usize, image: Arc<RgbaImage>) {
+        self.pending_textures.push((index, image));
+        trace::record(|| TraceEvent::Mutation {
+            change: format!("texture {index} requested"),
+        });
+    }
+
+    pub fn consume_textures(&mut self) -> Vec<(usize, Arc<RgbaImage>)> {
+        std::mem::take(&mut self.pending_textures)
+    }
+
+    pub fn consume_resize(&mut self) -> Option<(u32, u32)> {
+        self.pending_resize.take()
+    }
+
+    pub fn consume_mirror_resize(&mut self) -> Option<(u32, u32)> {
+        self.pending_mirror_resize.take()
+    }
+
+    pub fn consume_shader_reload(&mut self) -> Option<ShaderProject> {
         if self.should_reload_shader {
             self.should_reload_shader = false;
-            self.new_shader_source.take()
+            self.new_shader_project.take()
         } else {
             None
         }
@@ -104,21 +459,53 @@ pub type SharedFrameBufferHandle = Arc<Mutex<SharedFrameBuffer>>;
 pub type SharedUniformsHandle = Arc<Mutex<SharedUniforms>>;
 
 // AIDEV-NOTE: Error types for thread communication
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ThreadError {
     ShaderCompilationError(String),
+    // A reload is compiling in the background; the old shader renders until ShaderReloadSuccess
+    ShaderCompiling,
     ShaderReloadSuccess,
     GpuError(String),
+    // Rendering resumed on a new device after the old one was lost
+    DeviceRecovered,
+    // The GPU thread gave up after repeated errors and waits for a restart request
+    RenderingStopped(String),
     Shutdown,
 }
 
 pub type ErrorSender = std::sync::mpsc::Sender<ThreadError>;
 pub type ErrorReceiver = std::sync::mpsc::Receiver<ThreadError>;
 
+// Sends `error` to every receiver, noting it once in the --trace file
+pub fn send_error(senders: &[&ErrorSender], error: ThreadError) {
+    trace::record(|| TraceEvent::Error {
+        error: error.clone(),
+    });
+    for sender in senders {
+        let _ = sender.send(error.clone());
+    }
+}
+
+// Frame durations kept for the --perf sparkline and percentiles
+const FRAME_DURATION_HISTORY: usize = 240;
+// Sparkline levels, lowest first
+const SPARK_GLYPHS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+// Percentiles of recent frame durations, with a sparkline of the newest ones
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameTimeStats {
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub sparkline: String,
+}
+
 // AIDEV-NOTE: Performance monitoring for FPS and frame drop tracking
 #[derive(Debug)]
 pub struct PerformanceTracker {
     frame_times: VecDeque<Instant>,
+    // Time between consecutive frames, newest last
+    frame_durations: VecDeque<Duration>,
     last_fps_calculation: Instant,
     current_fps: f32,
     total_frames_rendered: u64,
@@ -129,6 +516,7 @@ impl PerformanceTracker {
     pub fn new() -> Self {
         Self {
             frame_times: VecDeque::new(),
+            frame_durations: VecDeque::new(),
             last_fps_calculation: Instant::now(),
             current_fps: 0.0,
             total_frames_rendered: 0,
@@ -139,6 +527,12 @@ impl PerformanceTracker {
     // AIDEV-NOTE: Record a new frame render completion
     pub fn record_frame(&mut self) {
         let now = Instant::now();
+        if let Some(&previous) = self.frame_times.back() {
+            if self.frame_durations.len() == FRAME_DURATION_HISTORY {
+                self.frame_durations.pop_front();
+            }
+            self.frame_durations.push_back(now - previous);
+        }
         self.frame_times.push_back(now);
         self.total_frames_rendered += 1;
 
@@ -175,6 +569,45 @@ impl PerformanceTracker {
     pub fn get_fps(&self) -> f32 {
         self.current_fps
     }
+
+    // None before the second frame. The sparkline shows the newest `sparkline_width` frames
+    pub fn frame_time_stats(&self, sparkline_width: usize) -> Option<FrameTimeStats> {
+        if self.frame_durations.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = self
+            .frame_durations
+            .iter()
+            .map(Duration::as_secs_f64)
+            .collect();
+        sorted.sort_by(f64::total_cmp);
+        let newest: Vec<f64> = self
+            .frame_durations
+            .iter()
+            .skip(self.frame_durations.len().saturating_sub(sparkline_width))
+            .map(Duration::as_secs_f64)
+            .collect();
+        Some(FrameTimeStats {
+            p50: Duration::from_secs_f64(percentile(&sorted, 50.0)),
+            p95: Duration::from_secs_f64(percentile(&sorted, 95.0)),
+            p99: Duration::from_secs_f64(percentile(&sorted, 99.0)),
+            sparkline: sparkline(&newest),
+        })
+    }
+}
+
+// AIDEV-NOTE: One glyph per value, scaled so the largest fills the cell; a flat line of small
+// values stays low instead of stretching noise over the whole height
+fn sparkline(values: &[f64]) -> String {
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|&value| {
+            let level = if max > 0.0 { value / max } else { 0.0 };
+            let index = (level * (SPARK_GLYPHS.len() - 1) as f64).round() as usize;
+            SPARK_GLYPHS[index.min(SPARK_GLYPHS.len() - 1)]
+        })
+        .collect()
 }
 
 // AIDEV-NOTE: Combined performance tracking for both GPU and Terminal rendering
@@ -182,6 +615,8 @@ impl PerformanceTracker {
 pub struct DualPerformanceTracker {
     pub gpu_tracker: PerformanceTracker,
     pub terminal_tracker: PerformanceTracker,
+    // GPU time of a recent frame from timestamp queries, None where the adapter has none
+    pub gpu_time: Option<Duration>,
 }
 
 impl DualPerformanceTracker {
@@ -189,6 +624,7 @@ impl DualPerformanceTracker {
         Self {
             gpu_tracker: PerformanceTracker::new(),
             terminal_tracker: PerformanceTracker::new(),
+            gpu_time: None,
         }
     }
 
@@ -207,6 +643,65 @@ impl DualPerformanceTracker {
     pub fn get_terminal_fps(&self) -> f32 {
         self.terminal_tracker.get_fps()
     }
+
+    // Of the frames drawn to the terminal, which is what stutter looks like
+    pub fn terminal_frame_time_stats(&self, sparkline_width: usize) -> Option<FrameTimeStats> {
+        self.terminal_tracker.frame_time_stats(sparkline_width)
+    }
 }
 
 pub type DualPerformanceTrackerHandle = Arc<Mutex<DualPerformanceTracker>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_time_stats() {
+        assert_eq!(sparkline(&[0.0, 4.0, 8.0]), "▁▅█");
+        assert_eq!(sparkline(&[0.0, 0.0]), "▁▁");
+
+        let mut tracker = PerformanceTracker::new();
+        assert_eq!(tracker.frame_time_stats(4), None);
+        tracker
+            .frame_durations
+            .extend((1..=100).map(|ms| Duration::from_millis(if ms == 100 { 50 } else { 10 })));
+        let stats = tracker.frame_time_stats(3).unwrap();
+        assert_eq!(stats.p50, Duration::from_millis(10));
+        assert_eq!(stats.p99, Duration::from_millis(10));
+        assert_eq!(stats.sparkline, "▂▂█");
+    }
+
+    #[test]
+    fn test_reset_clock() {
+        let mut uniforms = SharedUniforms::new(&[]);
+        uniforms.seek(3.0, 5.0);
+        uniforms.reset_clock(5.0);
+        assert_eq!(uniforms.time_offset, -5.0);
+
+        uniforms.toggle_pause(7.0);
+        assert_eq!(uniforms.paused_time, 2.0);
+        uniforms.reset_clock(7.0);
+        assert_eq!(uniforms.paused_time, 0.0);
+
+        uniforms.set_time(4.0, 7.0);
+        assert_eq!(uniforms.paused_time, 4.0);
+        uniforms.toggle_pause(7.0);
+        assert_eq!(uniforms.shader_time(9.0), 6.0);
+    }
+
+    #[test]
+    fn test_time_scale() {
+        let mut uniforms = SharedUniforms::new(&[]);
+        let time = |uniforms: &SharedUniforms, current_time: f32| {
+            current_time * uniforms.time_scale + uniforms.time_offset
+        };
+        uniforms.set_time_scale(2.0, 4.0);
+        assert_eq!(time(&uniforms, 4.0), 4.0);
+        assert_eq!(time(&uniforms, 5.0), 6.0);
+
+        uniforms.step_frames(-3, 5.0);
+        assert!(uniforms.time_paused);
+        assert_eq!(uniforms.paused_time, 6.0 - 3.0 * FRAME_STEP);
+    }
+}
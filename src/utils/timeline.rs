@@ -0,0 +1,29 @@
+use crate::renderers::headless_renderer::EXPORT_FPS;
+
+// AIDEV-NOTE: Scrubbing keys shared by the terminal and the window: , and . pause and step one
+// frame of the export timestep, [ and ] halve and double the clock's speed, 0 goes back to time 0.
+// Both clocks compute `elapsed * time_scale + offset`, rebasing the offset whenever the scale
+// changes so the shader's time doesn't jump
+pub const FRAME_STEP: f32 = 1.0 / EXPORT_FPS;
+
+const MIN_TIME_SCALE: f32 = 1.0 / 16.0;
+const MAX_TIME_SCALE: f32 = 16.0;
+
+// The speed after pressing ] (`faster`) or [
+pub fn step_time_scale(time_scale: f32, faster: bool) -> f32 {
+    let factor = if faster { 2.0 } else { 0.5 };
+    (time_scale * factor).clamp(MIN_TIME_SCALE, MAX_TIME_SCALE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_time_scale() {
+        assert_eq!(step_time_scale(1.0, true), 2.0);
+        assert_eq!(step_time_scale(1.0, false), 0.5);
+        assert_eq!(step_time_scale(MAX_TIME_SCALE, true), MAX_TIME_SCALE);
+        assert_eq!(step_time_scale(MIN_TIME_SCALE, false), MIN_TIME_SCALE);
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::gpu::Uniforms;
+use crate::utils::threading::ThreadError;
+use crate::utils::uniform_params::ParamValues;
+
+// AIDEV-NOTE: --trace FILE logs every message crossing the GPU/terminal thread boundary as JSON
+// lines: SharedUniforms mutations, what the GPU thread applied and rendered, frame buffer
+// writes/drops/reads and ThreadErrors, each with seconds since the trace started and the thread.
+// Set once at startup like gpu::device's adapter selection; record() costs one atomic load when
+// tracing is off. `shadertui replay` reads the file back (commands/replay.rs)
+struct Tracer {
+    file: Mutex<File>,
+    start: Instant,
+}
+
+static TRACER: OnceLock<Tracer> = OnceLock::new();
+
+pub fn start(path: &Path, shader: &Path) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Could not create trace file '{}': {e}", path.display()))?;
+    let _ = TRACER.set(Tracer {
+        file: Mutex::new(file),
+        start: Instant::now(),
+    });
+    record(|| TraceEvent::Start {
+        shader: shader.to_path_buf(),
+    });
+    Ok(())
+}
+
+// The event is only built when tracing is on
+pub fn record(event: impl FnOnce() -> TraceEvent) {
+    let Some(tracer) = TRACER.get() else {
+        return;
+    };
+    let record = TraceRecord {
+        t: tracer.start.elapsed().as_secs_f64(),
+        thread: std::thread::current()
+            .name()
+            .unwrap_or("unnamed")
+            .to_string(),
+        event: event(),
+    };
+    let Ok(mut line) = serde_json::to_string(&record) else {
+        return;
+    };
+    line.push('\n');
+    // One write per line, so a crash loses at most the event being written
+    let _ = tracer.file.lock().unwrap().write_all(line.as_bytes());
+}
+
+pub fn read(path: &Path) -> Result<Vec<TraceRecord>, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Could not open trace file '{}': {e}", path.display()))?;
+    let mut records = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Could not read '{}': {e}", path.display()))?;
+        // A trace cut off by a crash ends in a partial line
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(_) if line.trim().is_empty() => {}
+            Err(e) => {
+                eprintln!(
+                    "Warning: skipping line {} of '{}': {e}",
+                    index + 1,
+                    path.display()
+                );
+            }
+        }
+    }
+    Ok(records)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceRecord {
+    // Seconds since the trace started
+    pub t: f64,
+    pub thread: String,
+    #[serde(flatten)]
+    pub event: TraceEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    Start { shader: PathBuf },
+    // A SharedUniforms change, e.g. "move_cursor [40, 12]"
+    Mutation { change: String },
+    // What the GPU thread took from SharedUniforms
+    Reload,
+    Resize { width: u32, height: u32 },
+    Texture { index: usize },
+    // Uniforms the GPU thread computed a frame with
+    Frame(FrameUniforms),
+    // Written to the frame buffer, replacing `dropped` if the terminal hadn't taken it yet
+    FrameWritten { frame: u32, dropped: Option<u32> },
+    // Computed without a readback
+    FrameSkipped,
+    // Taken from the frame buffer by the terminal thread
+    FrameRead { frame: u32 },
+    Error { error: ThreadError },
+}
+
+// AIDEV-NOTE: Everything a frame's GPU uniforms are built from. `params` holds the values set by
+// name (unset ones use their defaults), since the packed uniform depends on the declarations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameUniforms {
+    pub frame: u32,
+    pub time: f32,
+    pub delta_time: f32,
+    pub resolution: [f32; 2],
+    pub cursor: [f32; 2],
+    pub mouse: [f32; 4],
+    pub zoom: [f32; 4],
+    pub subsamples: u32,
+    pub sample_index: u32,
+    pub params: BTreeMap<String, f32>,
+    pub readback: bool,
+}
+
+impl FrameUniforms {
+    pub fn new(uniforms: &Uniforms, params: &ParamValues, readback: bool) -> Self {
+        Self {
+            frame: uniforms.frame,
+            time: uniforms.time,
+            delta_time: uniforms.delta_time,
+            resolution: uniforms.resolution,
+            cursor: uniforms.cursor,
+            mouse: uniforms.mouse,
+            zoom: uniforms.zoom,
+            subsamples: uniforms.subsamples,
+            sample_index: uniforms.sample_index,
+            params: params.assignments(),
+            readback,
+        }
+    }
+
+    pub fn uniforms(&self) -> Uniforms {
+        Uniforms {
+            resolution: self.resolution,
+            cursor: self.cursor,
+            time: self.time,
+            frame: self.frame,
+            delta_time: self.delta_time,
+            sample_index: self.sample_index,
+            mouse: self.mouse,
+            zoom: self.zoom,
+            subsamples: self.subsamples,
+            ..Uniforms::new(0, 0, 0.0, [0, 0], 0, 0.0)
+        }
+    }
+
+    pub fn param_values(&self) -> ParamValues {
+        let mut values = ParamValues::default();
+        for (name, &value) in &self.params {
+            values.set(name, value);
+        }
+        values
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.resolution[0] as u32, self.resolution[1] as u32)
+    }
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TraceEvent::Start { shader } => write!(f, "trace of {}", shader.display()),
+            TraceEvent::Mutation { change } => write!(f, "set {change}"),
+            TraceEvent::Reload => write!(f, "reload applied"),
+            TraceEvent::Resize { width, height } => write!(f, "resize applied: {width}x{height}"),
+            TraceEvent::Texture { index } => write!(f, "texture {index} applied"),
+            TraceEvent::Frame(uniforms) => {
+                let (width, height) = uniforms.size();
+                write!(
+                    f,
+                    "frame {} t={:.4} dt={:.4} {width}x{height} cursor={:?} mouse={:?}",
+                    uniforms.frame,
+                    uniforms.time,
+                    uniforms.delta_time,
+                    uniforms.cursor,
+                    uniforms.mouse
+                )?;
+                if uniforms.zoom != crate::gpu::IDENTITY_ZOOM {
+                    write!(f, " zoom={:?}", uniforms.zoom)?;
+                }
+                if !uniforms.params.is_empty() {
+                    write!(f, " params={:?}", uniforms.params)?;
+                }
+                if !uniforms.readback {
+                    write!(f, " (no readback)")?;
+                }
+                Ok(())
+            }
+            TraceEvent::FrameWritten {
+                frame,
+                dropped: Some(dropped),
+            } => write!(f, "frame {frame} written, dropping unread frame {dropped}"),
+            TraceEvent::FrameWritten {
+                frame,
+                dropped: None,
+            } => write!(f, "frame {frame} written"),
+            TraceEvent::FrameSkipped => write!(f, "frame skipped"),
+            TraceEvent::FrameRead { frame } => write!(f, "frame {frame} read"),
+            TraceEvent::Error { error } => write!(f, "sent {error:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_record_round_trip() {
+        let mut params = ParamValues::default();
+        params.set("speed", 2.5);
+        let uniforms = Uniforms::new(80, 48, 1.25, [40, 24], 75, 0.016);
+        let record = TraceRecord {
+            t: 0.5,
+            thread: "gpu".to_string(),
+            event: TraceEvent::Frame(FrameUniforms::new(&uniforms, &params, true)),
+        };
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(line.starts_with(r#"{"t":0.5,"thread":"gpu","event":"frame","frame":75,"#));
+        let parsed: TraceRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed, record);
+
+        let TraceEvent::Frame(frame) = parsed.event else {
+            unreachable!()
+        };
+        assert_eq!(frame.size(), (80, 48));
+        assert_eq!(frame.uniforms().cursor, [40.0, 24.0]);
+        assert_eq!(frame.param_values().assignments(), params.assignments());
+
+        let error: TraceRecord = serde_json::from_str(
+            r#"{"t":1.0,"thread":"terminal","event":"error","error":"Shutdown"}"#,
+        )
+        .unwrap();
+        assert_eq!(error.event.to_string(), "sent Shutdown");
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::utils::lfo;
+
+const UNIFORM_PRAGMA: &str = "// @uniform ";
+// AIDEV-NOTE: Parameters live in fixed slots after the built-in fields of the Uniforms buffer (see
+// gpu::uniforms), so the buffer never has to be reallocated when a reload changes them
+pub const MAX_PARAMS: usize = 16;
+// Cells of a slider in the terminal's parameter panel
+const SLIDER_WIDTH: usize = 16;
+// Fields of the built-in Uniforms struct, which a parameter can't shadow
+const RESERVED_NAMES: [&str; 9] = [
+    "resolution",
+    "cursor",
+    "time",
+    "frame",
+    "delta_time",
+    "sample_index",
+    "mouse",
+    "zoom",
+    "subsamples",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamType {
+    F32,
+    I32,
+    U32,
+}
+
+impl ParamType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "f32" => Some(ParamType::F32),
+            "i32" => Some(ParamType::I32),
+            "u32" => Some(ParamType::U32),
+            _ => None,
+        }
+    }
+
+    pub fn wgsl_name(self) -> &'static str {
+        match self {
+            ParamType::F32 => "f32",
+            ParamType::I32 => "i32",
+            ParamType::U32 => "u32",
+        }
+    }
+}
+
+// AIDEV-NOTE: A tweakable value declared in a shader, read there as `uniforms.<name>`:
+//   // @uniform speed: f32 = 1.0 (0.0 .. 10.0)
+// The default (0 or the range's minimum if omitted) and the range are optional. Values are kept as
+// f32 on the CPU and converted to the declared type when packed into the uniform buffer
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniformParam {
+    pub name: String,
+    pub ty: ParamType,
+    pub default: f32,
+    pub range: Option<(f32, f32)>,
+}
+
+impl UniformParam {
+    // A hundredth of the range per key press, or 0.1 without one; integers move by 1
+    pub fn step(&self) -> f32 {
+        match (self.ty, self.range) {
+            (ParamType::F32, Some((min, max))) => (max - min) / 100.0,
+            (ParamType::F32, None) => 0.1,
+            _ => 1.0,
+        }
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        let value = match self.range {
+            Some((min, max)) => value.clamp(min, max),
+            None => value,
+        };
+        match self.ty {
+            ParamType::F32 => value,
+            ParamType::I32 => value.round(),
+            ParamType::U32 => value.round().max(0.0),
+        }
+    }
+
+    // The bits of `value` as the shader reads them
+    pub fn to_bits(&self, value: f32) -> u32 {
+        match self.ty {
+            ParamType::F32 => value.to_bits(),
+            ParamType::I32 => (value as i32) as u32,
+            ParamType::U32 => value as u32,
+        }
+    }
+
+    pub fn format_value(&self, value: f32) -> String {
+        match self.ty {
+            ParamType::F32 => format!("{value:.3}"),
+            _ => format!("{value}"),
+        }
+    }
+}
+
+// AIDEV-NOTE: Every `// @uniform` line of the given pass sources. A parameter may be declared in
+// several passes (e.g. in code shared by `// @pass` sections) as long as the declarations agree
+pub fn parse_params<'a>(
+    sources: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<UniformParam>, String> {
+    let mut params: Vec<UniformParam> = Vec::new();
+    for source in sources {
+        for line in source.lines() {
+            let Some(declaration) = line.trim().strip_prefix(UNIFORM_PRAGMA) else {
+                continue;
+            };
+            let param = parse_param(declaration.trim())
+                .map_err(|message| format!("'{}': {message}", line.trim()))?;
+            match params.iter().find(|existing| existing.name == param.name) {
+                Some(existing) if *existing == param => {}
+                Some(_) => {
+                    return Err(format!(
+                        "uniform '{}' is declared twice with different settings",
+                        param.name
+                    ))
+                }
+                None => params.push(param),
+            }
+        }
+    }
+    if params.len() > MAX_PARAMS {
+        return Err(format!(
+            "at most {MAX_PARAMS} @uniform parameters are supported"
+        ));
+    }
+    Ok(params)
+}
+
+fn parse_param(declaration: &str) -> Result<UniformParam, String> {
+    let (declaration, range) = match declaration.split_once('(') {
+        Some((declaration, range)) => {
+            let range = range
+                .strip_suffix(')')
+                .ok_or("the range must end with ')'")?;
+            let (min, max) = range
+                .split_once("..")
+                .ok_or("the range must look like (min .. max)")?;
+            let (min, max) = (parse_number(min)?, parse_number(max)?);
+            if min > max {
+                return Err("the range minimum is larger than its maximum".to_string());
+            }
+            (declaration, Some((min, max)))
+        }
+        None => (declaration, None),
+    };
+    let (declaration, default) = match declaration.split_once('=') {
+        Some((declaration, default)) => (declaration, Some(parse_number(default)?)),
+        None => (declaration, None),
+    };
+    let (name, ty) = declaration.split_once(':').ok_or("expected 'name: type'")?;
+    let (name, ty) = (name.trim(), ty.trim());
+
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_identifier {
+        return Err(format!("'{name}' is not a valid name"));
+    }
+    if RESERVED_NAMES.contains(&name) {
+        return Err(format!("'{name}' is a built-in uniform"));
+    }
+    let ty =
+        ParamType::parse(ty).ok_or(format!("unsupported type '{ty}' (use f32, i32 or u32)"))?;
+
+    let mut param = UniformParam {
+        name: name.to_string(),
+        ty,
+        default: 0.0,
+        range,
+    };
+    param.default = param.clamp(default.or(range.map(|(min, _)| min)).unwrap_or(0.0));
+    Ok(param)
+}
+
+fn parse_number(text: &str) -> Result<f32, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", text.trim()))
+}
+
+// Fields appended to the prelude's Uniforms struct
+pub fn struct_fields(params: &[UniformParam]) -> String {
+    params
+        .iter()
+        .map(|param| format!("    {}: {},\n", param.name, param.ty.wgsl_name()))
+        .collect()
+}
+
+// AIDEV-NOTE: Current parameter values, tweaked at runtime with Tab (next parameter) and +/-.
+// Values are kept by name, so they survive reloads that keep the declaration, and are looked up
+// against whichever declarations the renderer compiled
+#[derive(Debug, Clone, Default)]
+pub struct ParamValues {
+    params: Vec<UniformParam>,
+    values: HashMap<String, f32>,
+    selected: usize,
+}
+
+impl ParamValues {
+    pub fn new(params: &[UniformParam]) -> Self {
+        let mut values = Self::default();
+        values.set_params(params);
+        values
+    }
+
+    // Declarations of a newly loaded shader
+    pub fn set_params(&mut self, params: &[UniformParam]) {
+        self.params = params.to_vec();
+        if self.selected >= self.params.len() {
+            self.selected = 0;
+        }
+    }
+
+    pub fn value(&self, param: &UniformParam) -> f32 {
+        self.values
+            .get(&param.name)
+            .map_or(param.default, |&value| param.clamp(value))
+    }
+
+    // Selects the next parameter and describes it, None without parameters
+    pub fn select_next(&mut self) -> Option<String> {
+        self.select(1)
+    }
+
+    pub fn select_previous(&mut self) -> Option<String> {
+        self.select(self.params.len().saturating_sub(1))
+    }
+
+    fn select(&mut self, offset: usize) -> Option<String> {
+        if self.params.is_empty() {
+            return None;
+        }
+        self.selected = (self.selected + offset) % self.params.len();
+        self.selected_label()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    // AIDEV-NOTE: One line per parameter for the terminal's parameter panel: the name, a slider
+    // across the range (blank without one) and the value, padded to a common width
+    pub fn panel_lines(&self) -> Vec<String> {
+        let name_width = self.params.iter().map(|p| p.name.len()).max().unwrap_or(0);
+        let lines: Vec<String> = self
+            .params
+            .iter()
+            .map(|param| {
+                let value = self.value(param);
+                let slider = match param.range {
+                    Some((min, max)) => {
+                        let fraction = if max > min {
+                            (value - min) / (max - min)
+                        } else {
+                            1.0
+                        };
+                        let filled = (fraction * SLIDER_WIDTH as f32).round() as usize;
+                        format!(
+                            "{}{}",
+                            "█".repeat(filled),
+                            "░".repeat(SLIDER_WIDTH - filled)
+                        )
+                    }
+                    None => " ".repeat(SLIDER_WIDTH),
+                };
+                format!(
+                    " {:<name_width$} {slider} {} ",
+                    param.name,
+                    param.format_value(value)
+                )
+            })
+            .collect();
+        let width = lines.iter().map(|line| line.chars().count()).max();
+        lines
+            .into_iter()
+            .map(|line| {
+                let padding = width.unwrap_or(0) - line.chars().count();
+                line + &" ".repeat(padding)
+            })
+            .collect()
+    }
+
+    // Sets a parameter by name, even one the current shader doesn't declare, so it applies to a
+    // shader loaded afterwards
+    pub fn set(&mut self, name: &str, value: f32) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    // Sets the parameters bound with --lfo to their value at shader time `time`
+    pub fn animate(&mut self, time: f32) {
+        for binding in lfo::bindings() {
+            if let Some(param) = self.params.iter().find(|p| p.name == binding.param) {
+                let value = binding.value(time, param.range);
+                self.values.insert(param.name.clone(), value);
+            }
+        }
+    }
+
+    // Values set by name, which is what a --trace records
+    pub fn assignments(&self) -> BTreeMap<String, f32> {
+        self.values
+            .iter()
+            .map(|(name, &value)| (name.clone(), value))
+            .collect()
+    }
+
+    // Moves the selected parameter by `steps` steps and describes it
+    pub fn adjust(&mut self, steps: i32) -> Option<String> {
+        let param = self.params.get(self.selected)?;
+        let value = param.clamp(self.value(param) + steps as f32 * param.step());
+        self.values.insert(param.name.clone(), value);
+        self.selected_label()
+    }
+
+    fn selected_label(&self) -> Option<String> {
+        let param = self.params.get(self.selected)?;
+        Some(format!(
+            "{} = {}",
+            param.name,
+            param.format_value(self.value(param))
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_params() {
+        let source = "// @uniform speed: f32 = 1.0 (0.0 .. 10.0)\n\
+                      // @uniform count: u32\n\
+                      fn compute_color(coords: vec2<f32>) -> vec3<f32> {}\n";
+        let params = parse_params([source, "// @uniform count: u32"]).unwrap();
+        assert_eq!(
+            params,
+            vec![
+                UniformParam {
+                    name: "speed".to_string(),
+                    ty: ParamType::F32,
+                    default: 1.0,
+                    range: Some((0.0, 10.0)),
+                },
+                UniformParam {
+                    name: "count".to_string(),
+                    ty: ParamType::U32,
+                    default: 0.0,
+                    range: None,
+                },
+            ]
+        );
+        assert_eq!(struct_fields(&params), "    speed: f32,\n    count: u32,\n");
+
+        assert!(parse_params(["// @uniform time: f32"]).is_err());
+        assert!(parse_params(["// @uniform speed: vec2<f32>"]).is_err());
+        assert!(parse_params(["// @uniform a: f32 = 1", "// @uniform a: f32 = 2"]).is_err());
+
+        // Adjusting stays in range and keeps values by name across new declarations
+        let mut values = ParamValues::new(&params);
+        assert_eq!(values.adjust(-20).as_deref(), Some("speed = 0.000"));
+        assert_eq!(values.adjust(20).as_deref(), Some("speed = 2.000"));
+        assert_eq!(
+            values.panel_lines(),
+            vec![
+                " speed ███░░░░░░░░░░░░░ 2.000 ",
+                " count                  0     ",
+            ]
+        );
+        assert_eq!(values.select_previous().as_deref(), Some("count = 0"));
+        assert_eq!(values.selected(), 1);
+        values.set_params(&params[..1]);
+        assert_eq!(values.value(&params[0]), 2.0);
+        assert_eq!(values.value(&params[1]), 0.0);
+    }
+}
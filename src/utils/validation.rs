@@ -1,29 +1,259 @@
+use std::path::{Path, PathBuf};
+
+use naga::SourceLocation;
+use serde::Serialize;
+
+use crate::error::{ShadertuiError, SourceSpan};
+use crate::utils::project::{PassSource, ShaderProject};
+use crate::utils::shader_shell::{inject_user_shader, user_line_offset, ShellOptions, ShellType};
+
 // AIDEV-NOTE: Validate shader compilation using naga without GPU device
-pub fn validate_shader(shader_source: &str) -> Result<(), Box<dyn std::error::Error>> {
+#[cfg(test)]
+pub fn validate_shader(shader_source: &str) -> Result<(), ShadertuiError> {
+    check_shader(shader_source).map_err(|(message, _)| ShadertuiError::compile(message))
+}
+
+// Parse and validate, keeping where in `shader_source` the error points
+fn check_shader(shader_source: &str) -> Result<(), (String, Option<SourceLocation>)> {
     // Parse WGSL using naga frontend
-    let module = naga::front::wgsl::parse_str(shader_source)?;
+    let module = naga::front::wgsl::parse_str(shader_source)
+        .map_err(|e| (e.to_string(), e.location(shader_source)))?;
 
     // Validate the parsed module
     let mut validator = naga::valid::Validator::new(
         naga::valid::ValidationFlags::all(),
         naga::valid::Capabilities::all(),
     );
-    validator.validate(&module)?;
+    validator
+        .validate(&module)
+        .map_err(|e| (e.to_string(), e.location(shader_source)))?;
 
     Ok(())
 }
 
-// AIDEV-NOTE: Validate user shader for hot reload by injecting into shell and validating complete shader
-pub fn validate_user_shader_for_reload(
-    user_shader_source: &str,
-    shell_type: crate::utils::shader_shell::ShellType,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Inject user shader into appropriate shell
-    let complete_shader =
-        crate::utils::shader_shell::inject_user_shader(user_shader_source, shell_type)?;
+// AIDEV-NOTE: Validate every pass of a project; buffer passes always use the buffer shell
+pub fn validate_project(
+    project: &ShaderProject,
+    output_shell_type: ShellType,
+    shell_options: &ShellOptions,
+) -> Result<(), ShadertuiError> {
+    check_project(project, output_shell_type, shell_options).map_err(ShadertuiError::from)
+}
+
+// The first pass that doesn't compile, kept apart for `shadertui check`
+pub fn check_project(
+    project: &ShaderProject,
+    output_shell_type: ShellType,
+    shell_options: &ShellOptions,
+) -> Result<(), CompileFailure> {
+    for pass in project.passes() {
+        let shell_type = if std::ptr::eq(pass, &project.output) {
+            output_shell_type
+        } else {
+            ShellType::Buffer
+        };
+        let pass_options = ShellOptions {
+            channel_count: pass.inputs.len(),
+            buffer_format: pass.format,
+            params: project.params.clone(),
+            ..shell_options.clone()
+        };
 
-    // Validate the complete injected shader
-    validate_shader(&complete_shader)?;
+        let complete_shader =
+            inject_user_shader(&pass.source, shell_type, &pass_options).map_err(|e| {
+                CompileFailure {
+                    pass: None,
+                    message: e.to_string(),
+                    location: None,
+                }
+            })?;
+        check_shader(&complete_shader).map_err(|(message, location)| {
+            let offset = user_line_offset(shell_type, &pass_options);
+            CompileFailure {
+                pass: (!project.buffers.is_empty()).then(|| pass.name.clone()),
+                message,
+                location: location.and_then(|location| locate_error(location, pass, offset)),
+            }
+        })?;
+    }
 
     Ok(())
 }
+
+// A pass that doesn't compile: the compiler's message and, for errors inside the user's code,
+// where it points
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileFailure {
+    // Set in multi-pass projects
+    pass: Option<String>,
+    message: String,
+    location: Option<ErrorLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ErrorLocation {
+    file: PathBuf,
+    span: SourceSpan,
+    // The user's line the span is on
+    line: String,
+}
+
+// AIDEV-NOTE: One problem reported by `shadertui check --format json`. Errors outside the user's
+// code (generated or shell code, a broken import) point at `file` without a line
+#[derive(Debug, Serialize)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub length: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn unlocated(file: &Path, message: String) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            line: None,
+            column: None,
+            length: None,
+            message,
+        }
+    }
+}
+
+impl CompileFailure {
+    fn message(&self) -> String {
+        match &self.pass {
+            Some(pass) => format!("pass '{pass}': {}", self.message),
+            None => self.message.clone(),
+        }
+    }
+
+    // `shader_file` is the file that was checked, for errors without a location
+    pub fn diagnostic(&self, shader_file: &Path) -> Diagnostic {
+        match &self.location {
+            Some(location) => Diagnostic {
+                file: location.file.clone(),
+                line: Some(location.span.line),
+                column: Some(location.span.column),
+                length: Some(location.span.length),
+                message: self.message(),
+            },
+            None => Diagnostic::unlocated(shader_file, self.message()),
+        }
+    }
+}
+
+// AIDEV-NOTE: `file:line:col: message` plus the offending line and a caret under the span, for
+// errors inside the user's code
+impl From<CompileFailure> for ShadertuiError {
+    fn from(failure: CompileFailure) -> Self {
+        let Some(location) = failure.location else {
+            return ShadertuiError::compile(failure.message());
+        };
+        let ErrorLocation { file, span, line } = location;
+        let indent: String = line
+            .char_indices()
+            .take_while(|(index, _)| index + 1 < span.column)
+            .map(|(_, c)| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(span.length);
+        let message = format!(
+            "{}:{}:{}: {}\n   |\n   | {line}\n   | {indent}{carets}",
+            file.display(),
+            span.line,
+            span.column,
+            failure.message,
+        );
+        ShadertuiError::ShaderCompile {
+            file: Some(file),
+            span: Some(span),
+            message: match failure.pass {
+                Some(pass) => format!("pass '{pass}': {message}"),
+                None => message,
+            },
+        }
+    }
+}
+
+// Where in the user's code an error points; None when the line has no origin (shell or generated
+// code)
+fn locate_error(
+    location: SourceLocation,
+    pass: &PassSource,
+    offset: usize,
+) -> Option<ErrorLocation> {
+    let line_index = (location.line_number as usize).checked_sub(1 + offset)?;
+    let origin = pass.source_map.origin(line_index)?;
+    let line = pass.source.lines().nth(line_index)?;
+    Some(ErrorLocation {
+        file: origin.file.to_path_buf(),
+        span: SourceSpan {
+            line: origin.line,
+            column: location.line_position as usize,
+            length: (location.length as usize).max(1),
+        },
+        line: line.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_error_location_in_import() {
+        let dir = std::env::temp_dir().join("shadertui_test_error_location_in_import");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("main.wgsl"),
+            "// @import \"helpers.wgsl\"\n\
+             fn compute_color(coords: vec2<f32>) -> vec3<f32> {\n\
+             \x20   return vec3<f32>(helper());\n\
+             }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("helpers.wgsl"),
+            "fn helper() -> f32 {\n    return missing;\n}\n",
+        )
+        .unwrap();
+
+        let project = ShaderProject::load(&dir.join("main.wgsl")).unwrap();
+        let failure =
+            check_project(&project, ShellType::Terminal, &ShellOptions::default()).unwrap_err();
+        let error =
+            validate_project(&project, ShellType::Terminal, &ShellOptions::default()).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Diagnostics carry the compiler's message alone
+        let diagnostic = failure.diagnostic(&dir.join("main.wgsl"));
+        assert!(diagnostic.file.ends_with("helpers.wgsl"));
+        assert_eq!(
+            (diagnostic.line, diagnostic.column, diagnostic.length),
+            (Some(2), Some(12), Some(7))
+        );
+        assert!(!diagnostic.message.contains('\n'), "{}", diagnostic.message);
+
+        let ShadertuiError::ShaderCompile {
+            file: Some(file),
+            span: Some(span),
+            ..
+        } = &error
+        else {
+            panic!("{error:?}");
+        };
+        assert!(file.ends_with("helpers.wgsl"));
+        assert_eq!((span.line, span.column, span.length), (2, 12, 7));
+        let error = error.to_string();
+
+        let first_line = error.lines().next().unwrap();
+        assert!(first_line.contains("helpers.wgsl:2:12: "), "{error}");
+        assert!(
+            error.ends_with("   |     return missing;\n   |            ^^^^^^^"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::utils::font::{glyph_pixel, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::utils::image_export::RgbaImage;
+
+// Image height covered by one unit of text scale, so the text grows with the export
+const HEIGHT_PER_SCALE: u32 = 360;
+// Gap around the text and to the image edge, in font pixels
+const PADDING: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    // Decimal places, two unless given as {time:.N}
+    Time(usize),
+    Frame,
+}
+
+// AIDEV-NOTE: --watermark text, drawn with the built-in font (utils::font) in the bottom left corner
+// of exported frames and recordings, so a frame can be matched to its time in a review or bug
+// report. Drawn on the CPU after readback, so it never changes what the shader sees
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watermark {
+    parts: Vec<Part>,
+}
+
+impl Watermark {
+    // `{time}`, `{time:.N}` and `{frame}` are filled in per frame; `{{` and `}}` are literal braces
+    pub fn parse(format: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| format!("unclosed '{{' in '{format}'"))?;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(parse_placeholder(&rest[..end])?);
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => return Err(format!("unmatched '}}' in '{format}' (write '}}}}')")),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Self { parts })
+    }
+
+    pub fn text(&self, time: f32, frame: u32) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Time(decimals) => format!("{time:.decimals$}"),
+                Part::Frame => frame.to_string(),
+            })
+            .collect()
+    }
+
+    // White text on a darkened box, opaque even where the frame is transparent
+    pub fn draw(&self, image: &mut RgbaImage, time: f32, frame: u32) {
+        let text: Vec<char> = self.text(time, frame).chars().collect();
+        if text.is_empty() {
+            return;
+        }
+        let scale = (image.height / HEIGHT_PER_SCALE).max(1);
+        let advance = (GLYPH_WIDTH + 1) * scale;
+        let padding = PADDING * scale;
+        let box_width = text.len() as u32 * advance - scale + padding * 2;
+        let box_height = GLYPH_HEIGHT * scale + padding * 2;
+        let left = padding;
+        let top = image.height.saturating_sub(padding + box_height);
+
+        for y in top..(top + box_height).min(image.height) {
+            for x in left..(left + box_width).min(image.width) {
+                let (text_x, text_y) = (x - left, y - top);
+                let lit = text_y >= padding && text_x >= padding && {
+                    let (text_x, text_y) = (text_x - padding, text_y - padding);
+                    text.get((text_x / advance) as usize)
+                        .is_some_and(|&c| glyph_pixel(c, text_x % advance / scale, text_y / scale))
+                };
+                let index = ((y * image.width + x) * 4) as usize;
+                let pixel = &mut image.pixels[index..index + 4];
+                if lit {
+                    pixel.copy_from_slice(&[255, 255, 255, 255]);
+                } else {
+                    for channel in &mut pixel[..3] {
+                        *channel /= 2;
+                    }
+                    pixel[3] = 255;
+                }
+            }
+        }
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Part, String> {
+    match placeholder.split_once(':') {
+        None if placeholder == "time" => Ok(Part::Time(2)),
+        None if placeholder == "frame" => Ok(Part::Frame),
+        Some(("time", spec)) => spec
+            .strip_prefix('.')
+            .and_then(|decimals| decimals.parse().ok())
+            .map(Part::Time)
+            .ok_or_else(|| format!("invalid format '{{{placeholder}}}' (use e.g. {{time:.2}})")),
+        _ => Err(format!(
+            "unknown placeholder '{{{placeholder}}}' (use {{time}}, {{time:.N}} or {{frame}})"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermark_text() {
+        let watermark = Watermark::parse("time={time:.3}s frame={frame} {{raw}}").unwrap();
+        assert_eq!(watermark.text(1.5, 90), "time=1.500s frame=90 {raw}");
+        assert_eq!(Watermark::parse("{time}").unwrap().text(2.0, 0), "2.00");
+
+        assert!(Watermark::parse("{fps}").is_err());
+        assert!(Watermark::parse("{time:3}").is_err());
+        assert!(Watermark::parse("{time").is_err());
+        assert!(Watermark::parse("time}").is_err());
+    }
+
+    #[test]
+    fn test_watermark_draw() {
+        let mut image = RgbaImage::new(64, 32);
+        image.pixels.fill(200);
+        Watermark::parse("1").unwrap().draw(&mut image, 0.0, 0);
+        let pixel = |x: u32, y: u32| {
+            let index = ((y * image.width + x) * 4) as usize;
+            image.pixels[index..index + 4].to_vec()
+        };
+
+        // The box spans 9x11 pixels two in from the bottom left; '1' has its stem in column 2
+        assert_eq!(pixel(2, 19), vec![100, 100, 100, 255]);
+        assert_eq!(pixel(6, 25), vec![255, 255, 255, 255]);
+        assert_eq!(pixel(20, 25), vec![200, 200, 200, 200]);
+        assert_eq!(pixel(2, 31), vec![200, 200, 200, 200]);
+    }
+}
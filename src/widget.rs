@@ -0,0 +1,246 @@
+//! Live shaders inside ratatui apps. A [`ShaderHandle`] renders on its own thread; the widget
+//! copies the newest frame into the buffer whenever the app draws:
+//!
+//! ```no_run
+//! use shadertui::widget::{ShaderHandle, ShaderWidget};
+//! # fn draw(frame: &mut ratatui_core::terminal::Frame, area: ratatui_core::layout::Rect) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut shader = ShaderHandle::spawn("plasma.wgsl", 30)?;
+//! // In the app's draw closure; the shader follows the area's size
+//! frame.render_stateful_widget(ShaderWidget, area, &mut shader);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use ratatui_core::buffer::Buffer;
+use ratatui_core::layout::Rect;
+use ratatui_core::style::Color;
+use ratatui_core::widgets::{StatefulWidget, Widget};
+
+use crate::engine::{ShaderEngine, TerminalFrame};
+use crate::error::ShadertuiError;
+
+enum Command {
+    Resize(u16, u16),
+    SetParam(String, f32),
+    Reload,
+}
+
+#[derive(Default)]
+struct Latest {
+    frame: Option<TerminalFrame>,
+    error: Option<String>,
+}
+
+// AIDEV-NOTE: Owns the render thread of an embedded shader. The thread renders at `fps` with the
+// time since spawn() and keeps only the newest frame, so drawing never waits on the GPU. Dropping
+// the handle stops the thread and waits for it, which releases the GPU device
+pub struct ShaderHandle {
+    commands: Option<flume::Sender<Command>>,
+    latest: Arc<Mutex<Latest>>,
+    size: Mutex<(u16, u16)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ShaderHandle {
+    /// Compiles the shader and starts rendering it. Errors in the shader are returned here.
+    pub fn spawn(path: impl AsRef<Path>, fps: u32) -> Result<Self, ShadertuiError> {
+        let path = path.as_ref().to_path_buf();
+        let (commands, receiver) = flume::unbounded();
+        let (started, start_result) = flume::bounded(1);
+        let latest = Arc::new(Mutex::new(Latest::default()));
+        let thread = {
+            let latest = Arc::clone(&latest);
+            // Until the first draw sizes it, the shader renders a single cell
+            thread::spawn(move || match ShaderEngine::load(&path, 1, 1) {
+                Ok(engine) => {
+                    let _ = started.send(Ok(()));
+                    render_loop(engine, receiver, latest, fps.max(1));
+                }
+                Err(e) => {
+                    let _ = started.send(Err(e));
+                }
+            })
+        };
+        start_result
+            .recv()
+            .map_err(|_| ShadertuiError::Other("The shader thread panicked".to_string()))??;
+
+        Ok(Self {
+            commands: Some(commands),
+            latest,
+            size: Mutex::new((1, 1)),
+            thread: Some(thread),
+        })
+    }
+
+    /// The newest frame, which may still have the size of the previous draw.
+    pub fn frame(&self) -> Option<TerminalFrame> {
+        self.latest.lock().unwrap().frame.clone()
+    }
+
+    /// The last render or reload error, until a later frame succeeds.
+    pub fn error(&self) -> Option<String> {
+        self.latest.lock().unwrap().error.clone()
+    }
+
+    /// Sets a `// @uniform` parameter from the next frame on.
+    pub fn set_param(&self, name: &str, value: f32) {
+        self.send(Command::SetParam(name.to_string(), value));
+    }
+
+    /// Reads the shader again; on errors the old one keeps running and error() reports why.
+    pub fn reload(&self) {
+        self.send(Command::Reload);
+    }
+
+    /// Renders at `columns` x `rows` from the next frame on. The widgets call this with their area.
+    pub fn resize(&self, columns: u16, rows: u16) {
+        let mut size = self.size.lock().unwrap();
+        if *size != (columns, rows) {
+            *size = (columns, rows);
+            self.send(Command::Resize(columns, rows));
+        }
+    }
+
+    fn send(&self, command: Command) {
+        if let Some(commands) = &self.commands {
+            let _ = commands.send(command);
+        }
+    }
+}
+
+impl Drop for ShaderHandle {
+    fn drop(&mut self) {
+        // Closing the channel is the stop signal
+        self.commands.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn render_loop(
+    mut engine: ShaderEngine,
+    commands: flume::Receiver<Command>,
+    latest: Arc<Mutex<Latest>>,
+    fps: u32,
+) {
+    let start = Instant::now();
+    let interval = Duration::from_secs(1) / fps;
+    let mut next_frame = start;
+    loop {
+        // Commands arriving before the next frame is due are applied right away
+        loop {
+            let command = match commands.recv_deadline(next_frame) {
+                Ok(command) => command,
+                Err(flume::RecvTimeoutError::Timeout) => break,
+                Err(flume::RecvTimeoutError::Disconnected) => return,
+            };
+            let result = match command {
+                Command::Resize(columns, rows) => {
+                    engine.resize(columns, rows);
+                    Ok(())
+                }
+                Command::SetParam(name, value) => {
+                    engine.set_param(&name, value);
+                    Ok(())
+                }
+                Command::Reload => engine.reload(),
+            };
+            if let Err(e) = result {
+                latest.lock().unwrap().error = Some(e.to_string());
+            }
+        }
+
+        let rendered = engine.render(start.elapsed().as_secs_f32());
+        let mut latest = latest.lock().unwrap();
+        match rendered {
+            Ok(frame) => {
+                latest.frame = Some(frame);
+                latest.error = None;
+            }
+            Err(e) => latest.error = Some(e.to_string()),
+        }
+        // A frame that took longer than the interval delays the next one instead of queueing up
+        next_frame = (next_frame + interval).max(Instant::now());
+    }
+}
+
+/// Draws a [`ShaderHandle`]'s newest frame in half blocks, resizing the shader to the area.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShaderWidget;
+
+impl StatefulWidget for ShaderWidget {
+    type State = ShaderHandle;
+
+    fn render(self, area: Rect, buf: &mut Buffer, shader: &mut ShaderHandle) {
+        Widget::render(&*shader, area, buf);
+    }
+}
+
+impl Widget for &ShaderHandle {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.resize(area.width, area.height);
+        let latest = self.latest.lock().unwrap();
+        if let Some(frame) = &latest.frame {
+            draw_frame(frame, area, buf);
+        }
+    }
+}
+
+// A frame of another size (just after a resize) fills what overlaps and leaves the rest as is
+fn draw_frame(frame: &TerminalFrame, area: Rect, buf: &mut Buffer) {
+    let area = area.intersection(buf.area);
+    for row in 0..area.height.min(frame.rows()) {
+        for (column, cell) in frame.row(row).iter().take(area.width as usize).enumerate() {
+            let Some(target) = buf.cell_mut((area.x + column as u16, area.y + row)) else {
+                continue;
+            };
+            target.set_char(cell.glyph);
+            target.fg = cell
+                .fg
+                .map_or(Color::Reset, |(r, g, b)| Color::Rgb(r, g, b));
+            target.bg = cell
+                .bg
+                .map_or(Color::Reset, |(r, g, b)| Color::Rgb(r, g, b));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Cell;
+
+    #[test]
+    fn test_draw_frame() {
+        let red = Cell {
+            glyph: '▀',
+            fg: Some((255, 0, 0)),
+            bg: Some((0, 0, 255)),
+        };
+        let frame = TerminalFrame {
+            columns: 3,
+            rows: 1,
+            cells: vec![red; 3],
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 3));
+        draw_frame(&frame, Rect::new(1, 1, 2, 2), &mut buf);
+
+        let cell = &buf[(1, 1)];
+        assert_eq!(cell.symbol(), "▀");
+        assert_eq!(
+            (cell.fg, cell.bg),
+            (Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255))
+        );
+        // Clipped to the area's two columns and the frame's one row
+        assert_eq!(buf[(2, 1)].symbol(), "▀");
+        assert_eq!(buf[(3, 1)].symbol(), " ");
+        assert_eq!(buf[(1, 2)].symbol(), " ");
+    }
+}
@@ -1,42 +1,101 @@
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use winit::application::ApplicationHandler;
 use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::event::{ElementState, KeyEvent, MouseButton, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::keyboard::{KeyCode, PhysicalKey};
-use winit::window::{Window, WindowId};
-
-use crate::renderers::WindowRenderer;
+use winit::window::{Fullscreen, UserAttentionType, Window, WindowId};
+
+use crate::error::ShadertuiError;
+use crate::gpu::{check_compatibility, gpu_instance};
+use crate::renderers::{RetainedState, WindowRenderer};
+use crate::utils::control::{ControlCommand, ControlReceiver};
+use crate::utils::external_editor;
+use crate::utils::image_import::load_image;
+use crate::utils::messages::msg;
 use crate::utils::multi_file_watcher::MultiFileWatcher;
-use crate::utils::shader_import::{process_imports, DependencyInfo};
+use crate::utils::notification;
+use crate::utils::project::{sibling_shader, Domain, ShaderProject};
+use crate::utils::recording::Recorder;
+use crate::utils::shader_shell::ShellOptions;
+use crate::utils::signals::stop_on_signals;
 use crate::utils::{get_centered_window_position, get_window_size, Cli};
 
+// Frames in the --perf frame time sparkline of the window title
+const TITLE_SPARKLINE_WIDTH: usize = 16;
+// How long "GPU recovered" stays in the title, and how often a lost device is asked for again
+const RECOVERED_NOTICE_DURATION: Duration = Duration::from_secs(3);
+const DEVICE_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+// AIDEV-NOTE: The error shown in the window title. Its kind decides what clears it: a successful
+// resize or render only clears its own, a reload or a recovered device clears any
+enum WindowError {
+    // Loading or compiling a shader, and `shadertui ctl` commands
+    Shader(String),
+    // The renderer couldn't be created, so the window closes
+    Startup(String),
+    Resize(String),
+    Render(String),
+    // A lost or outdated surface, which resizing recreates
+    Surface,
+}
+
+impl WindowError {
+    fn message(&self) -> String {
+        match self {
+            WindowError::Shader(message)
+            | WindowError::Startup(message)
+            | WindowError::Resize(message)
+            | WindowError::Render(message) => message.clone(),
+            WindowError::Surface => msg!("window.surface_lost"),
+        }
+    }
+}
+
 // AIDEV-NOTE: WindowedApp handles the winit application lifecycle for basic window display
 struct WindowedApp {
     window: Option<Arc<Window>>,
     renderer: Option<WindowRenderer>,
     cli: Cli,
-    shader_source: String,
+    project: ShaderProject,
     cursor_position: [f32; 2],
 
     // Hot reload system
     file_watcher: Option<MultiFileWatcher>,
     shader_file_path: PathBuf,
-    dependency_info: Option<DependencyInfo>,
-    error_state: Option<String>,
+    last_error: Option<WindowError>,
+
+    // Daemon mode: commands from `shadertui ctl`
+    control: Option<ControlReceiver>,
+
+    // Started and stopped with `r`
+    recorder: Option<Recorder>,
+    recordings: u32,
+    // Set by SIGINT/SIGTERM/SIGHUP, which close the window like Q does
+    stop: Arc<AtomicBool>,
+
+    // Last `// @uniform` parameter tweaked with Tab or +/-, or setting changed with T, [ or ],
+    // shown in the title
+    param_label: Option<String>,
+
+    // Set while the GPU device is lost and no new one could be created yet, with the last attempt
+    lost_renderer: Option<(RetainedState, Instant)>,
+    recovered_at: Option<Instant>,
 }
 
 impl WindowedApp {
-    fn new(cli: Cli, shader_source: String) -> Self {
+    fn new(cli: Cli, project: ShaderProject, control: Option<ControlReceiver>) -> Self {
         let (width, height) = get_window_size();
-        let shader_file_path = cli.shader_file.clone();
+        let shader_file_path = cli.shader_file().to_path_buf();
 
         // Initialize file watcher for hot reload
         let file_watcher = match MultiFileWatcher::new(&shader_file_path) {
             Ok(watcher) => Some(watcher),
             Err(e) => {
-                eprintln!("Warning: Could not initialize file watcher: {e}");
+                eprintln!("{}", msg!("window.watcher_failed", error = e));
                 None
             }
         };
@@ -45,87 +104,322 @@ impl WindowedApp {
             window: None,
             renderer: None,
             cli,
-            shader_source,
+            project,
             cursor_position: [width as f32 / 2.0, height as f32 / 2.0],
             file_watcher,
             shader_file_path,
-            dependency_info: None,
-            error_state: None,
+            last_error: None,
+            control,
+            recorder: None,
+            recordings: 0,
+            stop: stop_on_signals(),
+            param_label: None,
+            lost_renderer: None,
+            recovered_at: None,
+        }
+    }
+
+    // AIDEV-NOTE: Base window title, including the shader's metadata title if it has one
+    fn base_title(&self) -> String {
+        match self.project.metadata.display_title() {
+            Some(title) => msg!("title.with_shader", title = title),
+            None => msg!("title.app"),
         }
     }
 
     // AIDEV-NOTE: Update window title with performance metrics if enabled
     fn update_window_title(&self) {
         if let (Some(window), Some(renderer)) = (&self.window, &self.renderer) {
-            let title = if let Some(error) = &self.error_state {
-                format!("ShaderTUI | Error: {error}")
+            let mut base_title = self.base_title();
+            if self.recorder.is_some() {
+                base_title.push_str(&format!(" | {}", msg!("status.recording")));
+            }
+            if let Some(label) = &self.param_label {
+                base_title.push_str(&format!(" | {label}"));
+            }
+            if self
+                .recovered_at
+                .is_some_and(|at| at.elapsed() < RECOVERED_NOTICE_DURATION)
+            {
+                base_title.push_str(&format!(" | {}", msg!("notice.gpu_recovered")));
+            }
+            let title = if let Some(error) = &self.last_error {
+                format!(
+                    "{base_title} | {}",
+                    msg!("status.error", error = error.message())
+                )
             } else if self.cli.perf {
-                if let Some(fps) = renderer.get_fps() {
-                    format!("ShaderTUI | FPS: {fps:.1}")
-                } else {
-                    "ShaderTUI | FPS: --".to_string()
+                let mut fps = match renderer.get_fps() {
+                    Some(fps) => msg!("status.fps", fps = format!("{fps:.1}")),
+                    None => msg!("status.fps_unknown"),
+                };
+                if self.cli.software_adapter {
+                    fps = format!("{} | {fps}", msg!("status.software"));
+                }
+                if let Some(gpu_time) = renderer.get_gpu_time() {
+                    let ms = format!("{:.2}", gpu_time.as_secs_f32() * 1000.0);
+                    fps.push_str(&format!(" | {}", msg!("status.gpu_time", ms = ms)));
+                }
+                if let Some(frame_times) = renderer.get_frame_time_stats(TITLE_SPARKLINE_WIDTH) {
+                    let ms = |duration: Duration| format!("{:.1}", duration.as_secs_f32() * 1000.0);
+                    let stats = msg!(
+                        "status.frame_times",
+                        p50 = ms(frame_times.p50),
+                        p95 = ms(frame_times.p95),
+                        p99 = ms(frame_times.p99)
+                    );
+                    fps.push_str(&format!(" | {stats} {}", frame_times.sparkline));
                 }
+                format!("{base_title} | {fps}")
             } else {
-                "ShaderTUI".to_string()
+                base_title
             };
             window.set_title(&title);
         }
     }
 
-    // AIDEV-NOTE: Handle file changes and attempt shader reload
-    fn handle_file_change(&mut self) -> bool {
+    fn create_renderer(
+        &self,
+        window: &Arc<Window>,
+        shell_options: ShellOptions,
+    ) -> Result<WindowRenderer, ShadertuiError> {
+        let instance = gpu_instance();
+        let surface = instance.create_surface(window.clone())?;
+        let window_size = window.inner_size();
+        WindowRenderer::new(
+            instance,
+            surface,
+            (window_size.width, window_size.height),
+            &self.project,
+            shell_options,
+            self.cli.scale.unwrap_or(1.0),
+            self.cli.loop_duration,
+            self.cli.delta_smoothing(),
+            self.cli.perf,
+        )
+    }
+
+    // AIDEV-NOTE: Device loss (driver reset, GPU switch): the old renderer and its surface are
+    // dropped and a new one built on a new device from the current project, carrying over the
+    // clock and parameters. Until that works it is retried every DEVICE_RETRY_INTERVAL
+    fn recover_renderer(&mut self) {
+        let Some(window) = self.window.clone() else {
+            return;
+        };
+        let retained = match (self.renderer.take(), self.lost_renderer.take()) {
+            (Some(renderer), _) => renderer.retain(),
+            (None, Some((retained, attempted_at))) => {
+                if attempted_at.elapsed() < DEVICE_RETRY_INTERVAL {
+                    self.lost_renderer = Some((retained, attempted_at));
+                    return;
+                }
+                retained
+            }
+            (None, None) => return,
+        };
+        match self.create_renderer(&window, retained.shell_options()) {
+            Ok(mut renderer) => {
+                renderer.restore(retained);
+                self.renderer = Some(renderer);
+                self.recovered_at = Some(Instant::now());
+                self.last_error = None;
+                println!("{}", msg!("notice.gpu_recovered"));
+            }
+            Err(e) => {
+                eprintln!("{}", msg!("error.device_lost", error = e));
+                self.lost_renderer = Some((retained, Instant::now()));
+            }
+        }
+    }
+
+    // AIDEV-NOTE: `r` starts a recording and stops it again, reporting the file on stdout
+    fn toggle_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            println!("{}", msg!("recording.finishing"));
+            match recorder.finish() {
+                Ok(path) => println!("{}", msg!("recording.saved", path = path.display())),
+                Err(e) => eprintln!("{}", msg!("recording.failed", error = e)),
+            }
+            return;
+        }
+        self.recordings += 1;
+        match Recorder::start(&self.cli.recording_options(), self.recordings) {
+            Ok(recorder) => {
+                println!(
+                    "{}",
+                    msg!("recording.started", path = recorder.path().display())
+                );
+                self.recorder = Some(recorder);
+            }
+            Err(e) => eprintln!("{}", msg!("recording.failed", error = e)),
+        }
+    }
+
+    // AIDEV-NOTE: Apply a pending daemon command. Returns true if the shader file was switched, which
+    // then reloads like a file change. A swapped texture also replaces the one in `cli.shell_options`
+    // so later reloads validate against the same bindings. Commands wait until the renderer exists,
+    // so a mirror window (utils::mirror) doesn't lose the clock it is sent at startup
+    fn handle_control_command(&mut self, event_loop: &ActiveEventLoop) -> bool {
+        if self.renderer.is_none() {
+            return false;
+        }
+        let Some(command) = self
+            .control
+            .as_ref()
+            .and_then(|control| control.try_recv().ok())
+        else {
+            return false;
+        };
+        match command {
+            ControlCommand::Load(path) => {
+                self.switch_shader(path);
+                true
+            }
+            ControlCommand::Skip(shaders) => {
+                match sibling_shader(&self.shader_file_path, shaders) {
+                    Ok(path) => {
+                        self.switch_shader(path);
+                        true
+                    }
+                    Err(e) => {
+                        eprintln!("{e}");
+                        self.last_error = Some(WindowError::Shader(e));
+                        false
+                    }
+                }
+            }
+            ControlCommand::Texture(index, path) => {
+                let result = load_image(&path).map(Arc::new).and_then(|image| {
+                    if let Some(renderer) = &mut self.renderer {
+                        renderer.set_texture(index, image.clone())?;
+                    }
+                    self.cli.shell_options.textures[index] = image;
+                    Ok(())
+                });
+                if let Err(e) = result {
+                    let error_msg = msg!("error.texture", error = e);
+                    self.last_error = Some(WindowError::Shader(error_msg.clone()));
+                    eprintln!("{error_msg}");
+                }
+                false
+            }
+            ControlCommand::Param(name, value) => {
+                if let Some(renderer) = &mut self.renderer {
+                    renderer.set_param(&name, value);
+                }
+                false
+            }
+            ControlCommand::Pause(paused) => {
+                if let Some(renderer) = &mut self.renderer {
+                    match paused {
+                        Some(paused) => renderer.set_paused(paused),
+                        None => renderer.toggle_pause(),
+                    }
+                }
+                false
+            }
+            ControlCommand::Seek(seconds) => {
+                if let Some(renderer) = &mut self.renderer {
+                    renderer.seek(seconds);
+                }
+                false
+            }
+            ControlCommand::Time(seconds) => {
+                if let Some(renderer) = &mut self.renderer {
+                    renderer.set_time(seconds);
+                }
+                false
+            }
+            ControlCommand::Speed(time_scale) => {
+                if let Some(renderer) = &mut self.renderer {
+                    renderer.set_time_scale(time_scale);
+                }
+                false
+            }
+            // The zoom inspector, fades and mirror windows belong to the terminal frontend
+            ControlCommand::Zoom(_) | ControlCommand::Fade(..) | ControlCommand::Window => false,
+            ControlCommand::Quit => {
+                event_loop.exit();
+                false
+            }
+        }
+    }
+
+    fn switch_shader(&mut self, path: PathBuf) {
+        match MultiFileWatcher::new(&path) {
+            Ok(watcher) => self.file_watcher = Some(watcher),
+            Err(e) => eprintln!("{}", msg!("window.watcher_failed", error = e)),
+        }
+        self.shader_file_path = path;
+    }
+
+    // AIDEV-NOTE: Handle file changes and attempt shader reload; `force` reloads without a change
+    fn handle_file_change(&mut self, force: bool) -> bool {
         if let Some(file_watcher) = &mut self.file_watcher {
-            if let Some(_changed_file) = file_watcher.check_for_changes() {
-                match std::fs::read_to_string(&self.shader_file_path) {
-                    Ok(raw_shader_source) => {
-                        match process_imports(&self.shader_file_path, &raw_shader_source) {
-                            Ok((processed_shader_source, deps)) => {
-                                // Update dependency tracking
-                                if let Err(e) = file_watcher.update_watched_files(&deps.all_files) {
-                                    eprintln!("Warning: Could not update watched files: {e}");
-                                }
-                                self.dependency_info = Some(deps);
-
-                                // Validate user shader before attempting reload
-                                match crate::utils::validation::validate_user_shader_for_reload(
-                                    &processed_shader_source,
-                                    crate::utils::shader_shell::ShellType::Window,
-                                ) {
-                                    Ok(()) => {
-                                        // Attempt shader reload
-                                        if let Some(renderer) = &mut self.renderer {
-                                            match renderer.reload_shader(&processed_shader_source) {
-                                                Ok(()) => {
-                                                    self.error_state = None;
-                                                    println!("Shader reloaded successfully");
-                                                    return true;
-                                                }
-                                                Err(e) => {
-                                                    let error_msg =
-                                                        format!("Compilation error: {e}");
-                                                    self.error_state = Some(error_msg.clone());
-                                                    eprintln!("{error_msg}");
+            if file_watcher.check_for_changes().is_some() || force {
+                match ShaderProject::load(&self.shader_file_path) {
+                    Ok(project) if project.domain == Domain::Samples => {
+                        let error_msg = msg!("window.sample_domain");
+                        eprintln!("{error_msg}");
+                        self.last_error = Some(WindowError::Shader(error_msg));
+                    }
+                    Ok(project) => {
+                        // Update dependency tracking
+                        if let Err(e) = file_watcher.update_watched_files(&project.all_files) {
+                            eprintln!("{}", msg!("window.watch_update_failed", error = e));
+                        }
+
+                        // Validate all passes before attempting reload
+                        match crate::utils::validation::validate_project(
+                            &project,
+                            crate::utils::shader_shell::ShellType::Window,
+                            &self.cli.shell_options,
+                        ) {
+                            Ok(()) => {
+                                // Attempt shader reload
+                                if let Some(renderer) = &mut self.renderer {
+                                    let reset = self.cli.reset_on_reload;
+                                    let started = Instant::now();
+                                    match renderer.reload_shader(&project, reset) {
+                                        Ok(()) => {
+                                            let was_failing = self.last_error.take().is_some();
+                                            if self.cli.notify
+                                                && notification::should_notify(
+                                                    was_failing,
+                                                    Some(started.elapsed()),
+                                                )
+                                            {
+                                                // Flashes the taskbar entry (or dock icon)
+                                                if let Some(window) = &self.window {
+                                                    window.request_user_attention(Some(
+                                                        UserAttentionType::Informational,
+                                                    ));
                                                 }
                                             }
+                                            self.project = project;
+                                            println!("{}", msg!("window.reloaded"));
+                                            return true;
+                                        }
+                                        Err(e) => {
+                                            let error_msg =
+                                                msg!("error.shader_compilation", error = e);
+                                            self.last_error =
+                                                Some(WindowError::Shader(error_msg.clone()));
+                                            eprintln!("{error_msg}");
                                         }
-                                    }
-                                    Err(e) => {
-                                        let error_msg = format!("Shader validation error: {e}");
-                                        self.error_state = Some(error_msg.clone());
-                                        eprintln!("{error_msg}");
                                     }
                                 }
                             }
                             Err(e) => {
-                                let error_msg = format!("Import error: {e}");
-                                self.error_state = Some(error_msg.clone());
+                                let error_msg = msg!("error.shader_validation", error = e);
+                                self.last_error = Some(WindowError::Shader(error_msg.clone()));
                                 eprintln!("{error_msg}");
                             }
                         }
                     }
                     Err(e) => {
-                        let error_msg = format!("File read error: {e}");
-                        self.error_state = Some(error_msg.clone());
+                        let error_msg = msg!("error.load", error = e);
+                        self.last_error = Some(WindowError::Shader(error_msg.clone()));
                         eprintln!("{error_msg}");
                     }
                 }
@@ -141,58 +435,27 @@ impl ApplicationHandler for WindowedApp {
         let position = get_centered_window_position(event_loop);
 
         let window_attributes = Window::default_attributes()
-            .with_title("ShaderTUI")
+            .with_title(self.base_title())
             .with_inner_size(PhysicalSize::new(width, height))
             .with_position(position)
-            .with_resizable(true);
+            .with_resizable(true)
+            .with_fullscreen(self.cli.screensaver.then_some(Fullscreen::Borderless(None)));
 
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
 
-        // Create wgpu instance and surface
-        let instance = wgpu::Instance::default();
-        let surface = instance.create_surface(window.clone()).unwrap();
-        let window_size = window.inner_size();
-
-        // Create renderer with the surface and shader
-        match WindowRenderer::new(
-            instance,
-            surface,
-            (window_size.width, window_size.height),
-            &self.shader_source,
-            self.cli.perf,
-        ) {
+        // Create renderer with a surface for the window and the shader
+        match self.create_renderer(&window, self.cli.shell_options.clone()) {
             Ok(mut renderer) => {
-                println!("Successfully initialized WindowRenderer");
-
                 // Set initial cursor position
                 renderer.update_cursor_position(self.cursor_position[0], self.cursor_position[1]);
 
                 self.renderer = Some(renderer);
                 self.window = Some(window);
 
-                // Initialize dependency tracking for the initial shader
-                match std::fs::read_to_string(&self.shader_file_path) {
-                    Ok(raw_shader_source) => {
-                        match process_imports(&self.shader_file_path, &raw_shader_source) {
-                            Ok((_processed_shader_source, deps)) => {
-                                if let Some(file_watcher) = &mut self.file_watcher {
-                                    if let Err(e) =
-                                        file_watcher.update_watched_files(&deps.all_files)
-                                    {
-                                        eprintln!(
-                                            "Warning: Could not initialize watched files: {e}"
-                                        );
-                                    }
-                                }
-                                self.dependency_info = Some(deps);
-                            }
-                            Err(e) => {
-                                eprintln!("Warning: Could not process initial imports: {e}");
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Warning: Could not read initial shader file: {e}");
+                // Initialize dependency tracking for the initial shader (and project passes)
+                if let Some(file_watcher) = &mut self.file_watcher {
+                    if let Err(e) = file_watcher.update_watched_files(&self.project.all_files) {
+                        eprintln!("{}", msg!("window.watch_init_failed", error = e));
                     }
                 }
 
@@ -202,9 +465,9 @@ impl ApplicationHandler for WindowedApp {
                 }
             }
             Err(e) => {
-                let error_msg = format!("Failed to create WindowRenderer: {e}");
+                let error_msg = msg!("error.window_renderer", error = e);
                 eprintln!("{error_msg}");
-                self.error_state = Some(error_msg);
+                self.last_error = Some(WindowError::Startup(error_msg));
 
                 // Still set up the window but without renderer
                 self.window = Some(window);
@@ -226,7 +489,6 @@ impl ApplicationHandler for WindowedApp {
     ) {
         match event {
             WindowEvent::CloseRequested => {
-                println!("Window close requested, exiting...");
                 event_loop.exit();
             }
             WindowEvent::KeyboardInput {
@@ -239,12 +501,10 @@ impl ApplicationHandler for WindowedApp {
                 ..
             } => {
                 match key_code {
-                    KeyCode::KeyQ => {
-                        println!("Q pressed, exiting...");
+                    _ if self.cli.screensaver => {
                         event_loop.exit();
                     }
-                    KeyCode::Escape => {
-                        println!("Escape pressed, exiting...");
+                    KeyCode::KeyQ | KeyCode::Escape => {
                         event_loop.exit();
                     }
                     KeyCode::Space => {
@@ -252,6 +512,68 @@ impl ApplicationHandler for WindowedApp {
                             renderer.toggle_pause();
                         }
                     }
+                    KeyCode::KeyR => {
+                        self.toggle_recording();
+                        self.update_window_title();
+                    }
+                    KeyCode::Tab
+                    | KeyCode::Equal
+                    | KeyCode::NumpadAdd
+                    | KeyCode::Minus
+                    | KeyCode::NumpadSubtract => {
+                        if let Some(renderer) = &mut self.renderer {
+                            let label = match key_code {
+                                KeyCode::Tab => renderer.select_next_param(),
+                                KeyCode::Minus | KeyCode::NumpadSubtract => {
+                                    renderer.adjust_param(-1)
+                                }
+                                _ => renderer.adjust_param(1),
+                            };
+                            if label.is_some() {
+                                self.param_label = label;
+                                self.update_window_title();
+                            }
+                        }
+                    }
+                    KeyCode::Comma | KeyCode::Period => {
+                        if let Some(renderer) = &mut self.renderer {
+                            let frames = if key_code == KeyCode::Period { 1 } else { -1 };
+                            renderer.step_frames(frames);
+                        }
+                    }
+                    KeyCode::BracketLeft | KeyCode::BracketRight => {
+                        if let Some(renderer) = &mut self.renderer {
+                            let scale = renderer.step_time_scale(key_code == KeyCode::BracketRight);
+                            self.param_label = Some(msg!("notice.time_scale", scale = scale));
+                            self.update_window_title();
+                        }
+                    }
+                    KeyCode::Digit0 | KeyCode::Numpad0 => {
+                        if let Some(renderer) = &mut self.renderer {
+                            renderer.reset_clock();
+                        }
+                    }
+                    KeyCode::KeyT => {
+                        self.cli.reset_on_reload = !self.cli.reset_on_reload;
+                        self.param_label = Some(if self.cli.reset_on_reload {
+                            msg!("notice.reset_on_reload_on")
+                        } else {
+                            msg!("notice.reset_on_reload_off")
+                        });
+                        self.update_window_title();
+                    }
+                    KeyCode::KeyE => {
+                        if let Err(e) = external_editor::spawn_detached(&self.shader_file_path) {
+                            eprintln!(
+                                "{}",
+                                msg!(
+                                    "error.editor_launch",
+                                    editor = external_editor::editor_name(),
+                                    error = e
+                                )
+                            );
+                        }
+                    }
                     KeyCode::ArrowUp => {
                         // Arrow up should move cursor up in window coords (decrease Y)
                         self.cursor_position[1] = (self.cursor_position[1] - 10.0).max(0.0);
@@ -319,17 +641,25 @@ impl ApplicationHandler for WindowedApp {
                     window.request_redraw();
                 }
             }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                if let Some(renderer) = &mut self.renderer {
+                    renderer.set_mouse_button(state == ElementState::Pressed);
+                }
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            }
             WindowEvent::Resized(size) => {
                 if let Some(renderer) = &mut self.renderer {
                     match renderer.resize(size.width, size.height) {
                         Ok(()) => {
                             // Clear any previous resize errors on successful resize
-                            if self
-                                .error_state
-                                .as_ref()
-                                .is_some_and(|e| e.contains("Resize error"))
-                            {
-                                self.error_state = None;
+                            if let Some(WindowError::Resize(_)) = self.last_error {
+                                self.last_error = None;
                             }
 
                             // Update cursor bounds for new window size
@@ -345,32 +675,52 @@ impl ApplicationHandler for WindowedApp {
                             self.update_window_title();
                         }
                         Err(e) => {
-                            let error_msg = format!("Resize error: {e}");
+                            let error_msg = msg!("error.resize", error = e);
                             eprintln!("{error_msg}");
-                            self.error_state = Some(error_msg);
+                            self.last_error = Some(WindowError::Resize(error_msg));
                             self.update_window_title();
                         }
                     }
                 }
             }
             WindowEvent::RedrawRequested => {
+                if self.renderer.as_ref().is_some_and(|r| r.device_lost())
+                    || self.lost_renderer.is_some()
+                {
+                    self.recover_renderer();
+                }
+
                 // Render the shader to the window surface
                 if let Some(renderer) = &mut self.renderer {
                     match renderer.render() {
                         Ok(()) => {
                             // Clear any previous render errors on successful render
-                            if self
-                                .error_state
-                                .as_ref()
-                                .is_some_and(|e| e.contains("Render error"))
+                            if let Some(WindowError::Render(_) | WindowError::Surface) =
+                                self.last_error
                             {
-                                self.error_state = None;
+                                self.last_error = None;
+                            }
+
+                            // Capture before the title update so it shows the recording state
+                            if let Some(recorder) =
+                                self.recorder.as_mut().filter(|r| r.wants_frame())
+                            {
+                                match renderer.capture_frame() {
+                                    Ok(image) => {
+                                        let (time, frame) = renderer.rendered_frame();
+                                        recorder.push_frame(image, time, frame)
+                                    }
+                                    Err(e) => {
+                                        eprintln!("{}", msg!("window.capture_failed", error = e))
+                                    }
+                                }
                             }
+
                             // Update window title with performance metrics after successful render
                             self.update_window_title();
                         }
                         Err(e) => {
-                            let error_msg = format!("Render error: {e}");
+                            let error_msg = msg!("error.render", error = e);
                             eprintln!("{error_msg}");
 
                             // Check for specific surface/GPU errors that might require special handling
@@ -380,10 +730,9 @@ impl ApplicationHandler for WindowedApp {
                                 || error_str.contains("Outdated")
                             {
                                 // Surface-related error - might need to recreate surface
-                                self.error_state =
-                                    Some("Surface error - try resizing window".to_string());
+                                self.last_error = Some(WindowError::Surface);
                             } else {
-                                self.error_state = Some(error_msg);
+                                self.last_error = Some(WindowError::Render(error_msg));
                             }
 
                             self.update_window_title();
@@ -396,8 +745,14 @@ impl ApplicationHandler for WindowedApp {
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        // Check for file changes and hot reload
-        if self.handle_file_change() {
+        if self.stop.load(Ordering::Relaxed) {
+            event_loop.exit();
+            return;
+        }
+
+        // Check for daemon commands, file changes and hot reload
+        let switched = self.handle_control_command(event_loop);
+        if self.handle_file_change(switched) {
             // Update window title to reflect any error state changes
             self.update_window_title();
 
@@ -419,19 +774,26 @@ impl ApplicationHandler for WindowedApp {
 
 pub fn run_windowed_event_loop(
     cli: Cli,
-    shader_source: String,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Starting ShaderTUI in windowed mode...");
-    println!("Window will display at 1280x800 pixels, centered on screen");
-    println!("Controls:");
-    println!("  Arrow keys: Move cursor position");
-    println!("  Spacebar: Pause/resume animation");
-    println!("  Q or Escape: Exit");
-    println!("  Mouse: Move cursor (alternative to arrow keys)");
+    project: ShaderProject,
+    control: Option<ControlReceiver>,
+) -> Result<(), ShadertuiError> {
+    // AIDEV-NOTE: The scope/spectrogram views are drawn on the CPU, which only the terminal path does
+    if project.domain == Domain::Samples {
+        return Err(msg!("window.sample_domain").into());
+    }
+    // The window's size in pixels isn't known until it opens
+    check_compatibility(&project, &cli.shell_options, None)?;
+
+    println!("{}", msg!("window.intro"));
 
     let event_loop = EventLoop::new()?;
-    let mut app = WindowedApp::new(cli, shader_source);
+    let mut app = WindowedApp::new(cli, project, control);
 
     event_loop.run_app(&mut app)?;
+
+    // Quitting mid-recording still saves it
+    if app.recorder.is_some() {
+        app.toggle_recording();
+    }
     Ok(())
 }